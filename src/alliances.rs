@@ -0,0 +1,136 @@
+use crate::borders::draw_dashed_line;
+use crate::replay::ReplayAction;
+use crate::GameState;
+use macroquad::prelude::*;
+
+const ALLIED_BORDER_ALPHA: f32 = 0.9;
+const ALLIED_BORDER_THICKNESS: f32 = 3.0;
+
+impl GameState {
+    // Aimed at the owner of the territory under the mouse: breaks an
+    // alliance with them, accepts one they offered, or offers one for them
+    // to accept on their own turn (pressing again takes it back). Computer
+    // players can't say no, so they're never asked.
+    pub fn toggle_alliance(&mut self) {
+        let me = self.game.current_player;
+        let other = self
+            .hovered_territory
+            .filter(|&i| self.visible_to(me, i))
+            .and_then(|i| self.game.territories[i].owner);
+        let Some(other) = other.filter(|&other| other != me) else {
+            self.status_message =
+                Some("Point at another player's territory to ally or break off".to_string());
+            return;
+        };
+        let (mine, theirs) = (
+            self.game.player(me).name.clone(),
+            self.game.player(other).name.clone(),
+        );
+        if self.game.player(other).is_ai {
+            self.status_message = Some(format!("{} doesn't make alliances", theirs));
+            return;
+        }
+        self.status_message = Some(if self.game.break_alliance(me, other) {
+            self.replay
+                .record(ReplayAction::BreakAlliance { a: me, b: other });
+            format!("{} broke off the alliance with {}", mine, theirs)
+        } else if self.alliance_offers.remove(&(other, me)) {
+            self.game.form_alliance(me, other);
+            self.replay
+                .record(ReplayAction::FormAlliance { a: me, b: other });
+            format!("{} and {} are now allies", mine, theirs)
+        } else if self.alliance_offers.remove(&(me, other)) {
+            format!("{} took back the offer to {}", mine, theirs)
+        } else {
+            self.alliance_offers.insert((me, other));
+            format!(
+                "{} offers {} an alliance; {} can accept on their turn",
+                mine, theirs, theirs
+            )
+        });
+        self.game.board_dirty = true;
+    }
+
+    // Borders between allies are dashed over in gold, so the blocs stand
+    // out from the one-colour-per-player board. Only where both sides are
+    // in view, since fog hides who holds a territory.
+    pub fn draw_alliance_borders(&self, on_screen: &[bool]) {
+        if self.game.alliances.is_empty() {
+            return;
+        }
+        let viewer = self.game.current_player;
+        let color = Color {
+            a: ALLIED_BORDER_ALPHA,
+            ..self.theme.alliance
+        };
+        for (a, territory) in self.game.territories.iter().enumerate() {
+            let Some(owner) = territory.owner.filter(|_| on_screen[a]) else {
+                continue;
+            };
+            if !self.visible_to(viewer, a) {
+                continue;
+            }
+            let n = territory.vertices.len();
+            for (edge, neighbour) in self.borders.neighbours_of(a).iter().enumerate() {
+                // Each border is drawn once, from the lower index
+                let Some(b) = neighbour.filter(|&b| a < b) else {
+                    continue;
+                };
+                let allied = self.game.territories[b]
+                    .owner
+                    .is_some_and(|other| self.game.allied(owner, other));
+                if !allied || !self.visible_to(viewer, b) {
+                    continue;
+                }
+                let start = self.camera.world_to_screen(territory.vertices[edge]);
+                let end = self
+                    .camera
+                    .world_to_screen(territory.vertices[(edge + 1) % n]);
+                draw_dashed_line(start, end, ALLIED_BORDER_THICKNESS, color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::player::PlayerId;
+    use crate::test_support::{game_state, territory};
+
+    #[test]
+    fn alliances_need_both_sides_and_replay() {
+        let territories = || {
+            vec![
+                territory("A", 0, 3, vec![1, 2]),
+                territory("B", 1, 3, vec![0]),
+                territory("C", 2, 3, vec![0]),
+            ]
+        };
+        let mut state = game_state(territories(), 0);
+        state.fog_of_war = false;
+        state.game.players[2].is_ai = true;
+        state.hovered_territory = Some(0);
+        state.toggle_alliance();
+        state.hovered_territory = Some(2);
+        state.toggle_alliance();
+        assert!(state.game.alliances.is_empty());
+
+        // An offer alone changes nothing until the other side accepts it
+        state.hovered_territory = Some(1);
+        state.toggle_alliance();
+        assert!(!state.game.allied(PlayerId(0), PlayerId(1)));
+        state.game.current_player = PlayerId(1);
+        state.hovered_territory = Some(0);
+        state.toggle_alliance();
+        assert!(state.game.allied(PlayerId(0), PlayerId(1)));
+
+        let mut replayed = game_state(territories(), 0);
+        state.replay.play(&mut replayed).unwrap();
+        assert!(replayed.game.allied(PlayerId(0), PlayerId(1)));
+
+        state.toggle_alliance();
+        assert!(!state.game.allied(PlayerId(0), PlayerId(1)));
+        state.replay.play(&mut replayed).unwrap();
+        assert!(replayed.game.alliances.is_empty());
+    }
+}
@@ -0,0 +1,168 @@
+use crate::button::Button;
+use crate::GameState;
+use macroquad::prelude::*;
+
+// An attack waiting on the player's yes or no. Nothing is rolled or recorded
+// until it's confirmed, so saying no leaves the game as it was.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PendingAttack {
+    pub attacker: usize,
+    pub defender: usize,
+    // Fight it out in one go rather than a single round
+    pub blitz: bool,
+}
+
+// Panel in the middle of a `hud`-sized window with its Attack and Cancel
+// buttons
+fn layout(hud: Vec2) -> (Rect, [Button<bool>; 2]) {
+    let (width, height) = (340.0, 130.0);
+    let panel = Rect::new((hud.x - width) / 2.0, (hud.y - height) / 2.0, width, height);
+    let (button_width, button_height) = (140.0, 30.0);
+    let y = panel.bottom() - button_height - 12.0;
+    let button = |x, label: &str, action| Button {
+        rect: Rect::new(x, y, button_width, button_height),
+        label: label.to_string(),
+        action,
+        enabled: true,
+    };
+    (
+        panel,
+        [
+            button(panel.x + 20.0, "Attack (Enter)", true),
+            button(panel.right() - 20.0 - button_width, "Cancel (Esc)", false),
+        ],
+    )
+}
+
+impl GameState {
+    // Attacks straight away, or with `confirm_attacks` on asks first
+    pub fn request_attack(&mut self, attacker: usize, defender: usize, blitz: bool) {
+        if self.confirm_attacks {
+            self.pending_attack = Some(PendingAttack {
+                attacker,
+                defender,
+                blitz,
+            });
+        } else {
+            self.launch_attack(attacker, defender, blitz);
+        }
+    }
+
+    pub fn confirm_pending_attack(&mut self) {
+        let Some(pending) = self.pending_attack.take() else {
+            return;
+        };
+        self.launch_attack(pending.attacker, pending.defender, pending.blitz);
+    }
+
+    pub fn cancel_pending_attack(&mut self) {
+        self.pending_attack = None;
+    }
+
+    pub fn handle_pending_attack_input(&mut self) {
+        if is_mouse_button_pressed(MouseButton::Left) {
+            let (_, buttons) = layout(self.hud_size());
+            let mouse = self.hud_mouse();
+            match buttons.iter().find_map(|button| button.hit(mouse)) {
+                Some(true) => self.confirm_pending_attack(),
+                Some(false) => self.cancel_pending_attack(),
+                None => {}
+            }
+            return;
+        }
+        if is_key_pressed(KeyCode::Enter) {
+            self.confirm_pending_attack();
+        } else if is_key_pressed(KeyCode::Escape) {
+            self.cancel_pending_attack();
+        }
+    }
+
+    pub fn draw_pending_attack(&self) {
+        let Some(pending) = &self.pending_attack else {
+            return;
+        };
+        let (panel, buttons) = layout(self.hud_size());
+        draw_rectangle(panel.x, panel.y, panel.w, panel.h, self.theme.panel);
+        draw_rectangle_lines(
+            panel.x,
+            panel.y,
+            panel.w,
+            panel.h,
+            2.0,
+            self.theme.panel_border,
+        );
+        let (attacker, defender) = (
+            &self.game.territories[pending.attacker],
+            &self.game.territories[pending.defender],
+        );
+        let lines = [
+            format!(
+                "{} {} from {}?",
+                if pending.blitz { "Blitz" } else { "Attack" },
+                defender.name,
+                attacker.name
+            ),
+            format!(
+                "{} armies against {}, {:.0}% to take it",
+                attacker.armies,
+                defender.armies,
+                self.game.conquest_odds(pending.attacker, pending.defender) * 100.0
+            ),
+        ];
+        for (i, line) in lines.iter().enumerate() {
+            draw_text(
+                line,
+                panel.x + 12.0,
+                panel.y + 26.0 + 24.0 * i as f32,
+                20.0,
+                self.theme.text,
+            );
+        }
+        let mouse = self.hud_mouse();
+        for button in &buttons {
+            button.draw(mouse, &self.theme);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::turn::Phase;
+    use crate::test_support::{game_state, territory};
+
+    #[test]
+    fn nothing_happens_until_the_attack_is_confirmed() {
+        let mut state = game_state(
+            vec![
+                territory("Home", 0, 8, vec![1]),
+                territory("East", 1, 3, vec![0]),
+            ],
+            0,
+        );
+        state.game.phase = Phase::Attack;
+        state.confirm_attacks = true;
+        let board = |state: &crate::GameState| {
+            state
+                .game
+                .territories
+                .iter()
+                .map(|t| (t.owner, t.armies))
+                .collect::<Vec<_>>()
+        };
+        let before = board(&state);
+
+        state.select(0);
+        state.click_territory(1, true);
+        assert!(state.pending_attack.is_some());
+        state.cancel_pending_attack();
+        assert_eq!(board(&state), before);
+        assert!(state.game.events.is_empty());
+        assert_eq!(state.replay.len(), 0);
+
+        state.click_territory(1, true);
+        state.confirm_pending_attack();
+        assert!(state.pending_attack.is_none());
+        assert_ne!(board(&state), before);
+        assert_eq!(state.replay.len(), 1);
+    }
+}
@@ -0,0 +1,116 @@
+use crate::game::turn::Phase;
+use crate::GameState;
+use macroquad::prelude::*;
+
+// The theme's attack target colour, a little see-through
+const ARROW_ALPHA: f32 = 0.85;
+// Screen pixels of the arrowhead's length and half its width
+const ARROW_HEAD: f32 = 16.0;
+const ARROW_HALF_WIDTH: f32 = 8.0;
+
+impl GameState {
+    // Pressing on a territory in the Attack phase starts a drag from it, if
+    // it has anything it could attack. It needn't be selected first.
+    pub fn begin_attack_drag(&mut self, pressed: usize) {
+        let can_launch = self.game.phase == Phase::Attack
+            && self.game.territories[pressed].owner == Some(self.game.current_player)
+            && self.game.territories[pressed]
+                .adjacent
+                .iter()
+                .any(|&n| self.game.can_attack(pressed, n));
+        self.attack_drag = can_launch.then_some(pressed);
+    }
+
+    // What the dragged arrow points at: the hovered territory, if the drag's
+    // source could attack it
+    pub fn attack_drag_target(&self) -> Option<usize> {
+        let source = self.attack_drag?;
+        self.hovered_territory
+            .filter(|&target| self.game.can_attack(source, target))
+    }
+
+    // Letting go over a valid target selects the source and attacks, as
+    // clicking the two would; anywhere else the drag just ends. Returns
+    // whether an attack started.
+    pub fn finish_attack_drag(&mut self, blitz: bool) -> bool {
+        let target = self.attack_drag_target();
+        let source = self.attack_drag.take();
+        match (source, target) {
+            (Some(source), Some(target)) => {
+                self.select(source);
+                self.click_territory(target, blitz);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // An arrow from the source to the mouse, snapped to the target's centre
+    // when hovering one
+    pub fn draw_attack_drag(&self) {
+        let Some(source) = self.attack_drag else {
+            return;
+        };
+        if !is_mouse_button_down(MouseButton::Left) {
+            return;
+        }
+        let start = self
+            .camera
+            .world_to_screen(self.game.territories[source].centroid());
+        let end = match self.attack_drag_target() {
+            Some(target) => self
+                .camera
+                .world_to_screen(self.game.territories[target].centroid()),
+            None => mouse_position().into(),
+        };
+        let length = start.distance(end);
+        if length < ARROW_HEAD {
+            return;
+        }
+        let color = Color {
+            a: ARROW_ALPHA,
+            ..self.theme.attack_target
+        };
+        let direction = (end - start) / length;
+        let base = end - direction * ARROW_HEAD;
+        let side = direction.perp() * ARROW_HALF_WIDTH;
+        draw_line(start.x, start.y, base.x, base.y, 4.0, color);
+        draw_triangle(end, base + side, base - side, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::turn::Phase;
+    use crate::test_support::{game_state, territory};
+
+    #[test]
+    fn drag_snaps_only_to_attackable_neighbours() {
+        let mut state = game_state(
+            vec![
+                territory("Source", 0, 5, vec![1, 2]),
+                territory("Enemy", 1, 2, vec![0]),
+                territory("Friend", 0, 1, vec![0]),
+                territory("Far", 1, 1, vec![]),
+            ],
+            0,
+        );
+        state.game.phase = Phase::Attack;
+        state.begin_attack_drag(0);
+        assert_eq!(state.attack_drag, Some(0));
+
+        for (hovered, target) in [(1, Some(1)), (2, None), (3, None), (0, None)] {
+            state.hovered_territory = Some(hovered);
+            assert_eq!(state.attack_drag_target(), target);
+        }
+
+        // Nothing to launch from a territory with a single army
+        state.begin_attack_drag(2);
+        assert_eq!(state.attack_drag, None);
+
+        // A drag still held when the phase ends goes with the selection
+        state.begin_attack_drag(0);
+        state.next_phase();
+        assert_eq!(state.attack_drag, None);
+    }
+}
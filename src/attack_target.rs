@@ -0,0 +1,98 @@
+use crate::game::turn::Phase;
+use crate::keys::Action;
+use crate::GameState;
+use macroquad::prelude::*;
+
+impl GameState {
+    // The target Tab has picked out for the selection, if it can still be
+    // attacked
+    pub fn highlighted_attack_target(&self) -> Option<usize> {
+        if self.game.phase != Phase::Attack {
+            return None;
+        }
+        let from = self.selected_territory?;
+        let targets = self.game.valid_attack_targets(from);
+        targets.get(self.attack_target?).copied()
+    }
+
+    // Moves the highlight on to the selection's next attackable neighbour,
+    // wrapping round to the first
+    pub fn cycle_attack_target(&mut self) {
+        let Some(from) = self.selected_territory else {
+            self.warn("Select one of your territories to attack from");
+            return;
+        };
+        let targets = self.game.valid_attack_targets(from);
+        if targets.is_empty() {
+            self.attack_target = None;
+            self.warn("Nothing to attack from here");
+            return;
+        }
+        self.attack_target = Some(match self.attack_target {
+            Some(index) => (index + 1) % targets.len(),
+            None => 0,
+        });
+    }
+
+    // Tab picks a target and Enter attacks it (Shift+Enter blitzes).
+    // Returns true when a key was handled.
+    pub fn handle_attack_target_input(&mut self) -> bool {
+        if self.game.phase != Phase::Attack {
+            return false;
+        }
+        if self.keys.pressed(Action::CycleTarget) {
+            self.cycle_attack_target();
+            return true;
+        }
+        if is_key_pressed(KeyCode::Enter) {
+            if let Some(target) = self.highlighted_attack_target() {
+                let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+                self.click_territory(target, shift);
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn draw_attack_target(&self) {
+        if let Some(target) = self.highlighted_attack_target() {
+            self.game.territories[target].draw_outline(&self.camera, self.theme.highlight, 6.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::turn::Phase;
+    use crate::test_support::{game_state, territory};
+
+    #[test]
+    fn tab_cycles_through_attackable_neighbours() {
+        let mut state = game_state(
+            vec![
+                territory("Home", 0, 5, vec![1, 2, 3]),
+                territory("East", 1, 1, vec![0]),
+                territory("Own", 0, 1, vec![0]),
+                territory("West", 1, 1, vec![0]),
+            ],
+            0,
+        );
+        state.game.phase = Phase::Attack;
+        assert_eq!(state.game.valid_attack_targets(0), [1, 3]);
+
+        state.select(0);
+        assert_eq!(state.highlighted_attack_target(), None);
+        state.cycle_attack_target();
+        assert_eq!(state.highlighted_attack_target(), Some(1));
+        state.cycle_attack_target();
+        assert_eq!(state.highlighted_attack_target(), Some(3));
+        state.cycle_attack_target();
+        assert_eq!(state.highlighted_attack_target(), Some(1));
+
+        // Choosing another territory starts the cycle again
+        state.select(2);
+        assert_eq!(state.attack_target, None);
+        state.cycle_attack_target();
+        assert_eq!(state.highlighted_attack_target(), None);
+    }
+}
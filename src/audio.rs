@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+
+const SOUNDS_DIR: &str = "resources/sounds";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SoundEffect {
+    Click,
+    Dice,
+    Conquest,
+}
+
+impl SoundEffect {
+    const ALL: [SoundEffect; 3] = [SoundEffect::Click, SoundEffect::Dice, SoundEffect::Conquest];
+
+    fn file_name(self) -> &'static str {
+        match self {
+            SoundEffect::Click => "click.wav",
+            SoundEffect::Dice => "dice.wav",
+            SoundEffect::Conquest => "conquest.wav",
+        }
+    }
+}
+
+#[cfg(feature = "audio")]
+type Sound = macroquad::audio::Sound;
+// Without the `audio` feature nothing is loaded and every effect is silent
+#[cfg(not(feature = "audio"))]
+type Sound = ();
+
+// Plays the game's sound effects. Any sound that fails to load is simply
+// left out, so a missing file means silence rather than a crash.
+pub struct AudioManager {
+    sounds: HashMap<SoundEffect, Sound>,
+    pub muted: bool,
+}
+
+impl AudioManager {
+    pub async fn load() -> AudioManager {
+        let mut sounds = HashMap::new();
+        for effect in SoundEffect::ALL {
+            let path = format!("{}/{}", SOUNDS_DIR, effect.file_name());
+            if let Some(sound) = load_sound(&path).await {
+                sounds.insert(effect, sound);
+            }
+        }
+        AudioManager {
+            sounds,
+            muted: false,
+        }
+    }
+
+    // Plays each distinct effect once, so a computer turn full of attacks
+    // doesn't stack dozens of dice rolls on the same frame
+    pub fn play_all(&self, effects: impl IntoIterator<Item = SoundEffect>) {
+        let mut played = Vec::new();
+        for effect in effects {
+            if self.muted || played.contains(&effect) {
+                continue;
+            }
+            played.push(effect);
+            if let Some(sound) = self.sounds.get(&effect) {
+                play_sound(sound);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "audio")]
+async fn load_sound(path: &str) -> Option<Sound> {
+    // Sounds are optional, so only complain about files that exist but are broken
+    if !std::path::Path::new(path).exists() {
+        return None;
+    }
+    match macroquad::audio::load_sound(path).await {
+        Ok(sound) => Some(sound),
+        Err(err) => {
+            eprintln!("Ignoring {}: {}", path, err);
+            None
+        }
+    }
+}
+
+#[cfg(not(feature = "audio"))]
+async fn load_sound(_path: &str) -> Option<Sound> {
+    None
+}
+
+#[cfg(feature = "audio")]
+fn play_sound(sound: &Sound) {
+    macroquad::audio::play_sound_once(sound);
+}
+
+#[cfg(not(feature = "audio"))]
+fn play_sound(_sound: &Sound) {}
@@ -0,0 +1,101 @@
+use crate::GameState;
+use macroquad::prelude::*;
+use serde::Deserialize;
+
+// Lines never get closer than this on screen; zoomed far out, every other
+// line is dropped until they're at least this far apart
+const MIN_LINE_GAP: f32 = 8.0;
+// Every this many lines is drawn stronger, to give a sense of scale. Lines
+// are the theme's text colour, faint enough to sit under everything.
+const MAJOR_EVERY: i64 = 5;
+const MINOR_ALPHA: f32 = 0.06;
+const MAJOR_ALPHA: f32 = 0.14;
+
+// A grid in world space under the territories, so there's something to
+// judge distance by when panning over sea. Off unless the config asks for it
+// (toggle with G).
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct BackgroundGrid {
+    pub enabled: bool,
+    // World units between lines, the same units as the map's outlines
+    pub spacing: f32,
+}
+
+impl Default for BackgroundGrid {
+    fn default() -> BackgroundGrid {
+        BackgroundGrid {
+            enabled: false,
+            spacing: 50.0,
+        }
+    }
+}
+
+impl BackgroundGrid {
+    // World units between the lines actually drawn at `zoom`
+    fn step(&self, zoom: f32) -> f32 {
+        let mut step = self.spacing.max(1.0);
+        while step * zoom < MIN_LINE_GAP {
+            step *= 2.0;
+        }
+        step
+    }
+}
+
+// Each grid line from `min` to `max` along one axis, as the line's index
+// (counting from the world origin) and its world position
+fn lines(min: f32, max: f32, step: f32) -> impl Iterator<Item = (i64, f32)> {
+    let first = (min / step).ceil() as i64;
+    let last = (max / step).floor() as i64;
+    (first..=last).map(move |i| (i, i as f32 * step))
+}
+
+fn line_color(index: i64, ink: Color) -> Color {
+    let a = if index % MAJOR_EVERY == 0 {
+        MAJOR_ALPHA
+    } else {
+        MINOR_ALPHA
+    };
+    Color { a, ..ink }
+}
+
+impl GameState {
+    // Under everything else on the board, through the same camera as the
+    // territories so the lines stay put on the map
+    pub fn draw_background_grid(&self) {
+        if !self.grid.enabled {
+            return;
+        }
+        let screen = vec2(screen_width(), screen_height());
+        let min = self.camera.screen_to_world(Vec2::ZERO);
+        let max = self.camera.screen_to_world(screen);
+        let step = self.grid.step(self.camera.zoom);
+        for (i, x) in lines(min.x, max.x, step) {
+            let at = self.camera.world_to_screen(vec2(x, 0.0)).x;
+            draw_line(at, 0.0, at, screen.y, 1.0, line_color(i, self.theme.text));
+        }
+        for (i, y) in lines(min.y, max.y, step) {
+            let at = self.camera.world_to_screen(vec2(0.0, y)).y;
+            draw_line(0.0, at, screen.x, at, 1.0, line_color(i, self.theme.text));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lines, BackgroundGrid};
+
+    #[test]
+    fn grid_lines_align_to_the_world_origin() {
+        let positions: Vec<(i64, f32)> = lines(-30.0, 120.0, 50.0).collect();
+        assert_eq!(positions, [(0, 0.0), (1, 50.0), (2, 100.0)]);
+
+        // Zoomed out, lines are thinned rather than crowding together
+        let grid = BackgroundGrid {
+            enabled: true,
+            spacing: 10.0,
+        };
+        assert_eq!(grid.step(1.0), 10.0);
+        assert_eq!(grid.step(0.25), 40.0);
+    }
+}
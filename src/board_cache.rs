@@ -0,0 +1,266 @@
+use crate::background::BackgroundGrid;
+use crate::camera::Camera;
+use crate::game::player::PlayerId;
+use crate::game::Territory;
+use crate::stroke::{MAX_DRAW_INDICES, MAX_DRAW_VERTICES};
+use crate::GameState;
+use macroquad::models::{draw_mesh, Mesh, Vertex};
+use macroquad::prelude::*;
+
+// Everything besides the board state that changes how the map looks; if any
+// of it differs from when the cache was drawn the image is out of date
+#[derive(Clone, Copy, PartialEq)]
+struct View {
+    offset: Vec2,
+    zoom: f32,
+    screen: Vec2,
+    show_labels: bool,
+    // Army counts are sized by it
+    hud_scale: f32,
+    grid: BackgroundGrid,
+    // Under fog of war the board looks different to each player
+    viewer: Option<PlayerId>,
+}
+
+// The map drawn once into an offscreen texture and reused every frame until
+// the board or the view changes. Highlights, the HUD and anything else that
+// follows the mouse are still drawn fresh on top each frame.
+#[derive(Default)]
+pub struct BoardCache {
+    target: Option<RenderTarget>,
+    view: Option<View>,
+    fills: FillMesh,
+    // Territories left out of the last redraw for being off screen, shown
+    // with the F3 and F1 overlays
+    culled: usize,
+}
+
+// Every territory's fill in world space, in as few meshes as macroquad
+// draws whole. Built when ownership or visibility changes and drawn through a
+// camera matching the view, so redrawing after a pan or zoom costs nothing
+// but the draw itself.
+#[derive(Default)]
+pub struct FillMesh {
+    meshes: Vec<Mesh>,
+}
+
+impl BoardCache {
+    pub fn culled(&self) -> usize {
+        self.culled
+    }
+}
+
+impl FillMesh {
+    pub fn build(territories: &[Territory], fill: impl Fn(usize) -> Color) -> FillMesh {
+        let mut meshes: Vec<Mesh> = Vec::new();
+        for (i, territory) in territories.iter().enumerate() {
+            let color = fill(i);
+            // Where each of the territory's vertices went in the current
+            // mesh. A territory too big for what's left carries on in the
+            // next one, taking the vertices its remaining triangles need.
+            let mut placed: Vec<Option<u16>> = vec![None; territory.vertices.len()];
+            for triangle in &territory.triangles {
+                let new = triangle.iter().filter(|&&v| placed[v].is_none()).count();
+                let full = meshes.last().is_none_or(|mesh| {
+                    mesh.vertices.len() + new >= MAX_DRAW_VERTICES
+                        || mesh.indices.len() + 3 >= MAX_DRAW_INDICES
+                });
+                if full {
+                    meshes.push(Mesh {
+                        vertices: Vec::new(),
+                        indices: Vec::new(),
+                        texture: None,
+                    });
+                    placed.fill(None);
+                }
+                let mesh = meshes.last_mut().unwrap();
+                for &vertex in triangle {
+                    let index = *placed[vertex].get_or_insert_with(|| {
+                        let v = territory.vertices[vertex];
+                        mesh.vertices
+                            .push(Vertex::new(v.x, v.y, 0.0, 0.0, 0.0, color));
+                        (mesh.vertices.len() - 1) as u16
+                    });
+                    mesh.indices.push(index);
+                }
+            }
+        }
+        FillMesh { meshes }
+    }
+}
+
+impl GameState {
+    pub fn draw_board(&mut self) {
+        let view = View {
+            offset: self.camera.offset,
+            zoom: self.camera.zoom,
+            screen: vec2(screen_width(), screen_height()),
+            show_labels: self.show_labels,
+            hud_scale: self.hud_scale,
+            grid: self.grid,
+            viewer: self.fog_of_war.then_some(self.game.current_player),
+        };
+
+        if self.game.board_dirty || self.board_cache.view != Some(view) {
+            let viewer_changed = self.board_cache.view.map(|old| old.viewer) != Some(view.viewer);
+            if self.game.board_dirty || viewer_changed {
+                let viewer = self.game.current_player;
+                self.board_cache.fills =
+                    FillMesh::build(&self.game.territories, |i| self.flashed_fill(viewer, i));
+                self.refresh_progress();
+            }
+            let target = match &self.board_cache.target {
+                Some(target) if target.texture.size() == view.screen => target.clone(),
+                _ => {
+                    let target = render_target(view.screen.x as u32, view.screen.y as u32);
+                    target.texture.set_filter(FilterMode::Linear);
+                    target
+                }
+            };
+            let mut camera =
+                Camera2D::from_display_rect(Rect::new(0.0, 0.0, view.screen.x, view.screen.y));
+            camera.render_target = Some(target.clone());
+            set_camera(&camera);
+            clear_background(self.theme.background);
+            self.board_cache.culled = self.draw_board_layer(&camera);
+            set_default_camera();
+
+            self.board_cache.target = Some(target);
+            self.board_cache.view = Some(view);
+            self.game.board_dirty = false;
+        }
+
+        if let Some(target) = &self.board_cache.target {
+            draw_texture_ex(
+                &target.texture,
+                0.0,
+                0.0,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(view.screen),
+                    // Render targets come out upside down
+                    flip_y: true,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+
+    pub fn draw_cull_stats(&self) {
+        if !self.show_adjacency {
+            return;
+        }
+        let total = self.game.territories.len();
+        let culled = self.board_cache.culled.min(total);
+        let text = format!(
+            "Territories drawn: {} of {} ({} off screen)",
+            total - culled,
+            total,
+            culled
+        );
+        draw_text(
+            &text,
+            10.0,
+            self.hud_size().y - 100.0,
+            18.0,
+            self.theme.muted_text,
+        );
+    }
+
+    // Draws the cached fills into whatever `screen_camera` targets, through
+    // a camera that maps world space the way the game camera does
+    pub fn draw_fills(&self, screen_camera: &Camera2D) {
+        let mut world = world_camera(&self.camera, vec2(screen_width(), screen_height()));
+        world.render_target = screen_camera.render_target.clone();
+        set_camera(&world);
+        for mesh in &self.board_cache.fills.meshes {
+            draw_mesh(mesh);
+        }
+        set_camera(screen_camera);
+    }
+}
+
+fn world_camera(camera: &Camera, screen: Vec2) -> Camera2D {
+    let size = screen / camera.zoom;
+    Camera2D::from_display_rect(Rect::new(camera.offset.x, camera.offset.y, size.x, size.y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FillMesh;
+    use crate::stroke::{MAX_DRAW_INDICES, MAX_DRAW_VERTICES};
+    use crate::test_support::{generate_stress_map, synthetic_map};
+    use macroquad::prelude::*;
+
+    fn triangles(fills: &FillMesh) -> usize {
+        fills.meshes.iter().map(|mesh| mesh.indices.len() / 3).sum()
+    }
+
+    #[test]
+    fn fills_cover_every_triangle_in_one_mesh() {
+        let territories = synthetic_map(5, 16);
+        let mesh = FillMesh::build(&territories, |_| RED);
+        let expected: usize = territories.iter().map(|t| t.triangles.len()).sum();
+        assert_eq!(mesh.meshes.len(), 1);
+        assert_eq!(triangles(&mesh), expected);
+    }
+
+    #[test]
+    fn big_maps_split_below_the_draw_limits() {
+        // Plenty of small territories, then one too big for a mesh alone
+        for territories in [synthetic_map(30, 24), synthetic_map(1, 2500)] {
+            let mesh = FillMesh::build(&territories, |_| RED);
+            let expected: usize = territories.iter().map(|t| t.triangles.len()).sum();
+            assert!(mesh.meshes.len() > 1);
+            assert_eq!(triangles(&mesh), expected);
+            for part in &mesh.meshes {
+                assert!(part.vertices.len() < MAX_DRAW_VERTICES);
+                assert!(part.indices.len() < MAX_DRAW_INDICES);
+            }
+        }
+    }
+
+    // Per-frame cost of preparing the fills: projecting every vertex and
+    // emitting its triangles, as drawing each territory did, against reusing
+    // the world-space mesh. Only the CPU side can be timed headless;
+    // `--bench-frames` times the draws in a window.
+    // cargo test --release bench_fill_mesh -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_fill_mesh() {
+        use std::time::Instant;
+        let territories = generate_stress_map(50, 64);
+        let (frames, zoom, offset) = (600, 1.5, vec2(20.0, 10.0));
+
+        let start = Instant::now();
+        let mut emitted = 0;
+        for _ in 0..frames {
+            for territory in &territories {
+                let screen: Vec<Vec2> = territory
+                    .vertices
+                    .iter()
+                    .map(|&v| (v - offset) * zoom)
+                    .collect();
+                for &[a, b, c] in &territory.triangles {
+                    emitted += std::hint::black_box([screen[a], screen[b], screen[c]]).len() / 3;
+                }
+            }
+        }
+        let per_territory = start.elapsed();
+
+        let start = Instant::now();
+        let mesh = FillMesh::build(&territories, |_| RED);
+        let build = start.elapsed();
+        assert_eq!(emitted, triangles(&mesh) * frames);
+
+        println!(
+            "{} territories x {} frames: per-territory {:?} ({:?}/frame), \
+             mesh built once in {:?} and reused",
+            territories.len(),
+            frames,
+            per_territory,
+            per_territory / frames as u32,
+            build
+        );
+    }
+}
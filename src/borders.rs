@@ -0,0 +1,165 @@
+use crate::game::geometry::shared_edges;
+use crate::game::Territory;
+use crate::stroke::Strokes;
+use crate::GameState;
+use macroquad::prelude::*;
+
+// Screen pixels of each dash and the gap after it on a sea route
+const DASH: f32 = 8.0;
+const GAP: f32 = 6.0;
+// Sea routes are drawn in the theme's colour at this alpha, and the F3
+// overlay in the text colour
+const SEA_ROUTE_ALPHA: f32 = 0.8;
+const ADJACENCY_ALPHA: f32 = 0.7;
+const RIVER_THICKNESS: f32 = 3.0;
+
+// World units two outlines may be apart and still count as one border;
+// traced maps rarely line neighbouring vertices up exactly
+const BORDER_TOLERANCE: f32 = 1.5;
+
+// Which outline edges are internal borders with a neighbour and which are
+// coastline. Worked out once per board, like the spatial index.
+pub struct Borders {
+    shared: Vec<Vec<bool>>,
+    // Per edge, the first neighbour it runs along, if any
+    neighbours: Vec<Vec<Option<usize>>>,
+}
+
+impl Borders {
+    pub fn new(territories: &[Territory]) -> Borders {
+        let shared = territories
+            .iter()
+            .map(|territory| {
+                let neighbours: Vec<&[_]> = territory
+                    .adjacent
+                    .iter()
+                    .map(|&i| territories[i].vertices.as_slice())
+                    .collect();
+                shared_edges(&territory.vertices, &neighbours, BORDER_TOLERANCE)
+            })
+            .collect();
+        let neighbours = territories
+            .iter()
+            .map(|territory| {
+                let mut along = vec![None; territory.vertices.len()];
+                for &n in &territory.adjacent {
+                    let outline = [territories[n].vertices.as_slice()];
+                    let edges = shared_edges(&territory.vertices, &outline, BORDER_TOLERANCE);
+                    for (edge, shared) in along.iter_mut().zip(edges) {
+                        if shared && edge.is_none() {
+                            *edge = Some(n);
+                        }
+                    }
+                }
+                along
+            })
+            .collect();
+        Borders { shared, neighbours }
+    }
+
+    // Per edge of the territory's outline, whether it's shared
+    pub fn of(&self, territory: usize) -> &[bool] {
+        &self.shared[territory]
+    }
+
+    // Per edge of the territory's outline, which neighbour is over it
+    pub fn neighbours_of(&self, territory: usize) -> &[Option<usize>] {
+        &self.neighbours[territory]
+    }
+}
+
+impl GameState {
+    // Sea routes have no border to show them, so each gets a dashed line
+    // between the two territories' label points. Drawn under the fills, so
+    // only the stretch over open water shows.
+    pub fn draw_sea_routes(&self) {
+        let color = Color {
+            a: SEA_ROUTE_ALPHA,
+            ..self.theme.sea_route
+        };
+        for (a, territory) in self.game.territories.iter().enumerate() {
+            for &b in territory.sea_routes.iter().filter(|&&b| a < b) {
+                let start = self.camera.world_to_screen(territory.centroid());
+                let end = self
+                    .camera
+                    .world_to_screen(self.game.territories[b].centroid());
+                draw_dashed_line(start, end, 2.0, color);
+            }
+        }
+    }
+}
+
+impl GameState {
+    // A river border is drawn over the outline along it, where it's known:
+    // the map's rivers show through fog of war like its outlines do
+    pub fn draw_river_borders(&self, on_screen: &[bool]) {
+        let color = Color {
+            a: 0.8,
+            ..self.theme.river
+        };
+        let mut strokes = Strokes::default();
+        for (a, territory) in self.game.territories.iter().enumerate() {
+            if territory.rivers.is_empty() || !on_screen[a] {
+                continue;
+            }
+            let n = territory.vertices.len();
+            for (edge, neighbour) in self.borders.neighbours_of(a).iter().enumerate() {
+                // Each border is drawn once, from the lower index
+                if !neighbour.is_some_and(|b| a < b && territory.rivers.contains(&b)) {
+                    continue;
+                }
+                let start = self.camera.world_to_screen(territory.vertices[edge]);
+                let end = self
+                    .camera
+                    .world_to_screen(territory.vertices[(edge + 1) % n]);
+                strokes.segment(start, end, RIVER_THICKNESS, color);
+            }
+        }
+        strokes.draw();
+    }
+}
+
+impl GameState {
+    // Debug view for mapmakers (F3): a line between the label points of
+    // every pair of neighbours. A loaded map's borders always go both ways;
+    // the editor is where one-way listings show up.
+    pub fn draw_adjacency_overlay(&self) {
+        if !self.show_adjacency {
+            return;
+        }
+        let point = |i: usize| {
+            self.camera
+                .world_to_screen(self.game.territories[i].centroid())
+        };
+        let color = Color {
+            a: ADJACENCY_ALPHA,
+            ..self.theme.text
+        };
+        for (a, territory) in self.game.territories.iter().enumerate() {
+            // Each pair drawn once, from the lower index
+            for &b in territory.adjacent.iter().filter(|&&b| a < b) {
+                let (start, end) = (point(a), point(b));
+                draw_line(start.x, start.y, end.x, end.y, 1.0, color);
+            }
+        }
+        for i in 0..self.game.territories.len() {
+            let center = point(i);
+            draw_circle(center.x, center.y, 4.0, color);
+        }
+    }
+}
+
+pub fn draw_dashed_line(start: Vec2, end: Vec2, thickness: f32, color: Color) {
+    let length = start.distance(end);
+    if length <= 0.0 {
+        return;
+    }
+    let direction = (end - start) / length;
+    let mut along = 0.0;
+    while along < length {
+        let from = start + direction * along;
+        let to = start + direction * (along + DASH).min(length);
+        draw_line(from.x, from.y, to.x, to.y, thickness, color);
+        along += DASH + GAP;
+    }
+}
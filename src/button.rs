@@ -0,0 +1,62 @@
+use crate::theme::Theme;
+use macroquad::prelude::*;
+
+const LABEL_SIZE: f32 = 18.0;
+
+// A labelled rectangle on the HUD that does `action` when clicked. Whoever
+// lays the buttons out decides what each action means and when it's allowed;
+// a disabled button is drawn faded and ignores clicks.
+pub struct Button<A> {
+    pub rect: Rect,
+    pub label: String,
+    pub action: A,
+    pub enabled: bool,
+}
+
+impl<A: Copy> Button<A> {
+    // The action, if `point` (in HUD units) is on an enabled button
+    pub fn hit(&self, point: Vec2) -> Option<A> {
+        (self.enabled && self.rect.contains(point)).then_some(self.action)
+    }
+
+    pub fn draw(&self, mouse: Vec2, theme: &Theme) {
+        let (fill, text) = if !self.enabled {
+            (theme.panel_at(0.5), theme.muted_text)
+        } else if self.rect.contains(mouse) {
+            (theme.panel_highlight, theme.text)
+        } else {
+            (theme.panel, theme.text)
+        };
+        let rect = self.rect;
+        draw_rectangle(rect.x, rect.y, rect.w, rect.h, fill);
+        draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2.0, theme.panel_border);
+        let dimensions = measure_text(&self.label, None, LABEL_SIZE as u16, 1.0);
+        draw_text(
+            &self.label,
+            rect.x + (rect.w - dimensions.width) / 2.0,
+            rect.y + rect.h / 2.0 + dimensions.height / 2.0,
+            LABEL_SIZE,
+            text,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Button;
+    use macroquad::prelude::*;
+
+    #[test]
+    fn only_enabled_buttons_take_clicks() {
+        let mut button = Button {
+            rect: Rect::new(10.0, 10.0, 100.0, 20.0),
+            label: "Go".to_string(),
+            action: 7,
+            enabled: true,
+        };
+        assert_eq!(button.hit(vec2(50.0, 20.0)), Some(7));
+        assert_eq!(button.hit(vec2(5.0, 20.0)), None);
+        button.enabled = false;
+        assert_eq!(button.hit(vec2(50.0, 20.0)), None);
+    }
+}
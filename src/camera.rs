@@ -0,0 +1,298 @@
+use macroquad::prelude::*;
+use serde::Deserialize;
+
+const MIN_ZOOM: f32 = 0.25;
+const MAX_ZOOM: f32 = 4.0;
+const ZOOM_STEP: f32 = 1.1;
+// How long recentring on something takes, in seconds
+pub const GLIDE_SECONDS: f32 = 0.35;
+
+// RTS-style scrolling when the pointer rests near the edge of the window.
+// Off unless the config asks for it.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct EdgeScroll {
+    pub enabled: bool,
+    // Screen pixels from the window edge that start a scroll
+    pub margin: f32,
+    // Screen pixels per second, so the feel is the same at any zoom
+    pub speed: f32,
+}
+
+impl Default for EdgeScroll {
+    fn default() -> EdgeScroll {
+        EdgeScroll {
+            enabled: false,
+            margin: 8.0,
+            speed: 600.0,
+        }
+    }
+}
+
+impl EdgeScroll {
+    // Which way to scroll, one unit per axis, for the pointer at `mouse`
+    pub fn direction(&self, mouse: Vec2, screen: Vec2) -> Vec2 {
+        let axis = |position: f32, size: f32| {
+            if position < self.margin {
+                -1.0
+            } else if position > size - self.margin {
+                1.0
+            } else {
+                0.0
+            }
+        };
+        vec2(axis(mouse.x, screen.x), axis(mouse.y, screen.y))
+    }
+}
+
+// An eased move from one view to another, started by `Camera::move_to`
+struct Glide {
+    from: (Vec2, f32),
+    to: (Vec2, f32),
+    elapsed: f32,
+    duration: f32,
+}
+
+// Slow at both ends, quickest in the middle
+fn ease_in_out(t: f32) -> f32 {
+    if t < 0.5 {
+        4.0 * t * t * t
+    } else {
+        1.0 - (2.0 - 2.0 * t).powi(3) / 2.0
+    }
+}
+
+// Maps world (map file) coordinates to screen pixels. Panned by dragging with
+// the middle mouse button and zoomed with the scroll wheel, unless
+// `wheel_zooms` is false because the wheel is being used for something else.
+pub struct Camera {
+    // World position shown at the top-left corner of the window
+    pub offset: Vec2,
+    pub zoom: f32,
+    pub edge_scroll: EdgeScroll,
+    drag_anchor: Option<Vec2>,
+    glide: Option<Glide>,
+}
+
+impl Camera {
+    pub fn new() -> Camera {
+        Camera {
+            offset: Vec2::ZERO,
+            zoom: 1.0,
+            edge_scroll: EdgeScroll::default(),
+            drag_anchor: None,
+            glide: None,
+        }
+    }
+
+    pub fn world_to_screen(&self, point: Vec2) -> Vec2 {
+        (point - self.offset) * self.zoom
+    }
+
+    pub fn screen_to_world(&self, point: Vec2) -> Vec2 {
+        point / self.zoom + self.offset
+    }
+
+    // Frames the world rectangle `min..max` in a view of `screen` pixels,
+    // leaving `margin` pixels clear on every side
+    pub fn fit(&mut self, min: Vec2, max: Vec2, screen: Vec2, margin: f32) {
+        let (offset, zoom) = Camera::fit_target(min, max, screen, margin);
+        self.snap_to(offset, zoom);
+    }
+
+    // The offset and zoom `fit` would give, for gliding there instead
+    pub fn fit_target(min: Vec2, max: Vec2, screen: Vec2, margin: f32) -> (Vec2, f32) {
+        let size = (max - min).max(Vec2::ONE);
+        let room = (screen - Vec2::splat(margin * 2.0)).max(Vec2::ONE);
+        let zoom = (room / size).min_element().clamp(MIN_ZOOM, MAX_ZOOM);
+        ((min + max) / 2.0 - screen / 2.0 / zoom, zoom)
+    }
+
+    // Jumps straight to a view, dropping any glide under way
+    pub fn snap_to(&mut self, offset: Vec2, zoom: f32) {
+        self.glide = None;
+        self.offset = offset;
+        self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+
+    // Eases from the current view to `target_pos` (the world point for the
+    // top-left corner, like `offset`) and `target_zoom` over `duration`
+    // seconds, moved along by `update`. No duration snaps.
+    pub fn move_to(&mut self, target_pos: Vec2, target_zoom: f32, duration: f32) {
+        let to = (target_pos, target_zoom.clamp(MIN_ZOOM, MAX_ZOOM));
+        if duration <= 0.0 {
+            self.snap_to(to.0, to.1);
+            return;
+        }
+        self.glide = Some(Glide {
+            from: (self.offset, self.zoom),
+            to,
+            elapsed: 0.0,
+            duration,
+        });
+    }
+
+    // Moves a glide on by `dt` seconds
+    pub fn advance_glide(&mut self, dt: f32) {
+        let Some(glide) = &mut self.glide else {
+            return;
+        };
+        glide.elapsed += dt;
+        let t = ease_in_out((glide.elapsed / glide.duration).min(1.0));
+        let ((from_offset, from_zoom), (to_offset, to_zoom)) = (glide.from, glide.to);
+        self.offset = from_offset.lerp(to_offset, t);
+        self.zoom = from_zoom + (to_zoom - from_zoom) * t;
+        if glide.elapsed >= glide.duration {
+            self.glide = None;
+        }
+    }
+
+    // Whether any of the world box `min..max` comes within `margin` pixels
+    // of a `screen`-sized view
+    pub fn sees(&self, min: Vec2, max: Vec2, screen: Vec2, margin: f32) -> bool {
+        let (min, max) = (self.world_to_screen(min), self.world_to_screen(max));
+        max.x >= -margin
+            && max.y >= -margin
+            && min.x <= screen.x + margin
+            && min.y <= screen.y + margin
+    }
+
+    // Zooms while keeping the world point under `screen_point` fixed
+    pub fn zoom_at(&mut self, screen_point: Vec2, factor: f32) {
+        self.glide = None;
+        let anchor = self.screen_to_world(screen_point);
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.offset = anchor - screen_point / self.zoom;
+    }
+
+    // The offset that puts `world` in the middle of a `screen`-sized view,
+    // at the zoom any glide is heading for
+    pub fn center_target(&self, world: Vec2, screen: Vec2) -> Vec2 {
+        let zoom = self.glide.as_ref().map_or(self.zoom, |glide| glide.to.1);
+        world - screen / zoom / 2.0
+    }
+
+    // Glides so `world` ends up in the middle, keeping the zoom
+    pub fn glide_to_center(&mut self, world: Vec2, screen: Vec2) {
+        let zoom = self.glide.as_ref().map_or(self.zoom, |glide| glide.to.1);
+        self.move_to(self.center_target(world, screen), zoom, GLIDE_SECONDS);
+    }
+
+    // Rescales the view for a window going from `old` to `new` pixels: the
+    // world point at the centre stays there, and the zoom follows the
+    // window's smaller dimension so a fitted map still fits
+    pub fn resize(&mut self, old: Vec2, new: Vec2) {
+        if old.min_element() <= 0.0 || new.min_element() <= 0.0 {
+            return;
+        }
+        // A view picked for the old size would be wrong for the new one
+        self.glide = None;
+        let center = self.screen_to_world(old / 2.0);
+        let factor = (new / old).min_element();
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.offset = center - new / 2.0 / self.zoom;
+    }
+
+    pub fn update(&mut self, wheel_zooms: bool) {
+        let mouse: Vec2 = mouse_position().into();
+
+        // Grabbing the map takes over from any glide
+        if is_mouse_button_pressed(MouseButton::Middle) {
+            self.glide = None;
+            self.drag_anchor = Some(mouse);
+        }
+        if is_mouse_button_released(MouseButton::Middle) {
+            self.drag_anchor = None;
+        }
+        if let Some(anchor) = self.drag_anchor {
+            self.offset -= (mouse - anchor) / self.zoom;
+            self.drag_anchor = Some(mouse);
+        } else if self.edge_scroll.enabled {
+            // Offsets are in world units, so the zoom scales the step down
+            let direction = self
+                .edge_scroll
+                .direction(mouse, vec2(screen_width(), screen_height()));
+            self.offset += direction * self.edge_scroll.speed * get_frame_time() / self.zoom;
+        }
+
+        // Wheel deltas differ wildly between platforms, so only the sign is used
+        let (_, wheel) = mouse_wheel();
+        if !wheel_zooms {
+            return;
+        }
+        if wheel > 0.0 {
+            self.zoom_at(mouse, ZOOM_STEP);
+        } else if wheel < 0.0 {
+            self.zoom_at(mouse, 1.0 / ZOOM_STEP);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resizing_keeps_the_centre_and_scales_with_the_window() {
+        let mut camera = Camera::new();
+        camera.fit(Vec2::ZERO, vec2(100.0, 100.0), vec2(800.0, 600.0), 0.0);
+        let before = camera.zoom;
+        camera.resize(vec2(800.0, 600.0), vec2(400.0, 600.0));
+        assert!((camera.zoom - before * 0.5).abs() < 1e-5);
+        let center = camera.screen_to_world(vec2(200.0, 300.0));
+        assert!(center.distance(vec2(50.0, 50.0)) < 1e-3);
+    }
+
+    #[test]
+    fn glides_ease_to_the_target_and_snapping_cancels_them() {
+        let mut camera = Camera::new();
+        camera.move_to(vec2(100.0, 0.0), 2.0, 1.0);
+        camera.advance_glide(0.25);
+        // Eased, so a quarter of the time covers less than a quarter of the way
+        assert!(camera.offset.x > 0.0 && camera.offset.x < 25.0);
+        camera.advance_glide(0.25);
+        assert!((camera.offset.x - 50.0).abs() < 1e-3);
+        assert!((camera.zoom - 1.5).abs() < 1e-3);
+        camera.advance_glide(1.0);
+        assert_eq!((camera.offset, camera.zoom), (vec2(100.0, 0.0), 2.0));
+        assert!(camera.glide.is_none());
+
+        // Centring mid-glide aims for the zoom it's heading to
+        camera.move_to(Vec2::ZERO, 1.0, 1.0);
+        let target = camera.center_target(vec2(400.0, 300.0), vec2(800.0, 600.0));
+        assert_eq!(target, vec2(0.0, 0.0));
+        camera.snap_to(vec2(5.0, 5.0), 3.0);
+        assert!(camera.glide.is_none());
+        assert_eq!((camera.offset, camera.zoom), (vec2(5.0, 5.0), 3.0));
+        camera.move_to(Vec2::ZERO, 1.0, 0.0);
+        assert_eq!((camera.offset, camera.zoom), (Vec2::ZERO, 1.0));
+    }
+
+    #[test]
+    fn boxes_off_screen_are_not_seen() {
+        let mut camera = Camera::new();
+        camera.offset = vec2(100.0, 0.0);
+        camera.zoom = 2.0;
+        let screen = vec2(800.0, 600.0);
+        // On screen from x = 100 to 500 in world units
+        assert!(camera.sees(vec2(450.0, 10.0), vec2(600.0, 20.0), screen, 0.0));
+        assert!(!camera.sees(vec2(0.0, 10.0), vec2(90.0, 20.0), screen, 0.0));
+        // Unless it's within the margin of the edge
+        assert!(camera.sees(vec2(0.0, 10.0), vec2(90.0, 20.0), screen, 30.0));
+        assert!(!camera.sees(vec2(200.0, 400.0), vec2(300.0, 500.0), screen, 30.0));
+    }
+
+    #[test]
+    fn edge_scroll_only_within_the_margin() {
+        let edge = EdgeScroll {
+            enabled: true,
+            margin: 10.0,
+            speed: 100.0,
+        };
+        let screen = vec2(800.0, 600.0);
+        assert_eq!(edge.direction(vec2(400.0, 300.0), screen), Vec2::ZERO);
+        assert_eq!(edge.direction(vec2(5.0, 300.0), screen), vec2(-1.0, 0.0));
+        assert_eq!(edge.direction(vec2(795.0, 595.0), screen), vec2(1.0, 1.0));
+        assert_eq!(edge.direction(vec2(400.0, 10.0), screen), Vec2::ZERO);
+    }
+}
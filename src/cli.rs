@@ -0,0 +1,220 @@
+use crate::game::hex::HexMapSource;
+use crate::game::map::{self, MapSource, StrMapSource};
+use crate::game::simulate::{Strategy, ALL_IN_MIN_ODDS};
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_MAP_PATH: &str = "resources/maps/classic.json";
+// The same map built in, for running from somewhere without the resources
+pub const BUILT_IN_MAP: &str = include_str!("../resources/maps/classic.json");
+
+const USAGE: &str = "Usage: risk [--map <path> | --hex <cols>x<rows>] [--scenario <path>] \
+                     [--distribute <armies>] [--seed <n>] \
+                     [--start-phase <reinforce|attack|fortify>] [--simulate <games> [--max-turns <turns>] [--strategy <ai|all-in>]] \
+                     [--bench-frames <frames>]";
+
+const DEFAULT_MAX_TURNS: usize = 500;
+
+// Which map was asked for. Generated maps are dealt out to the players, so
+// the source itself is only made once the player count is known.
+pub enum MapArg {
+    File(PathBuf),
+    Hex { cols: usize, rows: usize },
+}
+
+impl MapArg {
+    pub fn source(&self, players: usize) -> Box<dyn MapSource> {
+        match self {
+            MapArg::File(path) => map::source_for_path(path),
+            &MapArg::Hex { cols, rows } => Box::new(HexMapSource {
+                cols,
+                rows,
+                players,
+            }),
+        }
+    }
+}
+
+pub struct Args {
+    // None when no map was asked for, which opens the map selection screen
+    pub map: Option<MapArg>,
+    // Owners and armies to start the map from, in place of the map's own
+    pub scenario: Option<PathBuf>,
+    // Ignore the map's army counts and deal this many to each player
+    pub distribute: Option<i32>,
+    // Start from this seed rather than the clock, to replay a game exactly
+    pub seed: Option<u64>,
+    // Phase of the opening turn to begin in, checked once the game is set up
+    pub start_phase: Option<String>,
+    // Play this many computer-only games without a window and report who won
+    pub simulate: Option<usize>,
+    // Turn limit for each simulated game
+    pub max_turns: usize,
+    // How the simulated players play
+    pub strategy: Strategy,
+    // Time this many frames of drawing the map each way, then quit
+    pub bench_frames: Option<usize>,
+}
+
+impl Args {
+    // Parses the arguments after the program name
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+        let mut map = None;
+        let mut scenario = None;
+        let mut distribute = None;
+        let mut seed = None;
+        let mut start_phase = None;
+        let mut simulate = None;
+        let mut max_turns = DEFAULT_MAX_TURNS;
+        let mut strategy = Strategy::Ai;
+        let mut bench_frames = None;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--map" => {
+                    let path = args
+                        .next()
+                        .ok_or_else(|| format!("--map needs a path\n{}", USAGE))?;
+                    map = Some(MapArg::File(PathBuf::from(path)));
+                }
+                "--hex" => {
+                    let (cols, rows) = args
+                        .next()
+                        .as_deref()
+                        .and_then(parse_grid_size)
+                        .ok_or_else(|| format!("--hex needs a size like 8x6\n{}", USAGE))?;
+                    map = Some(MapArg::Hex { cols, rows });
+                }
+                "--scenario" => {
+                    let path = args
+                        .next()
+                        .ok_or_else(|| format!("--scenario needs a path\n{}", USAGE))?;
+                    scenario = Some(PathBuf::from(path));
+                }
+                "--distribute" => {
+                    let armies = args
+                        .next()
+                        .and_then(|value| value.parse::<i32>().ok())
+                        .filter(|&armies| armies > 0)
+                        .ok_or_else(|| {
+                            format!("--distribute needs a positive army count\n{}", USAGE)
+                        })?;
+                    distribute = Some(armies);
+                }
+                "--seed" => {
+                    let value = args
+                        .next()
+                        .and_then(|value| value.parse::<u64>().ok())
+                        .ok_or_else(|| format!("--seed needs a whole number\n{}", USAGE))?;
+                    seed = Some(value);
+                }
+                "--start-phase" => {
+                    let phase = args
+                        .next()
+                        .ok_or_else(|| format!("--start-phase needs a phase\n{}", USAGE))?;
+                    start_phase = Some(phase);
+                }
+                "--simulate" => {
+                    let games = args
+                        .next()
+                        .and_then(|value| value.parse::<usize>().ok())
+                        .filter(|&games| games > 0)
+                        .ok_or_else(|| format!("--simulate needs a number of games\n{}", USAGE))?;
+                    simulate = Some(games);
+                }
+                "--max-turns" => {
+                    max_turns = args
+                        .next()
+                        .and_then(|value| value.parse::<usize>().ok())
+                        .filter(|&turns| turns > 0)
+                        .ok_or_else(|| format!("--max-turns needs a number of turns\n{}", USAGE))?;
+                }
+                "--strategy" => {
+                    strategy = match args.next().as_deref() {
+                        Some("ai") => Strategy::Ai,
+                        Some("all-in") => Strategy::AllIn {
+                            min_odds: ALL_IN_MIN_ODDS,
+                        },
+                        _ => return Err(format!("--strategy needs ai or all-in\n{}", USAGE)),
+                    };
+                }
+                "--bench-frames" => {
+                    let frames = args
+                        .next()
+                        .and_then(|value| value.parse::<usize>().ok())
+                        .filter(|&frames| frames > 0)
+                        .ok_or_else(|| {
+                            format!("--bench-frames needs a number of frames\n{}", USAGE)
+                        })?;
+                    bench_frames = Some(frames);
+                }
+                "--help" | "-h" => return Err(USAGE.to_string()),
+                other => return Err(format!("Unknown argument '{}'\n{}", other, USAGE)),
+            }
+        }
+
+        if let Some(MapArg::File(path)) = &map {
+            if !path.exists() {
+                return Err(format!("Map file '{}' does not exist", path.display()));
+            }
+        }
+        if let Some(path) = &scenario {
+            if !path.exists() {
+                return Err(format!("Scenario file '{}' does not exist", path.display()));
+            }
+        }
+        Ok(Args {
+            map,
+            scenario,
+            distribute,
+            seed,
+            start_phase,
+            simulate,
+            max_turns,
+            strategy,
+            bench_frames,
+        })
+    }
+
+    // The map asked for, or the default one when there's no screen to pick
+    // from (as in a simulation). A generated map is dealt to the `configured`
+    // players, or two default ones when there are none, so one alone can't
+    // be dealt a game.
+    pub fn map_source(&self, configured: usize) -> Result<Box<dyn MapSource>, String> {
+        if configured == 1 && matches!(self.map, Some(MapArg::Hex { .. })) {
+            return Err("--hex needs two or more players, but the config lists one".to_string());
+        }
+        Ok(match &self.map {
+            Some(map) => map.source(configured.max(2)),
+            None if Path::new(DEFAULT_MAP_PATH).exists() => {
+                map::source_for_path(Path::new(DEFAULT_MAP_PATH))
+            }
+            None => Box::new(StrMapSource(BUILT_IN_MAP)),
+        })
+    }
+}
+
+// "8x6" as 8 columns by 6 rows; both must be positive
+fn parse_grid_size(value: &str) -> Option<(usize, usize)> {
+    let (cols, rows) = value.split_once(['x', 'X'])?;
+    let (cols, rows) = (cols.parse().ok()?, rows.parse().ok()?);
+    (cols > 0 && rows > 0).then_some((cols, rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Args;
+
+    #[test]
+    fn a_hex_map_needs_two_players() {
+        let args = Args::parse(["--hex".to_string(), "3x2".to_string()]).unwrap();
+        assert!(args.map_source(1).is_err());
+        for configured in [0, 2, 3] {
+            let data = args.map_source(configured).unwrap().load().unwrap();
+            let seats = data
+                .iter()
+                .filter_map(|t| Some(t.owner.as_ref()?.seat()?.0));
+            assert_eq!(seats.max(), Some(configured.max(2) - 1));
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use crate::game::combat::CombatResult;
+use crate::GameState;
+use macroquad::prelude::*;
+
+const ROLL_SECONDS: f32 = 0.6;
+const DISPLAY_SECONDS: f32 = 2.0;
+
+// A rolled attack waiting to be applied. The dice tumble briefly, settle on
+// the real result, and the casualties hit the board when the timer runs out.
+pub struct CombatAnimation {
+    pub attacker: usize,
+    pub defender: usize,
+    pub result: CombatResult,
+    elapsed: f32,
+}
+
+impl CombatAnimation {
+    pub fn new(attacker: usize, defender: usize, result: CombatResult) -> CombatAnimation {
+        CombatAnimation {
+            attacker,
+            defender,
+            result,
+            elapsed: 0.0,
+        }
+    }
+
+    fn is_rolling(&self) -> bool {
+        self.elapsed < ROLL_SECONDS
+    }
+
+    // Cosmetic tumbling faces while rolling, then the real dice
+    fn face(&self, value: u8, slot: usize) -> u8 {
+        if self.is_rolling() {
+            ((self.elapsed * 20.0) as usize + slot * 5) as u8 % 6 + 1
+        } else {
+            value
+        }
+    }
+}
+
+impl GameState {
+    pub fn start_attack_animation(&mut self, attacker: usize, defender: usize) {
+        let result = self.game.roll_attack(attacker, defender);
+        self.combat_animation = Some(CombatAnimation::new(attacker, defender, result));
+    }
+
+    // Advances the animation, applying the casualties once it finishes (or
+    // straight away if the player clicks to skip it)
+    pub fn update_combat_animation(&mut self, dt: f32, skip: bool) {
+        let Some(animation) = &mut self.combat_animation else {
+            return;
+        };
+        animation.elapsed += dt;
+        if animation.elapsed < ROLL_SECONDS + DISPLAY_SECONDS && !skip {
+            return;
+        }
+
+        if let Some(animation) = self.combat_animation.take() {
+            self.game
+                .apply_combat(animation.attacker, animation.defender, &animation.result);
+            if animation.result.conquered {
+                let moved = self.game.territories[animation.defender].armies;
+                self.animate_move(animation.attacker, animation.defender, moved);
+                self.begin_conquest_move(animation.attacker, animation.defender);
+            }
+            self.game.last_combat = Some(animation.result);
+        }
+    }
+
+    pub fn draw_combat_animation(&self) {
+        let Some(animation) = &self.combat_animation else {
+            return;
+        };
+        let die_size = 48.0;
+        let gap = 12.0;
+        // Room for three dice a side, or more if the rules allow them
+        let slots = (animation.result.attacker_dice.len())
+            .max(animation.result.defender_dice.len())
+            .max(3);
+        let width = slots as f32 * (die_size + gap) + gap;
+        let height = 2.0 * (die_size + gap) + 90.0;
+        let hud = self.hud_size();
+        let x = (hud.x - width) / 2.0;
+        let y = (hud.y - height) / 2.0;
+
+        draw_rectangle(x, y, width, height, self.theme.panel_at(0.9));
+        draw_rectangle_lines(x, y, width, height, 1.0, self.theme.panel_border);
+        let title = format!(
+            "{} attacks {}",
+            self.game.territories[animation.attacker].name,
+            self.game.territories[animation.defender].name
+        );
+        draw_text(&title, x + gap, y + 26.0, 22.0, self.theme.text);
+
+        let rows = [
+            (
+                &animation.result.attacker_dice,
+                self.theme.attacker_die,
+                self.theme.attacker_pips,
+            ),
+            (
+                &animation.result.defender_dice,
+                self.theme.defender_die,
+                self.theme.defender_pips,
+            ),
+        ];
+        for (row, (dice, face_color, pip_color)) in rows.into_iter().enumerate() {
+            let row_y = y + 40.0 + row as f32 * (die_size + gap);
+            for (slot, &value) in dice.iter().enumerate() {
+                let die_x = x + gap + slot as f32 * (die_size + gap);
+                draw_die(
+                    vec2(die_x, row_y),
+                    die_size,
+                    animation.face(value, slot + row * 3),
+                    face_color,
+                    pip_color,
+                    self.theme.panel_border,
+                );
+            }
+        }
+
+        if !animation.is_rolling() {
+            let mut summary = format!(
+                "Attacker -{}  Defender -{}",
+                animation.result.attacker_losses, animation.result.defender_losses
+            );
+            if animation.result.conquered {
+                summary.push_str("  Conquered!");
+            }
+            draw_text(
+                &summary,
+                x + gap,
+                y + height - 16.0,
+                20.0,
+                self.theme.highlight,
+            );
+        }
+    }
+}
+
+fn draw_die(top_left: Vec2, size: f32, value: u8, face: Color, pip: Color, edge: Color) {
+    draw_rectangle(top_left.x, top_left.y, size, size, face);
+    draw_rectangle_lines(top_left.x, top_left.y, size, size, 2.0, edge);
+
+    // Pip positions on a 3x3 grid, indexed by column and row
+    let pips: &[(f32, f32)] = match value {
+        1 => &[(1.0, 1.0)],
+        2 => &[(0.0, 0.0), (2.0, 2.0)],
+        3 => &[(0.0, 0.0), (1.0, 1.0), (2.0, 2.0)],
+        4 => &[(0.0, 0.0), (2.0, 0.0), (0.0, 2.0), (2.0, 2.0)],
+        5 => &[(0.0, 0.0), (2.0, 0.0), (1.0, 1.0), (0.0, 2.0), (2.0, 2.0)],
+        _ => &[
+            (0.0, 0.0),
+            (2.0, 0.0),
+            (0.0, 1.0),
+            (2.0, 1.0),
+            (0.0, 2.0),
+            (2.0, 2.0),
+        ],
+    };
+    let step = size / 4.0;
+    for &(col, row) in pips {
+        draw_circle(
+            top_left.x + step * (col + 1.0),
+            top_left.y + step * (row + 1.0),
+            size / 12.0,
+            pip,
+        );
+    }
+}
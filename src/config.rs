@@ -0,0 +1,224 @@
+use crate::background::BackgroundGrid;
+use crate::camera::EdgeScroll;
+use crate::game::ai::AiDifficulty;
+use crate::game::combat::CombatRules;
+use crate::game::concede::ConcedeRule;
+use crate::game::fortify::FortifyRule;
+use crate::game::player::{Pattern, Player, PlayerId};
+use crate::game::reinforce::RulesConfig;
+use crate::game::setup::{StartMode, Weighting};
+use crate::game::territory::{FillRule, Terrain};
+use crate::game::transform::MapTransform;
+use crate::label_detail::LabelDetail;
+use crate::progress_bar::ProgressMeasure;
+use crate::theme::ThemeConfig;
+use macroquad::prelude::*;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Clone, Deserialize)]
+pub struct PlayerConfig {
+    pub name: String,
+    // RGB, 0-255 per channel
+    pub color: [u8; 3],
+    // Fill pattern; defaults to one picked by seat
+    #[serde(default)]
+    pub pattern: Option<Pattern>,
+    // Let the computer take this player's turns
+    #[serde(default)]
+    pub ai: bool,
+    // "easy", "normal" or "hard", for computer players
+    #[serde(default)]
+    pub difficulty: AiDifficulty,
+}
+
+impl PlayerConfig {
+    pub fn to_player(&self, id: PlayerId) -> Player {
+        let [r, g, b] = self.color;
+        Player {
+            id,
+            name: self.name.clone(),
+            color: Color::from_rgba(r, g, b, 255),
+            pattern: self.pattern.unwrap_or(Pattern::for_seat(id.0)),
+            is_ai: self.ai,
+            difficulty: self.difficulty,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct WindowConfig {
+    pub title: String,
+    pub width: i32,
+    pub height: i32,
+    // Frames a second to hold the board to while nothing is moving;
+    // uncapped when absent
+    pub max_fps: Option<u32>,
+    // Ask the driver to sync to the display (true) or not (false); its own
+    // default when absent
+    pub vsync: Option<bool>,
+}
+
+impl Default for WindowConfig {
+    fn default() -> WindowConfig {
+        WindowConfig {
+            title: "Interactive Risk Map".to_string(),
+            width: 800,
+            height: 600,
+            max_fps: None,
+            vsync: None,
+        }
+    }
+}
+
+// Game settings that aren't part of a particular map. Every section is
+// optional so a missing or partial config still gives a playable game.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct Config {
+    pub players: Vec<PlayerConfig>,
+    pub window: WindowConfig,
+    // Draw territory names on the map from the start (toggle with N)
+    pub show_labels: bool,
+    pub combat: CombatRules,
+    pub rules: RulesConfig,
+    // Hide territories the player to move neither owns nor borders
+    pub fog_of_war: bool,
+    // "even_odd" (the default) or "non_zero" for clicking on maps whose
+    // outlines cross over themselves
+    pub fill_rule: FillRule,
+    pub edge_scroll: EdgeScroll,
+    // Camera zooms below which names, then army counts, are left off the
+    // board so a large map stays legible zoomed out
+    pub label_detail: LabelDetail,
+    // Lines under the map to judge distance by; the map editor snaps to
+    // the same spacing
+    pub grid: BackgroundGrid,
+    // Seconds each human gets per turn before it passes automatically;
+    // untimed when absent. Computer turns are never timed.
+    pub turn_time_limit: Option<f32>,
+    // Cap on armies in any one territory; unlimited when absent
+    pub max_armies: Option<i32>,
+    // "map" (owners from the map file), "random" or "draft"
+    pub start_mode: StartMode,
+    // Where --distribute puts armies: "even" or "borders"
+    pub distribute_weighting: Weighting,
+    // Players take turns placing their starting armies one at a time
+    // before the first turn, as in the classic game
+    pub initial_placement: bool,
+    // "attack" or "fortify" to open the first turn there with its
+    // reinforcements already placed, for testing; --start-phase overrides it
+    pub start_phase: Option<String>,
+    // Fortify one army per click rather than picking a count (toggle with F)
+    pub step_fortify: bool,
+    // Ask before each attack or blitz, showing the odds, since neither can
+    // be undone
+    pub confirm_attacks: bool,
+    // Turn off the move suggestions shown while H is held (toggle with Shift+H)
+    pub hide_hints: bool,
+    // Fit the view to each human's territories when their turn starts
+    // (Home does it at any time)
+    pub center_on_turn: bool,
+    // Connect territories whose outlines share at least two vertices within
+    // this many world units, on top of the borders the map lists
+    pub infer_adjacency: Option<f32>,
+    // Drop outline vertices closer than this many world units to the line
+    // through their neighbours, for very detailed imported maps
+    pub simplify_tolerance: Option<f32>,
+    // Flip, turn, scale and move the map's outlines as it loads, for imports
+    // that come in the wrong way round
+    pub map_transform: MapTransform,
+    // Territories to treat as capitals, beyond any the map marks
+    pub capitals: Vec<String>,
+    // Holding every capital wins the game
+    pub capital_victory: bool,
+    // Allies holding the whole map between them win together
+    pub team_victory: bool,
+    // "mountain", "river" or "plains" by territory name, over the map's own
+    pub terrain: HashMap<String, Terrain>,
+    // Size of HUD text and panels, 1.0 being normal; + and - change it in
+    // game and save it back here
+    pub hud_scale: Option<f32>,
+    // "neutral" leaves a conceding player's territories neutral; "leader"
+    // hands them to whoever is furthest ahead
+    pub concede_rule: ConcedeRule,
+    // "adjacent" moves fortifying armies only to a neighbour; "connected"
+    // anywhere along a chain of the player's own territories
+    pub fortify_rule: FortifyRule,
+    // "territories" or "armies" for the bar across the top showing each
+    // player's share, or "off"
+    pub progress_bar: ProgressMeasure,
+    // "light" or "dark" to start in, with any colours to change:
+    // {"base": "dark", "colors": {"selection": [255, 0, 255]}} (Y switches)
+    pub theme: ThemeConfig,
+    // Moves actions to other keys, as `"save": "F5"`; anything left out
+    // keeps its usual key
+    pub keys: HashMap<String, String>,
+}
+
+// Sets one top-level key in the config file, keeping every other setting
+// (though not the file's layout). A missing file is started afresh.
+pub fn save_setting<P: AsRef<Path>>(path: P, key: &str, value: Value) -> io::Result<()> {
+    let path = path.as_ref();
+    let mut settings = match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).map_err(io::Error::other)?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Value::Object(Map::new()),
+        Err(err) => return Err(err),
+    };
+    let Some(object) = settings.as_object_mut() else {
+        return Err(io::Error::other("the config isn't a JSON object"));
+    };
+    object.insert(key.to_string(), value);
+    let json = serde_json::to_string_pretty(&settings).map_err(io::Error::other)?;
+    fs::write(path, json + "\n")
+}
+
+impl Config {
+    pub fn load<P: AsRef<Path>>(path: P) -> Config {
+        let path = path.as_ref();
+        let file_content = match fs::read_to_string(path) {
+            Ok(file_content) => file_content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Config::default(),
+            Err(err) => {
+                eprintln!("Ignoring {}: {}", path.display(), err);
+                return Config::default();
+            }
+        };
+        let mut config: Config = serde_json::from_str(&file_content).unwrap_or_else(|err| {
+            eprintln!("Ignoring {}: {}", path.display(), err);
+            Config::default()
+        });
+        if let Err(message) = config.rules.validate() {
+            eprintln!("Using the classic rules instead: {}", message);
+            config.rules = RulesConfig::default();
+        }
+        if let Err(message) = config.combat.validate() {
+            eprintln!("Using the classic dice instead: {}", message);
+            config.combat = CombatRules::default();
+        }
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::save_setting;
+    use std::fs;
+
+    #[test]
+    fn saving_a_setting_keeps_the_others() {
+        let path = std::env::temp_dir().join("risk_save_setting.json");
+        fs::write(&path, r#"{"show_labels": true, "hud_scale": 1.0}"#).unwrap();
+        save_setting(&path, "hud_scale", 1.5.into()).unwrap();
+        let saved: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["hud_scale"], 1.5);
+        assert_eq!(saved["show_labels"], true);
+        fs::remove_file(&path).unwrap();
+    }
+}
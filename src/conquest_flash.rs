@@ -0,0 +1,77 @@
+use crate::game::player::PlayerId;
+use crate::GameState;
+use macroquad::prelude::*;
+
+// How long a conquered territory takes to fade from white into its new
+// owner's color
+const FLASH_SECONDS: f32 = 0.5;
+
+impl GameState {
+    // Starts (or restarts) `territory`'s flash; each territory keeps its own
+    // timer, so a run of conquests flashes one after another
+    pub fn flash(&mut self, territory: usize) {
+        self.flash_timers.insert(territory, FLASH_SECONDS);
+    }
+
+    // The board is redrawn every frame a flash is running, and once more as
+    // the last one ends so it settles on the plain fill
+    pub fn update_flashes(&mut self, dt: f32) {
+        if self.flash_timers.is_empty() {
+            return;
+        }
+        for remaining in self.flash_timers.values_mut() {
+            *remaining -= dt;
+        }
+        self.flash_timers.retain(|_, remaining| *remaining > 0.0);
+        self.game.board_dirty = true;
+    }
+
+    // The fill seen by `viewer`, washed towards white while it flashes.
+    // Territories fog of war hides don't flash, so the fog gives nothing away.
+    pub fn flashed_fill(&self, viewer: PlayerId, territory: usize) -> Color {
+        let fill = self.fill_seen_by(viewer, territory);
+        match self.flash_timers.get(&territory) {
+            Some(remaining) if self.visible_to(viewer, territory) => {
+                let white = (remaining / FLASH_SECONDS).clamp(0.0, 1.0);
+                let mix = |channel: f32| channel + (1.0 - channel) * white;
+                Color::new(mix(fill.r), mix(fill.g), mix(fill.b), fill.a)
+            }
+            _ => fill,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::player::PlayerId;
+    use crate::test_support::{game_state, territory};
+    use macroquad::prelude::*;
+
+    #[test]
+    fn conquests_flash_independently() {
+        let mut state = game_state(
+            vec![
+                territory("A", 0, 3, vec![1, 2]),
+                territory("B", 0, 1, vec![0]),
+                territory("C", 0, 1, vec![0]),
+            ],
+            0,
+        );
+        let settled = state.fill_seen_by(PlayerId(0), 1);
+        state.flash(1);
+        assert_eq!(state.flashed_fill(PlayerId(0), 1), WHITE);
+        state.update_flashes(0.3);
+        state.flash(2);
+        state.update_flashes(0.1);
+        let fading = state.flashed_fill(PlayerId(0), 1);
+        assert!(fading != settled && fading != WHITE);
+
+        // B has settled while C is still going
+        state.update_flashes(0.2);
+        assert_eq!(state.flashed_fill(PlayerId(0), 1), settled);
+        assert_ne!(state.flashed_fill(PlayerId(0), 2), settled);
+        state.game.board_dirty = false;
+        state.update_flashes(0.3);
+        assert!(state.flash_timers.is_empty() && state.game.board_dirty);
+    }
+}
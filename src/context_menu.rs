@@ -0,0 +1,214 @@
+use crate::game::turn::Phase;
+use crate::replay::ReplayAction;
+use crate::GameState;
+use macroquad::prelude::*;
+
+const MENU_WIDTH: f32 = 190.0;
+const ITEM_HEIGHT: f32 = 24.0;
+
+// What a right-click menu entry does to the territory it was opened on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MenuAction {
+    PlaceArmy,
+    AttackFrom,
+    AttackThis,
+    FortifyFrom,
+    FortifyTo,
+    Inspect,
+}
+
+impl MenuAction {
+    fn label(self) -> &'static str {
+        match self {
+            MenuAction::PlaceArmy => "Place an army",
+            MenuAction::AttackFrom => "Attack from here",
+            MenuAction::AttackThis => "Attack this",
+            MenuAction::FortifyFrom => "Fortify from here",
+            MenuAction::FortifyTo => "Fortify to here",
+            MenuAction::Inspect => "Inspect",
+        }
+    }
+}
+
+// A menu opened by right-clicking a territory, anchored where the click was
+pub struct ContextMenu {
+    territory: usize,
+    // In HUD units
+    position: Vec2,
+    actions: Vec<MenuAction>,
+}
+
+impl ContextMenu {
+    // HUD rect of each entry, kept inside a `hud`-sized window near its edges
+    fn items(&self, hud: Vec2) -> impl Iterator<Item = (Rect, MenuAction)> + '_ {
+        let height = ITEM_HEIGHT * self.actions.len() as f32;
+        let x = self.position.x.min(hud.x - MENU_WIDTH).max(0.0);
+        let y = self.position.y.min(hud.y - height).max(0.0);
+        self.actions.iter().enumerate().map(move |(i, &action)| {
+            let rect = Rect::new(x, y + ITEM_HEIGHT * i as f32, MENU_WIDTH, ITEM_HEIGHT);
+            (rect, action)
+        })
+    }
+}
+
+impl GameState {
+    // Only what the current player could legally do with `territory` right
+    // now; inspecting is always allowed
+    pub fn menu_actions(&self, territory: usize) -> Vec<MenuAction> {
+        let mut actions = Vec::new();
+        let player = self.game.current_player;
+        let owned = self.game.territories[territory].owner == Some(player);
+        let selected = self.selected_territory.filter(|&s| s != territory);
+        match self.game.phase {
+            Phase::Reinforce => {
+                if owned && self.unbuffered_reinforcements() > 0 && self.game.room_in(territory) > 0
+                {
+                    actions.push(MenuAction::PlaceArmy);
+                }
+            }
+            Phase::Attack => {
+                let targets = &self.game.territories[territory].adjacent;
+                if owned && targets.iter().any(|&n| self.game.can_attack(territory, n)) {
+                    actions.push(MenuAction::AttackFrom);
+                }
+                if selected.is_some_and(|source| self.game.can_attack(source, territory)) {
+                    actions.push(MenuAction::AttackThis);
+                }
+            }
+            Phase::Fortify => {
+                if owned && self.game.territories[territory].armies > 1 {
+                    actions.push(MenuAction::FortifyFrom);
+                }
+                let can_receive = |source: usize| {
+                    owned
+                        && self.game.territories[source].owner == Some(player)
+                        && self.game.territories[source].armies > 1
+                        && self.game.check_fortify_route(source, territory).is_ok()
+                        && self.game.room_in(territory) > 0
+                };
+                if selected.is_some_and(can_receive) {
+                    actions.push(MenuAction::FortifyTo);
+                }
+            }
+            Phase::Claim | Phase::InitialPlacement => {}
+        }
+        actions.push(MenuAction::Inspect);
+        actions
+    }
+
+    pub fn open_context_menu(&mut self, territory: usize, position: Vec2) {
+        self.context_menu = Some(ContextMenu {
+            territory,
+            position,
+            actions: self.menu_actions(territory),
+        });
+    }
+
+    // Takes every click and Escape while the menu is open. A click on an
+    // entry runs it; anywhere else (or Escape) just closes the menu.
+    pub fn handle_context_menu_input(&mut self) -> bool {
+        let Some(menu) = &self.context_menu else {
+            return false;
+        };
+        let left = is_mouse_button_pressed(MouseButton::Left);
+        let right = is_mouse_button_pressed(MouseButton::Right);
+        if is_key_pressed(KeyCode::Escape) || right {
+            self.context_menu = None;
+            return true;
+        }
+        if left {
+            let mouse = self.hud_mouse();
+            let territory = menu.territory;
+            let chosen = menu
+                .items(self.hud_size())
+                .find(|(rect, _)| rect.contains(mouse))
+                .map(|(_, action)| action);
+            self.context_menu = None;
+            if let Some(action) = chosen {
+                self.run_menu_action(territory, action);
+            }
+        }
+        true
+    }
+
+    fn run_menu_action(&mut self, territory: usize, action: MenuAction) {
+        // Legality is checked again, as the board may have moved on
+        if !self.menu_actions(territory).contains(&action) {
+            return;
+        }
+        match action {
+            MenuAction::PlaceArmy => {
+                self.select(territory);
+                self.replay
+                    .record(ReplayAction::PlaceReinforcement { territory });
+                self.game.place_reinforcement(territory);
+            }
+            MenuAction::AttackFrom | MenuAction::FortifyFrom => self.select(territory),
+            MenuAction::AttackThis => self.click_territory(territory, false),
+            MenuAction::FortifyTo => {
+                if let Some(source) = self.selected_territory {
+                    self.begin_fortify_move(source, territory);
+                }
+            }
+            MenuAction::Inspect => self.inspect(territory),
+        }
+    }
+
+    pub fn draw_context_menu(&self) {
+        let Some(menu) = &self.context_menu else {
+            return;
+        };
+        let mouse = self.hud_mouse();
+        for (rect, action) in menu.items(self.hud_size()) {
+            let fill = if rect.contains(mouse) {
+                self.theme.panel_highlight
+            } else {
+                self.theme.panel
+            };
+            draw_rectangle(rect.x, rect.y, rect.w, rect.h, fill);
+            draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, self.theme.panel_border);
+            draw_text(
+                action.label(),
+                rect.x + 8.0,
+                rect.y + ITEM_HEIGHT - 7.0,
+                20.0,
+                self.theme.text,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MenuAction;
+    use crate::game::turn::Phase;
+    use crate::test_support::{game_state, territory};
+
+    #[test]
+    fn menu_lists_only_legal_actions() {
+        let mut state = game_state(
+            vec![
+                territory("Home", 0, 4, vec![1, 2]),
+                territory("Enemy", 1, 2, vec![0]),
+                territory("Back", 0, 1, vec![0]),
+            ],
+            0,
+        );
+        use MenuAction::*;
+        assert_eq!(state.menu_actions(0), vec![PlaceArmy, Inspect]);
+        assert_eq!(state.menu_actions(1), vec![Inspect]);
+
+        state.game.phase = Phase::Attack;
+        assert_eq!(state.menu_actions(0), vec![AttackFrom, Inspect]);
+        assert_eq!(state.menu_actions(1), vec![Inspect]);
+        state.select(0);
+        assert_eq!(state.menu_actions(1), vec![AttackThis, Inspect]);
+
+        state.game.phase = Phase::Fortify;
+        assert_eq!(state.menu_actions(2), vec![FortifyTo, Inspect]);
+        // Back has a single army, so nothing can move out of it
+        state.select(2);
+        assert_eq!(state.menu_actions(2), vec![Inspect]);
+        assert_eq!(state.menu_actions(0), vec![FortifyFrom, Inspect]);
+    }
+}
@@ -0,0 +1,127 @@
+use crate::GameState;
+use macroquad::prelude::*;
+
+// macroquad 0.4 has no gamepad API, so the controller is read through the
+// keys a controller mapper (Steam Input, AntiMicroX, ...) sends for it: the
+// D-pad as the arrow keys, A as Enter and B as Escape.
+const DPAD: [(KeyCode, Vec2); 4] = [
+    (KeyCode::Up, vec2(0.0, -1.0)),
+    (KeyCode::Down, vec2(0.0, 1.0)),
+    (KeyCode::Left, vec2(-1.0, 0.0)),
+    (KeyCode::Right, vec2(1.0, 0.0)),
+];
+
+// Neighbours more than this far off the pressed direction are never picked
+const MIN_ALIGNMENT: f32 = 0.3;
+
+impl GameState {
+    // The neighbour of `from` that best continues in `direction` (screen
+    // space, y down), weighing how far off-axis it is against how far away
+    // its centroid sits
+    pub fn nearest_neighbor_in_direction(&self, from: usize, direction: Vec2) -> Option<usize> {
+        let origin = self.game.territories[from].centroid();
+        let direction = direction.normalize_or_zero();
+        self.game.territories[from]
+            .adjacent
+            .iter()
+            .filter_map(|&neighbour| {
+                let offset = self.game.territories[neighbour].centroid() - origin;
+                let distance = offset.length();
+                let alignment = offset.dot(direction) / distance;
+                (distance > 0.0 && alignment > MIN_ALIGNMENT)
+                    .then_some((neighbour, distance / alignment))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(neighbour, _)| neighbour)
+    }
+
+    // Returns true when a controller button was handled, so the same key
+    // press isn't acted on again as a keyboard shortcut. Enter and Escape
+    // only go to the cursor while it's on screen.
+    pub fn handle_controller_input(&mut self) -> bool {
+        // The mouse takes over from the cursor as soon as it's used
+        if mouse_delta_position() != Vec2::ZERO || is_mouse_button_pressed(MouseButton::Left) {
+            self.focused_territory = None;
+        }
+        for (key, direction) in DPAD {
+            if !is_key_pressed(key) {
+                continue;
+            }
+            // The cursor starts on the selection, or any territory we own
+            let from = self
+                .focused_territory
+                .or(self.selected_territory)
+                .or_else(|| {
+                    self.game
+                        .territories
+                        .iter()
+                        .position(|t| t.owner == Some(self.game.current_player))
+                });
+            self.focused_territory = match self.focused_territory {
+                Some(focused) => self
+                    .nearest_neighbor_in_direction(focused, direction)
+                    .or(Some(focused)),
+                None => from,
+            };
+            return true;
+        }
+
+        let Some(focused) = self.focused_territory else {
+            return false;
+        };
+        if is_key_pressed(KeyCode::Enter) {
+            self.click_territory(focused, false);
+            return true;
+        }
+        if is_key_pressed(KeyCode::Escape) {
+            self.focused_territory = None;
+            self.clear_selection();
+            return true;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::player::PlayerId;
+    use crate::game::Territory;
+    use crate::test_support::game_state;
+    use macroquad::prelude::*;
+
+    fn square(name: &str, x: f32, y: f32, adjacent: Vec<usize>) -> Territory {
+        let vertices = vec![
+            vec2(x, y),
+            vec2(x + 10.0, y),
+            vec2(x + 10.0, y + 10.0),
+            vec2(x, y + 10.0),
+        ];
+        Territory::new(name.to_string(), vertices, Some(PlayerId(0)), 1, adjacent)
+    }
+
+    #[test]
+    fn picks_the_neighbour_in_the_pressed_direction() {
+        // A plus shape around a centre square, plus a farther one to the
+        // right that loses out to the closer neighbour
+        let state = game_state(
+            vec![
+                square("Centre", 0.0, 0.0, vec![1, 2, 3, 4, 5]),
+                square("Up", 0.0, -20.0, vec![0]),
+                square("Down", 0.0, 20.0, vec![0]),
+                square("Left", -20.0, 0.0, vec![0]),
+                square("Right", 20.0, 0.0, vec![0]),
+                square("Far right", 60.0, 5.0, vec![0]),
+            ],
+            0,
+        );
+        let step = |direction| state.nearest_neighbor_in_direction(0, direction);
+        assert_eq!(step(vec2(0.0, -1.0)), Some(1));
+        assert_eq!(step(vec2(0.0, 1.0)), Some(2));
+        assert_eq!(step(vec2(-1.0, 0.0)), Some(3));
+        assert_eq!(step(vec2(1.0, 0.0)), Some(4));
+        assert_eq!(
+            state.nearest_neighbor_in_direction(1, vec2(0.0, -1.0)),
+            None
+        );
+    }
+}
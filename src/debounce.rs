@@ -0,0 +1,50 @@
+// Shortest gap between two clicks that both act on the board. Anything
+// quicker is a bouncing switch or a twitch, not a second click; real double
+// clicks are several times slower.
+const CLICK_COOLDOWN: f64 = 0.08;
+
+// Turns the mouse button's per-frame state into single clicks: one on the
+// frame a press starts, none while it stays held, and none for a press
+// coming within the cooldown of the last click or release
+#[derive(Default)]
+pub struct ClickDebounce {
+    held: bool,
+    last_change: Option<f64>,
+}
+
+impl ClickDebounce {
+    // Call once a frame with whether the button is down; true when this
+    // frame's press should act
+    pub fn click(&mut self, down: bool, now: f64) -> bool {
+        let (started, released) = (down && !self.held, !down && self.held);
+        self.held = down;
+        let settled = self.last_change.is_none_or(|at| now - at >= CLICK_COOLDOWN);
+        if released || (started && settled) {
+            self.last_change = Some(now);
+        }
+        started && settled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClickDebounce;
+
+    #[test]
+    fn a_held_button_clicks_once() {
+        let mut debounce = ClickDebounce::default();
+        let frame = 1.0 / 60.0;
+        let clicks = (0..30)
+            .filter(|&i| debounce.click(true, i as f64 * frame))
+            .count();
+        assert_eq!(clicks, 1);
+
+        // Let go and bounced straight back down: still the same click
+        assert!(!debounce.click(false, 30.0 * frame));
+        assert!(!debounce.click(true, 31.0 * frame));
+        assert!(!debounce.click(false, 32.0 * frame));
+        // A real second click a little later counts
+        assert!(debounce.click(true, 1.0));
+        assert!(!debounce.click(true, 1.0 + frame));
+    }
+}
@@ -0,0 +1,68 @@
+use crate::GameState;
+use macroquad::prelude::*;
+
+const LINE_HEIGHT: f32 = 18.0;
+const WIDTH: f32 = 260.0;
+
+impl GameState {
+    // What the overlay shows, given this frame's rate and length. Only
+    // counters the game keeps anyway, so it costs nothing while it's shut.
+    pub fn debug_lines(&self, fps: i32, frame_time: f32) -> Vec<String> {
+        let total = self.game.territories.len();
+        let culled = self.board_cache.culled().min(total);
+        let mut animations = self.movements.len() + self.flash_timers.len();
+        if self.combat_animation.is_some() {
+            animations += 1;
+        }
+        vec![
+            format!("FPS: {}", fps),
+            format!("Frame: {:.1} ms", frame_time * 1000.0),
+            format!("Territories: {} drawn, {} culled", total - culled, culled),
+            format!("Animations: {}", animations),
+            format!(
+                "Camera: ({:.0}, {:.0}) x{:.2}",
+                self.camera.offset.x, self.camera.offset.y, self.camera.zoom
+            ),
+        ]
+    }
+
+    // F1, for tuning the renderer: in the top-left corner over everything
+    // else in the HUD
+    pub fn draw_debug_overlay(&self) {
+        if !self.show_debug {
+            return;
+        }
+        let lines = self.debug_lines(get_fps(), get_frame_time());
+        let (x, y) = (10.0, 60.0);
+        let height = LINE_HEIGHT * lines.len() as f32 + 8.0;
+        draw_rectangle(x, y, WIDTH, height, Color::new(0.0, 0.0, 0.0, 0.7));
+        for (i, line) in lines.iter().enumerate() {
+            draw_text(
+                line,
+                x + 6.0,
+                y + LINE_HEIGHT * (i as f32 + 1.0),
+                16.0,
+                WHITE,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{game_state, territory};
+
+    #[test]
+    fn debug_lines_report_the_frame_and_the_board() {
+        let mut state = game_state(
+            vec![territory("A", 0, 3, vec![1]), territory("B", 1, 3, vec![0])],
+            0,
+        );
+        state.flash(1);
+        let lines = state.debug_lines(60, 1.0 / 60.0);
+        assert_eq!(lines[0], "FPS: 60");
+        assert_eq!(lines[1], "Frame: 16.7 ms");
+        assert_eq!(lines[2], "Territories: 2 drawn, 0 culled");
+        assert_eq!(lines[3], "Animations: 1");
+    }
+}
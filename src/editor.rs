@@ -0,0 +1,502 @@
+use crate::camera::Camera;
+use crate::game::geometry;
+use crate::game::map::{MapFile, MapOwner, TerritoryData, TerritoryRef};
+use crate::game::player::PlayerId;
+use crate::game::territory::Terrain;
+use crate::game::validate::one_way_adjacencies;
+use crate::keys::{Action, KeyBindings};
+use crate::theme::Theme;
+use macroquad::prelude::*;
+use std::fs;
+
+const EXPORT_PATH: &str = "resources/editor_export.json";
+// Screen pixels an arrow key moves the selection
+const NUDGE: f32 = 10.0;
+// A shift-drag shorter than this is a shift-click
+const CLICK_SLOP: f32 = 4.0;
+// Screen pixels from an existing vertex that a new one lands on it instead
+const SNAP_RADIUS: f32 = 8.0;
+
+// Where a click puts a new vertex while snapping is on. Landing exactly on a
+// neighbour's vertex is what lets shared edges and inferred adjacency find
+// the border.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SnapTarget {
+    Vertex(Vec2),
+    Grid(Vec2),
+}
+
+impl SnapTarget {
+    fn point(self) -> Vec2 {
+        match self {
+            SnapTarget::Vertex(point) | SnapTarget::Grid(point) => point,
+        }
+    }
+}
+
+// Map editor: click to place the vertices of a new territory, Enter to close
+// the polygon and type its name, then export everything as map JSON.
+// Shift-click or shift-drag a box to select territories and move, re-own or
+// delete them together. G snaps new vertices to nearby ones or the grid.
+pub struct Editor {
+    territories: Vec<TerritoryData>,
+    current: Vec<Vec2>,
+    // Some while the name of a just-closed polygon is being typed
+    naming: Option<String>,
+    message: Option<String>,
+    // Indices into `territories`, in ascending order
+    selected: Vec<usize>,
+    // Screen point a shift-drag started from, while the button is held
+    band_start: Option<Vec2>,
+    snap: bool,
+    // World units between grid points, the background grid's spacing
+    grid_spacing: f32,
+}
+
+impl Editor {
+    // Starts from an existing set of territories so a map can be extended
+    pub fn new(territories: Vec<TerritoryData>, grid_spacing: f32) -> Editor {
+        Editor {
+            territories,
+            current: Vec::new(),
+            naming: None,
+            message: None,
+            selected: Vec::new(),
+            band_start: None,
+            snap: false,
+            grid_spacing: grid_spacing.max(1.0),
+        }
+    }
+
+    // The nearest vertex within `SNAP_RADIUS` pixels of `world`, on any
+    // territory or the polygon in progress, or else the nearest grid point.
+    // None while snapping is off.
+    fn snap_target(&self, world: Vec2, zoom: f32) -> Option<SnapTarget> {
+        if !self.snap {
+            return None;
+        }
+        let vertex = self
+            .territories
+            .iter()
+            .flat_map(|territory| territory.vertices.iter().map(|&v| Vec2::from(v)))
+            .chain(self.current.iter().copied())
+            .map(|v| (v, v.distance(world)))
+            .filter(|&(_, distance)| distance * zoom <= SNAP_RADIUS)
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        Some(match vertex {
+            Some((v, _)) => SnapTarget::Vertex(v),
+            None => SnapTarget::Grid((world / self.grid_spacing).round() * self.grid_spacing),
+        })
+    }
+
+    // Where a click at `world` puts a vertex
+    fn place_point(&self, world: Vec2, zoom: f32) -> Vec2 {
+        self.snap_target(world, zoom)
+            .map_or(world, SnapTarget::point)
+    }
+
+    // Text entry swallows the keyboard, so global shortcuts must check this
+    pub fn is_typing(&self) -> bool {
+        self.naming.is_some()
+    }
+
+    pub fn handle_input(&mut self, camera: &Camera) {
+        if let Some(name) = &mut self.naming {
+            while let Some(c) = get_char_pressed() {
+                if !c.is_control() {
+                    name.push(c);
+                }
+            }
+            if is_key_pressed(KeyCode::Backspace) {
+                name.pop();
+            }
+            if is_key_pressed(KeyCode::Enter) {
+                self.finish_territory();
+            } else if is_key_pressed(KeyCode::Escape) {
+                self.naming = None;
+            }
+            return;
+        }
+        // Drain characters typed outside of naming so they don't leak into it
+        while get_char_pressed().is_some() {}
+
+        let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+        let mouse: Vec2 = mouse_position().into();
+        if is_mouse_button_pressed(MouseButton::Left) {
+            if shift {
+                self.band_start = Some(mouse);
+            } else {
+                let point = self.place_point(camera.screen_to_world(mouse), camera.zoom);
+                self.current.push(point);
+            }
+        }
+        if is_mouse_button_released(MouseButton::Left) {
+            if let Some(start) = self.band_start.take() {
+                if start.distance(mouse) < CLICK_SLOP {
+                    self.toggle_at(camera.screen_to_world(mouse));
+                } else {
+                    let (a, b) = (camera.screen_to_world(start), camera.screen_to_world(mouse));
+                    self.select_in_box(a.min(b), a.max(b));
+                }
+            }
+        }
+        if !self.selected.is_empty() {
+            self.handle_selection_input(camera);
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.current.pop();
+        }
+        if is_key_pressed(KeyCode::G) {
+            self.snap = !self.snap;
+        }
+        if is_key_pressed(KeyCode::Enter) {
+            if self.current.len() >= 3 {
+                self.naming = Some(String::new());
+            } else {
+                self.message = Some("A territory needs at least 3 vertices".to_string());
+            }
+        }
+        if is_key_pressed(KeyCode::X) {
+            self.message = Some(match self.export(EXPORT_PATH) {
+                Ok(()) => format!(
+                    "Exported {} territories to {}",
+                    self.territories.len(),
+                    EXPORT_PATH
+                ),
+                Err(err) => format!("Export failed: {}", err),
+            });
+        }
+    }
+
+    // Keys that act on every selected territory at once
+    fn handle_selection_input(&mut self, camera: &Camera) {
+        let step = NUDGE / camera.zoom;
+        for (key, direction) in [
+            (KeyCode::Left, vec2(-1.0, 0.0)),
+            (KeyCode::Right, vec2(1.0, 0.0)),
+            (KeyCode::Up, vec2(0.0, -1.0)),
+            (KeyCode::Down, vec2(0.0, 1.0)),
+        ] {
+            if is_key_pressed(key) {
+                self.move_selected(direction * step);
+            }
+        }
+        const OWNER_KEYS: [KeyCode; 10] = [
+            KeyCode::Key0,
+            KeyCode::Key1,
+            KeyCode::Key2,
+            KeyCode::Key3,
+            KeyCode::Key4,
+            KeyCode::Key5,
+            KeyCode::Key6,
+            KeyCode::Key7,
+            KeyCode::Key8,
+            KeyCode::Key9,
+        ];
+        if let Some(digit) = OWNER_KEYS.iter().position(|&key| is_key_pressed(key)) {
+            // 0 for neutral, otherwise the player's seat counting from 1
+            let owner = digit.checked_sub(1).map(PlayerId);
+            self.set_selected_owner(owner);
+        }
+        if is_key_pressed(KeyCode::Delete) {
+            let count = self.delete_selected();
+            self.message = Some(format!("Deleted {} territories", count));
+        }
+        if is_key_pressed(KeyCode::Escape) {
+            self.selected.clear();
+        }
+    }
+
+    // Shift-click: adds or removes the territory under the point
+    fn toggle_at(&mut self, world: Vec2) {
+        let hit = self.territories.iter().rposition(|territory| {
+            let outline: Vec<Vec2> = territory.vertices.iter().map(|&v| v.into()).collect();
+            geometry::contains(&outline, world)
+        });
+        if let Some(i) = hit {
+            match self.selected.binary_search(&i) {
+                Ok(at) => {
+                    self.selected.remove(at);
+                }
+                Err(at) => self.selected.insert(at, i),
+            }
+        }
+    }
+
+    // Adds every territory lying wholly inside the box
+    fn select_in_box(&mut self, min: Vec2, max: Vec2) {
+        for (i, territory) in self.territories.iter().enumerate() {
+            let inside = territory.vertices.iter().all(|&v| {
+                let v = Vec2::from(v);
+                v.cmpge(min).all() && v.cmple(max).all()
+            });
+            if inside && !self.selected.contains(&i) {
+                self.selected.push(i);
+            }
+        }
+        self.selected.sort_unstable();
+    }
+
+    fn move_selected(&mut self, delta: Vec2) {
+        for &i in &self.selected {
+            for vertex in &mut self.territories[i].vertices {
+                *vertex = (Vec2::from(*vertex) + delta).into();
+            }
+        }
+    }
+
+    fn set_selected_owner(&mut self, owner: Option<PlayerId>) {
+        for &i in &self.selected {
+            self.territories[i].owner = owner.map(MapOwner::Seat);
+        }
+    }
+
+    // Removes the selection and every border or sea route naming it, so
+    // the map still loads. Returns how many were deleted.
+    fn delete_selected(&mut self) -> usize {
+        let removed: Vec<TerritoryData> = self
+            .selected
+            .iter()
+            .map(|&i| self.territories[i].clone())
+            .collect();
+        for &i in self.selected.iter().rev() {
+            self.territories.remove(i);
+        }
+        self.selected.clear();
+        for territory in &mut self.territories {
+            let kept = |neighbour: &TerritoryRef| !removed.iter().any(|t| neighbour.points_to(t));
+            territory.adjacent.retain(kept);
+            territory.sea_routes.retain(kept);
+            territory.rivers.retain(kept);
+        }
+        removed.len()
+    }
+
+    fn finish_territory(&mut self) {
+        let Some(name) = self.naming.take() else {
+            return;
+        };
+        let name = name.trim().to_string();
+        if name.is_empty() || self.territories.iter().any(|t| t.name == name) {
+            self.message = Some("Territory names must be unique and non-empty".to_string());
+            self.naming = Some(name);
+            return;
+        }
+
+        // Loading gives it the next id free
+        self.territories.push(TerritoryData {
+            id: None,
+            name,
+            vertices: self.current.drain(..).map(|v| [v.x, v.y]).collect(),
+            owner: Some(MapOwner::Seat(PlayerId(0))),
+            armies: 1,
+            selected: false,
+            adjacent: Vec::new(),
+            sea_routes: Vec::new(),
+            rivers: Vec::new(),
+            capital: false,
+            terrain: Terrain::Plains,
+            continent: None,
+            bonus: 0,
+            capital_of: None,
+        });
+    }
+
+    pub fn export(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let file = MapFile::new(self.territories.clone());
+        fs::write(path, serde_json::to_string_pretty(&file)?)?;
+        Ok(())
+    }
+
+    // A line in the warning colour from each territory to any neighbour
+    // that doesn't list it back, with a dot at the end that does the
+    // listing. Returns how many.
+    fn draw_one_way_borders(&self, camera: &Camera, theme: &Theme) -> usize {
+        let one_way = one_way_adjacencies(&self.territories);
+        let point = |i: usize| {
+            let outline: Vec<Vec2> = self.territories[i]
+                .vertices
+                .iter()
+                .map(|&v| v.into())
+                .collect();
+            camera.world_to_screen(geometry::label_point(&outline))
+        };
+        for &(territory, neighbour) in &one_way {
+            let (start, end) = (point(territory), point(neighbour));
+            draw_line(start.x, start.y, end.x, end.y, 3.0, theme.warning);
+            draw_circle(start.x, start.y, 6.0, theme.warning);
+        }
+        one_way.len()
+    }
+
+    pub fn draw(&self, camera: &Camera, theme: &Theme, keys: &KeyBindings) {
+        for (t, territory) in self.territories.iter().enumerate() {
+            let (thickness, color) = if self.selected.contains(&t) {
+                (4.0, theme.highlight)
+            } else {
+                (2.0, theme.editor_outline)
+            };
+            let n = territory.vertices.len();
+            for i in 0..n {
+                let [x1, y1] = territory.vertices[i];
+                let [x2, y2] = territory.vertices[(i + 1) % n];
+                let start = camera.world_to_screen(vec2(x1, y1));
+                let end = camera.world_to_screen(vec2(x2, y2));
+                draw_line(start.x, start.y, end.x, end.y, thickness, color);
+            }
+        }
+        if let Some(start) = self.band_start {
+            let end: Vec2 = mouse_position().into();
+            let (min, size) = (start.min(end), (end - start).abs());
+            draw_rectangle(
+                min.x,
+                min.y,
+                size.x,
+                size.y,
+                Color {
+                    a: 0.15,
+                    ..theme.highlight
+                },
+            );
+            draw_rectangle_lines(min.x, min.y, size.x, size.y, 1.0, theme.highlight);
+        }
+
+        // The polygon in progress, plus a rubber-band edge to where the next
+        // vertex would go
+        let screen: Vec<Vec2> = self
+            .current
+            .iter()
+            .map(|&v| camera.world_to_screen(v))
+            .collect();
+        for pair in screen.windows(2) {
+            draw_line(
+                pair[0].x,
+                pair[0].y,
+                pair[1].x,
+                pair[1].y,
+                2.0,
+                theme.editor_polygon,
+            );
+        }
+        let mouse: Vec2 = mouse_position().into();
+        let snap = self.snap_target(camera.screen_to_world(mouse), camera.zoom);
+        let next = snap.map_or(mouse, |target| camera.world_to_screen(target.point()));
+        if let Some(&last) = screen.last() {
+            let edge = Color {
+                a: 0.6,
+                ..theme.editor_polygon
+            };
+            draw_line(last.x, last.y, next.x, next.y, 1.0, edge);
+        }
+        match snap {
+            Some(SnapTarget::Vertex(_)) => {
+                draw_circle_lines(next.x, next.y, 7.0, 2.0, theme.editor_snap)
+            }
+            Some(SnapTarget::Grid(_)) => {
+                draw_rectangle_lines(next.x - 4.0, next.y - 4.0, 8.0, 8.0, 1.5, theme.editor_snap)
+            }
+            None => {}
+        }
+        for point in &screen {
+            draw_circle(point.x, point.y, 4.0, theme.editor_polygon);
+        }
+        let one_way = self.draw_one_way_borders(camera, theme);
+
+        let help = match &self.naming {
+            Some(name) => format!("Name: {}_  (Enter: save, Esc: cancel)", name),
+            None => format!(
+                "EDITOR  Click: add vertex  Backspace: undo  Enter: close polygon  G: snap ({})  X: export  {}: exit  ({} vertices)",
+                if self.snap { "on" } else { "off" },
+                keys.name(Action::Editor),
+                self.current.len()
+            ),
+        };
+        draw_text(&help, 10.0, 20.0, 20.0, theme.text);
+        if let Some(message) = &self.message {
+            draw_text(message, 10.0, 44.0, 20.0, theme.warning);
+        } else if one_way > 0 {
+            let warning = format!(
+                "{} border(s) listed by only one side, lined out on the map; it won't load until both list them",
+                one_way
+            );
+            draw_text(&warning, 10.0, 44.0, 20.0, theme.warning);
+        }
+        if !self.selected.is_empty() {
+            let help = format!(
+                "{} selected  Arrows: move  0-9: owner (0 neutral)  Delete: remove  Esc: deselect",
+                self.selected.len()
+            );
+            draw_text(&help, 10.0, 68.0, 20.0, theme.text);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Editor, SnapTarget};
+    use crate::game::map::TerritoryData;
+    use crate::game::player::PlayerId;
+    use macroquad::prelude::*;
+
+    fn editor() -> Editor {
+        let data: Vec<TerritoryData> = serde_json::from_str(
+            r#"[
+                {"name": "A", "vertices": [[0, 0], [10, 0], [10, 10], [0, 10]], "owner": 0,
+                 "armies": 1, "selected": false, "adjacent": ["B"]},
+                {"name": "B", "vertices": [[10, 0], [20, 0], [20, 10], [10, 10]], "owner": 0,
+                 "armies": 1, "selected": false, "adjacent": ["A", "C"]},
+                {"name": "C", "vertices": [[20, 0], [30, 0], [30, 10], [20, 10]], "owner": 0,
+                 "armies": 1, "selected": false, "adjacent": ["B"], "sea_routes": ["A"]}
+            ]"#,
+        )
+        .unwrap();
+        Editor::new(data, 5.0)
+    }
+
+    #[test]
+    fn selection_acts_on_every_territory_in_it() {
+        let mut editor = editor();
+        // The box covers A and B whole but only part of C
+        editor.select_in_box(vec2(-1.0, -1.0), vec2(25.0, 11.0));
+        assert_eq!(editor.selected, [0, 1]);
+        editor.toggle_at(vec2(15.0, 5.0));
+        editor.toggle_at(vec2(25.0, 5.0));
+        assert_eq!(editor.selected, [0, 2]);
+
+        editor.move_selected(vec2(0.0, 5.0));
+        editor.set_selected_owner(Some(PlayerId(2)));
+        assert_eq!(editor.territories[0].vertices[0], [0.0, 5.0]);
+        assert_eq!(editor.territories[1].vertices[0], [10.0, 0.0]);
+        assert_eq!(editor.territories[2].owner, Some(PlayerId(2).into()));
+
+        editor.toggle_at(vec2(25.0, 7.0));
+        assert_eq!(editor.delete_selected(), 1);
+        let names: Vec<&str> = editor.territories.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["B", "C"]);
+        assert_eq!(editor.territories[0].adjacent, ["C"]);
+        assert!(editor.territories[1].sea_routes.is_empty());
+        assert!(editor.selected.is_empty());
+    }
+
+    #[test]
+    fn snapping_prefers_nearby_vertices_over_the_grid() {
+        let mut editor = editor();
+        let zoom = 2.0;
+        // Off by default
+        assert_eq!(editor.place_point(vec2(10.3, 9.8), zoom), vec2(10.3, 9.8));
+
+        editor.snap = true;
+        // 8 pixels is 4 world units at this zoom
+        assert_eq!(
+            editor.snap_target(vec2(10.3, 9.8), zoom),
+            Some(SnapTarget::Vertex(vec2(10.0, 10.0)))
+        );
+        assert_eq!(
+            editor.snap_target(vec2(16.0, 4.0), zoom),
+            Some(SnapTarget::Grid(vec2(15.0, 5.0)))
+        );
+        // The polygon being drawn can be snapped back onto too
+        editor.current.push(vec2(41.0, 41.0));
+        assert_eq!(editor.place_point(vec2(42.0, 43.0), zoom), vec2(41.0, 41.0));
+    }
+}
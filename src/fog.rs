@@ -0,0 +1,85 @@
+use crate::game::player::PlayerId;
+use crate::game::turn::Phase;
+use crate::GameState;
+use macroquad::prelude::*;
+
+impl GameState {
+    // Whether `player` can see who holds `territory` and with how many
+    // armies: always without fog of war, otherwise only for territories they
+    // own or that border one they own. Checked against the live board, so
+    // conquering a territory lifts the fog around it straight away. The
+    // draft is played in the open.
+    pub fn visible_to(&self, player: PlayerId, territory: usize) -> bool {
+        if !self.fog_of_war || self.game.phase == Phase::Claim {
+            return true;
+        }
+        let owned = |i: usize| self.game.territories[i].owner == Some(player);
+        owned(territory)
+            || self.game.territories[territory]
+                .adjacent
+                .iter()
+                .any(|&i| owned(i))
+    }
+
+    // The fill `player` should see for a territory
+    pub fn fill_seen_by(&self, player: PlayerId, territory: usize) -> Color {
+        if !self.visible_to(player, territory) {
+            return self.theme.fog;
+        }
+        self.game.territories[territory]
+            .owner
+            .map_or(self.theme.neutral, |id| self.game.player(id).color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::combat::CombatResult;
+    use crate::game::player::PlayerId;
+    use crate::test_support::{game_state, territory};
+
+    // A line of four: A (player 0) - B - C - D, the rest player 1's
+    fn line() -> crate::GameState {
+        game_state(
+            vec![
+                territory("A", 0, 5, vec![1]),
+                territory("B", 1, 1, vec![0, 2]),
+                territory("C", 1, 1, vec![1, 3]),
+                territory("D", 1, 1, vec![2]),
+            ],
+            0,
+        )
+    }
+
+    #[test]
+    fn everything_is_visible_without_fog() {
+        let state = line();
+        assert!((0..4).all(|i| state.visible_to(PlayerId(0), i)));
+    }
+
+    #[test]
+    fn fog_hides_territories_beyond_the_border() {
+        let mut state = line();
+        state.fog_of_war = true;
+        let seen: Vec<bool> = (0..4).map(|i| state.visible_to(PlayerId(0), i)).collect();
+        assert_eq!(seen, [true, true, false, false]);
+        // Player 1 holds B to D, which all border each other or A
+        assert!((0..4).all(|i| state.visible_to(PlayerId(1), i)));
+    }
+
+    #[test]
+    fn conquest_lifts_the_fog_immediately() {
+        let mut state = line();
+        state.fog_of_war = true;
+        let result = CombatResult {
+            attacker_dice: vec![6],
+            defender_dice: vec![1],
+            attacker_losses: 0,
+            defender_losses: 1,
+            conquered: true,
+        };
+        state.game.apply_combat(0, 1, &result);
+        assert!(state.visible_to(PlayerId(0), 2));
+        assert!(!state.visible_to(PlayerId(0), 3));
+    }
+}
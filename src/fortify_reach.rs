@@ -0,0 +1,66 @@
+use crate::game::turn::Phase;
+use crate::GameState;
+use macroquad::prelude::*;
+
+// The theme's reach colour, laid faintly over the territories it shades
+const REACH_ALPHA: f32 = 0.3;
+
+impl GameState {
+    // Where the selected territory can fortify to, shaded while choosing a
+    // destination. With the connected rule this runs well past its borders.
+    pub fn fortify_reachable(&self, from: usize) -> Vec<usize> {
+        if self.game.phase != Phase::Fortify
+            || self.game.territories[from].owner != Some(self.game.current_player)
+            || self.game.territories[from].armies < 2
+        {
+            return Vec::new();
+        }
+        self.game.fortify_reachable(from)
+    }
+
+    pub fn draw_fortify_reach(&self) {
+        let Some(from) = self.selected_territory else {
+            return;
+        };
+        let wash = Color {
+            a: REACH_ALPHA,
+            ..self.theme.reach
+        };
+        for territory in self.fortify_reachable(from) {
+            self.game.territories[territory].draw_wash(&self.camera, wash);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::fortify::{FortifyError, FortifyRule};
+    use crate::game::turn::Phase;
+    use crate::test_support::{game_state, territory};
+
+    #[test]
+    fn reach_follows_the_fortify_rule() {
+        // A chain A-B-C of one player's, cut off from D by an enemy
+        let mut state = game_state(
+            vec![
+                territory("A", 0, 5, vec![1]),
+                territory("B", 0, 1, vec![0, 2]),
+                territory("C", 0, 1, vec![1, 3]),
+                territory("X", 1, 1, vec![2, 4]),
+                territory("D", 0, 1, vec![3]),
+            ],
+            0,
+        );
+        state.game.phase = Phase::Fortify;
+        assert_eq!(state.fortify_reachable(0), [1]);
+        assert_eq!(state.game.fortify(0, 2, 1), Err(FortifyError::NotAdjacent));
+
+        state.game.fortify_rule = FortifyRule::Connected;
+        assert_eq!(state.fortify_reachable(0), [1, 2]);
+        // Nothing to send from a lone army
+        assert!(state.fortify_reachable(1).is_empty());
+        assert_eq!(state.game.fortify(0, 4, 1), Err(FortifyError::NotConnected));
+        assert_eq!(state.game.fortify(0, 2, 3), Ok(3));
+        assert_eq!(state.game.territories[2].armies, 4);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::game::geometry;
+use crate::{darken, GameState, BORDER_THICKNESS};
+use macroquad::prelude::*;
+use std::time::{Duration, Instant};
+
+// Frames drawn before timing starts, while the window and caches settle
+const WARMUP_FRAMES: usize = 30;
+
+// `--bench-frames`: the board drawn in a real window four ways, timing
+// whole frames (draw calls, the GPU and the swap) rather than just the CPU
+// side the headless benches can reach. Vsync is off for it, so the numbers
+// aren't all one refresh interval. A 50-territory map comes from
+// `--hex 10x5`.
+pub async fn run_frame_bench(mut state: GameState, frames: usize) {
+    // Each territory triangulated afresh and filled every frame, as before
+    // triangles were cached on the territory
+    let triangulated = time_frames(&mut state, frames, |state| {
+        let viewer = state.game.current_player;
+        for (i, territory) in state.game.territories.iter().enumerate() {
+            let fill = state.fill_seen_by(viewer, i);
+            let screen: Vec<Vec2> = territory
+                .vertices
+                .iter()
+                .map(|&v| state.camera.world_to_screen(v))
+                .collect();
+            for [a, b, c] in geometry::triangulate(&territory.vertices) {
+                draw_triangle(screen[a], screen[b], screen[c], fill);
+            }
+        }
+    })
+    .await;
+    // Every territory's fill and outline as its own draw calls, as the board
+    // was drawn before fills were batched into one mesh and cached
+    let per_territory = time_frames(&mut state, frames, |state| {
+        let viewer = state.game.current_player;
+        for (i, territory) in state.game.territories.iter().enumerate() {
+            let fill = state.fill_seen_by(viewer, i);
+            territory.draw_wash(&state.camera, fill);
+            territory.draw_outline(&state.camera, darken(fill, 0.6), BORDER_THICKNESS);
+        }
+    })
+    .await;
+    // The batched board, but redrawn from scratch every frame
+    let rebuilt = time_frames(&mut state, frames, |state| {
+        state.game.board_dirty = true;
+        state.draw_board();
+    })
+    .await;
+    // The batched board drawn once and reused, as on a static board
+    let cached = time_frames(&mut state, frames, GameState::draw_board).await;
+
+    println!(
+        "{} territories, {} frames each:",
+        state.game.territories.len(),
+        frames
+    );
+    for (name, time) in [
+        ("triangulated every frame (fills only)", triangulated),
+        ("per territory (fills and outlines only)", per_territory),
+        ("batched, redrawn every frame", rebuilt),
+        ("batched and cached", cached),
+    ] {
+        println!(
+            "  {:<42} {:>8.3} ms/frame",
+            name,
+            time.as_secs_f64() * 1000.0
+        );
+    }
+}
+
+async fn time_frames(
+    state: &mut GameState,
+    frames: usize,
+    mut draw: impl FnMut(&mut GameState),
+) -> Duration {
+    state.game.board_dirty = true;
+    let mut start = Instant::now();
+    for frame in 0..WARMUP_FRAMES + frames {
+        if frame == WARMUP_FRAMES {
+            start = Instant::now();
+        }
+        clear_background(state.theme.background);
+        draw(state);
+        next_frame().await;
+    }
+    start.elapsed() / frames as u32
+}
@@ -0,0 +1,97 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Holds an idle board to at most `max_fps` frames a second by sleeping off
+// the rest of each frame, so a game left open doesn't keep a core busy.
+// Frames with something moving on screen run as fast as they can.
+pub struct FrameLimiter {
+    frame_time: Option<Duration>,
+    frame_start: Instant,
+}
+
+impl FrameLimiter {
+    pub fn new(max_fps: Option<u32>) -> FrameLimiter {
+        FrameLimiter {
+            frame_time: max_fps
+                .filter(|&fps| fps > 0)
+                .map(|fps| Duration::from_secs_f64(1.0 / fps as f64)),
+            frame_start: Instant::now(),
+        }
+    }
+
+    // Call once a frame, after drawing it
+    pub fn wait(&mut self, animating: bool) {
+        let left = self.time_left(Instant::now(), animating);
+        if !left.is_zero() {
+            thread::sleep(left);
+        }
+        self.frame_start = Instant::now();
+    }
+
+    // What's left of the current frame at `now`, to be slept off
+    fn time_left(&self, now: Instant, animating: bool) -> Duration {
+        match self.frame_time.filter(|_| !animating) {
+            Some(frame_time) => frame_time.saturating_sub(now - self.frame_start),
+            None => Duration::ZERO,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FrameLimiter;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn idle_frames_are_held_to_the_cap() {
+        let mut limiter = FrameLimiter::new(Some(50));
+        let start = Instant::now();
+        limiter.frame_start = start;
+        let drawn = start + Duration::from_millis(5);
+        assert_eq!(limiter.time_left(drawn, false), Duration::from_millis(15));
+        // A frame that ran long isn't made any longer
+        let late = start + Duration::from_millis(30);
+        assert_eq!(limiter.time_left(late, false), Duration::ZERO);
+
+        // Nothing is held back while animating, or without a cap
+        assert_eq!(limiter.time_left(drawn, true), Duration::ZERO);
+        limiter.frame_time = None;
+        assert_eq!(limiter.time_left(drawn, false), Duration::ZERO);
+    }
+
+    // The process's own CPU time, user and system, from /proc (Linux only)
+    fn cpu_time() -> Option<Duration> {
+        let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+        // Fields after the command name, which is in brackets and may hold
+        // spaces; utime and stime are the 14th and 15th, in clock ticks
+        let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+        let ticks: u64 =
+            fields.get(11)?.parse::<u64>().ok()? + fields.get(12)?.parse::<u64>().ok()?;
+        // Nearly always 100 a second on Linux
+        Some(Duration::from_millis(ticks * 10))
+    }
+
+    // How busy an idle board keeps a core, capped at 60 frames a second
+    // against uncapped, with nothing drawn between frames
+    // cargo test --release bench_idle_cpu -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_idle_cpu() {
+        let busy = |max_fps| {
+            let mut limiter = FrameLimiter::new(max_fps);
+            let (start, cpu) = (Instant::now(), cpu_time()?);
+            while start.elapsed() < Duration::from_secs(2) {
+                limiter.wait(false);
+            }
+            Some((cpu_time()? - cpu).as_secs_f64() / start.elapsed().as_secs_f64())
+        };
+        match (busy(Some(60)), busy(None)) {
+            (Some(capped), Some(uncapped)) => println!(
+                "idle CPU: {:.1}% capped at 60 fps, {:.1}% uncapped",
+                capped * 100.0,
+                uncapped * 100.0
+            ),
+            _ => println!("CPU time needs /proc/self/stat"),
+        }
+    }
+}
@@ -0,0 +1,242 @@
+// The rules of the game: territories, combat, reinforcement and the turn
+// loop. Nothing in here draws or reads input (macroquad is only used for its
+// vector and color types), so whole games can also be played out headless.
+pub mod ai;
+pub mod cards;
+pub mod combat;
+pub mod concede;
+pub mod continent;
+pub mod diplomacy;
+pub mod events;
+pub mod fortify;
+pub mod geojson;
+pub mod geometry;
+#[cfg(test)]
+mod golden;
+pub mod hex;
+pub mod map;
+pub mod odds;
+pub mod player;
+pub mod reinforce;
+pub mod rng;
+pub mod save;
+pub mod setup;
+pub mod simulate;
+pub mod statistics;
+pub mod svg;
+pub mod territory;
+pub mod transform;
+pub mod turn;
+pub mod undo;
+pub mod validate;
+
+use territory::Terrain;
+pub use territory::Territory;
+
+use cards::Card;
+use combat::{CombatResult, CombatRules};
+use concede::ConcedeRule;
+use continent::Continent;
+use events::GameEvent;
+use fortify::FortifyRule;
+use macroquad::math::Vec2;
+use map::{MapLoadError, MapOwner, TerritoryData};
+use player::{Player, PlayerId};
+use reinforce::RulesConfig;
+use rng::Rng;
+use statistics::GameStatistics;
+use std::collections::{HashMap, HashSet};
+use turn::Phase;
+use undo::Action;
+
+pub struct Game {
+    pub territories: Vec<Territory>,
+    pub players: Vec<Player>,
+    pub continents: Vec<Continent>,
+    pub current_player: PlayerId,
+    pub phase: Phase,
+    pub reinforcements_remaining: i32,
+    // What the game was started from; everything random follows from it
+    pub seed: u64,
+    pub rng: Rng,
+    pub combat_rules: CombatRules,
+    pub rules: RulesConfig,
+    // Most armies a territory may hold, for variants that cap them
+    pub max_armies: Option<i32>,
+    // Whether holding every capital wins the game on its own
+    pub capital_victory: bool,
+    // Players who won't attack each other, as pairs lowest seat first
+    pub alliances: HashSet<(PlayerId, PlayerId)>,
+    // Whether allies holding the whole map between them win together
+    pub team_victory: bool,
+    pub concede_rule: ConcedeRule,
+    pub fortify_rule: FortifyRule,
+    pub last_combat: Option<CombatResult>,
+    // Actions taken this turn that undo can still take back
+    pub undo_stack: Vec<Action>,
+    pub hands: HashMap<PlayerId, Vec<Card>>,
+    // Card sets cashed in so far by anyone; each one is worth more
+    pub trades_completed: usize,
+    // Armies each seat still has to place during the opening placement,
+    // empty when the game doesn't open with one
+    pub initial_armies: Vec<i32>,
+    // Whether the current player has taken a territory, earning a card
+    pub conquered_this_turn: bool,
+    // Rounds a computer player has attacked this phase, for the per-turn cap
+    pub ai_attacks: usize,
+    // Everything that has happened so far, oldest first
+    pub events: Vec<GameEvent>,
+    // Running totals for the end-of-game summary; see `statistics()`
+    pub statistics: GameStatistics,
+    // Set whenever ownership, armies or selection change, so a renderer that
+    // caches the board knows to redraw it
+    pub board_dirty: bool,
+}
+
+impl Game {
+    // Checks and builds a loaded map. With no `players` given, every owner on
+    // the map gets a default player, and owners can't be given by name.
+    pub fn from_map(
+        data: &[TerritoryData],
+        players: Vec<Player>,
+        seed: u64,
+    ) -> Result<Game, MapLoadError> {
+        let mut resolved = data.to_vec();
+        map::resolve_owner_names(&mut resolved, &players)?;
+        let data = resolved.as_slice();
+        let player_count = if players.is_empty() {
+            data.iter()
+                .filter_map(|t| t.owner.as_ref().and_then(MapOwner::seat))
+                .map(|owner| owner.0 + 1)
+                .max()
+                .unwrap_or(0)
+        } else {
+            players.len()
+        };
+        validate::validate_map(data, player_count).map_err(MapLoadError::Invalid)?;
+        for i in validate::mixed_winding(data) {
+            eprintln!(
+                "{}: outline winds the other way to the rest of the map",
+                data[i].name
+            );
+        }
+        let territories = map::build_territories(data)?;
+        validate::validate_outlines(&territories).map_err(MapLoadError::Invalid)?;
+        let continents = map::build_continents(data)?;
+
+        let mut game = Game::from_territories(territories, seed);
+        if !players.is_empty() {
+            game.players = players;
+        }
+        game.continents = continents;
+        game.begin_reinforcements();
+        Ok(game)
+    }
+
+    // Without a players config, every owner on the map gets a default player
+    pub fn from_territories(mut territories: Vec<Territory>, seed: u64) -> Game {
+        // Numbered as they're listed, as a map without ids would be
+        for (i, territory) in territories.iter_mut().enumerate() {
+            territory.id = i as u32;
+        }
+        let player_count = territories
+            .iter()
+            .filter_map(|t| t.owner)
+            .map(|owner| owner.0 + 1)
+            .max()
+            .unwrap_or(0);
+        let mut game = Game {
+            players: (0..player_count).map(Player::with_default_color).collect(),
+            territories,
+            continents: Vec::new(),
+            current_player: PlayerId(0),
+            phase: Phase::Reinforce,
+            reinforcements_remaining: 0,
+            seed,
+            rng: Rng::new(seed),
+            combat_rules: CombatRules::default(),
+            rules: RulesConfig::default(),
+            max_armies: None,
+            capital_victory: false,
+            alliances: HashSet::new(),
+            team_victory: false,
+            concede_rule: ConcedeRule::default(),
+            fortify_rule: FortifyRule::default(),
+            last_combat: None,
+            undo_stack: Vec::new(),
+            hands: HashMap::new(),
+            trades_completed: 0,
+            initial_armies: Vec::new(),
+            conquered_this_turn: false,
+            ai_attacks: 0,
+            events: Vec::new(),
+            statistics: GameStatistics::default(),
+            board_dirty: true,
+        };
+        game.begin_reinforcements();
+        game
+    }
+
+    // Every owner id is checked against the player list when the map loads,
+    // so a missing player is a bug rather than bad input
+    pub fn player(&self, id: PlayerId) -> &Player {
+        self.players
+            .iter()
+            .find(|player| player.id == id)
+            .expect("territory owned by an unknown player")
+    }
+
+    // How many more armies a territory can take before hitting the cap
+    pub fn room_in(&self, territory: usize) -> i32 {
+        self.max_armies.map_or(i32::MAX, |cap| {
+            (cap - self.territories[territory].armies).max(0)
+        })
+    }
+
+    pub fn owner_name(&self, owner: Option<PlayerId>) -> &str {
+        owner.map_or("Neutral", |id| self.player(id).name.as_str())
+    }
+
+    // Makes the named territories capitals, on top of any the map marks.
+    // Unknown names are ignored.
+    pub fn designate_capitals(&mut self, names: &[String]) {
+        for territory in &mut self.territories {
+            if names.contains(&territory.name) {
+                territory.is_capital = true;
+            }
+        }
+    }
+
+    // Sets the terrain of each named territory, over whatever the map gives.
+    // Unknown names are ignored.
+    pub fn assign_terrain(&mut self, terrain: &HashMap<String, Terrain>) {
+        for territory in &mut self.territories {
+            if let Some(&kind) = terrain.get(&territory.name) {
+                territory.terrain = kind;
+            }
+        }
+    }
+
+    // Smallest world rectangle containing every territory; the origin for
+    // an empty map
+    pub fn map_bounds(&self) -> (Vec2, Vec2) {
+        if self.territories.is_empty() {
+            return (Vec2::ZERO, Vec2::ZERO);
+        }
+        self.territories.iter().fold(
+            (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+            |(min, max), territory| (min.min(territory.min), max.max(territory.max)),
+        )
+    }
+
+    // Attacks and fortification are only allowed between bordering territories
+    pub fn are_adjacent(&self, a: usize, b: usize) -> bool {
+        self.territories[a].adjacent.contains(&b)
+    }
+
+    pub fn territory_data(&self) -> Vec<TerritoryData> {
+        (0..self.territories.len())
+            .map(|i| TerritoryData::from_territory(i, &self.territories, &self.continents))
+            .collect()
+    }
+}
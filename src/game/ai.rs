@@ -0,0 +1,451 @@
+use crate::game::player::PlayerId;
+use crate::game::turn::Phase;
+use crate::game::Game;
+use serde::Deserialize;
+
+// Upper bound on attack rounds per turn so a lopsided board can't stall a frame
+const MAX_ATTACKS_PER_TURN: usize = 30;
+// Hard only attacks when it's at least this likely to take the territory
+const HARD_MIN_ODDS: f32 = 0.6;
+// Easy gives up attacking for the turn one round in this many
+const EASY_STOP_CHANCE: u32 = 4;
+
+// How well a computer player picks its attacks
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AiDifficulty {
+    // Attacks anything it can reach, whatever the odds
+    Easy,
+    // Attacks wherever it has more armies
+    #[default]
+    Normal,
+    // Attacks only with good odds, going for continents it nearly holds
+    Hard,
+}
+
+// A suggested next move for a human, worked out with the AI's own heuristics
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Hint {
+    Claim { territory: usize },
+    Reinforce { territory: usize },
+    // `odds` of eventually taking the territory, as `conquest_odds` gives it
+    Attack { from: usize, to: usize, odds: f32 },
+    Fortify { from: usize, to: usize },
+    // Nothing worth doing is left this phase
+    EndPhase,
+}
+
+// Every pick below breaks ties on territory index and the only randomness
+// comes from the game's seeded RNG, so a seeded AI turn always plays out the
+// same way. Keep it so: never pick by hash map order or an unseeded source.
+impl Game {
+    fn owned_by(&self, player: PlayerId) -> impl Iterator<Item = usize> + '_ {
+        (0..self.territories.len()).filter(move |&i| self.territories[i].owner == Some(player))
+    }
+
+    pub fn is_border(&self, territory: usize) -> bool {
+        let owner = self.territories[territory].owner;
+        self.territories[territory]
+            .adjacent
+            .iter()
+            .any(|&n| self.territories[n].owner != owner)
+    }
+
+    // The weakest territory facing an enemy, or the weakest overall if the
+    // player has no borders left. Ties go to the lowest index.
+    fn weakest_border(&self, player: PlayerId) -> Option<usize> {
+        let by_armies = |&i: &usize| (self.territories[i].armies, i);
+        let open = |&i: &usize| self.room_in(i) > 0;
+        self.owned_by(player)
+            .filter(open)
+            .filter(|&i| self.is_border(i))
+            .min_by_key(by_armies)
+            .or_else(|| self.owned_by(player).filter(open).min_by_key(by_armies))
+    }
+
+    fn possible_attacks(&self, player: PlayerId) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.owned_by(player)
+            .flat_map(|from| {
+                self.territories[from]
+                    .adjacent
+                    .iter()
+                    .map(move |&to| (from, to))
+            })
+            .filter(|&(from, to)| self.can_attack(from, to))
+    }
+
+    fn choose_attack(&mut self, player: PlayerId) -> Option<(usize, usize)> {
+        match self.player(player).difficulty {
+            AiDifficulty::Easy => self.random_attack(player),
+            AiDifficulty::Normal => self.best_attack(player),
+            AiDifficulty::Hard => self.likeliest_attack(player),
+        }
+    }
+
+    // Any attack at all, until it loses interest
+    fn random_attack(&mut self, player: PlayerId) -> Option<(usize, usize)> {
+        let attacks: Vec<_> = self.possible_attacks(player).collect();
+        if attacks.is_empty() || self.rng.gen_range(0, EASY_STOP_CHANCE) == 0 {
+            return None;
+        }
+        Some(attacks[self.rng.gen_range(0, attacks.len() as u32) as usize])
+    }
+
+    // The attack with the biggest army advantage, if any has one at all
+    fn best_attack(&self, player: PlayerId) -> Option<(usize, usize)> {
+        self.possible_attacks(player)
+            .filter(|&(from, to)| self.territories[from].armies > self.territories[to].armies)
+            .max_by_key(|&(from, to)| {
+                (
+                    self.territories[from].armies - self.territories[to].armies,
+                    std::cmp::Reverse((from, to)),
+                )
+            })
+    }
+
+    // Among attacks likely to succeed, the one that brings a continent
+    // closest to complete, then the surest
+    fn likeliest_attack(&self, player: PlayerId) -> Option<(usize, usize)> {
+        self.possible_attacks(player)
+            .map(|(from, to)| {
+                let odds = self.conquest_odds(from, to);
+                (from, to, odds)
+            })
+            .filter(|&(_, _, odds)| odds >= HARD_MIN_ODDS)
+            .max_by_key(|&(from, to, odds)| {
+                (
+                    self.continent_share_after_taking(player, to),
+                    (odds * 1000.0) as i32,
+                    std::cmp::Reverse((from, to)),
+                )
+            })
+            .map(|(from, to, _)| (from, to))
+    }
+
+    // Per mille of the best continent `territory` is in that `player` would
+    // hold after taking it
+    fn continent_share_after_taking(&self, player: PlayerId, territory: usize) -> usize {
+        self.continents
+            .iter()
+            .filter(|continent| continent.territories.contains(&territory))
+            .map(|continent| {
+                let held = continent
+                    .territories
+                    .iter()
+                    .filter(|&&i| i == territory || self.territories[i].owner == Some(player))
+                    .count();
+                held * 1000 / continent.territories.len()
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    // Prefers picks next to what it already holds, to build up a region
+    fn claim_pick(&self, player: PlayerId) -> Option<usize> {
+        (0..self.territories.len())
+            .filter(|&i| self.territories[i].owner.is_none())
+            .max_by_key(|&i| {
+                let friends = self.territories[i]
+                    .adjacent
+                    .iter()
+                    .filter(|&&n| self.territories[n].owner == Some(player))
+                    .count();
+                (friends, std::cmp::Reverse(i))
+            })
+    }
+
+    fn ai_claim(&mut self, player: PlayerId) {
+        if let Some(pick) = self.claim_pick(player) {
+            self.claim_territory(pick);
+        }
+    }
+
+    // Trades in a set if it has one, otherwise places a single army
+    fn ai_reinforce_step(&mut self, player: PlayerId) {
+        if let Some(set) = self.find_set(player) {
+            if self.trade_cards(player, set).is_ok() {
+                return;
+            }
+        }
+        if self.reinforcements_remaining <= 0 {
+            self.next_phase();
+            return;
+        }
+        match self.weakest_border(player) {
+            Some(territory) => self.place_reinforcement(territory),
+            None => {
+                self.reinforcements_remaining = 0;
+                self.next_phase();
+            }
+        }
+    }
+
+    // One round of dice, or the end of the phase once it's done attacking
+    fn ai_attack_step(&mut self, player: PlayerId) {
+        let attack = if self.ai_attacks < MAX_ATTACKS_PER_TURN {
+            self.choose_attack(player)
+        } else {
+            None
+        };
+        match attack {
+            Some((from, to)) => {
+                self.ai_attacks += 1;
+                let result = self.resolve_attack(from, to);
+                if result.conquered {
+                    let count = self.conquest_pick(from, to);
+                    self.follow_conquest(from, to, count);
+                }
+                self.last_combat = Some(result);
+            }
+            None => {
+                self.ai_attacks = 0;
+                self.next_phase();
+            }
+        }
+    }
+
+    // How many armies should end up in `to` just taken from `from`: all that
+    // can go if `from` is now behind the lines, only the minimum if `to` is,
+    // and otherwise half, so both sides of the new front are held
+    fn conquest_pick(&self, from: usize, to: usize) -> i32 {
+        let moved = self.territories[to].armies;
+        let available = moved + self.territories[from].armies - 1;
+        if !self.is_border(from) {
+            available
+        } else if !self.is_border(to) {
+            moved
+        } else {
+            ((available + 1) / 2).max(moved)
+        }
+    }
+
+    // Pulls the largest stack sitting behind the front line up to a border
+    fn fortify_pick(&self, player: PlayerId) -> Option<(usize, usize)> {
+        let from = self
+            .owned_by(player)
+            .filter(|&i| !self.is_border(i) && self.territories[i].armies > 1)
+            .max_by_key(|&i| (self.territories[i].armies, std::cmp::Reverse(i)))?;
+        self.territories[from]
+            .adjacent
+            .iter()
+            .copied()
+            .filter(|&to| self.territories[to].owner == Some(player) && self.room_in(to) > 0)
+            .min_by_key(|&to| (!self.is_border(to), self.territories[to].armies, to))
+            .map(|to| (from, to))
+    }
+
+    fn ai_fortify(&mut self, player: PlayerId) {
+        if let Some((from, to)) = self.fortify_pick(player) {
+            let count = self.territories[from].armies - 1;
+            // Both ends are owned and adjacent and there's room, so this
+            // can't fail (though the cap may shrink the move)
+            let _ = self.fortify(from, to, count);
+        }
+    }
+
+    // What the AI would do next in `player`'s place. Attacks are the
+    // careful kind Hard makes, falling back to Normal's, and never random.
+    pub fn hint(&self, player: PlayerId) -> Hint {
+        let suggestion = match self.phase {
+            Phase::Claim => self
+                .claim_pick(player)
+                .map(|territory| Hint::Claim { territory }),
+            Phase::InitialPlacement => self
+                .weakest_border(player)
+                .map(|territory| Hint::Reinforce { territory }),
+            Phase::Reinforce if self.reinforcements_placeable() > 0 => self
+                .weakest_border(player)
+                .map(|territory| Hint::Reinforce { territory }),
+            Phase::Reinforce => None,
+            Phase::Attack => self
+                .likeliest_attack(player)
+                .or_else(|| self.best_attack(player))
+                .map(|(from, to)| Hint::Attack {
+                    from,
+                    to,
+                    odds: self.conquest_odds(from, to),
+                }),
+            Phase::Fortify => self
+                .fortify_pick(player)
+                .map(|(from, to)| Hint::Fortify { from, to }),
+        };
+        suggestion.unwrap_or(Hint::EndPhase)
+    }
+
+    // Plays out the rest of `player`'s turn: reinforce the weakest borders,
+    // attack as its difficulty allows, then shore up the front line
+    pub fn run_ai_turn(&mut self, player: PlayerId) {
+        while !self.ai_step(player) {}
+    }
+
+    // Makes one of `player`'s decisions (a claim, a trade or a single army
+    // placed, a round of dice, the fortify) so a turn can be shown playing
+    // out. Returns whether the turn is over.
+    pub fn ai_step(&mut self, player: PlayerId) -> bool {
+        if self.current_player != player {
+            return true;
+        }
+        match self.phase {
+            Phase::Claim => {
+                self.ai_claim(player);
+                true
+            }
+            // One army on its weakest border, then it's the next seat's go
+            Phase::InitialPlacement => {
+                match self.weakest_border(player) {
+                    Some(territory) => {
+                        self.place_initial_army(territory);
+                    }
+                    None => self.pass_initial_placement(),
+                }
+                true
+            }
+            Phase::Reinforce => {
+                self.ai_reinforce_step(player);
+                false
+            }
+            Phase::Attack => {
+                self.ai_attack_step(player);
+                false
+            }
+            Phase::Fortify => {
+                self.ai_fortify(player);
+                self.next_phase();
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AiDifficulty, Hint};
+    use crate::game::events::GameEvent;
+    use crate::game::player::PlayerId;
+    use crate::game::turn::Phase;
+    use crate::test_support::{game, generate_stress_map, territory};
+
+    #[test]
+    fn ai_turn_is_deterministic_for_a_seed() {
+        let build = || {
+            let mut game = game(
+                vec![
+                    territory("A", 0, 6, vec![1]),
+                    territory("B", 1, 2, vec![0, 2]),
+                    territory("C", 1, 3, vec![1]),
+                ],
+                9,
+            );
+            game.run_ai_turn(PlayerId(0));
+            game
+        };
+        let first = build();
+        let second = build();
+        for (a, b) in first.territories.iter().zip(&second.territories) {
+            assert_eq!((a.owner, a.armies), (b.owner, b.armies));
+        }
+        assert_eq!(first.current_player, PlayerId(1));
+        assert_eq!(first.phase, Phase::Reinforce);
+    }
+
+    #[test]
+    fn equal_targets_are_broken_the_same_way_every_time() {
+        // Four identical neighbours, so every pick is a tie
+        let build = |difficulty| {
+            let mut game = game(
+                vec![
+                    territory("Hub", 0, 12, vec![1, 2, 3, 4]),
+                    territory("N", 1, 2, vec![0]),
+                    territory("E", 1, 2, vec![0]),
+                    territory("S", 1, 2, vec![0]),
+                    territory("W", 1, 2, vec![0]),
+                ],
+                3,
+            );
+            game.players[0].difficulty = difficulty;
+            game.run_ai_turn(PlayerId(0));
+            game
+        };
+        for difficulty in [AiDifficulty::Easy, AiDifficulty::Normal, AiDifficulty::Hard] {
+            let (first, second) = (build(difficulty), build(difficulty));
+            assert_eq!(first.events, second.events, "{:?}", difficulty);
+            assert_eq!(first.rng.state(), second.rng.state());
+        }
+
+        // Normal goes for the lowest-numbered of the equal targets first
+        let normal = build(AiDifficulty::Normal);
+        let first_attack = normal.events.iter().find_map(|event| match event {
+            GameEvent::Attacked { to, .. } => Some(*to),
+            _ => None,
+        });
+        assert_eq!(first_attack, Some(1));
+    }
+
+    #[test]
+    fn ai_reinforces_its_weakest_border() {
+        let mut game = game(
+            vec![
+                territory("Home", 0, 1, vec![1]),
+                territory("Front", 0, 2, vec![0, 2]),
+                territory("Enemy", 1, 50, vec![1]),
+            ],
+            1,
+        );
+        game.run_ai_turn(PlayerId(0));
+        // Home is interior, so every reinforcement lands on the front line
+        // (and the interior stack of 1 has nothing to fortify with)
+        assert_eq!(game.territories[0].armies, 1);
+        assert_eq!(game.territories[1].armies, 5);
+    }
+
+    #[test]
+    fn hints_follow_the_phase() {
+        let mut game = game(
+            vec![
+                territory("Home", 0, 3, vec![1]),
+                territory("Front", 0, 6, vec![0, 2, 3]),
+                territory("Weak", 1, 1, vec![1]),
+                territory("Strong", 1, 9, vec![1]),
+            ],
+            0,
+        );
+        let player = PlayerId(0);
+        assert_eq!(game.hint(player), Hint::Reinforce { territory: 1 });
+
+        game.phase = Phase::Attack;
+        let Hint::Attack { from, to, odds } = game.hint(player) else {
+            panic!("expected an attack hint");
+        };
+        assert_eq!((from, to), (1, 2));
+        assert!(odds > 0.9);
+
+        game.phase = Phase::Fortify;
+        assert_eq!(game.hint(player), Hint::Fortify { from: 0, to: 1 });
+        game.territories[0].armies = 1;
+        assert_eq!(game.hint(player), Hint::EndPhase);
+    }
+
+    // Time spent deciding and playing computer turns on a large board
+    // cargo test --release bench_ai_turns -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_ai_turns() {
+        use std::time::Instant;
+        let mut game = game(generate_stress_map(400, 16), 7);
+        let turns = 40;
+
+        let start = Instant::now();
+        for _ in 0..turns {
+            let player = game.current_player;
+            game.run_ai_turn(player);
+        }
+        let elapsed = start.elapsed();
+        println!(
+            "{} territories, {} turns: {:?} ({:?}/turn)",
+            game.territories.len(),
+            turns,
+            elapsed,
+            elapsed / turns
+        );
+    }
+}
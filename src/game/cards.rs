@@ -0,0 +1,207 @@
+use crate::game::events::GameEvent;
+use crate::game::player::PlayerId;
+use crate::game::rng::Rng;
+use crate::game::turn::Phase;
+use crate::game::Game;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Bonus armies for the first few trades; after that each trade is worth 5
+// more than the one before
+const TRADE_BONUSES: [i32; 6] = [4, 6, 8, 10, 12, 15];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Card {
+    Infantry,
+    Cavalry,
+    Artillery,
+    // Stands in for any other card
+    Wild,
+}
+
+impl Card {
+    // Draws with the odds of a classic 44-card deck: 42 cards split evenly
+    // between the three kinds, plus two wilds
+    fn draw(rng: &mut Rng) -> Card {
+        match rng.gen_range(0, 44) {
+            0..=1 => Card::Wild,
+            n if n % 3 == 0 => Card::Infantry,
+            n if n % 3 == 1 => Card::Cavalry,
+            _ => Card::Artillery,
+        }
+    }
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Card::Infantry => "Infantry",
+            Card::Cavalry => "Cavalry",
+            Card::Artillery => "Artillery",
+            Card::Wild => "Wild",
+        };
+        f.write_str(name)
+    }
+}
+
+// Three of a kind or one of each; a wild completes any pair
+fn is_set(cards: [Card; 3]) -> bool {
+    let kinds: Vec<Card> = cards.into_iter().filter(|&c| c != Card::Wild).collect();
+    match kinds.as_slice() {
+        [a, b, c] => (a == b && b == c) || (a != b && b != c && a != c),
+        _ => true,
+    }
+}
+
+fn trade_bonus(trades_completed: usize) -> i32 {
+    match TRADE_BONUSES.get(trades_completed) {
+        Some(&bonus) => bonus,
+        None => 15 + 5 * (trades_completed - TRADE_BONUSES.len() + 1) as i32,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeError {
+    // Cards are only cashed in at the start of your own turn
+    NotReinforcing,
+    NoSuchCard { index: usize },
+    DuplicateCard,
+    NotASet,
+}
+
+impl fmt::Display for TradeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TradeError::NotReinforcing => {
+                write!(f, "Cards can only be traded during your Reinforce phase")
+            }
+            TradeError::NoSuchCard { index } => write!(f, "There is no card {} in hand", index),
+            TradeError::DuplicateCard => write!(f, "Pick three different cards"),
+            TradeError::NotASet => {
+                write!(
+                    f,
+                    "Trade three of a kind, one of each, or a pair with a wild"
+                )
+            }
+        }
+    }
+}
+
+impl Game {
+    pub fn hand(&self, player: PlayerId) -> &[Card] {
+        self.hands.get(&player).map_or(&[], Vec::as_slice)
+    }
+
+    // Called as a turn ends: a player who took a territory earns one card
+    pub fn award_card(&mut self) {
+        if !self.conquered_this_turn {
+            return;
+        }
+        self.conquered_this_turn = false;
+        let card = Card::draw(&mut self.rng);
+        self.hands
+            .entry(self.current_player)
+            .or_default()
+            .push(card);
+    }
+
+    // The first three cards in `player`'s hand that make a set, if any
+    pub fn find_set(&self, player: PlayerId) -> Option<[usize; 3]> {
+        let hand = self.hand(player);
+        let n = hand.len();
+        (0..n)
+            .flat_map(|a| (a + 1..n).flat_map(move |b| (b + 1..n).map(move |c| [a, b, c])))
+            .find(|&[a, b, c]| is_set([hand[a], hand[b], hand[c]]))
+    }
+
+    // Cashes in three cards (by position in the hand) for bonus armies, which
+    // go straight into the reinforcement pool. Each trade is worth more than
+    // the last, counted across all players.
+    pub fn trade_cards(&mut self, player: PlayerId, cards: [usize; 3]) -> Result<i32, TradeError> {
+        if self.phase != Phase::Reinforce || self.current_player != player {
+            return Err(TradeError::NotReinforcing);
+        }
+        let hand = self.hand(player);
+        if let Some(&index) = cards.iter().find(|&&i| i >= hand.len()) {
+            return Err(TradeError::NoSuchCard { index });
+        }
+        let [a, b, c] = cards;
+        if a == b || b == c || a == c {
+            return Err(TradeError::DuplicateCard);
+        }
+        if !is_set([hand[a], hand[b], hand[c]]) {
+            return Err(TradeError::NotASet);
+        }
+
+        let mut indices = cards;
+        indices.sort_unstable_by(|x, y| y.cmp(x));
+        let hand = self.hands.entry(player).or_default();
+        for index in indices {
+            hand.remove(index);
+        }
+        let bonus = trade_bonus(self.trades_completed);
+        self.trades_completed += 1;
+        self.reinforcements_remaining += bonus;
+        self.record_trade(player);
+        self.log_event(GameEvent::CardsTraded { player, bonus });
+        Ok(bonus)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{game, territory};
+
+    fn game_with_hand(hand: Vec<Card>) -> Game {
+        let mut game = game(
+            vec![territory("A", 0, 3, vec![1]), territory("B", 1, 3, vec![0])],
+            0,
+        );
+        game.hands.insert(PlayerId(0), hand);
+        game
+    }
+
+    #[test]
+    fn matching_sets_are_accepted() {
+        assert!(is_set([Card::Cavalry, Card::Cavalry, Card::Cavalry]));
+        assert!(is_set([Card::Infantry, Card::Cavalry, Card::Artillery]));
+        assert!(is_set([Card::Infantry, Card::Infantry, Card::Wild]));
+        assert!(is_set([Card::Infantry, Card::Artillery, Card::Wild]));
+        assert!(!is_set([Card::Infantry, Card::Infantry, Card::Artillery]));
+    }
+
+    #[test]
+    fn trades_escalate_and_feed_the_pool() {
+        let mut game = game_with_hand(vec![Card::Infantry; 6]);
+        let pool = game.reinforcements_remaining;
+        assert_eq!(game.trade_cards(PlayerId(0), [0, 1, 2]), Ok(4));
+        assert_eq!(game.trade_cards(PlayerId(0), [2, 1, 0]), Ok(6));
+        assert_eq!(game.reinforcements_remaining, pool + 10);
+        assert!(game.hand(PlayerId(0)).is_empty());
+        assert_eq!(trade_bonus(6), 20);
+        assert_eq!(trade_bonus(7), 25);
+    }
+
+    #[test]
+    fn invalid_trades_leave_the_hand_alone() {
+        let mut game = game_with_hand(vec![Card::Infantry, Card::Infantry, Card::Cavalry]);
+        assert_eq!(
+            game.trade_cards(PlayerId(0), [0, 1, 2]),
+            Err(TradeError::NotASet)
+        );
+        assert_eq!(
+            game.trade_cards(PlayerId(0), [0, 0, 1]),
+            Err(TradeError::DuplicateCard)
+        );
+        assert_eq!(
+            game.trade_cards(PlayerId(0), [0, 1, 3]),
+            Err(TradeError::NoSuchCard { index: 3 })
+        );
+        assert_eq!(
+            game.trade_cards(PlayerId(1), [0, 1, 2]),
+            Err(TradeError::NotReinforcing)
+        );
+        assert_eq!(game.hand(PlayerId(0)).len(), 3);
+    }
+}
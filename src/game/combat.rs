@@ -0,0 +1,755 @@
+use crate::game::events::GameEvent;
+use crate::game::player::PlayerId;
+use crate::game::rng::Dice;
+use crate::game::territory::Terrain;
+use crate::game::Game;
+
+use serde::Deserialize;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TieWinner {
+    Attacker,
+    Defender,
+}
+
+// The dice rules that vary between Risk variants. The defaults are the
+// classic game: 3 attacking dice against 2, ties to the defender.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct CombatRules {
+    pub max_attacker_dice: i32,
+    pub max_defender_dice: i32,
+    pub ties_go_to: TieWinner,
+    // Extra dice a capital's defender may roll, on top of the usual limit
+    pub capital_defense_bonus: i32,
+    // Fewest armies that must follow a conquest in; the attacking dice
+    // when absent, as in the classic game
+    pub conquest_min: Option<i32>,
+    // Most that may follow it in; all but one when absent
+    pub conquest_max: Option<i32>,
+}
+
+// Most dice either side may be configured to roll, keeping a roll to a row
+// of dice that fits on screen
+const MAX_DICE: i32 = 6;
+
+impl CombatRules {
+    // Without at least one die a side can never lose an army, and an attack
+    // would stall
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, dice) in [
+            ("max_attacker_dice", self.max_attacker_dice),
+            ("max_defender_dice", self.max_defender_dice),
+        ] {
+            if !(1..=MAX_DICE).contains(&dice) {
+                return Err(format!(
+                    "{} must be between 1 and {}, not {}",
+                    name, MAX_DICE, dice
+                ));
+            }
+        }
+        if self.capital_defense_bonus < 0 {
+            return Err(format!(
+                "capital_defense_bonus can't be negative, not {}",
+                self.capital_defense_bonus
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for CombatRules {
+    fn default() -> CombatRules {
+        CombatRules {
+            max_attacker_dice: 3,
+            max_defender_dice: 2,
+            ties_go_to: TieWinner::Defender,
+            capital_defense_bonus: 1,
+            conquest_min: None,
+            conquest_max: None,
+        }
+    }
+}
+
+// The dice one particular attack is fought with, once the rules, the
+// defender's capital and its terrain are all taken into account
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DiceLimits {
+    pub attacker: i32,
+    pub defender: i32,
+    pub ties_go_to: TieWinner,
+    // Added to the defender's highest die
+    pub defender_bonus: u8,
+}
+
+// Outcome of a single round of dice, kept around so the HUD can show it
+#[derive(Clone, Debug, PartialEq)]
+pub struct CombatResult {
+    pub attacker_dice: Vec<u8>,
+    pub defender_dice: Vec<u8>,
+    pub attacker_losses: i32,
+    pub defender_losses: i32,
+    pub conquered: bool,
+}
+
+// Totals over every round of a blitz
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CombatSummary {
+    pub rounds: usize,
+    pub attacker_dice_rolled: usize,
+    pub defender_dice_rolled: usize,
+    pub attacker_losses: i32,
+    pub defender_losses: i32,
+    pub conquered: bool,
+}
+
+// Why an attack isn't allowed, for telling the player
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttackError {
+    NeutralAttacker,
+    SameOwner,
+    // One army has to stay behind
+    TooFewArmies,
+    NotAdjacent,
+    Allied,
+}
+
+impl fmt::Display for AttackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AttackError::NeutralAttacker => write!(f, "Neutral territories don't attack"),
+            AttackError::SameOwner => write!(f, "You can't attack your own territory"),
+            AttackError::TooFewArmies => {
+                write!(f, "Attacking needs two armies; one must stay behind")
+            }
+            AttackError::NotAdjacent => write!(f, "Those territories don't share a border"),
+            AttackError::Allied => write!(f, "You can't attack an ally; break the alliance first"),
+        }
+    }
+}
+
+// Rolls `count` dice, highest first
+fn roll_dice(source: &mut impl Dice, count: i32) -> Vec<u8> {
+    let mut dice: Vec<u8> = (0..count).map(|_| source.roll_die()).collect();
+    dice.sort_unstable_by(|a, b| b.cmp(a));
+    dice
+}
+
+// How many of the defender's armies a roll kills, comparing the highest dice
+// pairwise. Both rolls come highest first.
+pub fn defender_losses(attacker_dice: &[u8], defender_dice: &[u8], limits: DiceLimits) -> i32 {
+    let mut losses = 0;
+    for (i, (&a, &d)) in attacker_dice.iter().zip(defender_dice).enumerate() {
+        let d = if i == 0 { d + limits.defender_bonus } else { d };
+        if a > d || (a == d && limits.ties_go_to == TieWinner::Attacker) {
+            losses += 1;
+        }
+    }
+    losses
+}
+
+impl Game {
+    // A territory needs at least two armies to attack, since one must stay
+    // behind. Neutral territories never attack but can be attacked.
+    pub fn can_attack(&self, attacker: usize, defender: usize) -> bool {
+        self.check_attack(attacker, defender).is_ok()
+    }
+
+    pub fn check_attack(&self, attacker: usize, defender: usize) -> Result<(), AttackError> {
+        let from = &self.territories[attacker];
+        let to = &self.territories[defender];
+        if from.owner.is_none() {
+            Err(AttackError::NeutralAttacker)
+        } else if from.owner == to.owner {
+            Err(AttackError::SameOwner)
+        } else if from
+            .owner
+            .zip(to.owner)
+            .is_some_and(|(a, b)| self.allied(a, b))
+        {
+            Err(AttackError::Allied)
+        } else if !self.are_adjacent(attacker, defender) {
+            Err(AttackError::NotAdjacent)
+        } else if from.armies < 2 {
+            Err(AttackError::TooFewArmies)
+        } else {
+            Ok(())
+        }
+    }
+
+    // Territories `player` could launch an attack from right now
+    pub fn valid_attack_sources(&self, player: PlayerId) -> Vec<usize> {
+        (0..self.territories.len())
+            .filter(|&i| self.territories[i].owner == Some(player))
+            .filter(|&i| {
+                self.territories[i]
+                    .adjacent
+                    .iter()
+                    .any(|&n| self.can_attack(i, n))
+            })
+            .collect()
+    }
+
+    // Neighbours of `from` it could attack right now, in adjacency order
+    pub fn valid_attack_targets(&self, from: usize) -> Vec<usize> {
+        self.territories[from]
+            .adjacent
+            .iter()
+            .copied()
+            .filter(|&n| self.can_attack(from, n))
+            .collect()
+    }
+
+    // Resolves one round of dice between two territories, rolled by
+    // `roll_attack` and applied to the board. A defender left with no armies
+    // is taken over by the attacker.
+    pub fn resolve_attack(&mut self, attacker: usize, defender: usize) -> CombatResult {
+        let result = self.roll_attack(attacker, defender);
+        self.apply_combat(attacker, defender, &result);
+        result
+    }
+
+    // Keeps attacking until the defender falls or the attacker is down to its
+    // last army. Stops early if a round kills nobody (a defender with no
+    // armies rolls no dice), which would otherwise loop forever.
+    pub fn blitz_attack(&mut self, attacker: usize, defender: usize) -> CombatSummary {
+        let mut summary = CombatSummary::default();
+        while self.can_attack(attacker, defender) {
+            let result = self.resolve_attack(attacker, defender);
+            summary.rounds += 1;
+            summary.attacker_dice_rolled += result.attacker_dice.len();
+            summary.defender_dice_rolled += result.defender_dice.len();
+            summary.attacker_losses += result.attacker_losses;
+            summary.defender_losses += result.defender_losses;
+            summary.conquered |= result.conquered;
+            let casualties = result.attacker_losses + result.defender_losses;
+            self.last_combat = Some(result);
+            if casualties == 0 {
+                break;
+            }
+        }
+        summary
+    }
+
+    // Rolls the dice for an attack without touching the board, so the UI can
+    // show the roll before the casualties land. The attacker rolls one die
+    // fewer than its armies and the defender one per army, each up to its
+    // limit from `dice_limits`, and the highest dice are compared pairwise.
+    // Draws on and advances the game's own dice.
+    pub fn roll_attack(&mut self, attacker: usize, defender: usize) -> CombatResult {
+        let mut rng = self.rng.clone();
+        let result = self.roll_attack_with(attacker, defender, &mut rng);
+        self.rng = rng;
+        result
+    }
+
+    // Most dice each side may roll when `attacker` attacks `defender`, from
+    // rules `CombatRules::validate` has passed. A river, on the defender or
+    // along the border between them, costs a die but never the last one.
+    pub fn dice_limits(&self, attacker: usize, defender: usize) -> DiceLimits {
+        let rules = self.combat_rules;
+        let terrain = self.territories[defender].terrain;
+        let penalty = if self.territories[defender].rivers.contains(&attacker) {
+            Terrain::River.attacker_dice_penalty()
+        } else {
+            terrain.attacker_dice_penalty()
+        };
+        let mut max_defender_dice = rules.max_defender_dice;
+        if self.territories[defender].is_capital {
+            max_defender_dice += rules.capital_defense_bonus;
+        }
+        DiceLimits {
+            attacker: (rules.max_attacker_dice - penalty).max(1),
+            defender: max_defender_dice,
+            ties_go_to: rules.ties_go_to,
+            defender_bonus: terrain.defender_bonus(),
+        }
+    }
+
+    // The same with the dice supplied, attacker's first then defender's
+    pub fn roll_attack_with(
+        &self,
+        attacker: usize,
+        defender: usize,
+        dice: &mut impl Dice,
+    ) -> CombatResult {
+        let limits = self.dice_limits(attacker, defender);
+        let attack_count = (self.territories[attacker].armies - 1).clamp(0, limits.attacker);
+        let defend_count = self.territories[defender].armies.clamp(0, limits.defender);
+
+        let attacker_dice = roll_dice(dice, attack_count);
+        let defender_dice = roll_dice(dice, defend_count);
+
+        let defender_losses = defender_losses(&attacker_dice, &defender_dice, limits);
+        let attacker_losses = attacker_dice.len().min(defender_dice.len()) as i32 - defender_losses;
+
+        CombatResult {
+            conquered: defend_count > 0 && self.territories[defender].armies == defender_losses,
+            attacker_dice,
+            defender_dice,
+            attacker_losses,
+            defender_losses,
+        }
+    }
+
+    // The fewest and most armies that may move into a conquered territory,
+    // with `available` armies able to leave and `dice` attacking dice
+    // rolled. At least one always goes in, and never more than can leave or
+    // the territory can hold.
+    pub fn conquest_bounds(&self, available: i32, dice: usize) -> (i32, i32) {
+        let max = self
+            .combat_rules
+            .conquest_max
+            .unwrap_or(available)
+            .min(available)
+            .min(self.max_armies.unwrap_or(i32::MAX))
+            .max(1);
+        let min = self
+            .combat_rules
+            .conquest_min
+            .unwrap_or(dice as i32)
+            .clamp(1, max);
+        (min, max)
+    }
+
+    // Sends `count` armies in total into a territory just taken from
+    // `from`, on top of the minimum already there. Kept within the bounds;
+    // returns how many more went in.
+    pub fn follow_conquest(&mut self, from: usize, to: usize, count: i32) -> i32 {
+        let moved = self.territories[to].armies;
+        let available = moved + self.territories[from].armies - 1;
+        let (_, max) = self.conquest_bounds(available, 0);
+        let extra = (count.min(max) - moved).max(0);
+        self.territories[from].armies -= extra;
+        self.territories[to].armies += extra;
+        self.board_dirty = true;
+        extra
+    }
+
+    pub fn apply_combat(&mut self, attacker: usize, defender: usize, result: &CombatResult) {
+        // Dice can't be rerolled, so nothing before an attack is undoable
+        self.undo_stack.clear();
+        self.board_dirty = true;
+        self.record_combat(attacker, defender, result);
+        if let Some(player) = self.territories[attacker].owner {
+            self.log_event(GameEvent::Attacked {
+                player,
+                from: attacker,
+                to: defender,
+                attacker_losses: result.attacker_losses,
+                defender_losses: result.defender_losses,
+                conquered: result.conquered,
+                attacker_dice: result.attacker_dice.clone(),
+                defender_dice: result.defender_dice.clone(),
+            });
+        }
+        self.territories[attacker].armies -= result.attacker_losses;
+        self.territories[defender].armies -= result.defender_losses;
+
+        if result.conquered {
+            // The minimum goes in now; the player (or AI) can send more
+            let (moved, _) = self.conquest_bounds(
+                self.territories[attacker].armies - 1,
+                result.attacker_dice.len(),
+            );
+            let loser = self.territories[defender].owner;
+            self.territories[defender].owner = self.territories[attacker].owner;
+            self.territories[attacker].armies -= moved;
+            self.territories[defender].armies = moved;
+            self.conquered_this_turn = true;
+
+            // Taking a player's last territory knocks them out of the game,
+            // and their cards go to whoever did it
+            if let (Some(loser), Some(winner)) = (loser, self.territories[attacker].owner) {
+                if self.is_eliminated(loser) {
+                    let cards = self.hands.remove(&loser).unwrap_or_default();
+                    self.hands.entry(winner).or_default().extend(cards);
+                    self.log_event(GameEvent::Eliminated {
+                        player: loser,
+                        by: winner,
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AttackError, CombatRules, TieWinner, MAX_DICE};
+    use crate::game::cards::Card;
+    use crate::game::events::GameEvent;
+    use crate::game::player::PlayerId;
+    use crate::game::rng::Dice;
+    use crate::game::territory::Terrain;
+    use crate::test_support::{game, territory};
+
+    fn two_territory_game(attackers: i32, defenders: i32, seed: u64) -> crate::game::Game {
+        game(
+            vec![
+                territory("Attacker", 0, attackers, vec![1]),
+                territory("Defender", 1, defenders, vec![0]),
+            ],
+            seed,
+        )
+    }
+
+    #[test]
+    fn conquest_moves_stay_within_the_bounds() {
+        let mut game = two_territory_game(10, 1, 0);
+        // Classic: the dice rolled, up to all but one
+        assert_eq!(game.conquest_bounds(9, 3), (3, 9));
+        assert_eq!(game.conquest_bounds(2, 3), (2, 2));
+        assert_eq!(game.conquest_bounds(1, 1), (1, 1));
+
+        game.combat_rules.conquest_min = Some(5);
+        game.combat_rules.conquest_max = Some(6);
+        assert_eq!(game.conquest_bounds(9, 3), (5, 6));
+        assert_eq!(game.conquest_bounds(4, 1), (4, 4));
+        game.combat_rules.conquest_min = Some(0);
+        assert_eq!(game.conquest_bounds(9, 3), (1, 6));
+        game.max_armies = Some(3);
+        assert_eq!(game.conquest_bounds(9, 3), (1, 3));
+
+        // Following up can't pass the maximum or take anything back
+        game.max_armies = None;
+        game.combat_rules.conquest_max = Some(4);
+        game.territories[0].armies = 6;
+        game.territories[1].armies = 2;
+        assert_eq!(game.follow_conquest(0, 1, 100), 2);
+        assert_eq!(
+            (game.territories[0].armies, game.territories[1].armies),
+            (4, 4)
+        );
+        assert_eq!(game.follow_conquest(0, 1, 1), 0);
+        assert_eq!(
+            (game.territories[0].armies, game.territories[1].armies),
+            (4, 4)
+        );
+    }
+
+    #[test]
+    fn refused_attacks_say_why() {
+        let mut game = game(
+            vec![
+                territory("Attacker", 0, 3, vec![1, 2]),
+                territory("Defender", 1, 1, vec![0]),
+                territory("Friend", 0, 1, vec![0]),
+                territory("Far", 1, 1, vec![]),
+            ],
+            0,
+        );
+        assert_eq!(game.check_attack(0, 1), Ok(()));
+        assert_eq!(game.check_attack(0, 2), Err(AttackError::SameOwner));
+        assert_eq!(game.check_attack(0, 3), Err(AttackError::NotAdjacent));
+        game.territories[0].armies = 1;
+        assert_eq!(game.check_attack(0, 1), Err(AttackError::TooFewArmies));
+        game.territories[0].owner = None;
+        assert_eq!(game.check_attack(0, 1), Err(AttackError::NeutralAttacker));
+    }
+
+    #[test]
+    fn same_seed_gives_same_outcome() {
+        let mut first = two_territory_game(10, 10, 42);
+        let mut second = two_territory_game(10, 10, 42);
+        for _ in 0..5 {
+            assert_eq!(first.resolve_attack(0, 1), second.resolve_attack(0, 1));
+        }
+        assert_eq!(first.territories[0].armies, second.territories[0].armies);
+        assert_eq!(first.territories[1].armies, second.territories[1].armies);
+    }
+
+    #[test]
+    fn dice_counts_follow_army_sizes() {
+        let mut game = two_territory_game(3, 1, 7);
+        let result = game.resolve_attack(0, 1);
+        assert_eq!(result.attacker_dice.len(), 2);
+        assert_eq!(result.defender_dice.len(), 1);
+        assert_eq!(result.attacker_losses + result.defender_losses, 1);
+        assert!(result.attacker_dice[0] >= result.attacker_dice[1]);
+    }
+
+    #[test]
+    fn casualties_match_compared_dice() {
+        for seed in 0..50 {
+            let mut game = two_territory_game(10, 10, seed);
+            let result = game.resolve_attack(0, 1);
+            let expected_defender_losses = result
+                .attacker_dice
+                .iter()
+                .zip(&result.defender_dice)
+                .filter(|(a, d)| a > d)
+                .count() as i32;
+            assert_eq!(result.defender_losses, expected_defender_losses);
+            assert_eq!(result.attacker_losses, 2 - expected_defender_losses);
+            assert_eq!(game.territories[0].armies, 10 - result.attacker_losses);
+            assert_eq!(game.territories[1].armies, 10 - result.defender_losses);
+        }
+    }
+
+    #[test]
+    fn conquest_transfers_ownership_and_moves_armies() {
+        let conquering_seed = (0..100)
+            .find(|&seed| {
+                two_territory_game(4, 1, seed)
+                    .resolve_attack(0, 1)
+                    .conquered
+            })
+            .expect("some seed should win a 3-vs-1 roll");
+
+        let mut game = two_territory_game(4, 1, conquering_seed);
+        game.resolve_attack(0, 1);
+        assert_eq!(game.territories[1].owner, Some(PlayerId(0)));
+        assert_eq!(game.territories[1].armies, 3);
+        assert_eq!(game.territories[0].armies, 1);
+    }
+
+    #[test]
+    fn taking_the_last_territory_eliminates_and_hands_over_cards() {
+        let mut game = game(
+            vec![
+                territory("Attacker", 0, 10, vec![1]),
+                territory("Last stand", 1, 1, vec![0, 2]),
+                territory("Elsewhere", 2, 3, vec![1]),
+            ],
+            0,
+        );
+        game.phase = crate::game::turn::Phase::Attack;
+        game.hands
+            .insert(PlayerId(1), vec![Card::Infantry, Card::Cavalry]);
+        game.hands.insert(PlayerId(0), vec![Card::Artillery]);
+        while game.territories[1].owner == Some(PlayerId(1)) {
+            game.resolve_attack(0, 1);
+        }
+        assert!(game.is_eliminated(PlayerId(1)));
+        assert!(game.hand(PlayerId(1)).is_empty());
+        assert_eq!(
+            game.hand(PlayerId(0)),
+            [Card::Artillery, Card::Infantry, Card::Cavalry]
+        );
+        assert!(game.events.contains(&GameEvent::Eliminated {
+            player: PlayerId(1),
+            by: PlayerId(0),
+        }));
+
+        // The knocked-out player's seat is skipped from then on
+        game.phase = crate::game::turn::Phase::Fortify;
+        game.next_phase();
+        assert_eq!(game.current_player, PlayerId(2));
+        game.phase = crate::game::turn::Phase::Fortify;
+        game.next_phase();
+        assert_eq!(game.current_player, PlayerId(0));
+    }
+
+    // Dice that come up in a fixed order
+    struct LoadedDice(Vec<u8>);
+
+    impl Dice for LoadedDice {
+        fn roll_die(&mut self) -> u8 {
+            assert!(!self.0.is_empty(), "ran out of loaded dice");
+            self.0.remove(0)
+        }
+    }
+
+    fn loaded(rolls: &[u8]) -> LoadedDice {
+        LoadedDice(rolls.to_vec())
+    }
+
+    #[test]
+    fn loaded_dice_give_exact_casualties() {
+        let mut game = two_territory_game(4, 2, 0);
+        // 6 beats 5, then 5 against 5 is a tie, which the defender wins
+        let result = game.roll_attack_with(0, 1, &mut loaded(&[5, 2, 6, 5, 5]));
+        assert_eq!(result.attacker_dice, [6, 5, 2]);
+        assert_eq!(result.defender_dice, [5, 5]);
+        assert_eq!((result.attacker_losses, result.defender_losses), (1, 1));
+        assert!(!result.conquered);
+
+        game.apply_combat(0, 1, &result);
+        let result = game.roll_attack_with(0, 1, &mut loaded(&[4, 1, 3]));
+        assert_eq!(
+            (result.attacker_dice.len(), result.defender_dice.len()),
+            (2, 1)
+        );
+        assert_eq!((result.attacker_losses, result.defender_losses), (0, 1));
+        assert!(result.conquered);
+    }
+
+    #[test]
+    fn mountains_hold_where_plains_fall() {
+        // The attacker's 5 beats the plains defender's 4, but not a mountain's
+        let rolls = [5, 4];
+        let mut game = two_territory_game(2, 1, 0);
+        let result = game.roll_attack_with(0, 1, &mut loaded(&rolls));
+        assert!(result.conquered);
+
+        game.territories[1].terrain = Terrain::Mountain;
+        let result = game.roll_attack_with(0, 1, &mut loaded(&rolls));
+        assert_eq!((result.attacker_losses, result.defender_losses), (1, 0));
+        assert!(!result.conquered);
+        // The dice shown are the ones rolled
+        assert_eq!(result.defender_dice, [4]);
+
+        // Crossing a river costs the attacker a die
+        let mut game = two_territory_game(4, 2, 0);
+        game.territories[1].terrain = Terrain::River;
+        let result = game.roll_attack_with(0, 1, &mut loaded(&[6, 6, 1, 1]));
+        assert_eq!(result.attacker_dice.len(), 2);
+
+        // As does a river along the border, though not twice over
+        game.territories[1].rivers.push(0);
+        let result = game.roll_attack_with(0, 1, &mut loaded(&[6, 6, 1, 1]));
+        assert_eq!(result.attacker_dice.len(), 2);
+        game.territories[1].terrain = Terrain::Plains;
+        let result = game.roll_attack_with(0, 1, &mut loaded(&[6, 6, 1, 1]));
+        assert_eq!(result.attacker_dice.len(), 2);
+    }
+
+    #[test]
+    fn capitals_defend_with_an_extra_die() {
+        // The attacker's 6,5 beats the defender's 4,3 outright
+        let rolls = [6, 5, 4, 3, 6];
+        let mut game = two_territory_game(3, 3, 0);
+        let result = game.roll_attack_with(0, 1, &mut loaded(&rolls));
+        assert_eq!(result.defender_dice, [4, 3]);
+        assert_eq!((result.attacker_losses, result.defender_losses), (0, 2));
+
+        // A capital's defender rolls a third die, and its 6 holds the line
+        game.territories[1].is_capital = true;
+        let result = game.roll_attack_with(0, 1, &mut loaded(&rolls));
+        assert_eq!(result.defender_dice, [6, 4, 3]);
+        assert_eq!((result.attacker_losses, result.defender_losses), (1, 1));
+
+        game.combat_rules.capital_defense_bonus = 0;
+        let result = game.roll_attack_with(0, 1, &mut loaded(&rolls));
+        assert_eq!(result.defender_dice, [4, 3]);
+    }
+
+    // A one-die-each roll that comes up level
+    fn tied_seed() -> u64 {
+        (0..1000)
+            .find(|&seed| {
+                let result = two_territory_game(2, 1, seed).resolve_attack(0, 1);
+                result.attacker_dice == result.defender_dice
+            })
+            .expect("some seed should roll a tie")
+    }
+
+    #[test]
+    fn ties_go_to_the_defender_by_default() {
+        let mut game = two_territory_game(2, 1, tied_seed());
+        let result = game.resolve_attack(0, 1);
+        assert_eq!((result.attacker_losses, result.defender_losses), (1, 0));
+        assert!(!result.conquered);
+        assert_eq!(game.territories[0].armies, 1);
+        assert_eq!(game.territories[1].armies, 1);
+    }
+
+    #[test]
+    fn ties_can_go_to_the_attacker() {
+        let mut game = two_territory_game(2, 1, tied_seed());
+        game.combat_rules.ties_go_to = TieWinner::Attacker;
+        let result = game.resolve_attack(0, 1);
+        assert_eq!((result.attacker_losses, result.defender_losses), (0, 1));
+        assert!(result.conquered);
+        assert_eq!(game.territories[1].owner, Some(PlayerId(0)));
+    }
+
+    #[test]
+    fn dice_limits_come_from_the_rules() {
+        let mut game = two_territory_game(10, 10, 3);
+        game.combat_rules.max_attacker_dice = 2;
+        game.combat_rules.max_defender_dice = 1;
+        let result = game.resolve_attack(0, 1);
+        assert_eq!(result.attacker_dice.len(), 2);
+        assert_eq!(result.defender_dice.len(), 1);
+        assert_eq!(result.attacker_losses + result.defender_losses, 1);
+    }
+
+    #[test]
+    fn dice_limits_are_checked_on_load() {
+        assert!(CombatRules::default().validate().is_ok());
+        for (attacker, defender) in [(0, 2), (3, 0), (MAX_DICE + 1, 2)] {
+            let rules = CombatRules {
+                max_attacker_dice: attacker,
+                max_defender_dice: defender,
+                ..CombatRules::default()
+            };
+            assert!(rules.validate().is_err());
+        }
+    }
+
+    #[test]
+    fn blitz_runs_until_one_side_is_done() {
+        for seed in 0..50 {
+            let mut game = two_territory_game(12, 6, seed);
+            let summary = game.blitz_attack(0, 1);
+            if summary.conquered {
+                assert_eq!(game.territories[1].owner, Some(PlayerId(0)));
+                assert_eq!(summary.defender_losses, 6);
+            } else {
+                assert_eq!(game.territories[0].armies, 1);
+                assert_eq!(summary.attacker_losses, 11);
+            }
+            assert!(summary.rounds >= 1);
+        }
+    }
+
+    #[test]
+    fn blitz_with_two_armies_stops_after_a_lost_roll() {
+        let losing_seed = (0..100)
+            .find(|&seed| {
+                two_territory_game(2, 3, seed)
+                    .resolve_attack(0, 1)
+                    .attacker_losses
+                    == 1
+            })
+            .expect("some seed should lose a 1-vs-2 roll");
+        let mut game = two_territory_game(2, 3, losing_seed);
+        let summary = game.blitz_attack(0, 1);
+        assert_eq!(summary.rounds, 1);
+        assert_eq!(summary.attacker_dice_rolled, 1);
+        assert_eq!(summary.defender_dice_rolled, 2);
+        assert_eq!((summary.attacker_losses, summary.defender_losses), (1, 0));
+        assert_eq!(game.territories[0].armies, 1);
+    }
+
+    #[test]
+    fn blitz_against_an_empty_defender_terminates() {
+        let mut game = two_territory_game(5, 0, 0);
+        let summary = game.blitz_attack(0, 1);
+        assert_eq!(summary.rounds, 1);
+        assert!(!summary.conquered);
+    }
+
+    #[test]
+    fn cannot_attack_with_one_army() {
+        let game = two_territory_game(1, 1, 0);
+        assert!(!game.can_attack(0, 1));
+    }
+
+    #[test]
+    fn attack_sources_need_armies_and_an_enemy_neighbour() {
+        let game = game(
+            vec![
+                territory("Ready", 0, 3, vec![1, 3]),
+                territory("Interior", 0, 5, vec![0, 2]),
+                territory("Lone", 0, 1, vec![1, 3]),
+                territory("Enemy", 1, 2, vec![0, 2]),
+            ],
+            0,
+        );
+        assert_eq!(game.valid_attack_sources(PlayerId(0)), vec![0]);
+        assert_eq!(game.valid_attack_sources(PlayerId(1)), vec![3]);
+    }
+
+    #[test]
+    fn neutral_territories_can_be_attacked_but_never_attack() {
+        let mut game = two_territory_game(5, 5, 0);
+        game.territories[1].owner = None;
+        assert!(game.can_attack(0, 1));
+        assert!(!game.can_attack(1, 0));
+    }
+}
@@ -0,0 +1,132 @@
+use crate::game::events::GameEvent;
+use crate::game::player::PlayerId;
+use crate::game::turn::Phase;
+use crate::game::Game;
+use serde::Deserialize;
+
+// What happens to a conceding player's territories
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConcedeRule {
+    // They're left neutral, armies and all
+    #[default]
+    Neutral,
+    // They go to whoever is furthest ahead, along with the player's cards
+    Leader,
+}
+
+impl Game {
+    // The opponent holding the most territories, then the most armies, then
+    // sitting earliest
+    pub fn leading_opponent(&self, player: PlayerId) -> Option<PlayerId> {
+        self.players
+            .iter()
+            .map(|p| p.id)
+            .filter(|&id| id != player && !self.is_eliminated(id))
+            .max_by_key(|&id| {
+                let held = self.territories.iter().filter(|t| t.owner == Some(id));
+                let count = held.clone().count();
+                let armies: i32 = held.map(|t| t.armies).sum();
+                (count, armies, std::cmp::Reverse(id.0))
+            })
+    }
+
+    // Takes `player` out of the game by their own choice, handing their
+    // territories over by the concede rule. If it was their turn, play
+    // passes on as if they'd ended it. False if they were already out, or
+    // during the draft or opening placement, when there's nothing yet to
+    // give up.
+    pub fn concede(&mut self, player: PlayerId) -> bool {
+        if matches!(self.phase, Phase::Claim | Phase::InitialPlacement)
+            || self.is_eliminated(player)
+        {
+            return false;
+        }
+        let heir = match self.concede_rule {
+            ConcedeRule::Neutral => None,
+            ConcedeRule::Leader => self.leading_opponent(player),
+        };
+        for territory in &mut self.territories {
+            if territory.owner == Some(player) {
+                territory.owner = heir;
+                territory.selected = false;
+            }
+        }
+        let cards = self.hands.remove(&player).unwrap_or_default();
+        if let Some(heir) = heir {
+            self.hands.entry(heir).or_default().extend(cards);
+        }
+        // Nothing from before the handover can be taken back
+        self.undo_stack.clear();
+        self.board_dirty = true;
+        self.log_event(GameEvent::Conceded { player, to: heir });
+
+        if self.current_player == player {
+            // Skips straight to the end of the turn, without the card a
+            // conquest would have earned
+            self.reinforcements_remaining = 0;
+            self.conquered_this_turn = false;
+            self.phase = Phase::Fortify;
+            self.next_phase();
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConcedeRule;
+    use crate::game::cards::Card;
+    use crate::game::player::PlayerId;
+    use crate::game::turn::Phase;
+    use crate::test_support::{game, territory};
+
+    fn three_players() -> crate::game::Game {
+        game(
+            vec![
+                territory("A", 0, 4, vec![1]),
+                territory("B", 1, 2, vec![0, 2]),
+                territory("C", 2, 1, vec![1, 3]),
+                territory("D", 2, 1, vec![2]),
+            ],
+            0,
+        )
+    }
+
+    #[test]
+    fn conceding_on_your_turn_passes_it_on() {
+        let mut game = three_players();
+        game.phase = Phase::Attack;
+        assert!(game.concede(PlayerId(0)));
+        assert_eq!(game.territories[0].owner, None);
+        assert_eq!(game.territories[0].armies, 4);
+        assert_eq!(
+            (game.current_player, game.phase),
+            (PlayerId(1), Phase::Reinforce)
+        );
+        assert_eq!(game.reinforcements_remaining, 3);
+        // Already out
+        assert!(!game.concede(PlayerId(0)));
+
+        // Player 2 conceding out of turn leaves the turn where it is, and
+        // player 0 (out of the game) is skipped when it comes round again
+        game.concede_rule = ConcedeRule::Leader;
+        assert!(game.concede(PlayerId(2)));
+        assert_eq!(game.current_player, PlayerId(1));
+        assert_eq!(game.winner(), Some(PlayerId(1)));
+    }
+
+    #[test]
+    fn the_leader_inherits_territories_and_cards() {
+        let mut game = three_players();
+        game.concede_rule = ConcedeRule::Leader;
+        game.hands.insert(PlayerId(1), vec![Card::Wild]);
+        // Player 2 holds the most territories
+        assert_eq!(game.leading_opponent(PlayerId(1)), Some(PlayerId(2)));
+        assert_eq!(game.leading_opponent(PlayerId(2)), Some(PlayerId(0)));
+        assert!(game.concede(PlayerId(1)));
+        assert_eq!(game.territories[1].owner, Some(PlayerId(2)));
+        assert_eq!(game.hand(PlayerId(2)), [Card::Wild]);
+        assert_eq!(game.current_player, PlayerId(0));
+    }
+}
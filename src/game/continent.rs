@@ -0,0 +1,90 @@
+use crate::game::player::PlayerId;
+use crate::game::Game;
+
+// A group of territories that pays out bonus armies to whoever holds all of it
+pub struct Continent {
+    pub name: String,
+    pub territories: Vec<usize>,
+    pub bonus: i32,
+    // A territory outside the continent that must be held for the bonus too,
+    // such as a fortress guarding its way in
+    pub required_capital: Option<usize>,
+}
+
+impl Game {
+    pub fn controls_continent(&self, player: PlayerId, continent: &Continent) -> bool {
+        let held = |i: usize| self.territories[i].owner == Some(player);
+        !continent.territories.is_empty()
+            && continent.territories.iter().all(|&i| held(i))
+            && continent.required_capital.is_none_or(held)
+    }
+
+    // Who holds every territory of the continent, if anyone does
+    pub fn continent_controller(&self, continent: &Continent) -> Option<PlayerId> {
+        let owner = self.territories[*continent.territories.first()?].owner?;
+        self.controls_continent(owner, continent).then_some(owner)
+    }
+
+    pub fn continent_bonus_for(&self, player: PlayerId) -> i32 {
+        self.continents
+            .iter()
+            .filter(|continent| self.controls_continent(player, continent))
+            .map(|continent| continent.bonus)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Continent;
+    use crate::game::player::PlayerId;
+    use crate::test_support::{game, territory};
+
+    #[test]
+    fn controller_follows_ownership() {
+        let mut game = game(
+            vec![territory("A", 0, 1, vec![1]), territory("B", 1, 1, vec![0])],
+            0,
+        );
+        let continent = Continent {
+            name: "Both".to_string(),
+            territories: vec![0, 1],
+            bonus: 2,
+            required_capital: None,
+        };
+        assert_eq!(game.continent_controller(&continent), None);
+        game.territories[1].owner = Some(PlayerId(0));
+        assert_eq!(game.continent_controller(&continent), Some(PlayerId(0)));
+        game.territories[0].owner = None;
+        assert_eq!(game.continent_controller(&continent), None);
+    }
+
+    #[test]
+    fn a_required_capital_must_be_held_too() {
+        let mut game = game(
+            vec![
+                territory("A", 0, 1, vec![1]),
+                territory("B", 0, 1, vec![0, 2]),
+                territory("C", 1, 1, vec![1]),
+            ],
+            0,
+        );
+        game.continents.push(Continent {
+            name: "West".to_string(),
+            territories: vec![0, 1],
+            bonus: 3,
+            required_capital: Some(2),
+        });
+        // Every listed territory but not the capital
+        assert!(!game.controls_continent(PlayerId(0), &game.continents[0]));
+        assert_eq!(game.continent_bonus_for(PlayerId(0)), 0);
+        assert_eq!(game.continent_controller(&game.continents[0]), None);
+
+        game.territories[2].owner = Some(PlayerId(0));
+        assert_eq!(game.continent_bonus_for(PlayerId(0)), 3);
+        assert_eq!(
+            game.continent_controller(&game.continents[0]),
+            Some(PlayerId(0))
+        );
+    }
+}
@@ -0,0 +1,74 @@
+use super::player::PlayerId;
+use super::Game;
+
+// Pairs are kept lowest seat first, so each alliance has one entry
+fn pair(a: PlayerId, b: PlayerId) -> (PlayerId, PlayerId) {
+    if a.0 <= b.0 {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+impl Game {
+    pub fn allied(&self, a: PlayerId, b: PlayerId) -> bool {
+        a != b && self.alliances.contains(&pair(a, b))
+    }
+
+    // Whether it made a difference: nobody allies with themselves, and an
+    // alliance can only be formed once
+    pub fn form_alliance(&mut self, a: PlayerId, b: PlayerId) -> bool {
+        a != b && self.alliances.insert(pair(a, b))
+    }
+
+    pub fn break_alliance(&mut self, a: PlayerId, b: PlayerId) -> bool {
+        self.alliances.remove(&pair(a, b))
+    }
+
+    // Everyone `player` is allied with, by seat
+    pub fn allies_of(&self, player: PlayerId) -> Vec<PlayerId> {
+        let mut allies: Vec<PlayerId> = self
+            .players
+            .iter()
+            .map(|p| p.id)
+            .filter(|&other| self.allied(player, other))
+            .collect();
+        allies.sort_by_key(|id| id.0);
+        allies
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::combat::AttackError;
+    use crate::game::player::PlayerId;
+    use crate::test_support::{game, territory};
+
+    #[test]
+    fn allies_cannot_attack_each_other_and_can_win_together() {
+        let mut game = game(
+            vec![
+                territory("A", 0, 5, vec![1]),
+                territory("B", 1, 5, vec![0, 2]),
+                territory("C", 2, 5, vec![1]),
+            ],
+            0,
+        );
+        assert!(game.form_alliance(PlayerId(1), PlayerId(0)));
+        assert!(!game.form_alliance(PlayerId(0), PlayerId(1)));
+        assert!(game.allied(PlayerId(0), PlayerId(1)));
+        assert_eq!(game.check_attack(0, 1), Err(AttackError::Allied));
+        assert_eq!(game.check_attack(1, 2), Ok(()));
+        assert_eq!(game.allies_of(PlayerId(0)), [PlayerId(1)]);
+
+        // Once the third player is gone, the alliance holds the whole map
+        game.territories[2].owner = Some(PlayerId(1));
+        assert_eq!(game.winner(), None);
+        game.team_victory = true;
+        assert_eq!(game.winner(), Some(PlayerId(0)));
+
+        assert!(game.break_alliance(PlayerId(0), PlayerId(1)));
+        assert_eq!(game.winner(), None);
+        assert_eq!(game.check_attack(0, 1), Ok(()));
+    }
+}
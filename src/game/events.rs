@@ -0,0 +1,197 @@
+use crate::game::player::PlayerId;
+use crate::game::turn::Phase;
+use crate::game::Game;
+use serde::{Deserialize, Serialize};
+
+// Something that happened during the game, in enough detail to rebuild the
+// message later. Territories are indices into `Game::territories`,
+// which keep their order in save files.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GameEvent {
+    PhaseStarted {
+        player: PlayerId,
+        phase: Phase,
+    },
+    Reinforced {
+        player: PlayerId,
+        territory: usize,
+        count: i32,
+    },
+    Attacked {
+        player: PlayerId,
+        from: usize,
+        to: usize,
+        attacker_losses: i32,
+        defender_losses: i32,
+        conquered: bool,
+        // The dice as rolled, highest first; missing from older saves
+        #[serde(default)]
+        attacker_dice: Vec<u8>,
+        #[serde(default)]
+        defender_dice: Vec<u8>,
+    },
+    Fortified {
+        player: PlayerId,
+        from: usize,
+        to: usize,
+        count: i32,
+    },
+    CardsTraded {
+        player: PlayerId,
+        bonus: i32,
+    },
+    Undone {
+        player: PlayerId,
+    },
+    Eliminated {
+        player: PlayerId,
+        by: PlayerId,
+    },
+    TurnForfeited {
+        player: PlayerId,
+    },
+    // None when their territories were left neutral
+    Conceded {
+        player: PlayerId,
+        to: Option<PlayerId>,
+    },
+}
+
+impl Game {
+    pub fn log_event(&mut self, event: GameEvent) {
+        // Armies go down one click at a time; fold a run of them on the same
+        // territory into a single entry
+        if let (
+            GameEvent::Reinforced {
+                player, territory, ..
+            },
+            Some(GameEvent::Reinforced {
+                player: last_player,
+                territory: last_territory,
+                count,
+            }),
+        ) = (&event, self.events.last_mut())
+        {
+            if player == last_player && territory == last_territory {
+                *count += 1;
+                return;
+            }
+        }
+        self.events.push(event);
+    }
+
+    pub fn describe_event(&self, event: &GameEvent) -> String {
+        let name = |territory: usize| self.territories[territory].name.as_str();
+        match event {
+            GameEvent::PhaseStarted { player, phase } => {
+                format!("{}: {} phase", self.player(*player).name, phase)
+            }
+            GameEvent::Reinforced {
+                player,
+                territory,
+                count,
+            } => format!(
+                "{} placed {} on {}",
+                self.player(*player).name,
+                count,
+                name(*territory)
+            ),
+            GameEvent::Attacked {
+                player,
+                from,
+                to,
+                attacker_losses,
+                defender_losses,
+                conquered,
+                ..
+            } => {
+                let mut text = format!(
+                    "{} attacked {} from {}, lost {}, killed {}",
+                    self.player(*player).name,
+                    name(*to),
+                    name(*from),
+                    attacker_losses,
+                    defender_losses
+                );
+                if *conquered {
+                    text.push_str(", won territory");
+                }
+                text
+            }
+            GameEvent::Fortified {
+                player,
+                from,
+                to,
+                count,
+            } => format!(
+                "{} moved {} from {} to {}",
+                self.player(*player).name,
+                count,
+                name(*from),
+                name(*to)
+            ),
+            GameEvent::CardsTraded { player, bonus } => format!(
+                "{} traded cards for {} armies",
+                self.player(*player).name,
+                bonus
+            ),
+            GameEvent::Undone { player } => {
+                format!("{} undid their last move", self.player(*player).name)
+            }
+            GameEvent::TurnForfeited { player } => {
+                format!("{} ran out of time", self.player(*player).name)
+            }
+            GameEvent::Conceded { player, to } => format!(
+                "{} conceded; their territories went to {}",
+                self.player(*player).name,
+                self.owner_name(*to)
+            ),
+            GameEvent::Eliminated { player, by } => format!(
+                "{} was eliminated by {}",
+                self.player(*player).name,
+                self.player(*by).name
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{game, territory};
+
+    #[test]
+    fn consecutive_placements_fold_into_one_entry() {
+        let mut game = game(
+            vec![
+                territory("A", 0, 1, vec![1]),
+                territory("B", 0, 1, vec![0]),
+                territory("C", 1, 1, vec![]),
+            ],
+            0,
+        );
+        game.place_reinforcement(0);
+        game.place_reinforcement(0);
+        game.place_reinforcement(1);
+        // The third army empties the pool, which also starts the Attack phase
+        assert_eq!(
+            game.events[..2],
+            [
+                GameEvent::Reinforced {
+                    player: PlayerId(0),
+                    territory: 0,
+                    count: 2,
+                },
+                GameEvent::Reinforced {
+                    player: PlayerId(0),
+                    territory: 1,
+                    count: 1,
+                },
+            ]
+        );
+        assert_eq!(
+            game.describe_event(&game.events[0]),
+            "Player 1 placed 2 on A"
+        );
+    }
+}
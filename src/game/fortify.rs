@@ -0,0 +1,140 @@
+use crate::game::events::GameEvent;
+use crate::game::undo::Action;
+use crate::game::Game;
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::fmt;
+
+// How far armies may travel in one fortify
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FortifyRule {
+    // Only to a bordering territory, as in the classic rules
+    #[default]
+    Adjacent,
+    // Anywhere joined to the source through the player's own territories
+    Connected,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FortifyError {
+    SameTerritory,
+    NotOwned,
+    NotAdjacent,
+    // No chain of the player's territories leads there
+    NotConnected,
+    InvalidCount,
+    // Moving this many would leave the source empty
+    TooFewArmies { available: i32 },
+    // The destination is already at the army cap
+    Full,
+}
+
+impl fmt::Display for FortifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FortifyError::SameTerritory => write!(f, "Pick two different territories"),
+            FortifyError::NotOwned => {
+                write!(f, "You can only fortify between your own territories")
+            }
+            FortifyError::NotAdjacent => write!(f, "Those territories don't share a border"),
+            FortifyError::NotConnected => {
+                write!(f, "Those territories aren't joined by territories of yours")
+            }
+            FortifyError::InvalidCount => write!(f, "Move at least one army"),
+            FortifyError::TooFewArmies { available } => {
+                write!(
+                    f,
+                    "Only {} army(s) can leave; one must stay behind",
+                    available
+                )
+            }
+            FortifyError::Full => write!(f, "That territory can't hold any more armies"),
+        }
+    }
+}
+
+impl Game {
+    // Where `from`'s owner could send its armies this fortify under the
+    // fortify rule, nearest first (then by index)
+    pub fn fortify_reachable(&self, from: usize) -> Vec<usize> {
+        let owner = self.territories[from].owner;
+        let own_neighbours = |t: usize| {
+            self.territories[t]
+                .adjacent
+                .iter()
+                .copied()
+                .filter(move |&n| self.territories[n].owner == owner)
+        };
+        if self.fortify_rule == FortifyRule::Adjacent {
+            let mut reachable: Vec<usize> = own_neighbours(from).filter(|&n| n != from).collect();
+            reachable.sort_unstable();
+            reachable.dedup();
+            return reachable;
+        }
+        let mut seen = vec![false; self.territories.len()];
+        seen[from] = true;
+        let mut queue = VecDeque::from([from]);
+        let mut reachable = Vec::new();
+        while let Some(t) = queue.pop_front() {
+            let mut next: Vec<usize> = own_neighbours(t).filter(|&n| !seen[n]).collect();
+            next.sort_unstable();
+            next.dedup();
+            for n in next {
+                seen[n] = true;
+                reachable.push(n);
+                queue.push_back(n);
+            }
+        }
+        reachable
+    }
+
+    pub fn check_fortify_route(&self, from: usize, to: usize) -> Result<(), FortifyError> {
+        if self.fortify_reachable(from).contains(&to) {
+            Ok(())
+        } else if self.fortify_rule == FortifyRule::Connected {
+            Err(FortifyError::NotConnected)
+        } else {
+            Err(FortifyError::NotAdjacent)
+        }
+    }
+
+    // Moves armies between two of the current player's territories that the
+    // fortify rule lets them move between, always leaving at least one army
+    // in the source. The move shrinks to fit under the army cap; returns how
+    // many actually moved.
+    pub fn fortify(&mut self, from: usize, to: usize, count: i32) -> Result<i32, FortifyError> {
+        if from == to {
+            return Err(FortifyError::SameTerritory);
+        }
+        let current = Some(self.current_player);
+        if self.territories[from].owner != current || self.territories[to].owner != current {
+            return Err(FortifyError::NotOwned);
+        }
+        self.check_fortify_route(from, to)?;
+        if count <= 0 {
+            return Err(FortifyError::InvalidCount);
+        }
+        let available = self.territories[from].armies - 1;
+        if count > available {
+            return Err(FortifyError::TooFewArmies { available });
+        }
+        let count = count.min(self.room_in(to));
+        if count == 0 {
+            return Err(FortifyError::Full);
+        }
+
+        self.territories[from].armies -= count;
+        self.territories[to].armies += count;
+        self.note_stack(to);
+        self.board_dirty = true;
+        self.record(Action::Fortify { from, to, count });
+        self.log_event(GameEvent::Fortified {
+            player: self.current_player,
+            from,
+            to,
+            count,
+        });
+        Ok(count)
+    }
+}
@@ -0,0 +1,220 @@
+use crate::game::map::{MapLoadError, MapOwner, MapSource, TerritoryData};
+use crate::game::player::PlayerId;
+use crate::game::territory::Terrain;
+use serde::Deserialize;
+use std::f64::consts::PI;
+use std::fs;
+use std::path::PathBuf;
+
+// World units across the widest part of an imported map
+const MAP_WIDTH: f64 = 1000.0;
+
+#[derive(Deserialize)]
+struct FeatureCollection {
+    features: Vec<Feature>,
+}
+
+#[derive(Deserialize)]
+struct Feature {
+    #[serde(default)]
+    properties: Option<serde_json::Map<String, serde_json::Value>>,
+    geometry: Option<Geometry>,
+}
+
+// Positions are [lon, lat] with an optional altitude after them
+type Ring = Vec<Vec<f64>>;
+
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+enum Geometry {
+    Polygon {
+        coordinates: Vec<Ring>,
+    },
+    MultiPolygon {
+        coordinates: Vec<Vec<Ring>>,
+    },
+    #[serde(other)]
+    Unsupported,
+}
+
+// Imports a GeoJSON FeatureCollection. Each Polygon or MultiPolygon feature
+// becomes a territory named by its `name` property; other geometries are
+// skipped. Holes are ignored, and a MultiPolygon keeps only its largest
+// part (islands and exclaves are dropped, with a note on stderr).
+pub struct GeoJsonMapSource {
+    pub path: PathBuf,
+}
+
+impl MapSource for GeoJsonMapSource {
+    fn load(&self) -> Result<Vec<TerritoryData>, MapLoadError> {
+        let path = &self.path;
+        let file_content = fs::read_to_string(path).map_err(|source| MapLoadError::Io {
+            path: path.clone(),
+            source,
+        })?;
+        let collection: FeatureCollection =
+            serde_json::from_str(&file_content).map_err(|source| MapLoadError::Parse {
+                path: path.clone(),
+                source,
+            })?;
+        parse_features(collection).map_err(|message| MapLoadError::GeoJson {
+            path: path.clone(),
+            message,
+        })
+    }
+}
+
+fn parse_features(collection: FeatureCollection) -> Result<Vec<TerritoryData>, String> {
+    let mut outlines = Vec::new();
+    for (i, feature) in collection.features.into_iter().enumerate() {
+        let name = feature
+            .properties
+            .as_ref()
+            .and_then(|properties| properties.get("name"))
+            .and_then(|name| name.as_str())
+            .ok_or_else(|| format!("Feature {} has no \"name\" property", i))?
+            .to_string();
+        let ring = match feature.geometry {
+            Some(Geometry::Polygon { coordinates }) => coordinates.into_iter().next(),
+            Some(Geometry::MultiPolygon { coordinates }) => {
+                if coordinates.len() > 1 {
+                    eprintln!(
+                        "{}: keeping the largest of {} polygons",
+                        name,
+                        coordinates.len()
+                    );
+                }
+                coordinates
+                    .into_iter()
+                    .filter_map(|polygon| polygon.into_iter().next())
+                    .max_by(|a, b| ring_area(a).total_cmp(&ring_area(b)))
+            }
+            Some(Geometry::Unsupported) | None => continue,
+        };
+        let ring = ring.ok_or_else(|| format!("{} has an empty polygon", name))?;
+        let mut lon_lat = ring
+            .iter()
+            .map(|position| match position[..] {
+                [lon, lat, ..] => Ok([lon, lat]),
+                _ => Err(format!("{} has a position without two coordinates", name)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        // Rings repeat their first position at the end
+        if lon_lat.len() > 1 && lon_lat.first() == lon_lat.last() {
+            lon_lat.pop();
+        }
+        outlines.push((name, lon_lat));
+    }
+    Ok(project(outlines))
+}
+
+fn ring_area(ring: &Ring) -> f64 {
+    let n = ring.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (&ring[i], &ring[(i + 1) % n]);
+            a[0] * b[1] - b[0] * a[1]
+        })
+        .sum::<f64>()
+        .abs()
+        / 2.0
+}
+
+// Equirectangular projection: longitude is squeezed by the cosine of the
+// map's middle latitude so shapes near it keep their proportions, and
+// latitude is flipped since world y grows downwards. The result is shifted
+// to the origin and scaled to MAP_WIDTH.
+fn project(outlines: Vec<(String, Vec<[f64; 2]>)>) -> Vec<TerritoryData> {
+    let points = || outlines.iter().flat_map(|(_, ring)| ring.iter());
+    let (min_lat, max_lat) = points().fold((f64::MAX, f64::MIN), |(lo, hi), p| {
+        (lo.min(p[1]), hi.max(p[1]))
+    });
+    let squeeze = ((min_lat + max_lat) / 2.0 * PI / 180.0).cos();
+    let plane = |p: &[f64; 2]| [p[0] * squeeze, -p[1]];
+    let (min, max) = points()
+        .map(plane)
+        .fold(([f64::MAX; 2], [f64::MIN; 2]), |(min, max), p| {
+            (
+                [min[0].min(p[0]), min[1].min(p[1])],
+                [max[0].max(p[0]), max[1].max(p[1])],
+            )
+        });
+    let scale = MAP_WIDTH / (max[0] - min[0]).max(max[1] - min[1]).max(f64::EPSILON);
+
+    outlines
+        .iter()
+        .map(|(name, ring)| TerritoryData {
+            id: None,
+            name: name.clone(),
+            vertices: ring
+                .iter()
+                .map(|p| {
+                    let [x, y] = plane(p);
+                    [((x - min[0]) * scale) as f32, ((y - min[1]) * scale) as f32]
+                })
+                .collect(),
+            owner: Some(MapOwner::Seat(PlayerId(0))),
+            armies: 1,
+            selected: false,
+            adjacent: Vec::new(),
+            sea_routes: Vec::new(),
+            rivers: Vec::new(),
+            capital: false,
+            terrain: Terrain::Plains,
+            continent: None,
+            bonus: 0,
+            capital_of: None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> Result<Vec<TerritoryData>, String> {
+        parse_features(serde_json::from_str(json).unwrap())
+    }
+
+    #[test]
+    fn polygons_are_projected_and_named() {
+        let territories = parse(
+            r#"{"type": "FeatureCollection", "features": [
+                {"type": "Feature", "properties": {"name": "West"},
+                 "geometry": {"type": "Polygon", "coordinates":
+                    [[[0, 0], [1, 0], [1, 1], [0, 1], [0, 0]]]}},
+                {"type": "Feature", "properties": {"name": "East"},
+                 "geometry": {"type": "MultiPolygon", "coordinates": [
+                    [[[1, 0], [1.1, 0], [1.1, 0.1], [1, 0]]],
+                    [[[1, 0], [2, 0], [2, 1], [1, 1], [1, 0]]]]}},
+                {"type": "Feature", "properties": {"name": "Marker"},
+                 "geometry": {"type": "Point", "coordinates": [5, 5]}}
+            ]}"#,
+        )
+        .unwrap();
+        let names: Vec<&str> = territories.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["West", "East"]);
+        // Closing positions are dropped and the larger part of East is kept
+        assert_eq!(territories[0].vertices.len(), 4);
+        assert_eq!(territories[1].vertices.len(), 4);
+        // Two degrees wide fills the map, one degree tall is half of it,
+        // and north is up
+        assert!((territories[1].vertices[1][0] - MAP_WIDTH as f32).abs() < 0.01);
+        assert!((territories[0].vertices[0][1] - 500.0).abs() < 0.1);
+        assert_eq!(territories[0].vertices[3][1], 0.0);
+    }
+
+    #[test]
+    fn features_need_a_name() {
+        let result = parse(
+            r#"{"type": "FeatureCollection", "features": [
+                {"type": "Feature", "properties": {},
+                 "geometry": {"type": "Polygon", "coordinates": [[[0, 0], [1, 0], [0, 1]]]}}
+            ]}"#,
+        );
+        assert_eq!(
+            result.err().as_deref(),
+            Some("Feature 0 has no \"name\" property")
+        );
+    }
+}
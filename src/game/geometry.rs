@@ -0,0 +1,522 @@
+use macroquad::math::*;
+
+// Twice the signed area of a polygon; positive when the vertices run
+// counter-clockwise in a y-up coordinate system
+fn doubled_signed_area(vertices: &[Vec2]) -> f32 {
+    let n = vertices.len();
+    (0..n)
+        .map(|i| vertices[i].perp_dot(vertices[(i + 1) % n]))
+        .sum()
+}
+
+// Positive when the vertices run counter-clockwise with y up (so clockwise
+// on screen, where y points down)
+pub fn signed_area(vertices: &[Vec2]) -> f32 {
+    doubled_signed_area(vertices) / 2.0
+}
+
+// Reverses an outline that winds the other way, so every territory's
+// vertices share the positive winding the rest of this module can rely on
+pub fn normalize_winding(vertices: &mut [Vec2]) {
+    if doubled_signed_area(vertices) < 0.0 {
+        vertices.reverse();
+    }
+}
+
+fn point_in_triangle(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+    let d1 = (b - a).perp_dot(p - a);
+    let d2 = (c - b).perp_dot(p - b);
+    let d3 = (a - c).perp_dot(p - c);
+    let has_neg = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+    let has_pos = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+    !(has_neg && has_pos)
+}
+
+fn is_ear(vertices: &[Vec2], remaining: &[usize], prev: usize, curr: usize, next: usize) -> bool {
+    let (a, b, c) = (vertices[prev], vertices[curr], vertices[next]);
+
+    // Reflex or collinear corners can never be clipped
+    if (b - a).perp_dot(c - b) <= 0.0 {
+        return false;
+    }
+
+    remaining
+        .iter()
+        .filter(|&&i| i != prev && i != curr && i != next)
+        .all(|&i| !point_in_triangle(vertices[i], a, b, c))
+}
+
+// Which side of the line through `a` and `b` the point `p` falls on
+fn orientation(a: Vec2, b: Vec2, p: Vec2) -> f32 {
+    (b - a).perp_dot(p - a)
+}
+
+// Whether `p`, already known to be collinear with `a`-`b`, lies on that segment
+fn on_segment(a: Vec2, b: Vec2, p: Vec2) -> bool {
+    p.cmpge(a.min(b)).all() && p.cmple(a.max(b)).all()
+}
+
+// Closed segments `a`-`b` and `c`-`d` share at least one point
+fn segments_intersect(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> bool {
+    let (d1, d2) = (orientation(c, d, a), orientation(c, d, b));
+    let (d3, d4) = (orientation(a, b, c), orientation(a, b, d));
+    if ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+        && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
+    {
+        return true;
+    }
+    (d1 == 0.0 && on_segment(c, d, a))
+        || (d2 == 0.0 && on_segment(c, d, b))
+        || (d3 == 0.0 && on_segment(a, b, c))
+        || (d4 == 0.0 && on_segment(a, b, d))
+}
+
+// The first pair of non-neighbouring edges that touch or cross, as edge
+// indices (edge `i` runs from vertex `i` to vertex `i + 1`), or None for a
+// simple polygon
+pub fn self_intersection(vertices: &[Vec2]) -> Option<(usize, usize)> {
+    let n = vertices.len();
+    let edge = |i: usize| (vertices[i], vertices[(i + 1) % n]);
+    for i in 0..n {
+        // Neighbouring edges always share a vertex, so skip those; the first
+        // and last edges are neighbours too
+        for j in i + 2..n {
+            if i == 0 && j == n - 1 {
+                continue;
+            }
+            let ((a, b), (c, d)) = (edge(i), edge(j));
+            if segments_intersect(a, b, c, d) {
+                return Some((i, j));
+            }
+        }
+    }
+    None
+}
+
+// Even-odd test, for geometry that isn't a `Territory` yet
+pub fn contains(vertices: &[Vec2], p: Vec2) -> bool {
+    let n = vertices.len();
+    let mut inside = false;
+    for i in 0..n {
+        let (a, b) = (vertices[i], vertices[(i + 1) % n]);
+        if (a.y > p.y) != (b.y > p.y) && p.x < a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+fn distance_to_segment(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    let t = if ab.length_squared() > 0.0 {
+        ((p - a).dot(ab) / ab.length_squared()).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    p.distance(a + ab * t)
+}
+
+fn distance_to_outline(vertices: &[Vec2], p: Vec2) -> f32 {
+    let n = vertices.len();
+    (0..n)
+        .map(|i| distance_to_segment(p, vertices[i], vertices[(i + 1) % n]))
+        .fold(f32::MAX, f32::min)
+}
+
+// Drops each vertex within `epsilon` of the one before it, the last being
+// compared with the first too, leaving no zero-length edges. Traced and
+// hand-edited maps often repeat a point, most often closing the ring with a
+// copy of the first.
+pub fn remove_duplicate_vertices(vertices: &[Vec2], epsilon: f32) -> Vec<Vec2> {
+    let mut kept: Vec<Vec2> = Vec::with_capacity(vertices.len());
+    for &v in vertices {
+        if kept.last().is_none_or(|last| last.distance(v) > epsilon) {
+            kept.push(v);
+        }
+    }
+    while kept.len() > 1 && kept[0].distance(kept[kept.len() - 1]) <= epsilon {
+        kept.pop();
+    }
+    kept
+}
+
+// Ramer-Douglas-Peucker over a closed outline: drops every vertex that's
+// within `tolerance` of the line its kept neighbours would draw instead.
+// `pinned` vertices are always kept and the ring is simplified a stretch
+// between them at a time; with none pinned it's split at vertex 0 and the
+// vertex farthest from it, both kept. May leave fewer than 3 vertices, or a
+// crossing, for callers to reject.
+pub fn simplify_outline(vertices: &[Vec2], pinned: &[bool], tolerance: f32) -> Vec<Vec2> {
+    let n = vertices.len();
+    if n <= 3 {
+        return vertices.to_vec();
+    }
+    let mut keep: Vec<bool> = (0..n).map(|i| pinned.get(i) == Some(&true)).collect();
+    let pins: Vec<usize> = (0..n).filter(|&i| keep[i]).collect();
+    if pins.is_empty() {
+        let far = (1..n)
+            .max_by(|&a, &b| {
+                let (da, db) = (
+                    vertices[a].distance(vertices[0]),
+                    vertices[b].distance(vertices[0]),
+                );
+                da.total_cmp(&db)
+            })
+            .unwrap_or(1);
+        keep[0] = true;
+        keep[far] = true;
+        keep_farthest(vertices, 0, far, tolerance, &mut keep);
+        keep_farthest(vertices, far, n, tolerance, &mut keep);
+    } else {
+        for (i, &start) in pins.iter().enumerate() {
+            // The last stretch wraps round to the first pin
+            let end = pins.get(i + 1).copied().unwrap_or(pins[0] + n);
+            keep_farthest(vertices, start, end, tolerance, &mut keep);
+        }
+    }
+    vertices
+        .iter()
+        .zip(&keep)
+        .filter(|(_, &kept)| kept)
+        .map(|(&v, _)| v)
+        .collect()
+}
+
+// Marks the vertex between `start` and `end` furthest off the chord joining
+// them, if it's further than `tolerance`, and recurses either side. Indices
+// wrap round the ring, so `end` may run past the last vertex. A stretch
+// comes out the same walked either way round, as a border shared by two
+// outlines is: the chord is measured from its lower end and ties go to
+// the lower point.
+fn keep_farthest(vertices: &[Vec2], start: usize, end: usize, tolerance: f32, keep: &mut [bool]) {
+    let n = vertices.len();
+    let (a, b) = (vertices[start % n], vertices[end % n]);
+    let (a, b) = if lower(a, b) { (a, b) } else { (b, a) };
+    let farthest = (start + 1..end)
+        .map(|i| (i, distance_to_segment(vertices[i % n], a, b)))
+        .max_by(|&(i, x), &(j, y)| {
+            let (p, q) = (vertices[i % n], vertices[j % n]);
+            x.total_cmp(&y)
+                .then(q.x.total_cmp(&p.x))
+                .then(q.y.total_cmp(&p.y))
+        });
+    if let Some((i, _)) = farthest.filter(|&(_, distance)| distance > tolerance) {
+        keep[i % n] = true;
+        keep_farthest(vertices, start, i, tolerance, keep);
+        keep_farthest(vertices, i, end, tolerance, keep);
+    }
+}
+
+// A fixed order on points, for breaking ties the same way every time
+fn lower(a: Vec2, b: Vec2) -> bool {
+    (a.x, a.y) < (b.x, b.y)
+}
+
+// For each edge of `vertices` (edge i runs from vertex i to i + 1), whether
+// it lies along one of the `neighbours` outlines. Edges are sampled at a
+// quarter, half and three quarters of the way along, and count as shared
+// when most samples are within `tolerance`, so an edge that only partly
+// overlaps a neighbour's (or is split into several there) still counts.
+pub fn shared_edges(vertices: &[Vec2], neighbours: &[&[Vec2]], tolerance: f32) -> Vec<bool> {
+    let n = vertices.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (vertices[i], vertices[(i + 1) % n]);
+            let near = [0.25, 0.5, 0.75]
+                .iter()
+                .filter(|&&t| {
+                    let sample = a.lerp(b, t);
+                    neighbours
+                        .iter()
+                        .any(|outline| distance_to_outline(outline, sample) <= tolerance)
+                })
+                .count();
+            near >= 2
+        })
+        .collect()
+}
+
+// The pieces of the segment a-b that lie inside the polygon, found by
+// cutting it wherever it crosses the outline and keeping alternate spans
+pub fn clip_segment(vertices: &[Vec2], a: Vec2, b: Vec2) -> Vec<(Vec2, Vec2)> {
+    let n = vertices.len();
+    let ab = b - a;
+    let mut cuts: Vec<f32> = (0..n)
+        .filter_map(|i| {
+            let (c, d) = (vertices[i], vertices[(i + 1) % n]);
+            let cd = d - c;
+            let denominator = ab.perp_dot(cd);
+            if denominator.abs() <= f32::EPSILON {
+                return None;
+            }
+            let t = (c - a).perp_dot(cd) / denominator;
+            let u = (c - a).perp_dot(ab) / denominator;
+            // Half-open in u so a line through a vertex cuts there only once
+            ((0.0..=1.0).contains(&t) && (0.0..1.0).contains(&u)).then_some(t)
+        })
+        .collect();
+    cuts.push(0.0);
+    cuts.push(1.0);
+    cuts.sort_by(f32::total_cmp);
+    cuts.windows(2)
+        .filter(|span| span[1] > span[0] && contains(vertices, a + ab * (span[0] + span[1]) / 2.0))
+        .map(|span| (a + ab * span[0], a + ab * span[1]))
+        .collect()
+}
+
+// Centre of mass of the polygon's area (the shoelace formula weighted by each
+// edge's contribution), or None when it encloses no area
+pub fn area_centroid(vertices: &[Vec2]) -> Option<Vec2> {
+    let doubled_area = doubled_signed_area(vertices);
+    if doubled_area.abs() <= f32::EPSILON {
+        return None;
+    }
+    let n = vertices.len();
+    let sum: Vec2 = (0..n)
+        .map(|i| {
+            let (a, b) = (vertices[i], vertices[(i + 1) % n]);
+            (a + b) * a.perp_dot(b)
+        })
+        .sum();
+    Some(sum / (3.0 * doubled_area))
+}
+
+// Rough pole of inaccessibility: the inside point furthest from the outline.
+// A coarse grid over the bounding box is searched, then a finer one around
+// the best cell, a few times over.
+pub fn pole_of_inaccessibility(vertices: &[Vec2]) -> Option<Vec2> {
+    const GRID: usize = 16;
+    const REFINEMENTS: usize = 4;
+    let (min, max) = vertices.iter().fold(
+        (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+        |(min, max), &v| (min.min(v), max.max(v)),
+    );
+    let (mut min, mut max) = (min, max);
+    let mut best: Option<(Vec2, f32)> = None;
+    for _ in 0..REFINEMENTS {
+        let cell = (max - min) / GRID as f32;
+        for row in 0..GRID {
+            for col in 0..GRID {
+                let p = min + cell * vec2(col as f32 + 0.5, row as f32 + 0.5);
+                if !contains(vertices, p) {
+                    continue;
+                }
+                let distance = distance_to_outline(vertices, p);
+                if best.is_none_or(|(_, d)| distance > d) {
+                    best = Some((p, distance));
+                }
+            }
+        }
+        let (centre, _) = best?;
+        min = centre - cell;
+        max = centre + cell;
+    }
+    best.map(|(p, _)| p)
+}
+
+// Where labels go: the area centroid when it's inside the outline, which it
+// may not be for concave shapes, otherwise a point well inside instead
+pub fn label_point(vertices: &[Vec2]) -> Vec2 {
+    if vertices.is_empty() {
+        return Vec2::ZERO;
+    }
+    let average = vertices.iter().copied().sum::<Vec2>() / vertices.len() as f32;
+    match area_centroid(vertices) {
+        Some(centroid) if contains(vertices, centroid) => centroid,
+        Some(_) => pole_of_inaccessibility(vertices).unwrap_or(average),
+        None => average,
+    }
+}
+
+// Splits a simple polygon into triangles using ear clipping, returning
+// triangles as indices into `vertices`. Works for concave polygons wound as
+// `normalize_winding` leaves them; polygons with fewer than 3 vertices
+// produce no triangles.
+pub fn triangulate(vertices: &[Vec2]) -> Vec<[usize; 3]> {
+    let n = vertices.len();
+    if n < 3 {
+        return Vec::new();
+    }
+
+    let mut remaining: Vec<usize> = (0..n).collect();
+
+    let mut triangles = Vec::with_capacity(n - 2);
+    while remaining.len() > 3 {
+        let m = remaining.len();
+        let ear = (0..m).find(|&i| {
+            is_ear(
+                vertices,
+                &remaining,
+                remaining[(i + m - 1) % m],
+                remaining[i],
+                remaining[(i + 1) % m],
+            )
+        });
+
+        // A simple polygon always has an ear, so running out means the
+        // outline is self-intersecting; fill what we have rather than loop
+        let Some(i) = ear else {
+            break;
+        };
+
+        triangles.push([
+            remaining[(i + m - 1) % m],
+            remaining[i],
+            remaining[(i + 1) % m],
+        ]);
+        remaining.remove(i);
+    }
+
+    if remaining.len() == 3 {
+        triangles.push([remaining[0], remaining[1], remaining[2]]);
+    }
+    triangles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn l_shape(arm: f32) -> Vec<Vec2> {
+        vec![
+            vec2(0.0, 0.0),
+            vec2(arm, 0.0),
+            vec2(arm, 1.0),
+            vec2(1.0, 1.0),
+            vec2(1.0, arm),
+            vec2(0.0, arm),
+        ]
+    }
+
+    fn rectangle(min: Vec2, max: Vec2) -> Vec<Vec2> {
+        vec![min, vec2(max.x, min.y), max, vec2(min.x, max.y)]
+    }
+
+    #[test]
+    fn shared_edges_include_partial_overlaps() {
+        let left = rectangle(vec2(0.0, 0.0), vec2(2.0, 2.0));
+        // The two overlap along x = 2 for y in 0.5..2: all of the right edge
+        // of `left` bar a sliver, and most of the left edge of `right`
+        let right = rectangle(vec2(2.0, 0.5), vec2(4.0, 3.0));
+        assert_eq!(
+            shared_edges(&left, &[&right], 0.01),
+            [false, true, false, false]
+        );
+        assert_eq!(
+            shared_edges(&right, &[&left], 0.01),
+            [false, false, false, true]
+        );
+        // Against nothing every edge is coastline
+        assert_eq!(shared_edges(&left, &[], 0.01), [false; 4]);
+    }
+
+    #[test]
+    fn clip_segment_keeps_the_inside_spans() {
+        // Crossing both arms of an L, passing through the notch between them
+        let spans = clip_segment(&l_shape(3.0), vec2(-1.0, 2.0), vec2(4.0, 2.0));
+        assert_eq!(spans, [(vec2(0.0, 2.0), vec2(1.0, 2.0))]);
+        let spans = clip_segment(&l_shape(3.0), vec2(0.5, -1.0), vec2(0.5, 0.5));
+        assert_eq!(spans, [(vec2(0.5, 0.0), vec2(0.5, 0.5))]);
+        let spans = clip_segment(&l_shape(3.0), vec2(-1.0, 0.5), vec2(4.0, 0.5));
+        assert_eq!(spans, [(vec2(0.0, 0.5), vec2(3.0, 0.5))]);
+    }
+
+    #[test]
+    fn area_centroid_of_an_l_shape() {
+        // Three unit squares: the vertex average would sit on the inner corner
+        let centroid = area_centroid(&l_shape(2.0)).unwrap();
+        assert!(centroid.distance(vec2(5.0 / 6.0, 5.0 / 6.0)) < 1e-5);
+        assert_eq!(label_point(&l_shape(2.0)), centroid);
+
+        // Winding doesn't matter
+        let mut reversed = l_shape(2.0);
+        reversed.reverse();
+        assert!(area_centroid(&reversed).unwrap().distance(centroid) < 1e-5);
+    }
+
+    #[test]
+    fn either_winding_normalizes_to_the_same_outline() {
+        let counter_clockwise = l_shape(2.0);
+        assert_eq!(signed_area(&counter_clockwise), 3.0);
+        let mut clockwise = counter_clockwise.clone();
+        clockwise.reverse();
+        assert_eq!(signed_area(&clockwise), -3.0);
+
+        let mut normalized = counter_clockwise.clone();
+        normalize_winding(&mut normalized);
+        normalize_winding(&mut clockwise);
+        assert_eq!(normalized, counter_clockwise);
+        assert_eq!(clockwise, counter_clockwise);
+        assert_eq!(triangulate(&clockwise).len(), 4);
+    }
+
+    #[test]
+    fn thin_l_shape_label_stays_inside() {
+        let vertices = l_shape(10.0);
+        let centroid = area_centroid(&vertices).unwrap();
+        assert!(!contains(&vertices, centroid));
+
+        let label = label_point(&vertices);
+        assert!(contains(&vertices, label));
+        // Not hugging the outline either
+        assert!(distance_to_outline(&vertices, label) > 0.4);
+    }
+
+    #[test]
+    fn repeated_vertices_are_dropped_around_the_ring() {
+        let vertices = [
+            vec2(0.0, 0.0),
+            vec2(0.0, 0.0),
+            vec2(10.0, 0.0),
+            vec2(10.0, 0.00001),
+            vec2(10.0, 10.0),
+            vec2(0.0, 10.0),
+            vec2(0.0, 0.0),
+        ];
+        assert_eq!(
+            remove_duplicate_vertices(&vertices, 1e-4),
+            [
+                vec2(0.0, 0.0),
+                vec2(10.0, 0.0),
+                vec2(10.0, 10.0),
+                vec2(0.0, 10.0)
+            ]
+        );
+        // A ring of one point collapses to that point
+        assert_eq!(
+            remove_duplicate_vertices(&[vec2(1.0, 1.0); 3], 1e-4),
+            [vec2(1.0, 1.0)]
+        );
+        assert!(remove_duplicate_vertices(&[], 1e-4).is_empty());
+    }
+
+    #[test]
+    fn simplifying_keeps_the_corners_of_a_noisy_square() {
+        // Each side of a 10x10 square traced as 10 slightly wobbly points
+        let mut vertices = Vec::new();
+        let corners = [
+            vec2(0.0, 0.0),
+            vec2(10.0, 0.0),
+            vec2(10.0, 10.0),
+            vec2(0.0, 10.0),
+        ];
+        for side in 0..4 {
+            let (a, b) = (corners[side], corners[(side + 1) % 4]);
+            for step in 0..10 {
+                let wobble = if step % 2 == 1 { 0.05 } else { 0.0 };
+                vertices.push(a.lerp(b, step as f32 / 10.0) + (b - a).perp().normalize() * wobble);
+            }
+        }
+        let simplified = simplify_outline(&vertices, &[], 0.1);
+        assert_eq!(simplified, corners);
+        // Nothing is dropped at a tolerance finer than the wobble
+        assert_eq!(simplify_outline(&vertices, &[], 0.01).len(), 40);
+    }
+
+    #[test]
+    fn degenerate_outlines_fall_back_to_the_vertex_average() {
+        let line = [vec2(0.0, 0.0), vec2(2.0, 0.0), vec2(4.0, 0.0)];
+        assert_eq!(area_centroid(&line), None);
+        assert_eq!(label_point(&line), vec2(2.0, 0.0));
+    }
+}
@@ -0,0 +1,142 @@
+// Golden snapshots of the geometry derived from known maps. Rendering and
+// hit testing depend on it, so any change shows up here as a diff. After an
+// intentional change, regenerate the snapshots with
+//     UPDATE_GOLDEN=1 cargo test golden
+use crate::game::hex;
+use crate::game::map::{self, MapSource, TerritoryData};
+use crate::game::Territory;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+
+// Everything worked out from a territory's outline, rounded so the snapshot
+// doesn't depend on the last bits of float arithmetic
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Layout {
+    name: String,
+    vertices: usize,
+    triangles: usize,
+    centroid: [f32; 2],
+    min: [f32; 2],
+    max: [f32; 2],
+    adjacent: Vec<usize>,
+}
+
+fn round(value: f32) -> f32 {
+    (value * 1000.0).round() / 1000.0
+}
+
+fn layout(territory: &Territory) -> Layout {
+    let point = |v: macroquad::math::Vec2| [round(v.x), round(v.y)];
+    Layout {
+        name: territory.name.clone(),
+        vertices: territory.vertices.len(),
+        triangles: territory.triangles.len(),
+        centroid: point(territory.centroid()),
+        min: point(territory.min),
+        max: point(territory.max),
+        adjacent: territory.adjacent.clone(),
+    }
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.json", name))
+}
+
+// Compares the map's layout with the committed snapshot `name`, failing with
+// one line per territory field that moved
+fn check_golden(name: &str, data: &[TerritoryData]) {
+    let territories = map::build_territories(data).expect("golden maps should build");
+    let actual: Vec<Layout> = territories.iter().map(layout).collect();
+    let path = golden_path(name);
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        let json = serde_json::to_string_pretty(&actual).unwrap();
+        fs::write(&path, json + "\n").unwrap();
+        return;
+    }
+    let expected: Vec<Layout> =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap_or_else(|err| {
+            panic!("{}: {} (UPDATE_GOLDEN=1 writes it)", path.display(), err)
+        }))
+        .unwrap();
+
+    let mut diff = String::new();
+    if expected.len() != actual.len() {
+        let _ = writeln!(
+            diff,
+            "{} territories, expected {}",
+            actual.len(),
+            expected.len()
+        );
+    }
+    for (expected, actual) in expected.iter().zip(&actual) {
+        let fields: [(&str, String, String); 7] = [
+            (
+                "name",
+                format!("{:?}", expected.name),
+                format!("{:?}", actual.name),
+            ),
+            (
+                "vertices",
+                format!("{}", expected.vertices),
+                format!("{}", actual.vertices),
+            ),
+            (
+                "triangles",
+                format!("{}", expected.triangles),
+                format!("{}", actual.triangles),
+            ),
+            (
+                "centroid",
+                format!("{:?}", expected.centroid),
+                format!("{:?}", actual.centroid),
+            ),
+            (
+                "min",
+                format!("{:?}", expected.min),
+                format!("{:?}", actual.min),
+            ),
+            (
+                "max",
+                format!("{:?}", expected.max),
+                format!("{:?}", actual.max),
+            ),
+            (
+                "adjacent",
+                format!("{:?}", expected.adjacent),
+                format!("{:?}", actual.adjacent),
+            ),
+        ];
+        for (field, was, now) in fields {
+            if was != now {
+                let _ = writeln!(
+                    diff,
+                    "  {} {}: expected {}, got {}",
+                    expected.name, field, was, now
+                );
+            }
+        }
+    }
+    assert!(
+        diff.is_empty(),
+        "{} no longer matches {}:\n{}",
+        name,
+        path.display(),
+        diff
+    );
+}
+
+#[test]
+fn golden_default_map() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("resources/maps/classic.json");
+    check_golden("default_map", &map::JsonMapSource { path }.load().unwrap());
+}
+
+#[test]
+fn golden_hex_map() {
+    check_golden("hex_5x4", &hex::generate_hex_map(5, 4));
+}
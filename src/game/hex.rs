@@ -0,0 +1,154 @@
+use crate::game::map::{MapLoadError, MapOwner, MapSource, TerritoryData, TerritoryRef};
+use crate::game::player::PlayerId;
+use crate::game::territory::Terrain;
+
+// Distance from a hexagon's centre to its corners, in world units
+const HEX_RADIUS: f32 = 40.0;
+
+// Offsets to the six neighbours of a hex in "odd-r" layout, where odd rows
+// are shifted half a hex to the right. Even and odd rows differ in which
+// diagonal columns they touch.
+const EVEN_ROW_NEIGHBOURS: [(i32, i32); 6] = [(-1, 0), (1, 0), (-1, -1), (0, -1), (-1, 1), (0, 1)];
+const ODD_ROW_NEIGHBOURS: [(i32, i32); 6] = [(-1, 0), (1, 0), (0, -1), (1, -1), (0, 1), (1, 1)];
+
+fn hex_name(col: usize, row: usize) -> String {
+    format!("Hex {}-{}", col + 1, row + 1)
+}
+
+// Corners of the pointy-topped hexagon at grid position (`col`, `row`)
+fn hex_vertices(col: usize, row: usize) -> Vec<[f32; 2]> {
+    let width = HEX_RADIUS * 3f32.sqrt();
+    let shift = if row % 2 == 1 { width / 2.0 } else { 0.0 };
+    let cx = width * (col as f32 + 0.5) + shift;
+    let cy = HEX_RADIUS * (1.5 * row as f32 + 1.0);
+    (0..6)
+        .map(|corner| {
+            let angle = (60.0 * corner as f32 - 30.0).to_radians();
+            [cx + HEX_RADIUS * angle.cos(), cy + HEX_RADIUS * angle.sin()]
+        })
+        .collect()
+}
+
+fn hex_neighbours(col: usize, row: usize, cols: usize, rows: usize) -> Vec<String> {
+    let offsets = if row % 2 == 1 {
+        ODD_ROW_NEIGHBOURS
+    } else {
+        EVEN_ROW_NEIGHBOURS
+    };
+    offsets
+        .iter()
+        .filter_map(|&(dc, dr)| {
+            let c = col.checked_add_signed(dc as isize)?;
+            let r = row.checked_add_signed(dr as isize)?;
+            (c < cols && r < rows).then(|| hex_name(c, r))
+        })
+        .collect()
+}
+
+// A `cols` x `rows` grid of hexagons, named by position and adjacent to the
+// hexes they share an edge with. Every hex starts neutral with one army.
+pub fn generate_hex_map(cols: usize, rows: usize) -> Vec<TerritoryData> {
+    (0..rows)
+        .flat_map(|row| (0..cols).map(move |col| (col, row)))
+        .map(|(col, row)| TerritoryData {
+            id: None,
+            name: hex_name(col, row),
+            vertices: hex_vertices(col, row),
+            owner: None,
+            armies: 1,
+            selected: false,
+            adjacent: hex_neighbours(col, row, cols, rows)
+                .into_iter()
+                .map(TerritoryRef::Name)
+                .collect(),
+            sea_routes: Vec::new(),
+            rivers: Vec::new(),
+            capital: false,
+            terrain: Terrain::Plains,
+            continent: None,
+            bonus: 0,
+            capital_of: None,
+        })
+        .collect()
+}
+
+// A generated cols x rows grid, dealt out to `players` players since it
+// comes with no owners of its own
+pub struct HexMapSource {
+    pub cols: usize,
+    pub rows: usize,
+    pub players: usize,
+}
+
+impl MapSource for HexMapSource {
+    fn load(&self) -> Result<Vec<TerritoryData>, MapLoadError> {
+        let mut data = generate_hex_map(self.cols, self.rows);
+        deal_round_robin(&mut data, self.players);
+        Ok(data)
+    }
+}
+
+// Hands territories out to `players` players in turn
+pub fn deal_round_robin(data: &mut [TerritoryData], players: usize) {
+    for (i, territory) in data.iter_mut().enumerate() {
+        territory.owner = Some(MapOwner::Seat(PlayerId(i % players)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::map;
+    use crate::game::validate;
+
+    #[test]
+    fn hex_grid_is_a_valid_map() {
+        let mut data = generate_hex_map(4, 3);
+        deal_round_robin(&mut data, 2);
+        assert_eq!(data.len(), 12);
+        validate::validate_map(&data, 2).unwrap();
+        let territories = map::build_territories(&data).unwrap();
+        validate::validate_outlines(&territories).unwrap();
+    }
+
+    #[test]
+    fn hexes_touch_their_grid_neighbours() {
+        let data = generate_hex_map(4, 3);
+        let neighbours = |name: &str| {
+            let mut adjacent: Vec<String> = data
+                .iter()
+                .find(|t| t.name == name)
+                .unwrap()
+                .adjacent
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            adjacent.sort();
+            adjacent
+        };
+        assert_eq!(neighbours("Hex 1-1"), ["Hex 1-2", "Hex 2-1"]);
+        // Inner hex in an odd (shifted) row touches all six around it
+        assert_eq!(
+            neighbours("Hex 2-2"),
+            ["Hex 1-2", "Hex 2-1", "Hex 2-3", "Hex 3-1", "Hex 3-2", "Hex 3-3"]
+        );
+
+        // Neighbouring hexes share an edge, so two of their corners coincide
+        let corners = |name: &str| {
+            data.iter()
+                .find(|t| t.name == name)
+                .unwrap()
+                .vertices
+                .clone()
+        };
+        let (a, b) = (corners("Hex 2-2"), corners("Hex 3-3"));
+        let shared = a
+            .iter()
+            .filter(|p| {
+                b.iter()
+                    .any(|q| (p[0] - q[0]).abs() < 1e-3 && (p[1] - q[1]).abs() < 1e-3)
+            })
+            .count();
+        assert_eq!(shared, 2);
+    }
+}
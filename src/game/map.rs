@@ -0,0 +1,1157 @@
+use crate::game::continent::Continent;
+use crate::game::geojson::GeoJsonMapSource;
+use crate::game::player::{Player, PlayerId};
+use crate::game::svg::SvgMapSource;
+use crate::game::territory::{Terrain, SIMPLIFY_ATTEMPTS};
+use crate::game::validate::MapValidationError;
+use crate::game::Territory;
+use flate2::read::GzDecoder;
+use macroquad::math::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+// Anywhere a map's territories can come from. The game only ever sees the
+// loaded data, so a new format is one more implementation of this.
+pub trait MapSource {
+    fn load(&self) -> Result<Vec<TerritoryData>, MapLoadError>;
+}
+
+// Map files say which version of the format they were written in, so older
+// ones can be upgraded and newer ones refused with a clear message
+pub const MAP_FORMAT_VERSION: u64 = 2;
+
+// The game's own format: its territories with the format version
+#[derive(Serialize, Deserialize)]
+pub struct MapFile {
+    pub version: u64,
+    pub territories: Vec<TerritoryData>,
+}
+
+impl MapFile {
+    pub fn new(territories: Vec<TerritoryData>) -> MapFile {
+        MapFile {
+            version: MAP_FORMAT_VERSION,
+            territories,
+        }
+    }
+}
+
+pub struct JsonMapSource {
+    pub path: PathBuf,
+}
+
+impl MapSource for JsonMapSource {
+    fn load(&self) -> Result<Vec<TerritoryData>, MapLoadError> {
+        let path = &self.path;
+        let io_error = |source| MapLoadError::Io {
+            path: path.clone(),
+            source,
+        };
+        let bytes = fs::read(path).map_err(io_error)?;
+        let file_content = decompress(path, bytes).map_err(io_error)?;
+        load_territories_from_str(&file_content).map_err(|err| err.read_from(path))
+    }
+}
+
+// A map in the game's JSON that's already in memory, such as one built in
+// with `include_str!`
+pub struct StrMapSource<'a>(pub &'a str);
+
+impl MapSource for StrMapSource<'_> {
+    fn load(&self) -> Result<Vec<TerritoryData>, MapLoadError> {
+        load_territories_from_str(self.0)
+    }
+}
+
+// What errors call a map that wasn't read from a file
+const IN_MEMORY_MAP: &str = "(built-in map)";
+
+// The first two bytes of every gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+// Big imported maps can be kept gzipped: a `.gz` file, or one that starts
+// like gzip whatever it's called, is unpacked before parsing
+fn decompress(path: &Path, bytes: Vec<u8>) -> io::Result<String> {
+    let gzipped = path.extension().is_some_and(|ext| ext == "gz") || bytes.starts_with(&GZIP_MAGIC);
+    if gzipped {
+        let mut content = String::new();
+        GzDecoder::new(bytes.as_slice()).read_to_string(&mut content)?;
+        Ok(content)
+    } else {
+        String::from_utf8(bytes).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+// Reads a map in any version of the JSON format up to the current one.
+// Errors name it as in memory; a file's loader puts its own path on them.
+pub fn load_territories_from_str(content: &str) -> Result<Vec<TerritoryData>, MapLoadError> {
+    let path = Path::new(IN_MEMORY_MAP);
+    let parse_error = |source| MapLoadError::Parse {
+        path: path.to_path_buf(),
+        source,
+    };
+    let value = serde_json::from_str(content).map_err(parse_error)?;
+    let value = migrate(value).map_err(|version| MapLoadError::UnsupportedVersion {
+        path: path.to_path_buf(),
+        version,
+    })?;
+    let file: MapFile = serde_json::from_value(value).map_err(parse_error)?;
+    Ok(file.territories)
+}
+
+// Upgrades a parsed map file to the current version's shape, or gives back
+// the version if it's newer than this build understands
+fn migrate(value: Value) -> Result<Value, u64> {
+    let version = match &value {
+        // Version 1 was a bare array of territories
+        Value::Array(_) => 1,
+        // A missing version is left for deserializing to report
+        _ => value
+            .get("version")
+            .and_then(Value::as_u64)
+            .unwrap_or(MAP_FORMAT_VERSION),
+    };
+    if version > MAP_FORMAT_VERSION {
+        return Err(version);
+    }
+    Ok(match version {
+        // Every field added since version 1 has a default, so wrapping the
+        // array is the whole upgrade
+        1 => json!({ "version": 2, "territories": value }),
+        _ => value,
+    })
+}
+
+// Picks the loader for a map file by its extension; anything unrecognised
+// is read as the game's JSON
+pub fn source_for_path(path: &Path) -> Box<dyn MapSource> {
+    let path = path.to_path_buf();
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("svg") => Box::new(SvgMapSource { path }),
+        Some("geojson") => Box::new(GeoJsonMapSource { path }),
+        _ => Box::new(JsonMapSource { path }),
+    }
+}
+
+// Who holds a territory in the file: a seat number, or the name of one of
+// the configured players so maps survive the players being reordered
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MapOwner {
+    Seat(PlayerId),
+    Name(String),
+}
+
+impl MapOwner {
+    // None for a name that hasn't been resolved yet
+    pub fn seat(&self) -> Option<PlayerId> {
+        match self {
+            MapOwner::Seat(id) => Some(*id),
+            MapOwner::Name(_) => None,
+        }
+    }
+}
+
+impl From<PlayerId> for MapOwner {
+    fn from(id: PlayerId) -> MapOwner {
+        MapOwner::Seat(id)
+    }
+}
+
+// A neighbour as the file lists it: by id, which survives the territory
+// being renamed or the list reordered, or by name, which is easier to write
+// by hand. The game writes ids.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TerritoryRef {
+    Id(u32),
+    Name(String),
+}
+
+impl TerritoryRef {
+    pub fn points_to(&self, territory: &TerritoryData) -> bool {
+        match self {
+            TerritoryRef::Id(id) => territory.id == Some(*id),
+            TerritoryRef::Name(name) => &territory.name == name,
+        }
+    }
+}
+
+impl fmt::Display for TerritoryRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TerritoryRef::Id(id) => write!(f, "#{}", id),
+            TerritoryRef::Name(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+impl From<&str> for TerritoryRef {
+    fn from(name: &str) -> TerritoryRef {
+        TerritoryRef::Name(name.to_string())
+    }
+}
+
+impl PartialEq<&str> for TerritoryRef {
+    fn eq(&self, name: &&str) -> bool {
+        matches!(self, TerritoryRef::Name(own) if own == name)
+    }
+}
+
+// Every territory's id: its own where the file gives one, and otherwise the
+// next one free after the highest given, in file order. A file with no ids
+// at all numbers its territories from 0 as they're listed.
+pub fn territory_ids(data: &[TerritoryData]) -> Vec<u32> {
+    let mut next = data
+        .iter()
+        .filter_map(|t| t.id)
+        .max()
+        .map_or(0, |id| id + 1);
+    data.iter()
+        .map(|t| {
+            t.id.unwrap_or_else(|| {
+                next += 1;
+                next - 1
+            })
+        })
+        .collect()
+}
+
+// Finds territories by either kind of reference. A name or id used twice
+// is a validation error; here the first one listed wins.
+pub struct Lookup<'a> {
+    by_name: HashMap<&'a str, usize>,
+    by_id: HashMap<u32, usize>,
+}
+
+impl<'a> Lookup<'a> {
+    pub fn new(data: &'a [TerritoryData]) -> Lookup<'a> {
+        let mut lookup = Lookup {
+            by_name: HashMap::new(),
+            by_id: HashMap::new(),
+        };
+        for (i, (territory, id)) in data.iter().zip(territory_ids(data)).enumerate() {
+            lookup.by_name.entry(territory.name.as_str()).or_insert(i);
+            lookup.by_id.entry(id).or_insert(i);
+        }
+        lookup
+    }
+
+    pub fn find(&self, reference: &TerritoryRef) -> Option<usize> {
+        match reference {
+            TerritoryRef::Id(id) => self.by_id.get(id).copied(),
+            TerritoryRef::Name(name) => self.by_name.get(name.as_str()).copied(),
+        }
+    }
+}
+
+// Struct for (de)serializing JSON data
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TerritoryData {
+    // Stays with the territory whatever it's called or wherever it's listed,
+    // so saves and scenarios can point at it; see `territory_ids`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<u32>,
+    pub name: String,
+    pub vertices: Vec<[f32; 2]>,
+    // Absent or null for a neutral territory
+    #[serde(default)]
+    pub owner: Option<MapOwner>,
+    pub armies: i32,
+    pub selected: bool,
+    #[serde(default)]
+    pub adjacent: Vec<TerritoryRef>,
+    // Neighbours across water, with no shared border. Listing a route on
+    // either end is enough.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sea_routes: Vec<TerritoryRef>,
+    // Neighbours across a river, which an attack over loses a die to, as
+    // one on a river territory does. Listing it on either end is enough.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rivers: Vec<TerritoryRef>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub capital: bool,
+    // "plains", "mountain" or "river", changing how it's defended
+    #[serde(default, skip_serializing_if = "is_plains")]
+    pub terrain: Terrain,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub continent: Option<String>,
+    // Continent bonus; every territory of a continent must agree on it
+    #[serde(default)]
+    pub bonus: i32,
+    // Names another continent whose bonus also needs this territory held.
+    // Each continent takes at most one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capital_of: Option<String>,
+}
+
+fn is_plains(terrain: &Terrain) -> bool {
+    *terrain == Terrain::Plains
+}
+
+impl TerritoryData {
+    // Inverse of `to_territory`, so live territories can be written back out
+    pub fn from_territory(
+        index: usize,
+        territories: &[Territory],
+        continents: &[Continent],
+    ) -> TerritoryData {
+        let territory = &territories[index];
+        let continent = continents
+            .iter()
+            .find(|continent| continent.territories.contains(&index));
+
+        TerritoryData {
+            id: Some(territory.id),
+            name: territory.name.clone(),
+            vertices: territory.vertices.iter().map(|v| [v.x, v.y]).collect(),
+            owner: territory.owner.map(MapOwner::Seat),
+            armies: territory.armies,
+            selected: territory.selected,
+            adjacent: territory
+                .adjacent
+                .iter()
+                .filter(|i| !territory.sea_routes.contains(i))
+                .map(|&i| TerritoryRef::Id(territories[i].id))
+                .collect(),
+            sea_routes: territory
+                .sea_routes
+                .iter()
+                .map(|&i| TerritoryRef::Id(territories[i].id))
+                .collect(),
+            rivers: territory
+                .rivers
+                .iter()
+                .map(|&i| TerritoryRef::Id(territories[i].id))
+                .collect(),
+            capital: territory.is_capital,
+            terrain: territory.terrain,
+            continent: continent.map(|continent| continent.name.clone()),
+            bonus: continent.map_or(0, |continent| continent.bonus),
+            capital_of: continents
+                .iter()
+                .find(|continent| continent.required_capital == Some(index))
+                .map(|continent| continent.name.clone()),
+        }
+    }
+
+    // Neighbours are listed by id or name in the JSON and resolved to
+    // indices here
+    fn to_territory(&self, id: u32, lookup: &Lookup) -> Result<Territory, MapLoadError> {
+        let adjacent = self
+            .adjacent
+            .iter()
+            .map(|neighbour| {
+                lookup
+                    .find(neighbour)
+                    .ok_or_else(|| MapLoadError::UnknownNeighbour {
+                        territory: self.name.clone(),
+                        neighbour: neighbour.to_string(),
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+
+        let mut territory = Territory::new(
+            self.name.clone(),
+            self.vertices.iter().map(|v| vec2(v[0], v[1])).collect(),
+            self.owner.as_ref().and_then(MapOwner::seat),
+            self.armies,
+            adjacent,
+        );
+        territory.id = id;
+        territory.selected = self.selected;
+        territory.is_capital = self.capital;
+        territory.terrain = self.terrain;
+        Ok(territory)
+    }
+}
+
+// Everything that can go wrong turning a map file into territories
+#[derive(Debug)]
+pub enum MapLoadError {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    UnknownNeighbour {
+        territory: String,
+        neighbour: String,
+    },
+    ConflictingBonus {
+        continent: String,
+        territory: String,
+    },
+    // An owner given by a name none of the players has
+    UnknownOwner {
+        territory: String,
+        owner: String,
+    },
+    // A scenario setting its objectives for a seat nobody sits in
+    UnknownChallenger {
+        path: PathBuf,
+        challenger: String,
+    },
+    // A second territory marked as the continent's capital
+    ConflictingCapital {
+        continent: String,
+        territory: String,
+    },
+    // A capital for a continent no territory belongs to
+    UnknownContinent {
+        territory: String,
+        continent: String,
+    },
+    // A capital inside its own continent, which holding the continent holds
+    // anyway
+    CapitalInsideContinent {
+        continent: String,
+        territory: String,
+    },
+    Svg {
+        path: PathBuf,
+        message: String,
+    },
+    GeoJson {
+        path: PathBuf,
+        message: String,
+    },
+    // Written by a newer version of the game
+    UnsupportedVersion {
+        path: PathBuf,
+        version: u64,
+    },
+    Invalid(Vec<MapValidationError>),
+    // A save handing the turn, a hand of cards or an alliance to a seat
+    // nobody sits in
+    UnseatedPlayer {
+        what: &'static str,
+        player: PlayerId,
+        player_count: usize,
+    },
+    // A save with starting armies left for a different number of seats
+    StartingArmiesMismatch {
+        seats: usize,
+        player_count: usize,
+    },
+}
+
+impl MapLoadError {
+    // The same error for a map that came from `path`
+    fn read_from(self, path: &Path) -> MapLoadError {
+        match self {
+            MapLoadError::Parse { source, .. } => MapLoadError::Parse {
+                path: path.to_path_buf(),
+                source,
+            },
+            MapLoadError::UnsupportedVersion { version, .. } => MapLoadError::UnsupportedVersion {
+                path: path.to_path_buf(),
+                version,
+            },
+            err => err,
+        }
+    }
+}
+
+impl fmt::Display for MapLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapLoadError::Io { path, source } => {
+                write!(f, "Failed to read {}: {}", path.display(), source)
+            }
+            MapLoadError::Parse { path, source } => {
+                write!(f, "Failed to parse {}: {}", path.display(), source)
+            }
+            MapLoadError::UnknownNeighbour {
+                territory,
+                neighbour,
+            } => write!(
+                f,
+                "Territory '{}' lists unknown neighbour '{}'",
+                territory, neighbour
+            ),
+            MapLoadError::ConflictingBonus {
+                continent,
+                territory,
+            } => write!(
+                f,
+                "Territory '{}' gives continent '{}' a different bonus than its other territories",
+                territory, continent
+            ),
+            MapLoadError::UnknownOwner { territory, owner } => write!(
+                f,
+                "Territory '{}' is owned by '{}', but no player has that name",
+                territory, owner
+            ),
+            MapLoadError::UnknownChallenger { path, challenger } => write!(
+                f,
+                "{} sets its objectives for the player {}, who isn't in this game",
+                path.display(),
+                challenger
+            ),
+            MapLoadError::ConflictingCapital {
+                continent,
+                territory,
+            } => write!(
+                f,
+                "Territory '{}' is a second capital for continent '{}'",
+                territory, continent
+            ),
+            MapLoadError::UnknownContinent {
+                territory,
+                continent,
+            } => write!(
+                f,
+                "Territory '{}' is the capital of unknown continent '{}'",
+                territory, continent
+            ),
+            MapLoadError::CapitalInsideContinent {
+                continent,
+                territory,
+            } => write!(
+                f,
+                "Territory '{}' is part of continent '{}', so can't also be its capital",
+                territory, continent
+            ),
+            MapLoadError::Svg { path, message } | MapLoadError::GeoJson { path, message } => {
+                write!(f, "Failed to import {}: {}", path.display(), message)
+            }
+            MapLoadError::UnsupportedVersion { path, version } => write!(
+                f,
+                "{} is map format version {}, but this game only reads up to version {}",
+                path.display(),
+                version,
+                MAP_FORMAT_VERSION
+            ),
+            MapLoadError::Invalid(errors) => {
+                write!(f, "The map has {} problem(s):", errors.len())?;
+                for error in errors {
+                    write!(f, "\n  {}", error)?;
+                }
+                Ok(())
+            }
+            MapLoadError::UnseatedPlayer {
+                what,
+                player,
+                player_count,
+            } => write!(
+                f,
+                "The save gives {} to player {}, but there are only {} players",
+                what, player, player_count
+            ),
+            MapLoadError::StartingArmiesMismatch {
+                seats,
+                player_count,
+            } => write!(
+                f,
+                "The save has starting armies for {} players, but there are {}",
+                seats, player_count
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MapLoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MapLoadError::Io { source, .. } => Some(source),
+            MapLoadError::Parse { source, .. } => Some(source),
+            MapLoadError::UnknownNeighbour { .. }
+            | MapLoadError::ConflictingBonus { .. }
+            | MapLoadError::UnknownOwner { .. }
+            | MapLoadError::UnknownChallenger { .. }
+            | MapLoadError::ConflictingCapital { .. }
+            | MapLoadError::UnknownContinent { .. }
+            | MapLoadError::CapitalInsideContinent { .. }
+            | MapLoadError::Svg { .. }
+            | MapLoadError::GeoJson { .. }
+            | MapLoadError::UnsupportedVersion { .. }
+            | MapLoadError::Invalid(_)
+            | MapLoadError::UnseatedPlayer { .. }
+            | MapLoadError::StartingArmiesMismatch { .. } => None,
+        }
+    }
+}
+
+// Turns owners given by name into the seat of the player with that name
+pub fn resolve_owner_names(
+    data: &mut [TerritoryData],
+    players: &[Player],
+) -> Result<(), MapLoadError> {
+    for territory in data {
+        let Some(MapOwner::Name(name)) = &territory.owner else {
+            continue;
+        };
+        let player = players
+            .iter()
+            .find(|player| &player.name == name)
+            .ok_or_else(|| MapLoadError::UnknownOwner {
+                territory: territory.name.clone(),
+                owner: name.clone(),
+            })?;
+        territory.owner = Some(MapOwner::Seat(player.id));
+    }
+    Ok(())
+}
+
+// Converts loaded data into live territories, resolving adjacency by name
+pub fn build_territories(data: &[TerritoryData]) -> Result<Vec<Territory>, MapLoadError> {
+    let lookup = Lookup::new(data);
+    let mut territories: Vec<Territory> = data
+        .iter()
+        .zip(territory_ids(data))
+        .map(|(territory, id)| territory.to_territory(id, &lookup))
+        .collect::<Result<_, _>>()?;
+
+    // Sea routes are ordinary adjacencies both ways, remembered separately
+    // so they can be drawn
+    for (a, territory) in data.iter().enumerate() {
+        for neighbour in &territory.sea_routes {
+            let b = lookup
+                .find(neighbour)
+                .ok_or_else(|| MapLoadError::UnknownNeighbour {
+                    territory: territory.name.clone(),
+                    neighbour: neighbour.to_string(),
+                })?;
+            for (from, to) in [(a, b), (b, a)] {
+                if !territories[from].sea_routes.contains(&to) {
+                    territories[from].sea_routes.push(to);
+                }
+                if !territories[from].adjacent.contains(&to) {
+                    territories[from].adjacent.push(to);
+                }
+            }
+        }
+        for neighbour in &territory.rivers {
+            let b = lookup
+                .find(neighbour)
+                .ok_or_else(|| MapLoadError::UnknownNeighbour {
+                    territory: territory.name.clone(),
+                    neighbour: neighbour.to_string(),
+                })?;
+            for (from, to) in [(a, b), (b, a)] {
+                if !territories[from].rivers.contains(&to) {
+                    territories[from].rivers.push(to);
+                }
+            }
+        }
+    }
+    Ok(territories)
+}
+
+// Makes neighbours of any two territories with a common edge, that is at
+// least two vertices within `epsilon` of each other, so maps traced from
+// art (SVG, GeoJSON) don't need every border listed by hand. Only ever adds:
+// adjacencies and sea routes the map lists are kept as they are.
+pub fn infer_adjacency(territories: &mut [Territory], epsilon: f32) {
+    for a in 0..territories.len() {
+        for b in a + 1..territories.len() {
+            // Boxes further apart than `epsilon` can't share a vertex
+            let (first, second) = (&territories[a], &territories[b]);
+            if (first.min - epsilon).cmpgt(second.max).any()
+                || (second.min - epsilon).cmpgt(first.max).any()
+            {
+                continue;
+            }
+            let shared = territories[a]
+                .vertices
+                .iter()
+                .filter(|&&v| {
+                    territories[b]
+                        .vertices
+                        .iter()
+                        .any(|&w| v.distance(w) <= epsilon)
+                })
+                .count();
+            if shared < 2 {
+                continue;
+            }
+            for (from, to) in [(a, b), (b, a)] {
+                if !territories[from].adjacent.contains(&to) {
+                    territories[from].adjacent.push(to);
+                }
+            }
+        }
+    }
+}
+
+// Vertices of neighbouring outlines closer than this are the same point
+const SHARED_VERTEX_EPSILON: f32 = 1e-3;
+
+// Simplifies every outline (see `Territory::simplified`) without pulling
+// neighbours apart. Both sides of a border keep the vertices where it
+// starts and ends, and the stretch between comes out the same from either
+// side, so no gap or overlap opens along it. A territory that would cross
+// itself keeps its borders exactly, and so do its neighbours, while its
+// coastline is tried again at finer tolerances. Returns how many vertices
+// each territory lost.
+pub fn simplify_territories(territories: &mut [Territory], tolerance: f32) -> Vec<usize> {
+    // For each vertex, the neighbours with a vertex in the same place
+    let sharing: Vec<Vec<Vec<usize>>> = territories
+        .iter()
+        .map(|territory| {
+            territory
+                .vertices
+                .iter()
+                .map(|&v| {
+                    let mut with: Vec<usize> = territory
+                        .adjacent
+                        .iter()
+                        .copied()
+                        .filter(|&n| {
+                            territories[n]
+                                .vertices
+                                .iter()
+                                .any(|&w| v.distance(w) <= SHARED_VERTEX_EPSILON)
+                        })
+                        .collect();
+                    with.sort_unstable();
+                    with
+                })
+                .collect()
+        })
+        .collect();
+
+    // Worked out from the original outlines every time round; a territory
+    // found crossing itself has its borders frozen and everything restarts
+    let mut frozen = vec![false; territories.len()];
+    let outlines = 'restart: loop {
+        let mut outlines = Vec::with_capacity(territories.len());
+        for (t, territory) in territories.iter().enumerate() {
+            let shared = &sharing[t];
+            let n = shared.len();
+            let pinned: Vec<bool> = (0..n)
+                .map(|v| {
+                    let here = &shared[v];
+                    !here.is_empty()
+                        && (frozen[t]
+                            || here.len() > 1
+                            || here.iter().any(|&other| frozen[other])
+                            || shared[(v + n - 1) % n] != *here
+                            || shared[(v + 1) % n] != *here)
+                })
+                .collect();
+            // Finer tolerances are only tried once nothing that moves can
+            // be on a border, or the neighbour would disagree
+            let on_borders = shared.iter().any(|with| !with.is_empty());
+            let attempts = if frozen[t] || !on_borders {
+                SIMPLIFY_ATTEMPTS
+            } else {
+                1
+            };
+            match territory.simplified(tolerance, &pinned, attempts) {
+                None if attempts == 1 => {
+                    frozen[t] = true;
+                    continue 'restart;
+                }
+                outline => outlines.push(outline),
+            }
+        }
+        break outlines;
+    };
+
+    territories
+        .iter_mut()
+        .zip(outlines)
+        .map(|(territory, outline)| {
+            let before = territory.vertices.len();
+            match outline {
+                Some(outline) if outline.len() < before => {
+                    territory.set_outline(outline);
+                    before - territory.vertices.len()
+                }
+                _ => 0,
+            }
+        })
+        .collect()
+}
+
+// Groups territories into continents by the `continent` named on each one,
+// keeping continents in the order they first appear in the file, then gives
+// each the capital outside it that names it
+pub fn build_continents(data: &[TerritoryData]) -> Result<Vec<Continent>, MapLoadError> {
+    let mut continents: Vec<Continent> = Vec::new();
+    for (i, territory) in data.iter().enumerate() {
+        let Some(name) = &territory.continent else {
+            continue;
+        };
+        match continents.iter_mut().find(|c| &c.name == name) {
+            Some(continent) if continent.bonus != territory.bonus => {
+                return Err(MapLoadError::ConflictingBonus {
+                    continent: name.clone(),
+                    territory: territory.name.clone(),
+                });
+            }
+            Some(continent) => continent.territories.push(i),
+            None => continents.push(Continent {
+                name: name.clone(),
+                territories: vec![i],
+                bonus: territory.bonus,
+                required_capital: None,
+            }),
+        }
+    }
+    for (i, territory) in data.iter().enumerate() {
+        let Some(name) = &territory.capital_of else {
+            continue;
+        };
+        let Some(continent) = continents.iter_mut().find(|c| &c.name == name) else {
+            return Err(MapLoadError::UnknownContinent {
+                territory: territory.name.clone(),
+                continent: name.clone(),
+            });
+        };
+        if continent.territories.contains(&i) {
+            return Err(MapLoadError::CapitalInsideContinent {
+                continent: name.clone(),
+                territory: territory.name.clone(),
+            });
+        }
+        if continent.required_capital.is_some() {
+            return Err(MapLoadError::ConflictingCapital {
+                continent: name.clone(),
+                territory: territory.name.clone(),
+            });
+        }
+        continent.required_capital = Some(i);
+    }
+    Ok(continents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Game;
+
+    #[test]
+    fn version_1_maps_upgrade_and_newer_ones_are_refused() {
+        let territories = r#"[
+            {"name": "A", "vertices": [[0, 0], [1, 0], [0, 1]], "armies": 2, "selected": false,
+             "adjacent": ["B"]},
+            {"name": "B", "vertices": [[1, 0], [2, 0], [1, 1]], "armies": 1, "selected": false,
+             "adjacent": ["A"], "continent": "Middle", "bonus": 1}
+        ]"#;
+        let old = load_territories_from_str(territories).unwrap();
+        let current = format!(r#"{{"version": 2, "territories": {}}}"#, territories);
+        let new = load_territories_from_str(&current).unwrap();
+        assert_eq!(
+            serde_json::to_value(&old).unwrap(),
+            serde_json::to_value(&new).unwrap()
+        );
+        // Fields version 1 never had come out as their defaults
+        assert!(old[0].sea_routes.is_empty() && !old[0].capital);
+        assert_eq!(old[1].continent.as_deref(), Some("Middle"));
+
+        let future = format!(r#"{{"version": 3, "territories": {}}}"#, territories);
+        let file = std::env::temp_dir().join("risk_future_map.json");
+        fs::write(&file, future).unwrap();
+        let result = JsonMapSource { path: file.clone() }.load();
+        assert!(matches!(
+            result,
+            Err(MapLoadError::UnsupportedVersion { path, version: 3 }) if path == file
+        ));
+    }
+
+    #[test]
+    fn simplified_neighbours_still_meet() {
+        use crate::test_support::territory_with_vertices;
+        // A strip along the bottom, sloping down to the left, with a square
+        // sat on its right half and the border between them bowed and a
+        // little rough
+        let border: Vec<Vec2> = (11..20)
+            .map(|x| {
+                let bow = ((x - 10) as f32 * std::f32::consts::PI / 10.0).sin();
+                vec2(x as f32, 5.0 + bow + (x % 2) as f32 * 0.05)
+            })
+            .collect();
+        let mut strip = vec![vec2(0.0, 0.0), vec2(20.0, 0.0), vec2(20.0, 5.0)];
+        strip.extend(border.iter().rev());
+        strip.extend([vec2(10.0, 5.0), vec2(0.0, 2.0)]);
+        let mut square = vec![vec2(10.0, 5.0)];
+        square.extend(&border);
+        square.extend([vec2(20.0, 5.0), vec2(20.0, 10.0), vec2(10.0, 10.0)]);
+        let mut territories = vec![
+            territory_with_vertices(strip),
+            territory_with_vertices(square),
+        ];
+        territories[0].adjacent = vec![1];
+        territories[1].adjacent = vec![0];
+
+        let on_border = |vertices: &[Vec2]| {
+            let mut points: Vec<(i32, i32)> = vertices
+                .iter()
+                .filter(|v| v.x >= 10.0 && v.y > 4.5 && v.y < 7.0)
+                .map(|v| ((v.x * 100.0) as i32, (v.y * 100.0) as i32))
+                .collect();
+            points.sort_unstable();
+            points
+        };
+        // Simplified one at a time, the strip even drops the corner where
+        // the border starts
+        let alone: Vec<_> = territories
+            .iter()
+            .map(|t| on_border(&t.simplified(0.2, &[], 1).unwrap()))
+            .collect();
+        assert_ne!(alone[0], alone[1]);
+
+        let removed = simplify_territories(&mut territories, 0.2);
+        assert!(removed.iter().all(|&removed| removed > 0));
+        assert_eq!(
+            on_border(&territories[0].vertices),
+            on_border(&territories[1].vertices)
+        );
+    }
+
+    #[test]
+    fn maps_load_from_strings_without_touching_disk() {
+        let territories = load_territories_from_str(
+            r#"{"version": 2, "territories": [
+                {"name": "A", "vertices": [[0, 0], [1, 0], [0, 1]], "armies": 2, "owner": 0,
+                 "selected": false, "adjacent": ["B"]},
+                {"name": "B", "vertices": [[1, 0], [2, 0], [1, 1]], "armies": 1, "owner": 1,
+                 "selected": false, "adjacent": ["A"]}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(territories.len(), 2);
+        assert_eq!(territories[1].adjacent, ["A"]);
+
+        // The built-in copy of the default map is the file's
+        let built_in = StrMapSource(crate::cli::BUILT_IN_MAP).load().unwrap();
+        let file = source_for_path(Path::new(crate::cli::DEFAULT_MAP_PATH))
+            .load()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&built_in).unwrap(),
+            serde_json::to_value(&file).unwrap()
+        );
+
+        let Err(broken) = load_territories_from_str("{") else {
+            panic!("a truncated map should not load");
+        };
+        assert!(broken.to_string().contains(IN_MEMORY_MAP), "{}", broken);
+    }
+
+    #[test]
+    fn sources_are_picked_by_extension() {
+        let path = Path::new(crate::cli::DEFAULT_MAP_PATH);
+        let direct = JsonMapSource {
+            path: path.to_path_buf(),
+        }
+        .load()
+        .unwrap();
+        assert!(!direct.is_empty());
+        let picked = source_for_path(path).load().unwrap();
+        assert_eq!(picked.len(), direct.len());
+
+        // The same markup is an SVG map but not JSON
+        let svg = std::env::temp_dir().join("risk_source_for_path.svg");
+        fs::write(&svg, "<svg></svg>").unwrap();
+        assert!(source_for_path(&svg).load().unwrap().is_empty());
+        let misnamed = svg.with_extension("json");
+        fs::write(&misnamed, "<svg></svg>").unwrap();
+        let result = source_for_path(&misnamed).load();
+        assert!(matches!(result, Err(MapLoadError::Parse { .. })));
+    }
+
+    #[test]
+    fn gzipped_maps_load_like_plain_ones() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let plain = Path::new(crate::cli::DEFAULT_MAP_PATH);
+        let expected = source_for_path(plain).load().unwrap();
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&fs::read(plain).unwrap()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        // By extension, and by content when the name doesn't say
+        let dir = std::env::temp_dir();
+        for name in ["risk_gzipped_map.json.gz", "risk_gzipped_map_misnamed.json"] {
+            let path = dir.join(name);
+            fs::write(&path, &compressed).unwrap();
+            let loaded = source_for_path(&path).load().unwrap();
+            assert_eq!(
+                serde_json::to_value(&loaded).unwrap(),
+                serde_json::to_value(&expected).unwrap()
+            );
+        }
+
+        // A truncated stream is a read error, not a parse error
+        let path = dir.join("risk_gzipped_map_truncated.json.gz");
+        fs::write(&path, &compressed[..compressed.len() / 2]).unwrap();
+        let result = source_for_path(&path).load();
+        assert!(matches!(result, Err(MapLoadError::Io { .. })));
+    }
+
+    #[test]
+    fn sea_routes_are_adjacent_both_ways() {
+        let data: Vec<TerritoryData> = serde_json::from_str(
+            r#"[
+                {"name": "Alaska", "vertices": [[0, 0], [1, 0], [0, 1]], "owner": 0,
+                 "armies": 1, "selected": false, "sea_routes": ["Kamchatka"]},
+                {"name": "Kamchatka", "vertices": [[9, 0], [10, 0], [9, 1]], "owner": 1,
+                 "armies": 1, "selected": false}
+            ]"#,
+        )
+        .unwrap();
+        let territories = build_territories(&data).unwrap();
+        assert_eq!(territories[0].adjacent, [1]);
+        assert_eq!(territories[1].adjacent, [0]);
+        assert_eq!(territories[1].sea_routes, [0]);
+
+        // Written back out, the route stays a sea route
+        let saved = TerritoryData::from_territory(1, &territories, &[]);
+        assert!(saved.adjacent.is_empty());
+        assert_eq!(saved.sea_routes, [TerritoryRef::Id(0)]);
+    }
+
+    #[test]
+    fn rivers_run_both_ways() {
+        let data: Vec<TerritoryData> = serde_json::from_str(
+            r#"[
+                {"name": "West", "vertices": [[0, 0], [1, 0], [1, 1]], "owner": 0,
+                 "armies": 1, "selected": false, "adjacent": ["East"], "rivers": ["East"]},
+                {"name": "East", "vertices": [[1, 0], [2, 0], [1, 1]], "owner": 1,
+                 "armies": 1, "selected": false, "adjacent": ["West"]}
+            ]"#,
+        )
+        .unwrap();
+        let territories = build_territories(&data).unwrap();
+        assert_eq!(territories[0].rivers, [1]);
+        assert_eq!(territories[1].rivers, [0]);
+        let saved = TerritoryData::from_territory(1, &territories, &[]);
+        assert_eq!(saved.rivers, [TerritoryRef::Id(0)]);
+    }
+
+    #[test]
+    fn ids_are_kept_or_handed_out_and_either_can_name_a_neighbour() {
+        let data: Vec<TerritoryData> = serde_json::from_str(
+            r#"[
+                {"name": "A", "vertices": [[0, 0], [1, 0], [0, 1]], "armies": 1,
+                 "selected": false, "adjacent": [7, "C"]},
+                {"id": 7, "name": "B", "vertices": [[1, 0], [2, 0], [1, 1]], "armies": 1,
+                 "selected": false, "adjacent": ["A"]},
+                {"name": "C", "vertices": [[2, 0], [3, 0], [2, 1]], "armies": 1,
+                 "selected": false, "adjacent": ["A"]}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(territory_ids(&data), [8, 7, 9]);
+        let territories = build_territories(&data).unwrap();
+        assert_eq!(territories[0].adjacent, [1, 2]);
+        assert_eq!(territories[2].id, 9);
+
+        let saved = TerritoryData::from_territory(0, &territories, &[]);
+        assert_eq!(saved.id, Some(8));
+        assert_eq!(saved.adjacent, [TerritoryRef::Id(7), TerritoryRef::Id(9)]);
+    }
+
+    #[test]
+    fn a_continent_takes_one_capital_from_outside() {
+        let mut data = load_territories_from_str(
+            r#"[
+                {"name": "A", "vertices": [[0, 0], [1, 0], [0, 1]], "owner": 0, "armies": 1,
+                 "selected": false, "adjacent": ["B"], "continent": "West", "bonus": 2},
+                {"name": "B", "vertices": [[1, 0], [2, 0], [1, 1]], "owner": 0, "armies": 1,
+                 "selected": false, "adjacent": ["A", "C"], "continent": "West", "bonus": 2},
+                {"name": "C", "vertices": [[2, 0], [3, 0], [2, 1]], "owner": 1, "armies": 1,
+                 "selected": false, "adjacent": ["B"], "capital_of": "West"}
+            ]"#,
+        )
+        .unwrap();
+        let mut game = Game::from_map(&data, Vec::new(), 0).unwrap();
+        assert_eq!(game.continents[0].required_capital, Some(2));
+        // All of West, but not the fort outside it
+        assert_eq!(game.continent_bonus_for(PlayerId(0)), 0);
+        game.territories[2].owner = Some(PlayerId(0));
+        assert_eq!(game.continent_bonus_for(PlayerId(0)), 2);
+
+        let saved = TerritoryData::from_territory(2, &game.territories, &game.continents);
+        assert_eq!(saved.capital_of.as_deref(), Some("West"));
+        assert_eq!(
+            TerritoryData::from_territory(0, &game.territories, &game.continents).capital_of,
+            None
+        );
+
+        data[0].capital_of = Some("West".to_string());
+        assert!(matches!(
+            build_continents(&data),
+            Err(MapLoadError::CapitalInsideContinent { territory, .. }) if territory == "A"
+        ));
+        data[0].capital_of = None;
+        data[1].continent = None;
+        data[1].capital_of = Some("West".to_string());
+        assert!(matches!(
+            build_continents(&data),
+            Err(MapLoadError::ConflictingCapital { territory, .. }) if territory == "C"
+        ));
+        data[2].capital_of = Some("East".to_string());
+        assert!(matches!(
+            build_continents(&data),
+            Err(MapLoadError::UnknownContinent { continent, .. }) if continent == "East"
+        ));
+    }
+
+    #[test]
+    fn owners_can_be_seats_or_player_names() {
+        let mut data: Vec<TerritoryData> = serde_json::from_str(
+            r#"[
+                {"name": "A", "vertices": [[0, 0], [1, 0], [0, 1]], "owner": "Bea",
+                 "armies": 1, "selected": false},
+                {"name": "B", "vertices": [[1, 0], [2, 0], [1, 1]], "owner": 0,
+                 "armies": 1, "selected": false}
+            ]"#,
+        )
+        .unwrap();
+        let mut players: Vec<Player> = (0..2).map(Player::with_default_color).collect();
+        players[1].name = "Bea".to_string();
+        resolve_owner_names(&mut data, &players).unwrap();
+        assert_eq!(data[0].owner, Some(MapOwner::Seat(PlayerId(1))));
+        assert_eq!(data[1].owner, Some(MapOwner::Seat(PlayerId(0))));
+
+        data[1].owner = Some(MapOwner::Name("Cy".to_string()));
+        let err = resolve_owner_names(&mut data, &players).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Territory 'B' is owned by 'Cy', but no player has that name"
+        );
+    }
+
+    #[test]
+    fn territories_sharing_an_edge_are_inferred_adjacent() {
+        let square = |x: f32, y: f32| {
+            [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]
+                .iter()
+                .map(|&(dx, dy)| vec2(x + dx, y + dy))
+                .collect::<Vec<_>>()
+        };
+        let mut territories = vec![
+            Territory::new("West".to_string(), square(0.0, 0.0), None, 1, vec![]),
+            // Traced a little off, but within epsilon of West's right edge
+            Territory::new("East".to_string(), square(1.05, 0.0), None, 1, vec![]),
+            // Only touches East at a corner
+            Territory::new("Corner".to_string(), square(2.05, 1.0), None, 1, vec![]),
+            Territory::new("Island".to_string(), square(9.0, 9.0), None, 1, vec![0]),
+        ];
+        territories[0].adjacent.push(3);
+        territories[0].sea_routes.push(3);
+        territories[3].sea_routes.push(0);
+        infer_adjacency(&mut territories, 0.1);
+
+        assert_eq!(territories[0].adjacent, [3, 1]);
+        assert_eq!(territories[1].adjacent, [0]);
+        assert!(territories[2].adjacent.is_empty());
+        assert_eq!(territories[3].adjacent, [0]);
+        assert_eq!(territories[0].sea_routes, [3]);
+    }
+}
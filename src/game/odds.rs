@@ -0,0 +1,198 @@
+use crate::game::combat::{defender_losses, DiceLimits};
+use crate::game::Game;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+// Largest army counts the memoized tables cover; bigger battles are worked
+// out on the spot
+const TABLE_SIZE: usize = 128;
+
+type WinTable = Vec<Vec<f64>>;
+
+impl Game {
+    // Chance that `attacker` eventually takes `defender`, attacking until it
+    // wins or can't attack any more, with the dice this attack would use
+    pub fn conquest_odds(&self, attacker: usize, defender: usize) -> f32 {
+        combat_odds(
+            self.territories[attacker].armies,
+            self.territories[defender].armies,
+            self.dice_limits(attacker, defender),
+        )
+    }
+}
+
+// The same for any army counts: `attacker_armies` includes the one that has
+// to stay behind
+pub fn combat_odds(attacker_armies: i32, defender_armies: i32, limits: DiceLimits) -> f32 {
+    let attackers = (attacker_armies - 1).max(0) as usize;
+    let defenders = defender_armies.max(0) as usize;
+    if attackers < TABLE_SIZE && defenders < TABLE_SIZE {
+        table(limits)[attackers][defenders] as f32
+    } else {
+        win_table(attackers, defenders, limits)[attackers][defenders] as f32
+    }
+}
+
+// One table per set of dice, worked out the first time it's asked for. A
+// game only ever sees a handful: the rules, plus capitals and terrain.
+fn table(limits: DiceLimits) -> Arc<WinTable> {
+    static TABLES: OnceLock<Mutex<HashMap<DiceLimits, Arc<WinTable>>>> = OnceLock::new();
+    let mut tables = TABLES.get_or_init(Default::default).lock().unwrap();
+    tables
+        .entry(limits)
+        .or_insert_with(|| Arc::new(win_table(TABLE_SIZE - 1, TABLE_SIZE - 1, limits)))
+        .clone()
+}
+
+// Every way `count` dice can come up, highest first, with its chance. Dice
+// are sorted before comparing anyway, so each distinct set only needs
+// weighing once rather than trying all 6^n rolls.
+fn sorted_rolls(count: usize) -> Vec<(Vec<u8>, f64)> {
+    fn extend(roll: &mut Vec<u8>, count: usize, rolls: &mut Vec<(Vec<u8>, f64)>) {
+        if roll.len() == count {
+            // Orderings of these dice: n! over the repeats' factorials
+            let factorial = |n: usize| (1..=n).map(|k| k as f64).product::<f64>();
+            let repeats: f64 = (1..=6u8)
+                .map(|face| factorial(roll.iter().filter(|&&d| d == face).count()))
+                .product();
+            let chance = factorial(count) / repeats / 6f64.powi(count as i32);
+            rolls.push((roll.clone(), chance));
+            return;
+        }
+        let highest = roll.last().copied().unwrap_or(6);
+        for face in (1..=highest).rev() {
+            roll.push(face);
+            extend(roll, count, rolls);
+            roll.pop();
+        }
+    }
+    let mut rolls = Vec::new();
+    extend(&mut Vec::new(), count, &mut rolls);
+    rolls
+}
+
+// Probability of the defender losing each number of armies in one round
+// with the given numbers of dice
+fn round_outcomes(attack_dice: usize, defend_dice: usize, limits: DiceLimits) -> Vec<f64> {
+    let mut outcomes = vec![0.0; attack_dice.min(defend_dice) + 1];
+    let defence = sorted_rolls(defend_dice);
+    for (attack, attack_chance) in sorted_rolls(attack_dice) {
+        for (defend, defend_chance) in &defence {
+            outcomes[defender_losses(&attack, defend, limits) as usize] +=
+                attack_chance * defend_chance;
+        }
+    }
+    outcomes
+}
+
+// win[a][d]: chance that `a` armies able to attack beat `d` defenders
+fn win_table(max_attackers: usize, max_defenders: usize, limits: DiceLimits) -> WinTable {
+    let (most_attacking, most_defending) = (limits.attacker as usize, limits.defender as usize);
+    let outcomes: Vec<Vec<Vec<f64>>> = (0..=most_attacking.min(max_attackers))
+        .map(|attack_dice| {
+            (0..=most_defending.min(max_defenders))
+                .map(|defend_dice| {
+                    if attack_dice == 0 || defend_dice == 0 {
+                        Vec::new()
+                    } else {
+                        round_outcomes(attack_dice, defend_dice, limits)
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut win = vec![vec![0.0; max_defenders + 1]; max_attackers + 1];
+    for a in 0..=max_attackers {
+        win[a][0] = 1.0;
+        for d in 1..=max_defenders {
+            if a == 0 {
+                continue;
+            }
+            // Every outcome leaves fewer armies on some side, so the
+            // states it leads to are already filled in
+            let (attack_dice, defend_dice) = (a.min(most_attacking), d.min(most_defending));
+            let rolled = attack_dice.min(defend_dice);
+            win[a][d] = (0..=rolled)
+                .map(|killed| {
+                    let lost = rolled - killed;
+                    outcomes[attack_dice][defend_dice][killed] * win[a - lost][d - killed]
+                })
+                .sum();
+        }
+    }
+    win
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::combat::TieWinner;
+    use crate::game::territory::Terrain;
+    use crate::test_support::{game, territory};
+
+    const CLASSIC: DiceLimits = DiceLimits {
+        attacker: 3,
+        defender: 2,
+        ties_go_to: TieWinner::Defender,
+        defender_bonus: 0,
+    };
+
+    #[test]
+    fn single_dice_rounds_match_the_known_odds() {
+        // One die each: the attacker needs to roll higher, 15 of 36
+        let outcomes = round_outcomes(1, 1, CLASSIC);
+        assert!((outcomes[1] - 15.0 / 36.0).abs() < 1e-12);
+        assert!((outcomes[0] - 21.0 / 36.0).abs() < 1e-12);
+        // Three against two: the textbook 2890 / 2275 / 2611 of 7776
+        let [none, one, two] = round_outcomes(3, 2, CLASSIC)[..] else {
+            panic!("three outcomes");
+        };
+        assert!((two - 2890.0 / 7776.0).abs() < 1e-12);
+        assert!((none - 2275.0 / 7776.0).abs() < 1e-12);
+        assert!((one - 2611.0 / 7776.0).abs() < 1e-12);
+        // Ties to the attacker turn the 6 of 36 draws around
+        let ties = DiceLimits {
+            ties_go_to: TieWinner::Attacker,
+            ..CLASSIC
+        };
+        assert!((round_outcomes(1, 1, ties)[1] - 21.0 / 36.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn odds_follow_army_sizes() {
+        assert_eq!(combat_odds(1, 1, CLASSIC), 0.0);
+        assert!((combat_odds(2, 1, CLASSIC) - 15.0 / 36.0).abs() < 1e-6);
+        assert!(combat_odds(10, 3, CLASSIC) > 0.9);
+        assert!(combat_odds(3, 10, CLASSIC) < 0.01);
+        // Past the table the answer is computed directly and stays in range
+        let big = combat_odds(200, 150, CLASSIC);
+        assert!(big > 0.5 && big <= 1.0);
+    }
+
+    #[test]
+    fn odds_follow_the_rules_and_the_defender() {
+        let mut game = game(
+            vec![
+                territory("Attacker", 0, 6, vec![1]),
+                territory("Defender", 1, 4, vec![0]),
+            ],
+            0,
+        );
+        let classic = game.conquest_odds(0, 1);
+        assert!((classic - combat_odds(6, 4, CLASSIC)).abs() < 1e-6);
+
+        game.combat_rules.ties_go_to = TieWinner::Attacker;
+        assert!(game.conquest_odds(0, 1) > classic);
+        game.combat_rules.ties_go_to = TieWinner::Defender;
+
+        game.territories[1].is_capital = true;
+        assert!(game.conquest_odds(0, 1) < classic);
+        game.territories[1].is_capital = false;
+
+        for terrain in [Terrain::Mountain, Terrain::River] {
+            game.territories[1].terrain = terrain;
+            assert!(game.conquest_odds(0, 1) < classic, "{:?}", terrain);
+        }
+    }
+}
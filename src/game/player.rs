@@ -0,0 +1,68 @@
+use crate::game::ai::AiDifficulty;
+use macroquad::color::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Colors handed out to players the config doesn't describe
+pub const DEFAULT_PALETTE: [Color; 8] = [BLUE, GREEN, RED, ORANGE, PURPLE, BROWN, PINK, SKYBLUE];
+
+// Identifies a player by their seat at the table. Territories store this
+// rather than a bare index so ownership can't be mixed up with other numbers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PlayerId(pub usize);
+
+impl fmt::Display for PlayerId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+// Drawn over a player's territories so ownership doesn't rely on colour alone
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Pattern {
+    Solid,
+    DiagonalStripes,
+    Dots,
+    CrossHatch,
+    HorizontalStripes,
+}
+
+// Patterns handed out by seat, so the first five players all differ
+pub const DEFAULT_PATTERNS: [Pattern; 5] = [
+    Pattern::Solid,
+    Pattern::DiagonalStripes,
+    Pattern::Dots,
+    Pattern::CrossHatch,
+    Pattern::HorizontalStripes,
+];
+
+impl Pattern {
+    pub fn for_seat(index: usize) -> Pattern {
+        DEFAULT_PATTERNS[index % DEFAULT_PATTERNS.len()]
+    }
+}
+
+#[derive(Clone)]
+pub struct Player {
+    pub id: PlayerId,
+    pub name: String,
+    pub color: Color,
+    pub pattern: Pattern,
+    pub is_ai: bool,
+    pub difficulty: AiDifficulty,
+}
+
+impl Player {
+    pub fn with_default_color(index: usize) -> Player {
+        Player {
+            id: PlayerId(index),
+            name: format!("Player {}", index + 1),
+            color: DEFAULT_PALETTE[index % DEFAULT_PALETTE.len()],
+            pattern: Pattern::for_seat(index),
+            is_ai: false,
+            difficulty: AiDifficulty::default(),
+        }
+    }
+}
@@ -0,0 +1,385 @@
+use crate::game::events::GameEvent;
+use crate::game::player::PlayerId;
+use crate::game::turn::Phase;
+use crate::game::undo::Action;
+use crate::game::Game;
+use serde::Deserialize;
+
+// How much likelier auto_reinforce is to pick a border territory
+const BORDER_WEIGHT: u32 = 3;
+
+// The army economy, for variant maps that want more or fewer armies each
+// turn. The defaults are the classic rules.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct RulesConfig {
+    // Fewest armies a player gets a turn, however little they hold
+    pub reinforcement_floor: i32,
+    // Territories owned per army
+    pub reinforcement_divisor: i32,
+}
+
+impl Default for RulesConfig {
+    fn default() -> RulesConfig {
+        RulesConfig {
+            reinforcement_floor: 3,
+            reinforcement_divisor: 3,
+        }
+    }
+}
+
+impl RulesConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.reinforcement_divisor <= 0 {
+            return Err(format!(
+                "reinforcement_divisor must be positive, not {}",
+                self.reinforcement_divisor
+            ));
+        }
+        if self.reinforcement_floor < 0 {
+            return Err(format!(
+                "reinforcement_floor can't be negative, not {}",
+                self.reinforcement_floor
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Game {
+    // One army per `reinforcement_divisor` territories owned (three in
+    // classic Risk), but never fewer than the floor, plus the bonus of every
+    // continent held outright
+    pub fn reinforcements_for(&self, player: PlayerId) -> i32 {
+        let owned = self
+            .territories
+            .iter()
+            .filter(|territory| territory.owner == Some(player))
+            .count() as i32;
+        (owned / self.rules.reinforcement_divisor).max(self.rules.reinforcement_floor)
+            + self.continent_bonus_for(player)
+    }
+
+    pub fn begin_reinforcements(&mut self) {
+        self.reinforcements_remaining = self.reinforcements_for(self.current_player);
+    }
+
+    // How much of the pool can still go somewhere: all of it, unless the
+    // army cap leaves the current player's territories too little room
+    pub fn reinforcements_placeable(&self) -> i32 {
+        let room = (0..self.territories.len())
+            .filter(|&i| self.territories[i].owner == Some(self.current_player))
+            .fold(0i32, |room, i| room.saturating_add(self.room_in(i)));
+        self.reinforcements_remaining.min(room)
+    }
+
+    // Drops one army from the pool onto a territory the current player owns,
+    // moving on to the Attack phase once nothing more can be placed. A pool
+    // that no longer fits under the army cap is forfeited.
+    pub fn place_reinforcement(&mut self, territory: usize) {
+        if self.phase != Phase::Reinforce
+            || self.reinforcements_remaining <= 0
+            || self.territories[territory].owner != Some(self.current_player)
+            || self.room_in(territory) == 0
+        {
+            return;
+        }
+
+        self.territories[territory].armies += 1;
+        self.note_stack(territory);
+        self.reinforcements_remaining -= 1;
+        self.board_dirty = true;
+        self.record(Action::PlaceReinforcement { territory });
+        self.log_event(GameEvent::Reinforced {
+            player: self.current_player,
+            territory,
+            count: 1,
+        });
+        self.forfeit_unplaceable();
+    }
+
+    // Gives up a pool the army cap has left nowhere to go and moves on to
+    // Attack. Returns false, changing nothing, while any of it still fits.
+    pub fn forfeit_unplaceable(&mut self) -> bool {
+        if self.phase != Phase::Reinforce || self.reinforcements_placeable() > 0 {
+            return false;
+        }
+        self.reinforcements_remaining = 0;
+        self.next_phase();
+        true
+    }
+
+    // Places up to `count` armies at once, stopping early if the pool runs
+    // out; returns how many were placed
+    pub fn place_reinforcements(&mut self, territory: usize, count: i32) -> i32 {
+        let mut placed = 0;
+        while placed < count && self.phase == Phase::Reinforce && self.reinforcements_remaining > 0
+        {
+            let before = self.reinforcements_remaining;
+            self.place_reinforcement(territory);
+            if self.reinforcements_remaining == before {
+                break;
+            }
+            placed += 1;
+        }
+        placed
+    }
+
+    // Scatters the rest of `player`'s pool over their territories one army
+    // at a time, picking with the game's seeded rng and weighting territories
+    // that face an enemy above those safely inland
+    pub fn auto_reinforce(&mut self, player: PlayerId) {
+        if self.current_player != player {
+            return;
+        }
+        while self.phase == Phase::Reinforce && self.reinforcements_remaining > 0 {
+            // Territories at the army cap drop out as they fill up
+            let weighted: Vec<(usize, u32)> = (0..self.territories.len())
+                .filter(|&i| self.territories[i].owner == Some(player) && self.room_in(i) > 0)
+                .map(|i| (i, if self.is_border(i) { BORDER_WEIGHT } else { 1 }))
+                .collect();
+            let total: u32 = weighted.iter().map(|&(_, weight)| weight).sum();
+            if total == 0 {
+                break;
+            }
+            let mut pick = self.rng.gen_range(0, total);
+            let &(territory, _) = weighted
+                .iter()
+                .find(|&&(_, weight)| {
+                    let hit = pick < weight;
+                    pick = pick.saturating_sub(weight);
+                    hit
+                })
+                .expect("pick is below the total weight");
+            self.place_reinforcement(territory);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RulesConfig;
+    use crate::game::cards::Card;
+    use crate::game::continent::Continent;
+    use crate::game::player::PlayerId;
+    use crate::game::turn::Phase;
+    use crate::game::{Game, Territory};
+    use crate::test_support::{game, game_state, territory};
+
+    // Territories owned by the players listed, in order, with no borders
+    fn board(owners: &[usize]) -> Vec<Territory> {
+        owners
+            .iter()
+            .map(|&owner| territory("T", owner, 1, vec![]))
+            .collect()
+    }
+
+    fn continent(territories: Vec<usize>, bonus: i32) -> Continent {
+        Continent {
+            name: "C".to_string(),
+            territories,
+            bonus,
+            required_capital: None,
+        }
+    }
+
+    fn count(game: &Game, player: usize) -> i32 {
+        game.reinforcements_for(PlayerId(player))
+    }
+
+    #[test]
+    fn territory_count_is_divided_down_to_the_floor() {
+        // Everything below twelve territories earns just the floor of three
+        for (owned, expected) in [(1, 3), (2, 3), (11, 3), (12, 4), (14, 4), (15, 5), (42, 14)] {
+            let mut owners = vec![0; owned];
+            owners.push(1);
+            assert_eq!(
+                count(&game(board(&owners), 0), 0),
+                expected,
+                "{} owned",
+                owned
+            );
+        }
+        // A player with nothing left still counts the floor
+        assert_eq!(count(&game(board(&[0, 0]), 0), 1), 3);
+    }
+
+    #[test]
+    fn only_continents_held_outright_pay_their_bonus() {
+        let mut game = game(board(&[0, 0, 0, 1, 0, 0]), 0);
+        game.continents = vec![
+            continent(vec![0, 1], 2),
+            continent(vec![2, 3], 5),
+            continent(vec![4, 5], 3),
+        ];
+        assert_eq!(count(&game, 0), 3 + 2 + 3);
+        assert_eq!(count(&game, 1), 3);
+
+        // Taking the last territory of the middle one adds it on top
+        game.territories[3].owner = Some(PlayerId(0));
+        assert_eq!(count(&game, 0), 3 + 2 + 5 + 3);
+        // Losing any one territory costs that continent's whole bonus
+        game.territories[0].owner = None;
+        assert_eq!(count(&game, 0), 3 + 5 + 3);
+
+        // An empty continent belongs to nobody
+        game.continents.push(continent(vec![], 7));
+        assert_eq!(count(&game, 0), 3 + 5 + 3);
+    }
+
+    #[test]
+    fn bonuses_stack_on_the_rules_and_the_floor() {
+        let mut owners = vec![0; 14];
+        owners.push(1);
+        let mut game = game(board(&owners), 0);
+        game.continents = vec![continent((0..4).collect(), 2)];
+        game.rules = RulesConfig {
+            reinforcement_floor: 6,
+            reinforcement_divisor: 2,
+        };
+        assert_eq!(count(&game, 0), 7 + 2);
+        // The floor stands in for the territory count, not the bonus
+        game.rules.reinforcement_divisor = 5;
+        assert_eq!(count(&game, 0), 6 + 2);
+    }
+
+    #[test]
+    fn a_card_trade_adds_to_the_turns_pool() {
+        let mut state = game_state(board(&[0, 0, 0, 0, 1]), 0);
+        state.game.continents = vec![continent(vec![0, 1], 2)];
+        state.game.begin_reinforcements();
+        assert_eq!(state.game.reinforcements_remaining, 5);
+
+        // Trading doesn't change the turn's allowance, just the pool
+        state.game.hands.insert(PlayerId(0), vec![Card::Wild; 3]);
+        assert_eq!(state.game.trade_cards(PlayerId(0), [0, 1, 2]), Ok(4));
+        assert_eq!(state.game.reinforcements_remaining, 9);
+        assert_eq!(count(&state.game, 0), 5);
+
+        // Under an army cap only what fits can be placed
+        state.game.max_armies = Some(2);
+        assert_eq!(state.game.reinforcements_placeable(), 4);
+        assert_eq!(state.game.reinforcements_remaining, 9);
+    }
+
+    #[test]
+    fn floor_and_divisor_come_from_the_rules() {
+        let board: Vec<_> = (0..12).map(|_| territory("T", 0, 1, vec![])).collect();
+        let mut game = game(board, 0);
+        assert_eq!(game.reinforcements_for(PlayerId(0)), 4);
+
+        // A generous variant: an army per two territories, at least five
+        game.rules = RulesConfig {
+            reinforcement_floor: 5,
+            reinforcement_divisor: 2,
+        };
+        assert_eq!(game.reinforcements_for(PlayerId(0)), 6);
+
+        // A stingy one where the floor is all a small empire gets
+        game.rules = RulesConfig {
+            reinforcement_floor: 1,
+            reinforcement_divisor: 20,
+        };
+        assert_eq!(game.reinforcements_for(PlayerId(0)), 1);
+    }
+
+    #[test]
+    fn rules_need_a_positive_divisor() {
+        assert!(RulesConfig::default().validate().is_ok());
+        let zero = RulesConfig {
+            reinforcement_divisor: 0,
+            ..RulesConfig::default()
+        };
+        assert!(zero.validate().is_err());
+    }
+
+    #[test]
+    fn placing_several_is_capped_by_the_pool() {
+        let mut game = game(
+            vec![territory("A", 0, 1, vec![1]), territory("B", 1, 1, vec![0])],
+            0,
+        );
+        assert_eq!(game.reinforcements_remaining, 3);
+        assert_eq!(game.place_reinforcements(0, 2), 2);
+        assert_eq!(game.reinforcements_remaining, 1);
+        assert_eq!(game.place_reinforcements(0, 9), 1);
+        assert_eq!(game.territories[0].armies, 4);
+        assert_eq!(game.phase, Phase::Attack);
+        // Nothing left, and it's no longer the Reinforce phase
+        assert_eq!(game.place_reinforcements(0, 1), 0);
+    }
+
+    #[test]
+    fn placement_stops_at_the_army_cap() {
+        let mut game = game(
+            vec![
+                territory("A", 0, 1, vec![1]),
+                territory("B", 0, 4, vec![0, 2]),
+                territory("C", 1, 1, vec![1]),
+            ],
+            0,
+        );
+        game.max_armies = Some(5);
+        assert_eq!(game.reinforcements_remaining, 3);
+        assert_eq!(game.place_reinforcements(1, 3), 1);
+        assert_eq!(game.territories[1].armies, 5);
+        // Full territories turn placements away
+        game.place_reinforcement(1);
+        assert_eq!(game.reinforcements_remaining, 2);
+        assert_eq!(game.place_reinforcements(0, 2), 2);
+        assert_eq!(game.phase, Phase::Attack);
+    }
+
+    #[test]
+    fn a_pool_too_big_for_the_cap_is_forfeited() {
+        let mut game = game(
+            vec![territory("A", 0, 1, vec![1]), territory("B", 1, 1, vec![0])],
+            0,
+        );
+        game.max_armies = Some(3);
+        game.reinforcements_remaining = 5;
+        assert_eq!(game.reinforcements_placeable(), 2);
+        assert_eq!(game.place_reinforcements(0, 5), 2);
+        assert_eq!(game.territories[0].armies, 3);
+        assert_eq!(game.reinforcements_remaining, 0);
+        assert_eq!(game.phase, Phase::Attack);
+
+        // A turn that starts with every territory full gives its pool up too
+        game.phase = Phase::Reinforce;
+        game.reinforcements_remaining = 4;
+        game.max_armies = Some(4);
+        assert!(!game.forfeit_unplaceable());
+        game.max_armies = Some(3);
+        assert!(game.forfeit_unplaceable());
+        assert_eq!(game.reinforcements_remaining, 0);
+        assert_eq!(game.phase, Phase::Attack);
+    }
+
+    #[test]
+    fn auto_reinforce_spends_the_pool_deterministically() {
+        let start = || {
+            game(
+                vec![
+                    territory("Inland", 0, 1, vec![1]),
+                    territory("Border", 0, 1, vec![0, 2]),
+                    territory("Enemy", 1, 1, vec![1]),
+                ],
+                7,
+            )
+        };
+        let mut game = start();
+        game.reinforcements_remaining = 40;
+        game.auto_reinforce(PlayerId(0));
+        assert_eq!(game.reinforcements_remaining, 0);
+        assert_eq!(game.phase, Phase::Attack);
+        let (inland, border) = (game.territories[0].armies, game.territories[1].armies);
+        assert_eq!(inland + border, 42);
+        assert!(border > inland);
+        assert_eq!(game.territories[2].armies, 1);
+
+        let mut again = start();
+        again.reinforcements_remaining = 40;
+        again.auto_reinforce(PlayerId(0));
+        assert_eq!(again.territories[1].armies, border);
+    }
+}
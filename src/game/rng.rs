@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+// Anything that can roll a six-sided die. Combat takes one of these so
+// tests can load the dice with a known sequence.
+pub trait Dice {
+    fn roll_die(&mut self) -> u8;
+}
+
+// Small seedable PRNG (SplitMix64). Keeping it in-tree means a game can be
+// reproduced exactly from its seed, which the combat tests rely on.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    // The whole generator state; `Rng::new` on it carries on the same sequence
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // Uniform value in `low..high`
+    pub fn gen_range(&mut self, low: u32, high: u32) -> u32 {
+        low + (self.next_u64() % u64::from(high - low)) as u32
+    }
+}
+
+impl Dice for Rng {
+    fn roll_die(&mut self) -> u8 {
+        self.gen_range(1, 7) as u8
+    }
+}
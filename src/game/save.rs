@@ -0,0 +1,374 @@
+use crate::game::cards::Card;
+use crate::game::events::GameEvent;
+use crate::game::map::{self, MapLoadError, TerritoryData};
+use crate::game::player::PlayerId;
+use crate::game::rng::Rng;
+use crate::game::statistics::GameStatistics;
+use crate::game::turn::Phase;
+use crate::game::validate;
+use crate::game::Game;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Bumped whenever the save layout changes; saves from before it was
+// recorded count as version 1
+pub const SAVE_FORMAT_VERSION: u64 = 2;
+
+fn first_version() -> u64 {
+    1
+}
+
+// Everything needed to pick a game back up. Territories are stored in full
+// (geometry included) so a save doesn't depend on the map file staying the same.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct GameSnapshot {
+    #[serde(default = "first_version")]
+    version: u64,
+    territories: Vec<TerritoryData>,
+    current_player: PlayerId,
+    phase: Phase,
+    reinforcements_remaining: i32,
+    // Older saves predate cards
+    #[serde(default)]
+    hands: HashMap<PlayerId, Vec<Card>>,
+    #[serde(default)]
+    trades_completed: usize,
+    #[serde(default)]
+    conquered_this_turn: bool,
+    #[serde(default)]
+    initial_armies: Vec<i32>,
+    #[serde(default)]
+    statistics: GameStatistics,
+    #[serde(default)]
+    events: Vec<GameEvent>,
+    // Where the dice had got to, so a resumed game rolls exactly what it
+    // would have; older saves carry on with whatever the RNG is at
+    #[serde(default)]
+    rng: Option<Rng>,
+    #[serde(default)]
+    seed: u64,
+    #[serde(default)]
+    alliances: Vec<(PlayerId, PlayerId)>,
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Json {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    Map(MapLoadError),
+    // Written by a newer version of the game
+    UnsupportedVersion {
+        path: PathBuf,
+        version: u64,
+    },
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            SaveError::Json { path, source } => write!(f, "{}: {}", path.display(), source),
+            SaveError::Map(err) => err.fmt(f),
+            SaveError::UnsupportedVersion { path, version } => write!(
+                f,
+                "{} is save format version {}, but this game only reads up to version {}",
+                path.display(),
+                version,
+                SAVE_FORMAT_VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SaveError::Io { source, .. } => Some(source),
+            SaveError::Json { source, .. } => Some(source),
+            SaveError::Map(err) => Some(err),
+            SaveError::UnsupportedVersion { .. } => None,
+        }
+    }
+}
+
+impl Game {
+    pub fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            version: SAVE_FORMAT_VERSION,
+            territories: self.territory_data(),
+            current_player: self.current_player,
+            phase: self.phase,
+            reinforcements_remaining: self.reinforcements_remaining,
+            hands: self.hands.clone(),
+            trades_completed: self.trades_completed,
+            conquered_this_turn: self.conquered_this_turn,
+            initial_armies: self.initial_armies.clone(),
+            statistics: self.statistics.clone(),
+            events: self.events.clone(),
+            rng: Some(self.rng.clone()),
+            seed: self.seed,
+            alliances: {
+                let mut alliances: Vec<_> = self.alliances.iter().copied().collect();
+                alliances.sort_by_key(|(a, b)| (a.0, b.0));
+                alliances
+            },
+        }
+    }
+
+    // A save is checked against this game's players before anything is
+    // replaced, so a hand-edited one can't leave territories, the turn or
+    // anything else to a seat that isn't there
+    pub fn restore(&mut self, snapshot: &GameSnapshot) -> Result<(), MapLoadError> {
+        let unseated = validate::owners_out_of_range(&snapshot.territories, self.players.len());
+        if !unseated.is_empty() {
+            return Err(MapLoadError::Invalid(unseated));
+        }
+        snapshot.check_seats(self.players.len())?;
+        self.territories = map::build_territories(&snapshot.territories)?;
+        self.continents = map::build_continents(&snapshot.territories)?;
+        self.current_player = snapshot.current_player;
+        self.phase = snapshot.phase;
+        self.reinforcements_remaining = snapshot.reinforcements_remaining;
+        self.hands = snapshot.hands.clone();
+        self.trades_completed = snapshot.trades_completed;
+        self.conquered_this_turn = snapshot.conquered_this_turn;
+        self.initial_armies = snapshot.initial_armies.clone();
+        self.statistics = snapshot.statistics.clone();
+        self.events = snapshot.events.clone();
+        self.seed = snapshot.seed;
+        self.alliances = snapshot.alliances.iter().copied().collect();
+        if let Some(rng) = &snapshot.rng {
+            self.rng = rng.clone();
+        }
+        self.last_combat = None;
+        self.ai_attacks = 0;
+        self.undo_stack.clear();
+        self.board_dirty = true;
+        Ok(())
+    }
+
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveError> {
+        let path = path.as_ref();
+        let json =
+            serde_json::to_string_pretty(&self.snapshot()).map_err(|source| SaveError::Json {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        fs::write(path, json).map_err(|source| SaveError::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+}
+
+impl GameSnapshot {
+    // The first seat the save names, besides territory owners, that's
+    // beyond the table. Starting armies are kept per seat, so there must be
+    // one for each while they're being placed and none or one each after.
+    fn check_seats(&self, player_count: usize) -> Result<(), MapLoadError> {
+        let unseated = |what, player: PlayerId| {
+            (player.0 >= player_count).then_some(MapLoadError::UnseatedPlayer {
+                what,
+                player,
+                player_count,
+            })
+        };
+        let mut holders: Vec<PlayerId> = self.hands.keys().copied().collect();
+        holders.sort_by_key(|player| player.0);
+        let found = unseated("the turn", self.current_player)
+            .or_else(|| {
+                holders
+                    .iter()
+                    .find_map(|&player| unseated("a hand of cards", player))
+            })
+            .or_else(|| {
+                self.alliances.iter().find_map(|&(a, b)| {
+                    unseated("an alliance", a).or_else(|| unseated("an alliance", b))
+                })
+            });
+        if let Some(err) = found {
+            return Err(err);
+        }
+        let seats = self.initial_armies.len();
+        let placing = self.phase == Phase::InitialPlacement;
+        if seats != player_count && (seats > 0 || placing) {
+            return Err(MapLoadError::StartingArmiesMismatch {
+                seats,
+                player_count,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<GameSnapshot, SaveError> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path).map_err(|source| SaveError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        GameSnapshot::parse(path, &json)
+    }
+
+    // Older saves only lack fields that have defaults, so they load as they
+    // are; newer ones are refused before their layout can confuse anything
+    fn parse(path: &Path, json: &str) -> Result<GameSnapshot, SaveError> {
+        let json_error = |source| SaveError::Json {
+            path: path.to_path_buf(),
+            source,
+        };
+        let value: serde_json::Value = serde_json::from_str(json).map_err(json_error)?;
+        let version = value
+            .get("version")
+            .and_then(serde_json::Value::as_u64)
+            .unwrap_or(1);
+        if version > SAVE_FORMAT_VERSION {
+            return Err(SaveError::UnsupportedVersion {
+                path: path.to_path_buf(),
+                version,
+            });
+        }
+        serde_json::from_value(value).map_err(json_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GameSnapshot, SaveError, SAVE_FORMAT_VERSION};
+    use crate::game::map::{MapLoadError, MapOwner};
+    use crate::game::player::{Player, PlayerId};
+    use crate::game::turn::Phase;
+    use crate::game::validate::MapValidationError;
+    use crate::game::Game;
+    use crate::test_support::{game, territory};
+
+    #[test]
+    fn resuming_a_save_rolls_the_same_dice() {
+        let board = || {
+            vec![
+                territory("A", 0, 10, vec![1]),
+                territory("B", 1, 10, vec![0]),
+            ]
+        };
+        let mut original = game(board(), 21);
+        original.phase = Phase::Attack;
+        // Use up some of the sequence first, so the save is truly mid-game
+        original.resolve_attack(0, 1);
+
+        let json = serde_json::to_string(&original.snapshot()).unwrap();
+        let snapshot: GameSnapshot = serde_json::from_str(&json).unwrap();
+        let before = original.resolve_attack(0, 1);
+
+        // Started from a different seed, so only the save can line it up
+        let mut resumed = game(board(), 999);
+        resumed.restore(&snapshot).unwrap();
+        let after = resumed.resolve_attack(0, 1);
+        assert_eq!(before.attacker_dice, after.attacker_dice);
+        assert_eq!(before.defender_dice, after.defender_dice);
+    }
+
+    #[test]
+    fn territories_held_by_a_missing_seat_are_refused() {
+        let mut original = game(
+            vec![territory("A", 0, 3, vec![1]), territory("B", 1, 3, vec![0])],
+            0,
+        );
+        let unseated = [MapValidationError::OwnerOutOfRange {
+            territory: "B".to_string(),
+            owner: PlayerId(4),
+            player_count: 2,
+        }];
+
+        let mut data = original.territory_data();
+        data[1].owner = Some(MapOwner::Seat(PlayerId(4)));
+        let players = (0..2).map(Player::with_default_color).collect();
+        match Game::from_map(&data, players, 0) {
+            Err(MapLoadError::Invalid(errors)) => assert_eq!(errors, unseated),
+            _ => panic!("a map with an unseated owner should not load"),
+        }
+
+        // Nor can a save slip one past, and the game is left as it was
+        let mut snapshot = original.snapshot();
+        snapshot.territories[1].owner = Some(MapOwner::Seat(PlayerId(4)));
+        match original.restore(&snapshot) {
+            Err(MapLoadError::Invalid(errors)) => assert_eq!(errors, unseated),
+            _ => panic!("a save with an unseated owner should not load"),
+        }
+        assert_eq!(original.territories[1].owner, Some(PlayerId(1)));
+
+        // Likewise the turn, a hand, an alliance or the starting armies
+        let mut refused = |edit: fn(&mut GameSnapshot)| {
+            let mut snapshot = original.snapshot();
+            edit(&mut snapshot);
+            let err = original.restore(&snapshot).unwrap_err().to_string();
+            assert_eq!(original.current_player, PlayerId(0));
+            err
+        };
+        assert_eq!(
+            refused(|s| s.current_player = PlayerId(7)),
+            "The save gives the turn to player 7, but there are only 2 players"
+        );
+        assert_eq!(
+            refused(|s| {
+                s.hands.insert(PlayerId(3), Vec::new());
+            }),
+            "The save gives a hand of cards to player 3, but there are only 2 players"
+        );
+        assert_eq!(
+            refused(|s| s.alliances = vec![(PlayerId(0), PlayerId(2))]),
+            "The save gives an alliance to player 2, but there are only 2 players"
+        );
+        assert_eq!(
+            refused(|s| s.initial_armies = vec![5, 5, 5]),
+            "The save has starting armies for 3 players, but there are 2"
+        );
+    }
+
+    #[test]
+    fn renaming_a_territory_keeps_the_save_playable() {
+        let mut original = game(
+            vec![
+                territory("A", 0, 3, vec![1]),
+                territory("B", 1, 3, vec![0, 2]),
+                territory("C", 1, 3, vec![1]),
+            ],
+            0,
+        );
+        let mut json = serde_json::to_value(original.snapshot()).unwrap();
+        json["territories"][1]["name"] = "Bohemia".into();
+        let snapshot =
+            GameSnapshot::parse(std::path::Path::new("save.json"), &json.to_string()).unwrap();
+
+        original.restore(&snapshot).unwrap();
+        assert_eq!(original.territories[1].name, "Bohemia");
+        assert_eq!(original.territories[0].adjacent, [1]);
+        assert_eq!(original.territories[1].adjacent, [0, 2]);
+        assert_eq!(original.territories[1].id, 1);
+    }
+
+    #[test]
+    fn saves_without_a_version_load_and_newer_ones_are_refused() {
+        let path = std::path::Path::new("save.json");
+        let mut json =
+            serde_json::to_value(game(vec![territory("A", 0, 3, vec![])], 0).snapshot()).unwrap();
+        json.as_object_mut().unwrap().remove("version");
+        let snapshot = GameSnapshot::parse(path, &json.to_string()).unwrap();
+        assert_eq!(snapshot.version, 1);
+
+        json["version"] = (SAVE_FORMAT_VERSION + 1).into();
+        let result = GameSnapshot::parse(path, &json.to_string());
+        assert!(matches!(
+            result,
+            Err(SaveError::UnsupportedVersion { version: 3, .. })
+        ));
+    }
+}
@@ -0,0 +1,439 @@
+use crate::game::events::GameEvent;
+use crate::game::player::{Player, PlayerId};
+use crate::game::rng::Rng;
+use crate::game::turn::Phase;
+use crate::game::Game;
+use serde::Deserialize;
+
+// Who starts out owning what
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartMode {
+    // Whatever owners the map file gives
+    #[default]
+    Map,
+    // Shuffled and dealt out round-robin
+    Random,
+    // Players pick one territory at a time, in seat order
+    Draft,
+}
+
+// Where quick-start armies beyond each territory's first one go
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Weighting {
+    // Anywhere the player holds, all alike
+    #[default]
+    Even,
+    // Mostly where an enemy is next door, and most of all where that border
+    // is also a continent's edge, the likeliest place to be fought over
+    Borders,
+}
+
+// Armies each player starts with in the classic game: fewer the more
+// players there are
+pub fn starting_armies(players: usize) -> i32 {
+    match players {
+        0..=2 => 40,
+        3 => 35,
+        4 => 30,
+        5 => 25,
+        _ => 20,
+    }
+}
+
+impl Game {
+    // Replaces the map's owners according to `mode`, so the map itself only
+    // needs geometry and adjacency. Dealt territories start with one army;
+    // drafting starts the game in the Claim phase with everything unowned.
+    pub fn assign_starting_territories(&mut self, mode: StartMode, seed: u64) {
+        if mode == StartMode::Map {
+            return;
+        }
+        // An ownerless map with no players configured still needs someone
+        // to hand territories to
+        if self.players.is_empty() {
+            self.players = (0..2).map(Player::with_default_color).collect();
+        }
+        let mut order: Vec<usize> = (0..self.territories.len()).collect();
+        let mut rng = Rng::new(seed);
+        for i in (1..order.len()).rev() {
+            let j = rng.gen_range(0, i as u32 + 1) as usize;
+            order.swap(i, j);
+        }
+        for (deal, &territory) in order.iter().enumerate() {
+            let territory = &mut self.territories[territory];
+            match mode {
+                StartMode::Random => {
+                    territory.owner = Some(PlayerId(deal % self.players.len()));
+                    territory.armies = 1;
+                }
+                _ => {
+                    territory.owner = None;
+                    territory.armies = 0;
+                }
+            }
+        }
+        self.current_player = PlayerId(0);
+        self.phase = if mode == StartMode::Draft {
+            Phase::Claim
+        } else {
+            Phase::Reinforce
+        };
+        self.reinforcements_remaining = 0;
+        self.board_dirty = true;
+        if self.phase == Phase::Reinforce {
+            self.begin_reinforcements();
+        }
+    }
+
+    // The current player takes an unowned territory during the draft and
+    // passes the pick on. The last pick starts the first turn proper.
+    pub fn claim_territory(&mut self, territory: usize) -> bool {
+        if self.phase != Phase::Claim || self.territories[territory].owner.is_some() {
+            return false;
+        }
+        self.territories[territory].owner = Some(self.current_player);
+        self.territories[territory].armies = 1;
+        self.board_dirty = true;
+        // The claiming army comes out of the starting armies, if placing
+        // them follows
+        if let Some(left) = self.initial_armies.get_mut(self.current_player.0) {
+            *left = (*left - 1).max(0);
+        }
+        if self.territories.iter().any(|t| t.owner.is_none()) {
+            self.current_player = PlayerId((self.current_player.0 + 1) % self.players.len());
+            return true;
+        }
+        self.phase = Phase::InitialPlacement;
+        self.current_player = PlayerId(self.players.len() - 1);
+        self.pass_initial_placement();
+        true
+    }
+
+    // Opens the game with the classic placement: each player gets the
+    // starting armies for the table's size, less one for every territory
+    // they already hold, and the seats take turns placing them one by one.
+    // Call after the territories are handed out; a draft places them once
+    // the last territory is claimed.
+    pub fn begin_initial_placement(&mut self) {
+        let per_player = starting_armies(self.players.len());
+        self.initial_armies = (0..self.players.len())
+            .map(|seat| {
+                let held = self
+                    .territories
+                    .iter()
+                    .filter(|t| t.owner == Some(PlayerId(seat)))
+                    .count() as i32;
+                (per_player - held).max(0)
+            })
+            .collect();
+        if self.phase == Phase::Reinforce {
+            self.reinforcements_remaining = 0;
+            self.phase = Phase::InitialPlacement;
+            self.current_player = PlayerId(self.players.len().max(1) - 1);
+            self.pass_initial_placement();
+        }
+    }
+
+    // The current player drops one starting army on a territory of theirs
+    // and play passes to the next seat with armies left
+    pub fn place_initial_army(&mut self, territory: usize) -> bool {
+        if self.phase != Phase::InitialPlacement
+            || self.territories[territory].owner != Some(self.current_player)
+            || self.room_in(territory) == 0
+        {
+            return false;
+        }
+        self.territories[territory].armies += 1;
+        self.note_stack(territory);
+        self.initial_armies[self.current_player.0] -= 1;
+        self.board_dirty = true;
+        self.pass_initial_placement();
+        true
+    }
+
+    // Hands the placement to the next seat that still has armies to place
+    // and room to put them, starting the first turn proper once nobody does
+    pub fn pass_initial_placement(&mut self) {
+        let seats = self.players.len();
+        for step in 1..=seats {
+            let seat = PlayerId((self.current_player.0 + step) % seats);
+            let has_room = (0..self.territories.len())
+                .any(|i| self.territories[i].owner == Some(seat) && self.room_in(i) > 0);
+            if self
+                .initial_armies
+                .get(seat.0)
+                .is_some_and(|&left| left > 0)
+                && has_room
+            {
+                self.current_player = seat;
+                return;
+            }
+        }
+        // Whatever the army cap left over is given up
+        self.initial_armies.clear();
+        self.current_player = PlayerId(0);
+        self.phase = Phase::Reinforce;
+        self.log_event(GameEvent::PhaseStarted {
+            player: self.current_player,
+            phase: self.phase,
+        });
+        self.begin_reinforcements();
+    }
+
+    // How likely a spare army is to land on `territory`, relative to the
+    // owner's other territories
+    fn distribution_weight(&self, territory: usize, weighting: Weighting) -> u32 {
+        match weighting {
+            Weighting::Even => 1,
+            Weighting::Borders => {
+                let owner = self.territories[territory].owner;
+                let continent_of = |i: usize| {
+                    self.continents
+                        .iter()
+                        .position(|c| c.territories.contains(&i))
+                };
+                let mut enemies = self.territories[territory]
+                    .adjacent
+                    .iter()
+                    .filter(|&&n| self.territories[n].owner != owner)
+                    .peekable();
+                if enemies.peek().is_none() {
+                    1
+                } else if enemies.any(|&n| continent_of(n) != continent_of(territory)) {
+                    5
+                } else {
+                    3
+                }
+            }
+        }
+    }
+
+    // Quick-start setup: every player's territories get one army each, then
+    // the rest of that player's `per_player_total` lands on their territories
+    // at random, spread by `weighting`. Every player gets the same total
+    // whatever they hold, except that players with more territories than
+    // armies still get one per territory. Neutral territories keep their
+    // armies from the map.
+    pub fn distribute_armies(&mut self, per_player_total: i32, seed: u64, weighting: Weighting) {
+        let mut rng = Rng::new(seed);
+        self.board_dirty = true;
+        for player in 0..self.players.len() {
+            let owned: Vec<usize> = (0..self.territories.len())
+                .filter(|&i| self.territories[i].owner == Some(PlayerId(player)))
+                .collect();
+            if owned.is_empty() {
+                continue;
+            }
+            for &territory in &owned {
+                self.territories[territory].armies = 1;
+            }
+            let weights: Vec<u32> = owned
+                .iter()
+                .map(|&i| self.distribution_weight(i, weighting))
+                .collect();
+            let total_weight: u32 = weights.iter().sum();
+            let spare = per_player_total - owned.len() as i32;
+            for _ in 0..spare.max(0) {
+                let mut roll = rng.gen_range(0, total_weight);
+                let pick = weights
+                    .iter()
+                    .position(|&weight| {
+                        let hit = roll < weight;
+                        roll = roll.saturating_sub(weight);
+                        hit
+                    })
+                    .unwrap_or(0);
+                self.territories[owned[pick]].armies += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StartMode, Weighting};
+    use crate::game::hex;
+    use crate::game::player::PlayerId;
+    use crate::game::turn::Phase;
+    use crate::game::Game;
+    use crate::test_support::{game, territory};
+
+    fn board() -> Game {
+        game(
+            vec![
+                territory("A", 0, 9, vec![]),
+                territory("B", 0, 9, vec![]),
+                territory("C", 1, 9, vec![]),
+                territory("D", 1, 9, vec![]),
+                territory("E", 1, 9, vec![]),
+            ],
+            0,
+        )
+    }
+
+    fn armies_of(game: &Game, player: usize) -> Vec<i32> {
+        game.territories
+            .iter()
+            .filter(|t| t.owner == Some(PlayerId(player)))
+            .map(|t| t.armies)
+            .collect()
+    }
+
+    #[test]
+    fn each_player_gets_the_whole_pool_with_at_least_one_per_territory() {
+        let mut game = board();
+        game.distribute_armies(20, 5, Weighting::Even);
+        for player in 0..2 {
+            let armies = armies_of(&game, player);
+            assert_eq!(armies.iter().sum::<i32>(), 20);
+            assert!(armies.iter().all(|&a| a >= 1));
+        }
+
+        // A pool smaller than the territory count still leaves one on each
+        game.distribute_armies(2, 5, Weighting::Even);
+        assert_eq!(armies_of(&game, 1), vec![1, 1, 1]);
+    }
+
+    #[test]
+    fn random_start_gives_every_territory_exactly_one_owner() {
+        let mut game = board();
+        game.assign_starting_territories(StartMode::Random, 3);
+        assert!(game.territories.iter().all(|t| t.owner.is_some()));
+        assert!(game.territories.iter().all(|t| t.armies == 1));
+        // Dealt round-robin, so nobody is more than one territory ahead
+        assert_eq!(armies_of(&game, 0).len(), 3);
+        assert_eq!(armies_of(&game, 1).len(), 2);
+
+        let mut again = board();
+        again.assign_starting_territories(StartMode::Random, 3);
+        let owners = |game: &Game| game.territories.iter().map(|t| t.owner).collect::<Vec<_>>();
+        assert_eq!(owners(&game), owners(&again));
+    }
+
+    #[test]
+    fn drafting_alternates_picks_until_the_board_is_taken() {
+        let mut game = board();
+        game.assign_starting_territories(StartMode::Draft, 3);
+        assert_eq!(game.phase, Phase::Claim);
+        assert!(game.territories.iter().all(|t| t.owner.is_none()));
+        assert_eq!(game.winner(), None);
+
+        for pick in 0..5 {
+            assert_eq!(game.current_player, PlayerId(pick % 2));
+            assert!(game.claim_territory(pick));
+            assert!(!game.claim_territory(pick));
+        }
+        assert_eq!(game.phase, Phase::Reinforce);
+        assert_eq!(game.current_player, PlayerId(0));
+        assert_eq!(game.reinforcements_remaining, 3);
+        assert_eq!(armies_of(&game, 0), vec![1, 1, 1]);
+    }
+
+    #[test]
+    fn starting_armies_are_placed_one_at_a_time_in_turn() {
+        let mut game = board();
+        game.begin_initial_placement();
+        assert_eq!(game.phase, Phase::InitialPlacement);
+        assert_eq!(game.reinforcements_remaining, 0);
+        // 40 each for two players, less the territories already held
+        assert_eq!(game.initial_armies, [38, 37]);
+        assert_eq!(game.winner(), None);
+
+        assert!(!game.place_initial_army(2));
+        assert!(game.place_initial_army(0));
+        assert_eq!(game.current_player, PlayerId(1));
+        // The computer puts down one army and passes back
+        game.run_ai_turn(PlayerId(1));
+        assert_eq!(game.current_player, PlayerId(0));
+        assert_eq!(armies_of(&game, 1).iter().sum::<i32>(), 28);
+
+        while game.phase == Phase::InitialPlacement {
+            let seat = game.current_player.0;
+            let own = if seat == 0 { 1 } else { 4 };
+            assert!(game.place_initial_army(own));
+        }
+        assert_eq!(armies_of(&game, 0).iter().sum::<i32>(), 9 * 2 + 38);
+        assert_eq!(armies_of(&game, 1).iter().sum::<i32>(), 9 * 3 + 37);
+        assert_eq!(game.current_player, PlayerId(0));
+        assert_eq!(game.reinforcements_remaining, 3);
+        assert!(game.initial_armies.is_empty());
+    }
+
+    #[test]
+    fn a_draft_places_the_rest_once_everything_is_claimed() {
+        let mut game = board();
+        game.assign_starting_territories(StartMode::Draft, 3);
+        game.begin_initial_placement();
+        assert_eq!(game.phase, Phase::Claim);
+        for pick in 0..5 {
+            game.claim_territory(pick);
+        }
+        // Each claim used one of the 40
+        assert_eq!(game.phase, Phase::InitialPlacement);
+        assert_eq!(game.initial_armies, [37, 38]);
+        assert_eq!(game.current_player, PlayerId(0));
+    }
+
+    // A whole opening from one seed: deal, distribute, then a scripted turn
+    // of reinforcing and attacking everything in reach
+    fn scripted_game(seed: u64) -> String {
+        let map = hex::generate_hex_map(4, 3);
+        let mut game = Game::from_map(&map, Vec::new(), seed).unwrap();
+        game.assign_starting_territories(StartMode::Random, seed);
+        game.distribute_armies(20, seed, Weighting::Even);
+        while game.reinforcements_remaining > 0 {
+            let first_owned = (0..game.territories.len())
+                .find(|&i| game.territories[i].owner == Some(game.current_player))
+                .unwrap();
+            game.place_reinforcement(first_owned);
+        }
+        for from in 0..game.territories.len() {
+            for to in game.territories[from].adjacent.clone() {
+                if game.can_attack(from, to) {
+                    game.resolve_attack(from, to);
+                }
+            }
+        }
+        serde_json::to_string(&game.snapshot()).unwrap()
+    }
+
+    #[test]
+    fn same_seed_and_actions_give_the_same_game() {
+        assert_eq!(scripted_game(42), scripted_game(42));
+        assert_ne!(scripted_game(42), scripted_game(43));
+    }
+
+    #[test]
+    fn distribution_is_reproducible_from_the_seed() {
+        let mut first = board();
+        let mut second = board();
+        first.distribute_armies(30, 11, Weighting::Borders);
+        second.distribute_armies(30, 11, Weighting::Borders);
+        for player in 0..2 {
+            assert_eq!(armies_of(&first, player), armies_of(&second, player));
+        }
+    }
+
+    #[test]
+    fn weighted_distribution_keeps_the_budget_and_favours_borders() {
+        let mut game = game(
+            vec![
+                territory("Home", 0, 1, vec![1]),
+                territory("Front", 0, 1, vec![0, 2]),
+                territory("Enemy", 1, 1, vec![1]),
+            ],
+            0,
+        );
+        for weighting in [Weighting::Even, Weighting::Borders] {
+            game.distribute_armies(60, 3, weighting);
+            for player in 0..2 {
+                assert_eq!(armies_of(&game, player).iter().sum::<i32>(), 60);
+            }
+        }
+        // Three to one odds over 58 spare armies
+        let placed = armies_of(&game, 0);
+        assert!(placed[1] > 2 * placed[0], "{:?}", placed);
+    }
+}
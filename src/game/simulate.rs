@@ -0,0 +1,231 @@
+use crate::game::ai::AiDifficulty;
+use crate::game::map::{MapLoadError, TerritoryData};
+use crate::game::player::PlayerId;
+use crate::game::turn::Phase;
+use crate::game::Game;
+use std::fmt;
+
+// An all-in player keeps attacking while it can find odds better than this
+pub const ALL_IN_MIN_ODDS: f32 = 0.5;
+
+// How every seat plays in a simulated game
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Strategy {
+    // The computer player proper, at each seat's difficulty
+    Ai,
+    // `auto_attack_all`, much quicker than the AI for big batches
+    AllIn { min_odds: f32 },
+}
+
+// Totals over a batch of computer-only games
+#[derive(Debug, PartialEq)]
+pub struct GameOutcome {
+    // Games won, indexed by player seat
+    pub wins: Vec<usize>,
+    // Games still undecided when the turn limit ran out
+    pub unfinished: usize,
+    // Turns played across every game, to average over
+    pub total_turns: usize,
+}
+
+impl GameOutcome {
+    pub fn games(&self) -> usize {
+        self.wins.iter().sum::<usize>() + self.unfinished
+    }
+}
+
+impl fmt::Display for GameOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let games = self.games();
+        writeln!(f, "{} game(s) played", games)?;
+        for (seat, wins) in self.wins.iter().enumerate() {
+            writeln!(f, "  Player {}: {} win(s)", seat + 1, wins)?;
+        }
+        writeln!(f, "  Unfinished after the turn limit: {}", self.unfinished)?;
+        write!(
+            f,
+            "  Average length: {:.1} turns",
+            self.total_turns as f32 / games.max(1) as f32
+        )
+    }
+}
+
+impl Game {
+    // A scripted turn for `player` that only attacks: trade any set and
+    // spread the reinforcements, then blitz the surest attack again and
+    // again while one beats `min_odds`, and pass without fortifying. Ties go
+    // to the lowest indices, so a seed always plays out the same way.
+    pub fn auto_attack_all(&mut self, player: PlayerId, min_odds: f32) {
+        if self.current_player != player {
+            return;
+        }
+        if self.phase == Phase::Reinforce {
+            while let Some(set) = self.find_set(player) {
+                if self.trade_cards(player, set).is_err() {
+                    break;
+                }
+            }
+            self.auto_reinforce(player);
+            // Anything left over had nowhere under the army cap to go
+            self.forfeit_unplaceable();
+        }
+        while self.phase == Phase::Attack {
+            let Some((from, to)) = self.surest_attack(player, min_odds) else {
+                break;
+            };
+            let summary = self.blitz_attack(from, to);
+            // Rules where nobody can lose would have it attack forever
+            if summary.attacker_losses + summary.defender_losses == 0 {
+                break;
+            }
+        }
+        while self.current_player == player && self.phase != Phase::Reinforce {
+            self.next_phase();
+        }
+    }
+
+    fn surest_attack(&self, player: PlayerId, min_odds: f32) -> Option<(usize, usize)> {
+        (0..self.territories.len())
+            .filter(|&from| self.territories[from].owner == Some(player))
+            .flat_map(|from| {
+                self.valid_attack_targets(from)
+                    .into_iter()
+                    .map(move |to| (from, to))
+            })
+            .map(|(from, to)| {
+                let odds = self.conquest_odds(from, to);
+                (from, to, odds)
+            })
+            .filter(|&(_, _, odds)| odds > min_odds)
+            .max_by_key(|&(from, to, odds)| ((odds * 1000.0) as i32, std::cmp::Reverse((from, to))))
+            .map(|(from, to, _)| (from, to))
+    }
+}
+
+// Plays one computer-only game per seed on `map` with no window, giving
+// each game up to `max_turns` player turns. With the AI strategy seats play
+// at `difficulties`, by index, and Normal past the end of it.
+pub fn simulate(
+    map: &[TerritoryData],
+    seeds: &[u64],
+    max_turns: usize,
+    difficulties: &[AiDifficulty],
+    strategy: Strategy,
+) -> Result<GameOutcome, MapLoadError> {
+    let mut outcome = GameOutcome {
+        wins: Vec::new(),
+        unfinished: 0,
+        total_turns: 0,
+    };
+    for &seed in seeds {
+        let mut game = Game::from_map(map, Vec::new(), seed)?;
+        outcome.wins.resize(game.players.len(), 0);
+        for (player, &difficulty) in game.players.iter_mut().zip(difficulties) {
+            player.difficulty = difficulty;
+        }
+
+        let mut turns = 0;
+        while game.winner().is_none() && turns < max_turns {
+            match strategy {
+                Strategy::Ai => game.run_ai_turn(game.current_player),
+                Strategy::AllIn { min_odds } => game.auto_attack_all(game.current_player, min_odds),
+            }
+            turns += 1;
+        }
+        outcome.total_turns += turns;
+        match game.winner() {
+            Some(winner) => outcome.wins[winner.0] += 1,
+            None => outcome.unfinished += 1,
+        }
+    }
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::hex;
+    use crate::test_support::{game, territory};
+
+    fn hex_map() -> Vec<TerritoryData> {
+        let mut map = hex::generate_hex_map(4, 3);
+        hex::deal_round_robin(&mut map, 2);
+        map
+    }
+
+    #[test]
+    fn every_game_is_counted_once() {
+        let outcome = simulate(&hex_map(), &[1, 2, 3, 4], 200, &[], Strategy::Ai).unwrap();
+        assert_eq!(outcome.games(), 4);
+        assert_eq!(outcome.wins.len(), 2);
+        assert!(outcome.total_turns > 0);
+    }
+
+    #[test]
+    fn simulations_are_reproducible() {
+        let seeds = [7, 8, 9];
+        assert_eq!(
+            simulate(&hex_map(), &seeds, 200, &[], Strategy::Ai).unwrap(),
+            simulate(&hex_map(), &seeds, 200, &[], Strategy::Ai).unwrap()
+        );
+    }
+
+    #[test]
+    fn hard_beats_easy_most_of_the_time() {
+        let seeds: Vec<u64> = (0..40).collect();
+        // Alternate seats so going first isn't what decides it
+        let first = simulate(
+            &hex_map(),
+            &seeds,
+            300,
+            &[AiDifficulty::Hard, AiDifficulty::Easy],
+            Strategy::Ai,
+        )
+        .unwrap();
+        let second = simulate(
+            &hex_map(),
+            &seeds,
+            300,
+            &[AiDifficulty::Easy, AiDifficulty::Hard],
+            Strategy::Ai,
+        )
+        .unwrap();
+        let hard_wins = first.wins[0] + second.wins[1];
+        assert!(hard_wins > seeds.len(), "Hard won {} of 80", hard_wins);
+    }
+
+    #[test]
+    fn all_in_takes_only_good_odds_and_passes() {
+        let mut game = game(
+            vec![
+                territory("Home", 0, 10, vec![1, 2]),
+                territory("Weak", 1, 1, vec![0, 2]),
+                territory("Strong", 1, 30, vec![0, 1]),
+            ],
+            5,
+        );
+        game.auto_attack_all(PlayerId(0), ALL_IN_MIN_ODDS);
+        assert_eq!(game.territories[1].owner, Some(PlayerId(0)));
+        assert_eq!(game.territories[2].armies, 30);
+        assert_eq!(game.current_player, PlayerId(1));
+        assert_eq!(game.phase, Phase::Reinforce);
+
+        let strategy = Strategy::AllIn {
+            min_odds: ALL_IN_MIN_ODDS,
+        };
+        let seeds = [3, 4, 5];
+        let outcome = simulate(&hex_map(), &seeds, 50, &[], strategy).unwrap();
+        assert_eq!(outcome.games(), 3);
+        assert_eq!(
+            outcome,
+            simulate(&hex_map(), &seeds, 50, &[], strategy).unwrap()
+        );
+    }
+
+    #[test]
+    fn turn_limit_leaves_games_unfinished() {
+        let outcome = simulate(&hex_map(), &[1, 2], 1, &[], Strategy::Ai).unwrap();
+        assert_eq!(outcome.unfinished, 2);
+        assert_eq!(outcome.total_turns, 2);
+    }
+}
@@ -0,0 +1,192 @@
+use crate::game::combat::CombatResult;
+use crate::game::player::PlayerId;
+use crate::game::Game;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// One player's totals over the game so far
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlayerStatistics {
+    // Filled in when the summary is taken, so saves don't repeat it
+    #[serde(default)]
+    pub name: String,
+    // Rounds of dice thrown as the attacker
+    pub attacks: usize,
+    // Attacking and defending
+    pub dice_rolled: usize,
+    pub territories_conquered: usize,
+    pub armies_lost: i32,
+    pub armies_destroyed: i32,
+    pub card_sets_traded: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ArmyStack {
+    pub territory: String,
+    pub owner: Option<PlayerId>,
+    pub armies: i32,
+}
+
+// Counters kept as the game is played, for balance analysis alongside the
+// headless simulations
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameStatistics {
+    // Player turns finished, by anyone
+    pub turns_played: usize,
+    // By seat
+    pub players: Vec<PlayerStatistics>,
+    // The most armies ever seen on one territory
+    pub largest_stack: Option<ArmyStack>,
+}
+
+impl GameStatistics {
+    fn player(&mut self, player: PlayerId) -> &mut PlayerStatistics {
+        if self.players.len() <= player.0 {
+            self.players
+                .resize_with(player.0 + 1, PlayerStatistics::default);
+        }
+        &mut self.players[player.0]
+    }
+}
+
+impl fmt::Display for GameStatistics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} turn(s) played", self.turns_played)?;
+        for player in &self.players {
+            write!(
+                f,
+                "\n  {}: {} attack(s), {} dice, {} conquered, {} lost, {} destroyed, {} set(s) traded",
+                player.name,
+                player.attacks,
+                player.dice_rolled,
+                player.territories_conquered,
+                player.armies_lost,
+                player.armies_destroyed,
+                player.card_sets_traded
+            )?;
+        }
+        if let Some(stack) = &self.largest_stack {
+            write!(
+                f,
+                "\n  Largest stack: {} armies on {}",
+                stack.armies, stack.territory
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Game {
+    // The totals so far with each player's name, and the board as it is now
+    // counted towards the largest stack
+    pub fn statistics(&self) -> GameStatistics {
+        let mut statistics = self.statistics.clone();
+        statistics
+            .players
+            .resize_with(self.players.len(), PlayerStatistics::default);
+        for (totals, player) in statistics.players.iter_mut().zip(&self.players) {
+            totals.name = player.name.clone();
+        }
+        for territory in 0..self.territories.len() {
+            note_stack(&mut statistics, self, territory);
+        }
+        statistics
+    }
+
+    // Call before the casualties land, while the defender still owns its
+    // territory
+    pub fn record_combat(&mut self, attacker: usize, defender: usize, result: &CombatResult) {
+        self.note_stack(attacker);
+        if let Some(owner) = self.territories[attacker].owner {
+            let totals = self.statistics.player(owner);
+            totals.attacks += 1;
+            totals.dice_rolled += result.attacker_dice.len();
+            totals.armies_lost += result.attacker_losses;
+            totals.armies_destroyed += result.defender_losses;
+            totals.territories_conquered += result.conquered as usize;
+        }
+        if let Some(owner) = self.territories[defender].owner {
+            let totals = self.statistics.player(owner);
+            totals.dice_rolled += result.defender_dice.len();
+            totals.armies_lost += result.defender_losses;
+            totals.armies_destroyed += result.attacker_losses;
+        }
+    }
+
+    pub fn record_trade(&mut self, player: PlayerId) {
+        self.statistics.player(player).card_sets_traded += 1;
+    }
+
+    // Call whenever a territory gains armies
+    pub fn note_stack(&mut self, territory: usize) {
+        let mut statistics = std::mem::take(&mut self.statistics);
+        note_stack(&mut statistics, self, territory);
+        self.statistics = statistics;
+    }
+}
+
+fn note_stack(statistics: &mut GameStatistics, game: &Game, territory: usize) {
+    let t = &game.territories[territory];
+    if statistics
+        .largest_stack
+        .as_ref()
+        .is_some_and(|stack| stack.armies >= t.armies)
+    {
+        return;
+    }
+    statistics.largest_stack = Some(ArmyStack {
+        territory: t.name.clone(),
+        owner: t.owner,
+        armies: t.armies,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::player::PlayerId;
+    use crate::game::turn::Phase;
+    use crate::test_support::{game, territory};
+
+    #[test]
+    fn combat_trades_and_turns_are_counted() {
+        let mut game = game(
+            vec![
+                territory("A", 0, 30, vec![1]),
+                territory("B", 1, 1, vec![0]),
+            ],
+            4,
+        );
+        game.reinforcements_remaining = 0;
+        game.next_phase();
+        assert_eq!(game.phase, Phase::Attack);
+        let summary = game.blitz_attack(0, 1);
+        assert!(summary.conquered);
+        game.next_phase();
+        game.next_phase();
+
+        let statistics = game.statistics();
+        assert_eq!(statistics.turns_played, 1);
+        let (attacker, defender) = (&statistics.players[0], &statistics.players[1]);
+        assert_eq!(attacker.name, "Player 1");
+        assert_eq!(attacker.attacks, summary.rounds);
+        assert_eq!(attacker.dice_rolled, summary.attacker_dice_rolled);
+        assert_eq!(defender.dice_rolled, summary.defender_dice_rolled);
+        assert_eq!(attacker.territories_conquered, 1);
+        assert_eq!(attacker.armies_destroyed, 1);
+        assert_eq!(defender.armies_lost, 1);
+        assert_eq!(attacker.armies_lost, defender.armies_destroyed);
+        let stack = statistics.largest_stack.as_ref().unwrap();
+        assert_eq!((stack.territory.as_str(), stack.armies), ("A", 30));
+
+        // Shrinking the stack doesn't lose the record
+        game.territories[0].armies = 2;
+        assert_eq!(game.statistics().largest_stack.unwrap().armies, 30);
+
+        game.record_trade(PlayerId(1));
+        let text = game.statistics().to_string();
+        assert!(text.starts_with("1 turn(s) played"));
+        assert!(text.contains("Player 2: 0 attack(s)"));
+        assert!(text.contains("1 set(s) traded"));
+    }
+}
@@ -0,0 +1,206 @@
+use crate::game::map::{MapLoadError, MapOwner, MapSource, TerritoryData};
+use crate::game::player::PlayerId;
+use crate::game::territory::Terrain;
+use std::fs;
+use std::path::PathBuf;
+
+// Imports territory outlines drawn in a vector editor. Every `<polygon>` and
+// `<path>` element becomes a territory named after its `id`. Paths may only
+// use straight-line commands (M/L/H/V/Z, absolute or relative); curves are
+// rejected since territories are plain polygons.
+pub struct SvgMapSource {
+    pub path: PathBuf,
+}
+
+impl MapSource for SvgMapSource {
+    fn load(&self) -> Result<Vec<TerritoryData>, MapLoadError> {
+        let path = &self.path;
+        let svg = fs::read_to_string(path).map_err(|source| MapLoadError::Io {
+            path: path.clone(),
+            source,
+        })?;
+        parse_svg(&svg).map_err(|message| MapLoadError::Svg {
+            path: path.clone(),
+            message,
+        })
+    }
+}
+
+fn parse_svg(svg: &str) -> Result<Vec<TerritoryData>, String> {
+    let mut territories = Vec::new();
+    let mut rest = svg;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let end = rest
+            .find('>')
+            .ok_or_else(|| "Unterminated tag".to_string())?;
+        let tag = &rest[..end];
+        rest = &rest[end + 1..];
+
+        let element = tag.split_whitespace().next().unwrap_or_default();
+        let vertices = match element {
+            "polygon" => parse_points(attribute(tag, "points").unwrap_or_default())?,
+            "path" => parse_path(attribute(tag, "d").unwrap_or_default())?,
+            _ => continue,
+        };
+        let name = attribute(tag, "id")
+            .ok_or_else(|| format!("A <{}> element has no id to name it by", element))?;
+        territories.push(TerritoryData {
+            id: None,
+            name: name.to_string(),
+            vertices,
+            owner: Some(MapOwner::Seat(PlayerId(0))),
+            armies: 1,
+            selected: false,
+            adjacent: Vec::new(),
+            sea_routes: Vec::new(),
+            rivers: Vec::new(),
+            capital: false,
+            terrain: Terrain::Plains,
+            continent: None,
+            bonus: 0,
+            capital_of: None,
+        });
+    }
+    Ok(territories)
+}
+
+// Value of `name="..."` (or single-quoted) within a tag's text
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut search = tag;
+    while let Some(i) = search.find(name) {
+        let preceded_by_space = search[..i]
+            .chars()
+            .next_back()
+            .is_none_or(char::is_whitespace);
+        let after = search[i + name.len()..].trim_start();
+        if preceded_by_space {
+            if let Some(value) = after.strip_prefix('=') {
+                let value = value.trim_start();
+                let quote = value.chars().next()?;
+                if quote == '"' || quote == '\'' {
+                    let value = &value[1..];
+                    return value.find(quote).map(|end| &value[..end]);
+                }
+            }
+        }
+        search = &search[i + name.len()..];
+    }
+    None
+}
+
+fn numbers(text: &str) -> Result<Vec<f32>, String> {
+    text.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            token
+                .parse()
+                .map_err(|_| format!("'{}' is not a number", token))
+        })
+        .collect()
+}
+
+fn parse_points(points: &str) -> Result<Vec<[f32; 2]>, String> {
+    let values = numbers(points)?;
+    if values.len() % 2 != 0 {
+        return Err("Polygon has an odd number of coordinates".to_string());
+    }
+    Ok(values.chunks(2).map(|pair| [pair[0], pair[1]]).collect())
+}
+
+fn parse_path(d: &str) -> Result<Vec<[f32; 2]>, String> {
+    // Split into (command, arguments) runs
+    let mut commands: Vec<(char, String)> = Vec::new();
+    for c in d.chars() {
+        if c.is_ascii_alphabetic() && c != 'e' && c != 'E' {
+            commands.push((c, String::new()));
+        } else if let Some((_, args)) = commands.last_mut() {
+            // A '-' starts a new number even without a separator
+            if c == '-' && !args.ends_with(['e', 'E']) {
+                args.push(' ');
+            }
+            args.push(c);
+        } else if !c.is_whitespace() {
+            return Err("Path data must start with a command".to_string());
+        }
+    }
+
+    let mut vertices: Vec<[f32; 2]> = Vec::new();
+    let mut cursor = [0.0f32, 0.0];
+    let mut closed = false;
+    for (command, args) in commands {
+        if closed {
+            return Err("Paths with more than one subpath are not supported".to_string());
+        }
+        let values = numbers(&args)?;
+        let relative = command.is_ascii_lowercase();
+        let origin = |cursor: [f32; 2]| if relative { cursor } else { [0.0, 0.0] };
+        match command.to_ascii_uppercase() {
+            // Extra coordinate pairs after a move are implicit line-tos
+            'M' | 'L' => {
+                if values.len() % 2 != 0 {
+                    return Err(format!("'{}' needs coordinate pairs", command));
+                }
+                if command.eq_ignore_ascii_case(&'M') && !vertices.is_empty() {
+                    return Err("Paths with more than one subpath are not supported".to_string());
+                }
+                for pair in values.chunks(2) {
+                    let base = origin(cursor);
+                    cursor = [base[0] + pair[0], base[1] + pair[1]];
+                    vertices.push(cursor);
+                }
+            }
+            'H' => {
+                for x in values {
+                    cursor[0] = origin(cursor)[0] + x;
+                    vertices.push(cursor);
+                }
+            }
+            'V' => {
+                for y in values {
+                    cursor[1] = origin(cursor)[1] + y;
+                    vertices.push(cursor);
+                }
+            }
+            'Z' => closed = true,
+            _ => {
+                return Err(format!(
+                    "Path command '{}' (curves and arcs) is not supported yet",
+                    command
+                ))
+            }
+        }
+    }
+
+    // An explicit return to the start duplicates the first vertex
+    if vertices.len() > 1 && vertices.first() == vertices.last() {
+        vertices.pop();
+    }
+    Ok(vertices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_svg;
+    use crate::game::transform::MapTransform;
+
+    #[test]
+    fn the_top_of_the_drawing_is_the_top_of_the_board() {
+        let mut data = parse_svg(
+            r#"<svg><polygon id="North" points="0,0 10,0 10,10 0,10"/>
+                    <path id="South" d="M 0 20 H 10 V 30 H 0 Z"/></svg>"#,
+        )
+        .unwrap();
+        MapTransform::default().apply_to(&mut data);
+        let top = |i: usize| {
+            data[i]
+                .vertices
+                .iter()
+                .map(|v| v[1])
+                .fold(f32::MAX, f32::min)
+        };
+        // World y grows downwards, so the smaller y is drawn higher up
+        assert!(top(0) < top(1));
+        assert_eq!(data[1].vertices[0], [0.0, 20.0]);
+    }
+}
@@ -0,0 +1,626 @@
+use crate::game::geometry;
+use crate::game::player::PlayerId;
+use macroquad::math::Vec2;
+use serde::{Deserialize, Serialize};
+
+// Vertices closer together than this (in world units) are the same point
+const DUPLICATE_EPSILON: f32 = 1e-4;
+
+// Times `simplified` may halve its tolerance looking for an outline that
+// doesn't cross itself
+pub const SIMPLIFY_ATTEMPTS: u32 = 4;
+
+// How hit testing decides what's inside an outline. They only disagree where
+// an outline crosses over itself, so that part of it is covered twice: a
+// five-pointed star drawn in one stroke has a centre that is outside by
+// even-odd but inside by non-zero winding. Outlines that merely touch
+// themselves at a vertex come out the same either way.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FillRule {
+    #[default]
+    EvenOdd,
+    NonZero,
+}
+
+// Ground that helps whoever defends it. Plains, the default, change nothing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Terrain {
+    #[default]
+    Plains,
+    // The defender's highest die counts one higher
+    Mountain,
+    // Attackers wade across and roll one die fewer, though always at least one
+    River,
+}
+
+impl Terrain {
+    pub fn defender_bonus(self) -> u8 {
+        match self {
+            Terrain::Mountain => 1,
+            Terrain::Plains | Terrain::River => 0,
+        }
+    }
+
+    pub fn attacker_dice_penalty(self) -> i32 {
+        match self {
+            Terrain::River => 1,
+            Terrain::Plains | Terrain::Mountain => 0,
+        }
+    }
+}
+
+// Struct representing a territory
+pub struct Territory {
+    // Stable across renames and reordering; see `map::territory_ids`
+    pub id: u32,
+    pub name: String,
+    pub vertices: Vec<Vec2>,
+    // None for a neutral territory
+    pub owner: Option<PlayerId>,
+    pub armies: i32,
+    pub selected: bool,
+    pub adjacent: Vec<usize>,
+    // The subset of `adjacent` reached over water rather than a border
+    pub sea_routes: Vec<usize>,
+    // Neighbours across a river border
+    pub rivers: Vec<usize>,
+    // Capitals give their defender extra dice
+    pub is_capital: bool,
+    pub terrain: Terrain,
+    // Axis-aligned bounding box, used to skip most polygon tests
+    pub min: Vec2,
+    pub max: Vec2,
+    // Derived from `vertices` once, since the outline never changes after
+    // loading and triangulating it every frame is the costliest part of drawing
+    pub triangles: Vec<[usize; 3]>,
+    centroid: Vec2,
+}
+
+impl Territory {
+    pub fn new(
+        name: String,
+        vertices: Vec<Vec2>,
+        owner: Option<PlayerId>,
+        armies: i32,
+        adjacent: Vec<usize>,
+    ) -> Territory {
+        let mut territory = Territory {
+            id: 0,
+            name,
+            vertices: Vec::new(),
+            owner,
+            armies,
+            selected: false,
+            adjacent,
+            sea_routes: Vec::new(),
+            rivers: Vec::new(),
+            is_capital: false,
+            terrain: Terrain::Plains,
+            min: Vec2::ZERO,
+            max: Vec2::ZERO,
+            triangles: Vec::new(),
+            centroid: Vec2::ZERO,
+        };
+        territory.set_outline(vertices);
+        territory
+    }
+
+    // Replaces the outline and everything worked out from it. Repeated
+    // vertices are dropped first, as zero-length edges trip up
+    // triangulation and the crossing checks.
+    pub fn set_outline(&mut self, vertices: Vec<Vec2>) {
+        let mut vertices = geometry::remove_duplicate_vertices(&vertices, DUPLICATE_EPSILON);
+        geometry::normalize_winding(&mut vertices);
+        (self.min, self.max) = vertices.iter().fold(
+            (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+            |(min, max), &v| (min.min(v), max.max(v)),
+        );
+        self.triangles = geometry::triangulate(&vertices);
+        self.centroid = geometry::label_point(&vertices);
+        self.vertices = vertices;
+    }
+
+    // An over-detailed outline (as traced maps often are) thinned down to
+    // the vertices that stray more than `tolerance` from a straight line,
+    // keeping every `pinned` one. A result that would cross itself is
+    // retried at finer tolerances, up to `attempts` tries in all; None if
+    // none works. See `map::simplify_territories` for a whole map.
+    pub fn simplified(&self, tolerance: f32, pinned: &[bool], attempts: u32) -> Option<Vec<Vec2>> {
+        let mut tolerance = tolerance;
+        for _ in 0..attempts {
+            let simplified = geometry::simplify_outline(&self.vertices, pinned, tolerance);
+            if simplified.len() >= 3 && geometry::self_intersection(&simplified).is_none() {
+                return Some(simplified);
+            }
+            tolerance /= 2.0;
+        }
+        None
+    }
+
+    // Positive for every built territory, since `new` normalizes the winding
+    pub fn signed_area(&self) -> f32 {
+        geometry::signed_area(&self.vertices)
+    }
+
+    // A polygon is simple when no two of its edges cross or touch except
+    // neighbouring edges at their shared vertex
+    pub fn is_simple(&self) -> bool {
+        self.self_intersection().is_none()
+    }
+
+    pub fn self_intersection(&self) -> Option<(usize, usize)> {
+        geometry::self_intersection(&self.vertices)
+    }
+
+    // Cheap bounding-box rejection before the full polygon test
+    pub fn contains_in_bounds(&self, point: Vec2, rule: FillRule) -> bool {
+        point.cmpge(self.min).all() && point.cmple(self.max).all() && self.contains(point, rule)
+    }
+
+    pub fn contains(&self, point: Vec2, rule: FillRule) -> bool {
+        match rule {
+            FillRule::EvenOdd => self.is_point_inside(point),
+            FillRule::NonZero => self.contains_winding(point),
+        }
+    }
+
+    // Even-odd ray casting towards +x. Points exactly on the outline follow
+    // the usual fill rule: top and left edges (in screen space, y down) are
+    // inside, bottom and right edges are not, so a point on a border shared
+    // by two territories belongs to exactly one of them.
+    pub fn is_point_inside(&self, point: Vec2) -> bool {
+        // Fewer than 3 vertices encloses no area
+        let Some(&last) = self.vertices.last() else {
+            return false;
+        };
+        if self.vertices.len() < 3 {
+            return false;
+        }
+
+        // Each vertex paired with the one before it, the first with the last
+        let mut previous = last;
+        let mut is_inside = false;
+        for &current in &self.vertices {
+            // Each edge is tested from its upper end so that a border shared
+            // with a neighbour (walked the other way round) rounds the same
+            let (a, b) = if current.y <= previous.y {
+                (current, previous)
+            } else {
+                (previous, current)
+            };
+            previous = current;
+
+            // Half-open in y: an edge covers its top end but not its bottom,
+            // which also skips horizontal and zero-length edges entirely.
+            // Nothing is divided by the edge's height, so a flat edge can't
+            // blow up the test.
+            if a.y <= point.y && point.y < b.y && (b - a).perp_dot(point - a) > 0.0 {
+                is_inside = !is_inside;
+            }
+        }
+        is_inside
+    }
+
+    // Non-zero winding: counts how many times the outline goes round the
+    // point, adding upward crossings and subtracting downward ones. Edges are
+    // half-open in y exactly as in `is_point_inside`, so on simple outlines
+    // the two always agree, boundary points included.
+    pub fn contains_winding(&self, point: Vec2) -> bool {
+        let n = self.vertices.len();
+        if n < 3 {
+            return false;
+        }
+
+        let mut winding = 0;
+        for i in 0..n {
+            let (a, b) = (self.vertices[i], self.vertices[(i + 1) % n]);
+            if a.y <= point.y && point.y < b.y && (b - a).perp_dot(point - a) > 0.0 {
+                winding += 1;
+            } else if b.y <= point.y && point.y < a.y && (a - b).perp_dot(point - b) > 0.0 {
+                winding -= 1;
+            }
+        }
+        winding != 0
+    }
+
+    pub fn centroid(&self) -> Vec2 {
+        self.centroid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FillRule, SIMPLIFY_ATTEMPTS};
+    use crate::game::rng::Rng;
+    use crate::game::{geometry, validate, Territory};
+    use crate::test_support::{square, territory_with_vertices};
+    use macroquad::math::*;
+
+    #[test]
+    fn simplifying_rebuilds_the_outline_or_gives_up() {
+        // A square with a redundant midpoint on every side
+        let mut territory = territory_with_vertices(
+            [
+                (0.0, 0.0),
+                (5.0, 0.0),
+                (10.0, 0.0),
+                (10.0, 5.0),
+                (10.0, 10.0),
+                (5.0, 10.0),
+                (0.0, 10.0),
+                (0.0, 5.0),
+            ]
+            .iter()
+            .map(|&(x, y)| vec2(x, y))
+            .collect(),
+        );
+        let simplified = territory.simplified(0.5, &[], SIMPLIFY_ATTEMPTS).unwrap();
+        territory.set_outline(simplified);
+        assert_eq!(territory.vertices.len(), 4);
+        assert_eq!(territory.triangles.len(), 2);
+        assert_eq!(
+            (territory.min, territory.max),
+            (vec2(0.0, 0.0), vec2(10.0, 10.0))
+        );
+
+        // A sliver would collapse to a line at any of the tolerances tried
+        let sliver: Vec<Vec2> = (0..=10)
+            .map(|x| vec2(x as f32, 0.0))
+            .chain((0..=10).rev().map(|x| vec2(x as f32, 0.01)))
+            .collect();
+        let territory = territory_with_vertices(sliver);
+        assert_eq!(territory.simplified(1.0, &[], SIMPLIFY_ATTEMPTS), None);
+    }
+
+    #[test]
+    fn duplicate_vertices_are_cleaned_up_and_harmless() {
+        // Every corner doubled, a flat run along the top with a repeat in
+        // it, and the ring closed with a copy of its first vertex
+        let raw: Vec<Vec2> = [
+            (0.0, 0.0),
+            (0.0, 0.0),
+            (5.0, 0.0),
+            (5.0, 0.0),
+            (10.0, 0.0),
+            (10.0, 10.0),
+            (10.0, 10.0),
+            (0.0, 10.0),
+            (0.0, 0.0),
+        ]
+        .iter()
+        .map(|&(x, y)| vec2(x, y))
+        .collect();
+        let mut territory = territory_with_vertices(raw.clone());
+        assert_eq!(territory.vertices.len(), 5);
+        assert!(territory.is_simple());
+        assert_eq!(territory.triangles.len(), 3);
+
+        // Hit testing gives the same answers with the duplicates left in
+        for outline in [territory.vertices.clone(), raw] {
+            territory.vertices = outline;
+            for (point, inside) in [
+                (vec2(5.0, 5.0), true),
+                (vec2(5.0, 0.0), true),
+                (vec2(0.0, 5.0), true),
+                (vec2(10.0, 5.0), false),
+                (vec2(5.0, 10.0), false),
+                (vec2(-1.0, 0.0), false),
+                (vec2(11.0, 10.0), false),
+            ] {
+                assert_eq!(territory.is_point_inside(point), inside, "{}", point);
+                assert_eq!(territory.contains_winding(point), inside, "{}", point);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_polygon_contains_nothing() {
+        let territory = territory_with_vertices(Vec::new());
+        assert!(!territory.is_point_inside(vec2(0.0, 0.0)));
+    }
+
+    #[test]
+    fn two_vertex_line_contains_nothing() {
+        let territory = territory_with_vertices(vec![vec2(0.0, 0.0), vec2(10.0, 10.0)]);
+        assert!(!territory.is_point_inside(vec2(5.0, 5.0)));
+        assert!(!territory.is_point_inside(vec2(0.0, 0.0)));
+    }
+
+    #[test]
+    fn triangle_contains_interior_points_only() {
+        let territory =
+            territory_with_vertices(vec![vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(0.0, 10.0)]);
+        assert!(territory.is_point_inside(vec2(2.0, 2.0)));
+        assert!(!territory.is_point_inside(vec2(8.0, 8.0)));
+        assert!(!territory.is_point_inside(vec2(-1.0, 5.0)));
+    }
+
+    // Every point on an outline shared by a set of tiles must land in exactly
+    // one of them
+    fn assert_covered_once(tiles: &[Territory], point: Vec2) {
+        let hits = tiles.iter().filter(|t| t.is_point_inside(point)).count();
+        assert_eq!(hits, 1, "{:?} is inside {} territories", point, hits);
+    }
+
+    #[test]
+    fn bowtie_is_not_simple() {
+        let bowtie = territory_with_vertices(vec![
+            vec2(0.0, 0.0),
+            vec2(10.0, 10.0),
+            vec2(10.0, 0.0),
+            vec2(0.0, 10.0),
+        ]);
+        assert!(!bowtie.is_simple());
+        // The two diagonals, edges 0 and 2, cross in the middle
+        assert_eq!(bowtie.self_intersection(), Some((0, 2)));
+
+        let errors = validate::validate_outlines(&[bowtie]).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![validate::MapValidationError::SelfIntersecting {
+                territory: "Test".to_string(),
+                first_edge: 0,
+                second_edge: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn convex_and_concave_outlines_are_simple() {
+        assert!(territory_with_vertices(square(0.0, 10.0)).is_simple());
+        let arrow = territory_with_vertices(vec![
+            vec2(0.0, 0.0),
+            vec2(10.0, 5.0),
+            vec2(0.0, 10.0),
+            vec2(4.0, 5.0),
+        ]);
+        assert!(arrow.is_simple());
+        // An outline that doubles back to touch one of its own vertices
+        let pinched = territory_with_vertices(vec![
+            vec2(0.0, 0.0),
+            vec2(10.0, 0.0),
+            vec2(5.0, 5.0),
+            vec2(10.0, 10.0),
+            vec2(0.0, 10.0),
+            vec2(5.0, 5.0),
+        ]);
+        assert!(!pinched.is_simple());
+    }
+
+    #[test]
+    fn winding_and_even_odd_differ_only_on_overlaps() {
+        let points: Vec<Vec2> = (0..=20)
+            .flat_map(|x| (0..=20).map(move |y| vec2(x as f32 * 0.5, y as f32 * 0.5)))
+            .collect();
+        let agree = |territory: &Territory| {
+            points
+                .iter()
+                .all(|&p| territory.is_point_inside(p) == territory.contains_winding(p))
+        };
+
+        // Simple outlines, either way round, and one pinched at a vertex
+        let mut reversed = square(0.0, 10.0);
+        reversed.reverse();
+        let pinched = vec![
+            vec2(0.0, 0.0),
+            vec2(10.0, 0.0),
+            vec2(5.0, 5.0),
+            vec2(10.0, 10.0),
+            vec2(0.0, 10.0),
+            vec2(5.0, 5.0),
+        ];
+        for vertices in [square(0.0, 10.0), reversed, pinched] {
+            assert!(agree(&territory_with_vertices(vertices)));
+        }
+
+        // A star in one stroke covers its centre twice
+        let star = territory_with_vertices(
+            [0, 2, 4, 1, 3]
+                .iter()
+                .map(|&k| {
+                    let angle = k as f32 / 5.0 * std::f32::consts::TAU;
+                    vec2(5.0, 5.0) + vec2(angle.sin(), -angle.cos()) * 5.0
+                })
+                .collect(),
+        );
+        let centre = vec2(5.0, 5.0);
+        assert!(!star.is_point_inside(centre));
+        assert!(star.contains_winding(centre));
+        assert!(star.contains(centre, FillRule::NonZero));
+        // Out in a point of the star both say inside
+        let tip = vec2(5.0, 1.5);
+        assert!(star.is_point_inside(tip) && star.contains_winding(tip));
+    }
+
+    #[test]
+    fn top_and_left_edges_are_inside() {
+        let territory = territory_with_vertices(square(0.0, 10.0));
+        // Vertices: only the top-left corner belongs to the square
+        assert!(territory.is_point_inside(vec2(0.0, 0.0)));
+        assert!(!territory.is_point_inside(vec2(10.0, 0.0)));
+        assert!(!territory.is_point_inside(vec2(10.0, 10.0)));
+        assert!(!territory.is_point_inside(vec2(0.0, 10.0)));
+        // Horizontal edges: top in, bottom out
+        assert!(territory.is_point_inside(vec2(5.0, 0.0)));
+        assert!(!territory.is_point_inside(vec2(5.0, 10.0)));
+        // Vertical edges: left in, right out
+        assert!(territory.is_point_inside(vec2(0.0, 5.0)));
+        assert!(!territory.is_point_inside(vec2(10.0, 5.0)));
+    }
+
+    #[test]
+    fn shared_borders_belong_to_exactly_one_territory() {
+        // A 3x3 grid of squares: every edge and corner point is shared
+        let tiles: Vec<Territory> = (0..3)
+            .flat_map(|row| (0..3).map(move |col| (row, col)))
+            .map(|(row, col)| {
+                let origin = vec2(col as f32, row as f32) * 10.0;
+                territory_with_vertices(vec![
+                    origin,
+                    origin + vec2(10.0, 0.0),
+                    origin + vec2(10.0, 10.0),
+                    origin + vec2(0.0, 10.0),
+                ])
+            })
+            .collect();
+        for y in 1..6 {
+            for x in 1..6 {
+                assert_covered_once(&tiles, vec2(x as f32, y as f32) * 5.0);
+            }
+        }
+    }
+
+    #[test]
+    fn diagonal_borders_belong_to_exactly_one_territory() {
+        // Two triangles split along an awkward diagonal, wound in opposite
+        // directions so the shared edge is walked both ways
+        let (a, b) = (vec2(0.3, 0.1), vec2(97.1, 71.7));
+        let tiles = [
+            territory_with_vertices(vec![a, vec2(97.1, 0.1), b]),
+            territory_with_vertices(vec![b, vec2(0.3, 71.7), a]),
+        ];
+        for i in 1..100 {
+            let t = i as f32 / 100.0;
+            assert_covered_once(&tiles, a.lerp(b, t));
+        }
+    }
+
+    // The index-based loop `is_point_inside` used to be, kept to check the
+    // rewrite against
+    fn indexed_point_inside(vertices: &[Vec2], point: Vec2) -> bool {
+        if vertices.len() < 3 {
+            return false;
+        }
+        let mut is_inside = false;
+        let mut j = vertices.len() - 1;
+        for i in 0..vertices.len() {
+            let (a, b) = if vertices[i].y <= vertices[j].y {
+                (vertices[i], vertices[j])
+            } else {
+                (vertices[j], vertices[i])
+            };
+            if a.y <= point.y && point.y < b.y && (b - a).perp_dot(point - a) > 0.0 {
+                is_inside = !is_inside;
+            }
+            j = i;
+        }
+        is_inside
+    }
+
+    // A polygon of up to `max_vertices` corners on a whole-number grid, so
+    // plenty of the probes below land exactly on vertices and along edges,
+    // which is where the two could disagree. Outlines may cross themselves.
+    fn random_polygon(rng: &mut Rng, max_vertices: u32) -> Vec<Vec2> {
+        let count = rng.gen_range(0, max_vertices + 1);
+        (0..count)
+            .map(|_| vec2(rng.gen_range(0, 21) as f32, rng.gen_range(0, 21) as f32))
+            .collect()
+    }
+
+    #[test]
+    fn rewritten_point_test_matches_the_indexed_one() {
+        let mut rng = Rng::new(42);
+        for _ in 0..2000 {
+            let territory = territory_with_vertices(random_polygon(&mut rng, 12));
+            // As stored, since building may turn the outline round
+            let vertices = &territory.vertices;
+            for _ in 0..50 {
+                // Halves as well as whole numbers, slightly past either side
+                let point = vec2(
+                    rng.gen_range(0, 45) as f32 / 2.0 - 1.0,
+                    rng.gen_range(0, 45) as f32 / 2.0 - 1.0,
+                );
+                assert_eq!(
+                    territory.is_point_inside(point),
+                    indexed_point_inside(vertices, point),
+                    "{:?} in {:?}",
+                    point,
+                    vertices
+                );
+            }
+        }
+    }
+
+    // The rewrite against the indexed loop on detailed outlines.
+    // cargo test --release bench_point_inside -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_point_inside() {
+        use std::time::Instant;
+        let territories = crate::test_support::synthetic_map(10, 2048);
+        let points: Vec<Vec2> = (0..200)
+            .map(|i| vec2((i * 37 % 1000) as f32 + 0.5, (i * 53 % 1000) as f32 + 0.5))
+            .collect();
+
+        let start = Instant::now();
+        let mut indexed_hits = 0;
+        for territory in &territories {
+            for &point in &points {
+                indexed_hits += indexed_point_inside(&territory.vertices, point) as usize;
+            }
+        }
+        let indexed = start.elapsed();
+
+        let start = Instant::now();
+        let mut hits = 0;
+        for territory in &territories {
+            for &point in &points {
+                hits += territory.is_point_inside(point) as usize;
+            }
+        }
+        let rewritten = start.elapsed();
+
+        assert_eq!(hits, indexed_hits);
+        println!(
+            "{} territories x {} points at {} vertices: indexed {:?}, iterator {:?}",
+            territories.len(),
+            points.len(),
+            territories[0].vertices.len(),
+            indexed,
+            rewritten
+        );
+    }
+
+    // Compares the per-frame triangulation this file used to do with the
+    // cached triangles: the CPU cost of the triangles alone, with nothing
+    // drawn. The frames themselves, draw calls and all, are timed in a
+    // window by `--bench-frames`.
+    // cargo test --release bench_cached_triangulation -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_cached_triangulation() {
+        use std::time::Instant;
+        let territories = crate::test_support::generate_stress_map(400, 64);
+        let frames = 60;
+
+        let start = Instant::now();
+        let mut triangles = 0;
+        for _ in 0..frames {
+            for territory in &territories {
+                triangles += geometry::triangulate(&territory.vertices).len();
+            }
+        }
+        let uncached = start.elapsed();
+
+        let start = Instant::now();
+        let mut cached_triangles = 0;
+        for _ in 0..frames {
+            for territory in &territories {
+                cached_triangles += territory.triangles.len();
+            }
+        }
+        let cached = start.elapsed();
+
+        assert_eq!(triangles, cached_triangles);
+        println!(
+            "{} territories x {} frames: triangulating {:?} ({:?}/frame), cached {:?}",
+            territories.len(),
+            frames,
+            uncached,
+            uncached / frames,
+            cached
+        );
+    }
+}
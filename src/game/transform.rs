@@ -0,0 +1,93 @@
+use crate::game::map::TerritoryData;
+use serde::Deserialize;
+
+// Straightens out an imported map that comes in mirrored, turned or at the
+// wrong size. Every vertex is flipped first, then turned, scaled and moved,
+// all about the world origin; the camera fits whatever comes out.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct MapTransform {
+    // Mirror top to bottom, for maps drawn with y growing upwards. World y
+    // grows downwards like SVG's, and GeoJSON is flipped as it's read.
+    pub flip_y: bool,
+    // Quarter turns clockwise
+    pub quarter_turns: u32,
+    pub scale: f32,
+    // World units to move the map by, after scaling
+    pub translate: [f32; 2],
+}
+
+// Leaves the map as it loads. SVG needs no flip by default: its y already
+// grows downwards, as the world's and the screen's do, so what's at the top
+// of the drawing is at the top of the board. Only maps in y-up coordinates
+// (a plotted CAD export, say) want `flip_y`.
+impl Default for MapTransform {
+    fn default() -> MapTransform {
+        MapTransform {
+            flip_y: false,
+            quarter_turns: 0,
+            scale: 1.0,
+            translate: [0.0, 0.0],
+        }
+    }
+}
+
+impl MapTransform {
+    pub fn is_identity(&self) -> bool {
+        *self == MapTransform::default()
+    }
+
+    pub fn apply(&self, [mut x, mut y]: [f32; 2]) -> [f32; 2] {
+        if self.flip_y {
+            y = -y;
+        }
+        for _ in 0..self.quarter_turns % 4 {
+            // Right turns to down, as y grows downwards
+            (x, y) = (-y, x);
+        }
+        [
+            x * self.scale + self.translate[0],
+            y * self.scale + self.translate[1],
+        ]
+    }
+
+    // Outlines are all the geometry a map has, so this is the whole job
+    pub fn apply_to(&self, data: &mut [TerritoryData]) {
+        if self.is_identity() {
+            return;
+        }
+        for vertex in data.iter_mut().flat_map(|t| t.vertices.iter_mut()) {
+            *vertex = self.apply(*vertex);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MapTransform;
+
+    #[test]
+    fn flipped_and_turned_vertices_land_where_expected() {
+        let triangle = [[0.0, 0.0], [4.0, 0.0], [0.0, 2.0]];
+        let flip = MapTransform {
+            flip_y: true,
+            ..MapTransform::default()
+        };
+        assert_eq!(
+            triangle.map(|v| flip.apply(v)),
+            [[0.0, 0.0], [4.0, 0.0], [0.0, -2.0]]
+        );
+
+        let all = MapTransform {
+            flip_y: true,
+            quarter_turns: 1,
+            scale: 2.0,
+            translate: [10.0, 5.0],
+        };
+        assert_eq!(
+            triangle.map(|v| all.apply(v)),
+            [[10.0, 5.0], [10.0, 13.0], [14.0, 5.0]]
+        );
+        assert!(MapTransform::default().is_identity());
+    }
+}
@@ -0,0 +1,264 @@
+use crate::game::events::GameEvent;
+use crate::game::player::PlayerId;
+use crate::game::Game;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// The parts of a turn, in the order they're played
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Phase {
+    // Drafted games only: players take turns picking the unowned
+    // territories before the first real turn
+    Claim,
+    // Classic opening: players take turns dropping one army at a time on
+    // their own territories until their starting armies are used up
+    InitialPlacement,
+    #[default]
+    Reinforce,
+    Attack,
+    Fortify,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Phase::Claim => "Claim",
+            Phase::InitialPlacement => "Placement",
+            Phase::Reinforce => "Reinforce",
+            Phase::Attack => "Attack",
+            Phase::Fortify => "Fortify",
+        };
+        f.write_str(name)
+    }
+}
+
+impl Phase {
+    // The phase a name typed on the command line or in the config means,
+    // ignoring case
+    pub fn from_name(name: &str) -> Option<Phase> {
+        [
+            Phase::Claim,
+            Phase::InitialPlacement,
+            Phase::Reinforce,
+            Phase::Attack,
+            Phase::Fortify,
+        ]
+        .into_iter()
+        .find(|phase| phase.to_string().eq_ignore_ascii_case(name))
+    }
+}
+
+impl Game {
+    // Advances Reinforce -> Attack -> Fortify, then hands the turn to the
+    // next player starting again at Reinforce
+    pub fn next_phase(&mut self) {
+        self.phase = match self.phase {
+            // Each ends by itself, once every territory is taken or every
+            // starting army placed
+            Phase::Claim | Phase::InitialPlacement => return,
+            Phase::Reinforce => Phase::Attack,
+            Phase::Attack => Phase::Fortify,
+            Phase::Fortify => {
+                self.award_card();
+                self.statistics.turns_played += 1;
+                // Eliminated players sit out; bounded in case nobody has
+                // a territory at all
+                let seats = self.players.len().max(1);
+                for _ in 0..seats {
+                    self.current_player = PlayerId((self.current_player.0 + 1) % seats);
+                    if !self.is_eliminated(self.current_player) {
+                        break;
+                    }
+                }
+                self.undo_stack.clear();
+                Phase::Reinforce
+            }
+        };
+        self.log_event(GameEvent::PhaseStarted {
+            player: self.current_player,
+            phase: self.phase,
+        });
+        if self.phase == Phase::Reinforce {
+            self.begin_reinforcements();
+        }
+    }
+}
+
+impl Game {
+    // Plays the opening turn straight through to `phase`, for trying out
+    // combat or fortifying without clicking through the start. The first
+    // player's reinforcements are scattered as D would place them. Returns
+    // false, leaving the game alone, when it doesn't open with an ordinary
+    // turn (as in a draft) or `phase` is part of the setup.
+    pub fn skip_to_phase(&mut self, phase: Phase) -> bool {
+        if self.phase != Phase::Reinforce || matches!(phase, Phase::Claim | Phase::InitialPlacement)
+        {
+            return false;
+        }
+        if phase == Phase::Reinforce {
+            return true;
+        }
+        self.auto_reinforce(self.current_player);
+        // Whatever the army cap left unplaced is given up
+        self.forfeit_unplaceable();
+        while self.phase != phase {
+            self.next_phase();
+        }
+        true
+    }
+
+    // Ends the current player's turn on the spot, throwing away any
+    // reinforcements they hadn't placed (a timed game running out)
+    pub fn forfeit_turn(&mut self) {
+        self.log_event(GameEvent::TurnForfeited {
+            player: self.current_player,
+        });
+        self.reinforcements_remaining = 0;
+        // A draft pick that runs out of time just passes to the next seat
+        if self.phase == Phase::Claim {
+            self.current_player = PlayerId((self.current_player.0 + 1) % self.players.len());
+            return;
+        }
+        // Likewise a turn at placing, the army stays in hand
+        if self.phase == Phase::InitialPlacement {
+            self.pass_initial_placement();
+            return;
+        }
+        loop {
+            let last_phase = self.phase == Phase::Fortify;
+            self.next_phase();
+            if last_phase {
+                break;
+            }
+        }
+    }
+}
+
+impl Game {
+    // Out of the game once they have no territory left
+    pub fn is_eliminated(&self, player: PlayerId) -> bool {
+        !self
+            .territories
+            .iter()
+            .any(|territory| territory.owner == Some(player))
+    }
+
+    // A player wins by owning every territory that isn't neutral; neutrals
+    // don't have to be conquered, only every other player eliminated. With
+    // `team_victory`, players all allied with each other win together, and
+    // the first of them on the map is returned.
+    pub fn winner(&self) -> Option<PlayerId> {
+        if matches!(self.phase, Phase::Claim | Phase::InitialPlacement) {
+            return None;
+        }
+        if self.capital_victory {
+            // Holding every capital wins outright, whatever else is left
+            let mut capitals = self.territories.iter().filter(|t| t.is_capital);
+            // A neutral capital means nobody has them all yet
+            if let Some(owner) = capitals.next().and_then(|first| first.owner) {
+                if capitals.all(|t| t.owner == Some(owner)) {
+                    return Some(owner);
+                }
+            }
+        }
+        let mut owners: Vec<PlayerId> = Vec::new();
+        for owner in self.territories.iter().filter_map(|t| t.owner) {
+            if !owners.contains(&owner) {
+                owners.push(owner);
+            }
+        }
+        let &first = owners.first()?;
+        let together =
+            |a: PlayerId, b: PlayerId| a == b || (self.team_victory && self.allied(a, b));
+        owners
+            .iter()
+            .all(|&a| owners.iter().all(|&b| together(a, b)))
+            .then_some(first)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Phase;
+    use crate::game::player::PlayerId;
+    use crate::test_support::{game, territory};
+
+    #[test]
+    fn forfeiting_skips_to_the_next_player() {
+        let mut game = game(
+            vec![territory("A", 0, 2, vec![1]), territory("B", 1, 2, vec![0])],
+            0,
+        );
+        assert_eq!(game.reinforcements_remaining, 3);
+        game.forfeit_turn();
+        assert_eq!(game.current_player, PlayerId(1));
+        assert_eq!(game.phase, Phase::Reinforce);
+        assert_eq!(game.territories[0].armies, 2);
+        assert_eq!(game.reinforcements_remaining, 3);
+    }
+
+    #[test]
+    fn skipping_ahead_places_the_opening_reinforcements() {
+        assert_eq!(Phase::from_name("fortify"), Some(Phase::Fortify));
+        assert_eq!(Phase::from_name("retreat"), None);
+
+        let mut game = game(
+            vec![territory("A", 0, 2, vec![1]), territory("B", 1, 2, vec![0])],
+            0,
+        );
+        assert!(game.skip_to_phase(Phase::Fortify));
+        assert_eq!(
+            (game.phase, game.current_player),
+            (Phase::Fortify, PlayerId(0))
+        );
+        assert_eq!(game.territories[0].armies, 5);
+        assert_eq!(game.reinforcements_remaining, 0);
+
+        game.phase = Phase::Claim;
+        assert!(!game.skip_to_phase(Phase::Attack));
+        assert_eq!(game.phase, Phase::Claim);
+    }
+
+    #[test]
+    fn neutral_territories_do_not_block_a_win() {
+        let mut neutral = territory("Neutral", 0, 3, vec![0]);
+        neutral.owner = None;
+        let mut game = game(
+            vec![
+                territory("A", 0, 2, vec![1, 2]),
+                territory("B", 1, 2, vec![0]),
+                neutral,
+            ],
+            0,
+        );
+        assert_eq!(game.winner(), None);
+        game.territories[1].owner = Some(PlayerId(0));
+        assert_eq!(game.winner(), Some(PlayerId(0)));
+    }
+
+    #[test]
+    fn holding_every_capital_wins_when_enabled() {
+        let mut game = game(
+            vec![
+                territory("A", 0, 2, vec![1]),
+                territory("B", 1, 2, vec![0, 2]),
+                territory("C", 1, 2, vec![1]),
+            ],
+            0,
+        );
+        game.territories[0].is_capital = true;
+        game.territories[2].is_capital = true;
+        game.capital_victory = true;
+        assert_eq!(game.winner(), None);
+        game.territories[2].owner = Some(PlayerId(0));
+        assert_eq!(game.winner(), Some(PlayerId(0)));
+        game.capital_victory = false;
+        assert_eq!(game.winner(), None);
+
+        // A neutral capital doesn't stop a win by taking the whole map
+        game.capital_victory = true;
+        game.territories[0].owner = None;
+        game.territories[1].owner = Some(PlayerId(0));
+        assert_eq!(game.winner(), Some(PlayerId(0)));
+    }
+}
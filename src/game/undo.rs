@@ -0,0 +1,42 @@
+use crate::game::events::GameEvent;
+use crate::game::turn::Phase;
+use crate::game::Game;
+
+// A reversible change made during the current turn. Attacks are never
+// recorded: their dice can't be taken back, so resolving one clears the stack.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    PlaceReinforcement { territory: usize },
+    Fortify { from: usize, to: usize, count: i32 },
+}
+
+impl Game {
+    pub fn record(&mut self, action: Action) {
+        self.undo_stack.push(action);
+    }
+
+    // Reverts the most recent action, returning false when there is nothing
+    // left to undo this turn
+    pub fn undo(&mut self) -> bool {
+        let Some(action) = self.undo_stack.pop() else {
+            return false;
+        };
+        match action {
+            Action::PlaceReinforcement { territory } => {
+                self.territories[territory].armies -= 1;
+                self.reinforcements_remaining += 1;
+                // Placing the last army moves on automatically; step back
+                self.phase = Phase::Reinforce;
+            }
+            Action::Fortify { from, to, count } => {
+                self.territories[from].armies += count;
+                self.territories[to].armies -= count;
+            }
+        }
+        self.board_dirty = true;
+        self.log_event(GameEvent::Undone {
+            player: self.current_player,
+        });
+        true
+    }
+}
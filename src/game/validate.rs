@@ -0,0 +1,420 @@
+use crate::game::geometry;
+use crate::game::map::{Lookup, MapOwner, TerritoryData};
+use crate::game::player::PlayerId;
+use crate::game::Territory;
+use macroquad::math::*;
+use std::collections::HashSet;
+use std::fmt;
+
+// A problem with a map that would make the game behave incorrectly
+#[derive(Debug, Clone, PartialEq)]
+pub enum MapValidationError {
+    DuplicateName {
+        name: String,
+    },
+    DuplicateId {
+        id: u32,
+    },
+    OwnerOutOfRange {
+        territory: String,
+        owner: PlayerId,
+        player_count: usize,
+    },
+    UnknownNeighbour {
+        territory: String,
+        neighbour: String,
+    },
+    NonReciprocalAdjacency {
+        territory: String,
+        neighbour: String,
+    },
+    // Edges are numbered from 0, edge `i` running from vertex `i` to `i + 1`
+    SelfIntersecting {
+        territory: String,
+        first_edge: usize,
+        second_edge: usize,
+    },
+    // No border or sea route at all, so it can never be attacked or
+    // fortified into
+    Isolated {
+        territory: String,
+    },
+    // A group cut off from the rest of the map, which nothing outside it
+    // can ever reach
+    Disconnected {
+        territories: Vec<String>,
+    },
+}
+
+impl fmt::Display for MapValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapValidationError::DuplicateName { name } => {
+                write!(f, "More than one territory is named '{}'", name)
+            }
+            MapValidationError::DuplicateId { id } => {
+                write!(f, "More than one territory has id {}", id)
+            }
+            MapValidationError::OwnerOutOfRange {
+                territory,
+                owner,
+                player_count,
+            } => write!(
+                f,
+                "Territory '{}' is owned by player {} but there are only {} players",
+                territory, owner, player_count
+            ),
+            MapValidationError::UnknownNeighbour {
+                territory,
+                neighbour,
+            } => write!(
+                f,
+                "Territory '{}' lists unknown neighbour '{}'",
+                territory, neighbour
+            ),
+            MapValidationError::NonReciprocalAdjacency {
+                territory,
+                neighbour,
+            } => write!(
+                f,
+                "Territory '{}' borders '{}' but '{}' doesn't list it back",
+                territory, neighbour, neighbour
+            ),
+            MapValidationError::SelfIntersecting {
+                territory,
+                first_edge,
+                second_edge,
+            } => write!(
+                f,
+                "Territory '{}' has an outline that crosses itself (edges {} and {})",
+                territory, first_edge, second_edge
+            ),
+            MapValidationError::Isolated { territory } => {
+                write!(f, "Territory '{}' has no neighbours", territory)
+            }
+            MapValidationError::Disconnected { territories } => write!(
+                f,
+                "Territories {} can't be reached from the rest of the map",
+                territories
+                    .iter()
+                    .map(|name| format!("'{}'", name))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+// Territories held by a seat nobody is sitting in. Left alone they'd be
+// drawn and counted as a player that doesn't exist, so they're refused.
+pub fn owners_out_of_range(data: &[TerritoryData], player_count: usize) -> Vec<MapValidationError> {
+    data.iter()
+        .filter_map(|territory| {
+            let owner = territory.owner.as_ref().and_then(MapOwner::seat)?;
+            (owner.0 >= player_count).then(|| MapValidationError::OwnerOutOfRange {
+                territory: territory.name.clone(),
+                owner,
+                player_count,
+            })
+        })
+        .collect()
+}
+
+// Checks a loaded map for problems, collecting every one found rather than
+// stopping at the first so a mapmaker can fix them all in one pass
+pub fn validate_map(
+    data: &[TerritoryData],
+    player_count: usize,
+) -> Result<(), Vec<MapValidationError>> {
+    let mut errors = Vec::new();
+
+    let mut seen = HashSet::new();
+    for territory in data {
+        if !seen.insert(territory.name.as_str()) {
+            errors.push(MapValidationError::DuplicateName {
+                name: territory.name.clone(),
+            });
+        }
+    }
+    let mut ids = HashSet::new();
+    for id in data.iter().filter_map(|t| t.id) {
+        if !ids.insert(id) {
+            errors.push(MapValidationError::DuplicateId { id });
+        }
+    }
+
+    errors.extend(owners_out_of_range(data, player_count));
+
+    let lookup = Lookup::new(data);
+    for territory in data {
+        // Sea routes only need listing on one end
+        for neighbour in territory.adjacent.iter().chain(&territory.sea_routes) {
+            if lookup.find(neighbour).is_none() {
+                errors.push(MapValidationError::UnknownNeighbour {
+                    territory: territory.name.clone(),
+                    neighbour: neighbour.to_string(),
+                });
+            }
+        }
+    }
+    for (territory, neighbour) in one_way_adjacencies(data) {
+        errors.push(MapValidationError::NonReciprocalAdjacency {
+            territory: data[territory].name.clone(),
+            neighbour: data[neighbour].name.clone(),
+        });
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+// Land borders listed by only one side, as (territory, neighbour) indices.
+// Either side may list the other by id or by name. References that don't
+// resolve are left to the unknown-neighbour check.
+pub fn one_way_adjacencies(data: &[TerritoryData]) -> Vec<(usize, usize)> {
+    let lookup = Lookup::new(data);
+    let mut pairs = Vec::new();
+    for (territory, entry) in data.iter().enumerate() {
+        for neighbour in &entry.adjacent {
+            if let Some(other) = lookup.find(neighbour) {
+                let listed_back = data[other]
+                    .adjacent
+                    .iter()
+                    .any(|back| lookup.find(back) == Some(territory));
+                if !listed_back {
+                    pairs.push((territory, other));
+                }
+            }
+        }
+    }
+    pairs
+}
+
+// Territories whose outlines wind the opposite way to most of the map's, as
+// indices. Loading fixes them up, but a mixed file usually means some were
+// drawn or exported differently and are worth a second look.
+pub fn mixed_winding(data: &[TerritoryData]) -> Vec<usize> {
+    let clockwise: Vec<bool> = data
+        .iter()
+        .map(|t| {
+            let vertices: Vec<Vec2> = t.vertices.iter().map(|v| vec2(v[0], v[1])).collect();
+            geometry::signed_area(&vertices) < 0.0
+        })
+        .collect();
+    let majority = clockwise.iter().filter(|&&c| c).count() * 2 > clockwise.len();
+    (0..data.len())
+        .filter(|&i| clockwise[i] != majority)
+        .collect()
+}
+
+// Checks the built territories' outlines. A polygon that crosses itself can't
+// be filled properly and makes "inside" ambiguous for clicks.
+pub fn validate_outlines(territories: &[Territory]) -> Result<(), Vec<MapValidationError>> {
+    // Triangulation relies on every outline having been normalized
+    debug_assert!(territories.iter().all(|t| t.signed_area() >= 0.0));
+    let errors: Vec<MapValidationError> = territories
+        .iter()
+        .filter(|territory| !territory.is_simple())
+        .filter_map(|territory| {
+            let (first_edge, second_edge) = territory.self_intersection()?;
+            Some(MapValidationError::SelfIntersecting {
+                territory: territory.name.clone(),
+                first_edge,
+                second_edge,
+            })
+        })
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+// The built territories split into groups that can reach each other over
+// borders and sea routes, each in index order and the groups by their first
+// territory. Borders are followed both ways, since a one-way listing is
+// reported on its own.
+pub fn connected_components(territories: &[Territory]) -> Vec<Vec<usize>> {
+    let mut links = vec![Vec::new(); territories.len()];
+    for (i, territory) in territories.iter().enumerate() {
+        for &neighbour in &territory.adjacent {
+            links[i].push(neighbour);
+            links[neighbour].push(i);
+        }
+    }
+    let mut seen = vec![false; territories.len()];
+    let mut components = Vec::new();
+    for start in 0..territories.len() {
+        if seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        let mut component = vec![start];
+        let mut frontier = vec![start];
+        while let Some(current) = frontier.pop() {
+            for &next in &links[current] {
+                if !seen[next] {
+                    seen[next] = true;
+                    component.push(next);
+                    frontier.push(next);
+                }
+            }
+        }
+        component.sort_unstable();
+        components.push(component);
+    }
+    components
+}
+
+// Checks the map can be played across once borders are final (inferred
+// ones included): every territory has a neighbour, and every group of them
+// joins up with the largest
+pub fn validate_connectivity(territories: &[Territory]) -> Result<(), Vec<MapValidationError>> {
+    let mut errors: Vec<MapValidationError> = territories
+        .iter()
+        .filter(|t| t.adjacent.is_empty())
+        .map(|t| MapValidationError::Isolated {
+            territory: t.name.clone(),
+        })
+        .collect();
+    let components = connected_components(territories);
+    let largest = components
+        .iter()
+        .enumerate()
+        .max_by_key(|(i, component)| (component.len(), std::cmp::Reverse(*i)))
+        .map(|(i, _)| i);
+    for (i, component) in components.iter().enumerate() {
+        // A lone territory is already reported as isolated
+        if Some(i) == largest || component.len() < 2 {
+            continue;
+        }
+        errors.push(MapValidationError::Disconnected {
+            territories: component
+                .iter()
+                .map(|&t| territories[t].name.clone())
+                .collect(),
+        });
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        connected_components, mixed_winding, one_way_adjacencies, validate_connectivity,
+        validate_map, validate_outlines, MapValidationError,
+    };
+    use crate::game::map::TerritoryData;
+    use crate::test_support::{generate_stress_map, territory};
+    use macroquad::math::vec2;
+
+    #[test]
+    fn one_way_borders_are_found_by_index() {
+        let data: Vec<TerritoryData> = serde_json::from_str(
+            r#"[
+                {"name": "A", "vertices": [[0, 0], [1, 0], [0, 1]], "armies": 1, "selected": false,
+                 "adjacent": ["B", "C"]},
+                {"name": "B", "vertices": [[1, 0], [2, 0], [1, 1]], "armies": 1, "selected": false,
+                 "adjacent": ["A"]},
+                {"name": "C", "vertices": [[2, 0], [3, 0], [2, 1]], "armies": 1, "selected": false,
+                 "adjacent": ["Nowhere"]}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(one_way_adjacencies(&data), vec![(0, 2)]);
+
+        let errors = validate_map(&data, 0).unwrap_err();
+        assert!(
+            errors.contains(&MapValidationError::NonReciprocalAdjacency {
+                territory: "A".to_string(),
+                neighbour: "C".to_string(),
+            })
+        );
+        assert!(errors.contains(&MapValidationError::UnknownNeighbour {
+            territory: "C".to_string(),
+            neighbour: "Nowhere".to_string(),
+        }));
+    }
+
+    #[test]
+    fn isolated_and_cut_off_territories_are_reported_by_name() {
+        let territories = vec![
+            territory("A", 0, 1, vec![1]),
+            territory("B", 0, 1, vec![0, 2]),
+            territory("C", 0, 1, vec![1]),
+            territory("Island", 0, 1, vec![]),
+            territory("D", 0, 1, vec![5]),
+            territory("E", 0, 1, vec![4]),
+        ];
+        assert_eq!(
+            connected_components(&territories),
+            vec![vec![0, 1, 2], vec![3], vec![4, 5]]
+        );
+        let errors = validate_connectivity(&territories).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                MapValidationError::Isolated {
+                    territory: "Island".to_string(),
+                },
+                MapValidationError::Disconnected {
+                    territories: vec!["D".to_string(), "E".to_string()],
+                },
+            ]
+        );
+        assert_eq!(
+            errors[1].to_string(),
+            "Territories 'D', 'E' can't be reached from the rest of the map"
+        );
+        assert!(validate_connectivity(&territories[..3]).is_ok());
+    }
+
+    #[test]
+    fn territories_against_the_majority_winding_are_found() {
+        let data: Vec<TerritoryData> = serde_json::from_str(
+            r#"[
+                {"name": "A", "vertices": [[0, 0], [1, 0], [0, 1]], "armies": 1, "selected": false},
+                {"name": "B", "vertices": [[1, 0], [2, 0], [1, 1]], "armies": 1, "selected": false},
+                {"name": "C", "vertices": [[2, 0], [2, 1], [3, 0]], "armies": 1, "selected": false}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(mixed_winding(&data), vec![2]);
+        assert!(mixed_winding(&data[..2]).is_empty());
+    }
+
+    #[test]
+    fn stress_map_tiles_without_gaps() {
+        // Three full rows of four and a short fourth row
+        let territories = generate_stress_map(14, 16);
+        assert!(validate_outlines(&territories).is_ok());
+        for (i, territory) in territories.iter().enumerate() {
+            assert_eq!(territory.vertices.len(), 16);
+            for &neighbour in &territory.adjacent {
+                assert!(territories[neighbour].adjacent.contains(&i));
+            }
+        }
+        assert_eq!(territories[5].adjacent, [4, 6, 1, 9]);
+        assert_eq!(territories[11].adjacent, [10, 7]);
+
+        // Every point inside the full rows is in exactly one territory
+        for row in 0..97 {
+            for col in 0..130 {
+                let point = vec2(10.3 + col as f32 * 2.9, 10.7 + row as f32 * 2.9);
+                let hits = territories
+                    .iter()
+                    .filter(|t| t.is_point_inside(point))
+                    .count();
+                assert_eq!(hits, 1, "{:?}", point);
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use crate::game::ai::Hint;
+use crate::keys::Action;
+use crate::GameState;
+use macroquad::prelude::*;
+
+impl GameState {
+    // The suggestion shown while H is held, for a human's turn only
+    pub fn hint_text(&self) -> Option<String> {
+        let player = self.game.current_player;
+        if !self.hints_enabled || self.game.player(player).is_ai || self.game.winner().is_some() {
+            return None;
+        }
+        let name = |i: usize| &self.game.territories[i].name;
+        Some(match self.game.hint(player) {
+            Hint::Claim { territory } => format!("Claim {}", name(territory)),
+            Hint::Reinforce { territory } => {
+                format!("Place reinforcements on {}", name(territory))
+            }
+            Hint::Attack { from, to, odds } => format!(
+                "Attack {} from {} ({:.0}% odds)",
+                name(to),
+                name(from),
+                odds * 100.0
+            ),
+            Hint::Fortify { from, to } => {
+                format!("Move armies from {} to {}", name(from), name(to))
+            }
+            Hint::EndPhase => "Nothing more worth doing: end the phase".to_string(),
+        })
+    }
+
+    pub fn draw_hint(&self) {
+        if !self.keys.down(Action::Hint) || self.is_typing() {
+            return;
+        }
+        let Some(text) = self.hint_text() else {
+            return;
+        };
+        let text = format!("Hint: {}", text);
+        let dimensions = measure_text(&text, None, 22, 1.0);
+        let (width, height) = (dimensions.width + 20.0, 34.0);
+        let (x, y) = ((self.hud_size().x - width) / 2.0, 60.0);
+        draw_rectangle(x, y, width, height, self.theme.panel_at(0.95));
+        draw_rectangle_lines(x, y, width, height, 2.0, self.theme.highlight);
+        draw_text(
+            &text,
+            x + 10.0,
+            y + height / 2.0 + dimensions.offset_y / 2.0,
+            22.0,
+            self.theme.text,
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use crate::GameState;
+use macroquad::prelude::*;
+
+// How long the button has to stay down on one territory before its
+// neighbours are shown
+const HOLD_SECONDS: f64 = 0.4;
+const RING_ALPHA: f32 = 0.85;
+
+#[derive(Clone, Copy)]
+pub struct Hold {
+    territory: usize,
+    since: f64,
+}
+
+impl GameState {
+    // Call once a frame with whether the left button is down. Moving off the
+    // territory starts the wait again on the next one; letting go ends it.
+    // A press that lasts long enough to show the ring was for looking, so it
+    // no longer clicks when let go.
+    pub fn update_hold(&mut self, down: bool, now: f64) {
+        self.hold = match (down, self.hovered_territory, self.hold) {
+            (true, Some(territory), Some(hold)) if hold.territory == territory => Some(hold),
+            (true, Some(territory), _) => Some(Hold {
+                territory,
+                since: now,
+            }),
+            _ => None,
+        };
+        if self.held_territory(now).is_some() {
+            self.pending_click = None;
+        }
+    }
+
+    // The territory held long enough to show its neighbours. A drag carried
+    // off onto another territory has its own arrow, so it doesn't get the
+    // ring as well.
+    pub fn held_territory(&self, now: f64) -> Option<usize> {
+        let hold = self.hold?;
+        let dragged_off = self
+            .attack_drag
+            .is_some_and(|source| source != hold.territory);
+        (now - hold.since >= HOLD_SECONDS && !dragged_off).then_some(hold.territory)
+    }
+
+    // A line out to every neighbour, whoever holds it, so the map's
+    // connections can be read off without selecting anything
+    pub fn draw_hold_ring(&self) {
+        let Some(held) = self.held_territory(get_time()) else {
+            return;
+        };
+        let point = |i: usize| {
+            self.camera
+                .world_to_screen(self.game.territories[i].centroid())
+        };
+        let ring = Color {
+            a: RING_ALPHA,
+            ..self.theme.focus
+        };
+        let center = point(held);
+        for &neighbour in &self.game.territories[held].adjacent {
+            let end = point(neighbour);
+            draw_line(center.x, center.y, end.x, end.y, 2.0, ring);
+            draw_circle_lines(end.x, end.y, 8.0, 2.0, ring);
+        }
+        draw_circle(center.x, center.y, 5.0, ring);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::turn::Phase;
+    use crate::test_support::{game_state, territory};
+
+    #[test]
+    fn holding_still_shows_the_ring_until_release() {
+        let mut state = game_state(
+            vec![territory("A", 0, 3, vec![1]), territory("B", 1, 3, vec![0])],
+            0,
+        );
+        state.hovered_territory = Some(0);
+        state.update_hold(true, 1.0);
+        state.update_hold(true, 1.2);
+        assert_eq!(state.held_territory(1.2), None);
+        state.update_hold(true, 1.5);
+        assert_eq!(state.held_territory(1.5), Some(0));
+
+        // Sliding onto a neighbour starts the wait over
+        state.hovered_territory = Some(1);
+        state.update_hold(true, 1.6);
+        assert_eq!(state.held_territory(1.6), None);
+        assert_eq!(state.held_territory(2.05), Some(1));
+
+        state.update_hold(false, 2.1);
+        assert_eq!(state.held_territory(2.1), None);
+    }
+
+    #[test]
+    fn a_hold_only_looks() {
+        let mut state = game_state(
+            vec![territory("A", 0, 3, vec![1]), territory("B", 1, 3, vec![0])],
+            0,
+        );
+        state.hovered_territory = Some(0);
+        let armies = state.game.territories[0].armies;
+
+        // Held until the ring shows: nothing placed or selected on release
+        state.press_territory(0, 1.0);
+        state.update_hold(true, 1.0);
+        state.update_hold(true, 1.5);
+        state.update_hold(false, 1.6);
+        state.release_press(false);
+        assert_eq!(state.game.territories[0].armies, armies);
+        assert_eq!(state.selected_territory, None);
+
+        // A quick click still places
+        state.press_territory(0, 3.0);
+        state.update_hold(true, 3.0);
+        state.update_hold(false, 3.1);
+        state.release_press(false);
+        assert_eq!(state.game.territories[0].armies, armies + 1);
+        assert_eq!(state.selected_territory, Some(0));
+
+        // Holding a territory to attack from still shows its ring
+        state.clear_selection();
+        state.game.phase = Phase::Attack;
+        state.game.territories[0].armies = 3;
+        state.press_territory(0, 5.0);
+        assert_eq!(state.attack_drag, Some(0));
+        state.update_hold(true, 5.0);
+        state.update_hold(true, 5.5);
+        assert_eq!(state.held_territory(5.5), Some(0));
+        state.update_hold(false, 5.6);
+        state.release_press(false);
+        assert_eq!(state.selected_territory, None);
+    }
+}
@@ -0,0 +1,86 @@
+use crate::config;
+use crate::keys::Action;
+use crate::GameState;
+use macroquad::prelude::*;
+
+const MIN_HUD_SCALE: f32 = 0.5;
+const MAX_HUD_SCALE: f32 = 3.0;
+// + and - change the scale by a tenth
+const STEPS_PER_UNIT: f32 = 10.0;
+
+// Kept within bounds and rounded to a step, so pressing + and - lands back
+// on the same sizes
+pub fn clamp_hud_scale(scale: f32) -> f32 {
+    ((scale * STEPS_PER_UNIT).round() / STEPS_PER_UNIT).clamp(MIN_HUD_SCALE, MAX_HUD_SCALE)
+}
+
+// Maps HUD units onto the window with y growing downwards, like the default
+// camera. `Camera2D::from_display_rect` is y-up when drawing to the screen.
+fn hud_camera(size: Vec2) -> Camera2D {
+    Camera2D {
+        target: size / 2.0,
+        zoom: vec2(2.0 / size.x, 2.0 / size.y),
+        ..Camera2D::default()
+    }
+}
+
+impl GameState {
+    // The window in HUD units, its pixels divided by the HUD scale. The HUD
+    // is laid out in these and drawn through `begin_hud`'s camera, so a
+    // bigger scale grows text, panels and the gaps between them together.
+    pub fn hud_size(&self) -> Vec2 {
+        vec2(screen_width(), screen_height()) / self.hud_scale
+    }
+
+    // The pointer in HUD units, for clicks on panels and buttons
+    pub fn hud_mouse(&self) -> Vec2 {
+        Vec2::from(mouse_position()) / self.hud_scale
+    }
+
+    // Everything drawn after this, until `set_default_camera`, is scaled
+    pub fn begin_hud(&self) {
+        set_camera(&hud_camera(self.hud_size()));
+    }
+
+    // + and - (on either keyboard) step the scale, saving it to the config
+    // so the next game opens at the same size
+    pub fn handle_hud_scale_input(&mut self) {
+        let step = if self.keys.pressed(Action::TextBigger) || is_key_pressed(KeyCode::KpAdd) {
+            1.0 / STEPS_PER_UNIT
+        } else if self.keys.pressed(Action::TextSmaller) || is_key_pressed(KeyCode::KpSubtract) {
+            -1.0 / STEPS_PER_UNIT
+        } else {
+            return;
+        };
+        let scale = clamp_hud_scale(self.hud_scale + step);
+        if scale == self.hud_scale {
+            return;
+        }
+        self.hud_scale = scale;
+        self.status_message = Some(format!("Text size {:.0}%", scale * 100.0));
+        if let Err(err) = config::save_setting(crate::CONFIG_PATH, "hud_scale", scale.into()) {
+            self.warn(format!("Couldn't save the text size: {}", err));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{clamp_hud_scale, hud_camera};
+    use macroquad::prelude::*;
+
+    #[test]
+    fn scale_steps_stay_tidy_and_in_bounds() {
+        assert_eq!(clamp_hud_scale(1.0 + 0.1 + 0.1 + 0.1), 1.3);
+        assert_eq!(clamp_hud_scale(0.2), 0.5);
+        assert_eq!(clamp_hud_scale(9.0), 3.0);
+    }
+
+    #[test]
+    fn hud_origin_is_the_top_left_of_the_screen() {
+        let matrix = hud_camera(vec2(800.0, 600.0)).matrix();
+        let clip = |x: f32, y: f32| matrix.transform_point3(vec3(x, y, 0.0)).truncate();
+        assert_eq!(clip(0.0, 0.0), vec2(-1.0, 1.0));
+        assert_eq!(clip(800.0, 600.0), vec2(1.0, -1.0));
+    }
+}
@@ -0,0 +1,190 @@
+use crate::button::Button;
+use crate::game::turn::Phase;
+use crate::keys::Action;
+use crate::GameState;
+use macroquad::prelude::*;
+
+// The column of buttons under the minimap
+const LEFT_OF_EDGE: f32 = 210.0;
+const TOP: f32 = 225.0;
+const WIDTH: f32 = 200.0;
+const HEIGHT: f32 = 28.0;
+const GAP: f32 = 4.0;
+
+// What the HUD's buttons do, each the same as its key
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HudAction {
+    TradeCards,
+    AutoReinforce,
+    Attack,
+    Blitz,
+    EndPhase,
+}
+
+impl GameState {
+    // The attack the Attack and Blitz buttons would make: the target Tab
+    // picked out, or the selection's only target when it has just one
+    pub fn button_attack_target(&self) -> Option<(usize, usize)> {
+        if self.game.phase != Phase::Attack {
+            return None;
+        }
+        let from = self.selected_territory?;
+        let target = self.highlighted_attack_target().or_else(|| {
+            match self.game.valid_attack_targets(from)[..] {
+                [only] => Some(only),
+                _ => None,
+            }
+        })?;
+        Some((from, target))
+    }
+
+    // The current phase's main actions, laid out for a `hud`-sized window,
+    // each enabled only when it's legal right now
+    pub fn hud_buttons(&self, hud: Vec2) -> Vec<Button<HudAction>> {
+        let key = |action| self.keys.name(action);
+        let player = self.game.current_player;
+        let end_label = match self.game.phase {
+            Phase::Reinforce => "End reinforcing",
+            Phase::Attack => "End attacks",
+            _ => "End turn",
+        };
+        let end_phase = (
+            HudAction::EndPhase,
+            format!("{} ({})", end_label, key(Action::EndPhase)),
+            self.can_end_phase(),
+        );
+        let entries = match self.game.phase {
+            Phase::Claim | Phase::InitialPlacement => Vec::new(),
+            Phase::Reinforce => vec![
+                (
+                    HudAction::TradeCards,
+                    format!("Trade cards ({})", key(Action::TradeCards)),
+                    self.game.find_set(player).is_some(),
+                ),
+                (
+                    HudAction::AutoReinforce,
+                    format!("Distribute the rest ({})", key(Action::Distribute)),
+                    self.unbuffered_reinforcements() > 0,
+                ),
+                end_phase,
+            ],
+            Phase::Attack => {
+                let attack = self.button_attack_target().is_some();
+                vec![
+                    (HudAction::Attack, "Attack".to_string(), attack),
+                    (HudAction::Blitz, "Blitz".to_string(), attack),
+                    end_phase,
+                ]
+            }
+            Phase::Fortify => vec![end_phase],
+        };
+        entries
+            .into_iter()
+            .enumerate()
+            .map(|(i, (action, label, enabled))| Button {
+                rect: Rect::new(
+                    hud.x - LEFT_OF_EDGE,
+                    TOP + (HEIGHT + GAP) * i as f32,
+                    WIDTH,
+                    HEIGHT,
+                ),
+                label,
+                action,
+                enabled,
+            })
+            .collect()
+    }
+
+    // Where the column of buttons ends, for whatever goes under it
+    pub fn hud_buttons_bottom(&self) -> f32 {
+        self.hud_buttons(self.hud_size())
+            .last()
+            .map_or(TOP, |button| button.rect.bottom())
+    }
+
+    // Returns whether the click landed on a button, enabled or not, so it
+    // doesn't fall through to the map underneath
+    pub fn handle_hud_button_click(&mut self) -> bool {
+        let mouse = self.hud_mouse();
+        let buttons = self.hud_buttons(self.hud_size());
+        let Some(button) = buttons.iter().find(|button| button.rect.contains(mouse)) else {
+            return false;
+        };
+        if let Some(action) = button.hit(mouse) {
+            self.run_hud_action(action);
+        }
+        true
+    }
+
+    fn run_hud_action(&mut self, action: HudAction) {
+        match action {
+            HudAction::TradeCards => self.trade_cards(),
+            HudAction::AutoReinforce => self.auto_reinforce(),
+            HudAction::Attack | HudAction::Blitz => {
+                if let Some((_, target)) = self.button_attack_target() {
+                    self.click_territory(target, action == HudAction::Blitz);
+                }
+            }
+            HudAction::EndPhase => self.end_phase(),
+        }
+    }
+
+    pub fn draw_hud_buttons(&self) {
+        let mouse = self.hud_mouse();
+        for button in self.hud_buttons(self.hud_size()) {
+            button.draw(mouse, &self.theme);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HudAction;
+    use crate::game::turn::Phase;
+    use crate::test_support::{game_state, territory};
+    use macroquad::prelude::*;
+
+    #[test]
+    fn buttons_follow_the_phase_and_what_is_legal() {
+        let mut state = game_state(
+            vec![
+                territory("Home", 0, 5, vec![1, 2]),
+                territory("East", 1, 1, vec![0]),
+                territory("West", 1, 1, vec![0]),
+            ],
+            0,
+        );
+        let hud = vec2(800.0, 600.0);
+        let enabled = |state: &crate::GameState| {
+            state
+                .hud_buttons(hud)
+                .iter()
+                .map(|button| (button.action, button.enabled))
+                .collect::<Vec<_>>()
+        };
+        assert!(state.game.reinforcements_remaining > 0);
+        assert_eq!(
+            enabled(&state),
+            [
+                (HudAction::TradeCards, false),
+                (HudAction::AutoReinforce, true),
+                (HudAction::EndPhase, false),
+            ]
+        );
+
+        // Placing the last army moves the turn on by itself
+        state.run_hud_action(HudAction::AutoReinforce);
+        assert_eq!(state.game.phase, Phase::Attack);
+
+        // Two targets, so nothing to attack until one is picked
+        state.select(0);
+        assert_eq!(enabled(&state)[0], (HudAction::Attack, false));
+        state.cycle_attack_target();
+        assert_eq!(state.button_attack_target(), Some((0, 1)));
+        assert_eq!(enabled(&state)[1], (HudAction::Blitz, true));
+
+        let before = state.game.events.len();
+        state.run_hud_action(HudAction::Blitz);
+        assert!(state.game.events.len() > before);
+    }
+}
@@ -0,0 +1,162 @@
+use crate::game::territory::Terrain;
+use crate::GameState;
+use macroquad::prelude::*;
+
+// Two clicks on one territory this close together open its details
+const DOUBLE_CLICK_SECONDS: f64 = 0.35;
+const PANEL_WIDTH: f32 = 260.0;
+const LINE_HEIGHT: f32 = 20.0;
+// Left of the minimap, level with its top
+const PANEL_TOP: f32 = 65.0;
+const PANEL_RIGHT_MARGIN: f32 = 220.0;
+
+impl GameState {
+    pub fn inspect(&mut self, territory: usize) {
+        self.inspecting = Some(territory);
+    }
+
+    // Remembers each click so the next one can tell whether it's the second
+    // half of a double click
+    pub fn is_double_click(&mut self, territory: usize, now: f64) -> bool {
+        let double = self
+            .last_click
+            .is_some_and(|(last, at)| last == territory && now - at <= DOUBLE_CLICK_SECONDS);
+        // A third click starts a new pair rather than opening it again
+        self.last_click = (!double).then_some((territory, now));
+        double
+    }
+
+    // Everything known about the inspected territory, rebuilt from the game
+    // each time so it keeps up with attacks and moves while open. Owner and
+    // armies stay hidden under fog of war.
+    pub fn inspect_lines(&self, territory: usize) -> Vec<String> {
+        let t = &self.game.territories[territory];
+        let mut lines = Vec::new();
+        if self.visible_to(self.game.current_player, territory) {
+            lines.push(format!("Owner: {}", self.game.owner_name(t.owner)));
+            lines.push(format!("Armies: {}", t.armies));
+        } else {
+            lines.push("Owner: unknown".to_string());
+            lines.push("Armies: ?".to_string());
+        }
+        lines.push(format!(
+            "Capital: {}",
+            if t.is_capital { "yes" } else { "no" }
+        ));
+        if t.terrain != Terrain::Plains {
+            lines.push(format!("Terrain: {:?}", t.terrain));
+        }
+        let continents: Vec<&str> = self
+            .game
+            .continents
+            .iter()
+            .filter(|c| c.territories.contains(&territory))
+            .map(|c| c.name.as_str())
+            .collect();
+        lines.push(if continents.is_empty() {
+            "Continent: none".to_string()
+        } else {
+            format!("Continent: {}", continents.join(", "))
+        });
+        lines.push(format!("Neighbours ({}):", t.adjacent.len()));
+        for &neighbour in &t.adjacent {
+            let by_sea = if t.sea_routes.contains(&neighbour) {
+                " (by sea)"
+            } else {
+                ""
+            };
+            lines.push(format!(
+                "  {}{}",
+                self.game.territories[neighbour].name, by_sea
+            ));
+        }
+        lines
+    }
+
+    pub fn draw_inspect_panel(&self) {
+        let Some(territory) = self.inspecting else {
+            return;
+        };
+        let lines = self.inspect_lines(territory);
+        let x = self.hud_size().x - PANEL_RIGHT_MARGIN - PANEL_WIDTH;
+        let height = LINE_HEIGHT * (lines.len() + 2) as f32;
+        draw_rectangle(x, PANEL_TOP, PANEL_WIDTH, height, self.theme.panel_at(0.92));
+        draw_rectangle_lines(
+            x,
+            PANEL_TOP,
+            PANEL_WIDTH,
+            height,
+            1.0,
+            self.theme.panel_border,
+        );
+        draw_text(
+            &self.game.territories[territory].name,
+            x + 8.0,
+            PANEL_TOP + LINE_HEIGHT,
+            24.0,
+            self.theme.text,
+        );
+        for (i, line) in lines.iter().enumerate() {
+            draw_text(
+                line,
+                x + 8.0,
+                PANEL_TOP + LINE_HEIGHT * (i as f32 + 2.0),
+                18.0,
+                self.theme.text,
+            );
+        }
+        draw_text(
+            "Esc: close",
+            x + 8.0,
+            PANEL_TOP + height - 6.0,
+            16.0,
+            self.theme.muted_text,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{game_state, territory};
+
+    #[test]
+    fn inspect_panel_follows_the_board() {
+        let mut state = game_state(
+            vec![
+                territory("Home", 0, 4, vec![1]),
+                territory("Enemy", 1, 2, vec![0]),
+            ],
+            0,
+        );
+        assert!(!state.is_double_click(0, 1.0));
+        assert!(!state.is_double_click(1, 1.1));
+        assert!(state.is_double_click(1, 1.3));
+        assert!(!state.is_double_click(1, 1.4));
+        assert!(!state.is_double_click(1, 2.0));
+
+        // Opening the details doesn't click the territory a second time
+        state.last_click = None;
+        let armies = state.game.territories[0].armies;
+        state.hovered_territory = Some(0);
+        state.press_territory(0, 3.0);
+        state.release_press(false);
+        state.press_territory(0, 3.1);
+        state.release_press(false);
+        assert_eq!(state.inspecting, Some(0));
+        assert_eq!(state.game.territories[0].armies, armies + 1);
+        assert_eq!(state.selected_territory, Some(0));
+
+        state.inspect(1);
+        assert_eq!(
+            state.inspect_lines(1)[..2],
+            ["Owner: Player 2", "Armies: 2"]
+        );
+        state.game.territories[1].armies = 7;
+        assert_eq!(state.inspect_lines(1)[1], "Armies: 7");
+        assert_eq!(state.inspect_lines(1)[4..], ["Neighbours (1):", "  Home"]);
+
+        state.escape();
+        assert_eq!(state.inspecting, None);
+        assert!(state.pause_menu.is_none());
+    }
+}
@@ -0,0 +1,371 @@
+use macroquad::prelude::*;
+use std::collections::HashMap;
+
+// Everything in the game that a key can be bound to. Arrows, digits, Enter,
+// Escape, Pause and the keypad's + and - keep their fixed meanings and can't
+// be taken.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Action {
+    EndPhase,
+    // Pressed with Ctrl
+    Undo,
+    Save,
+    Load,
+    SaveStatistics,
+    ExportImage,
+    Reset,
+    TradeCards,
+    Distribute,
+    Search,
+    StepFortify,
+    // Held to show; Shift turns hints on and off
+    Hint,
+    ToggleLabels,
+    ToggleContinents,
+    ToggleGrid,
+    ToggleTheme,
+    PlanRoute,
+    CycleTarget,
+    AdjacencyOverlay,
+    ShowTerritories,
+    HistoryUp,
+    HistoryDown,
+    Spectate,
+    Pause,
+    Mute,
+    TextBigger,
+    TextSmaller,
+    Editor,
+    Replay,
+    ChooseMap,
+    Alliance,
+    DebugOverlay,
+    // In the replay viewer, where the game's own keys are asleep
+    ReplayStart,
+    ReplayEnd,
+}
+
+// Each action with its name in the config and its default key
+const ACTIONS: [(Action, &str, KeyCode); 34] = [
+    (Action::EndPhase, "end_phase", KeyCode::Space),
+    (Action::Undo, "undo", KeyCode::Z),
+    (Action::Save, "save", KeyCode::S),
+    (Action::Load, "load", KeyCode::L),
+    (Action::SaveStatistics, "save_statistics", KeyCode::J),
+    (Action::ExportImage, "export_image", KeyCode::I),
+    (Action::Reset, "reset", KeyCode::R),
+    (Action::TradeCards, "trade_cards", KeyCode::T),
+    (Action::Distribute, "distribute", KeyCode::D),
+    (Action::Search, "search", KeyCode::Slash),
+    (Action::StepFortify, "step_fortify", KeyCode::F),
+    (Action::Hint, "hint", KeyCode::H),
+    (Action::ToggleLabels, "toggle_labels", KeyCode::N),
+    (Action::ToggleContinents, "toggle_continents", KeyCode::C),
+    (Action::ToggleGrid, "toggle_grid", KeyCode::G),
+    (Action::ToggleTheme, "toggle_theme", KeyCode::Y),
+    (Action::PlanRoute, "plan_route", KeyCode::B),
+    (Action::CycleTarget, "cycle_target", KeyCode::Tab),
+    (Action::AdjacencyOverlay, "adjacency_overlay", KeyCode::F3),
+    (Action::ShowTerritories, "show_territories", KeyCode::Home),
+    (Action::HistoryUp, "history_up", KeyCode::PageUp),
+    (Action::HistoryDown, "history_down", KeyCode::PageDown),
+    (Action::Spectate, "spectate", KeyCode::V),
+    (Action::Pause, "pause", KeyCode::F9),
+    (Action::Mute, "mute", KeyCode::M),
+    (Action::TextBigger, "text_bigger", KeyCode::Equal),
+    (Action::TextSmaller, "text_smaller", KeyCode::Minus),
+    (Action::Editor, "editor", KeyCode::E),
+    (Action::Replay, "replay", KeyCode::P),
+    (Action::ChooseMap, "choose_map", KeyCode::F2),
+    (Action::Alliance, "alliance", KeyCode::A),
+    (Action::DebugOverlay, "debug_overlay", KeyCode::F1),
+    (Action::ReplayStart, "replay_start", KeyCode::Home),
+    (Action::ReplayEnd, "replay_end", KeyCode::End),
+];
+
+// Everything the replay viewer answers to: its own actions, saving and
+// loading (the replay rather than the game) and the keys that close it.
+// The viewer's own actions may share a key with anything outside this.
+const REPLAY_VIEWER: [Action; 6] = [
+    Action::ReplayStart,
+    Action::ReplayEnd,
+    Action::Save,
+    Action::Load,
+    Action::Replay,
+    Action::Editor,
+];
+
+// Keys by the names the config uses for them (matched ignoring case), which
+// are also how the key help shows them
+const KEY_NAMES: [(&str, KeyCode); 67] = [
+    ("A", KeyCode::A),
+    ("B", KeyCode::B),
+    ("C", KeyCode::C),
+    ("D", KeyCode::D),
+    ("E", KeyCode::E),
+    ("F", KeyCode::F),
+    ("G", KeyCode::G),
+    ("H", KeyCode::H),
+    ("I", KeyCode::I),
+    ("J", KeyCode::J),
+    ("K", KeyCode::K),
+    ("L", KeyCode::L),
+    ("M", KeyCode::M),
+    ("N", KeyCode::N),
+    ("O", KeyCode::O),
+    ("P", KeyCode::P),
+    ("Q", KeyCode::Q),
+    ("R", KeyCode::R),
+    ("S", KeyCode::S),
+    ("T", KeyCode::T),
+    ("U", KeyCode::U),
+    ("V", KeyCode::V),
+    ("W", KeyCode::W),
+    ("X", KeyCode::X),
+    ("Y", KeyCode::Y),
+    ("Z", KeyCode::Z),
+    ("0", KeyCode::Key0),
+    ("1", KeyCode::Key1),
+    ("2", KeyCode::Key2),
+    ("3", KeyCode::Key3),
+    ("4", KeyCode::Key4),
+    ("5", KeyCode::Key5),
+    ("6", KeyCode::Key6),
+    ("7", KeyCode::Key7),
+    ("8", KeyCode::Key8),
+    ("9", KeyCode::Key9),
+    ("F1", KeyCode::F1),
+    ("F2", KeyCode::F2),
+    ("F3", KeyCode::F3),
+    ("F4", KeyCode::F4),
+    ("F5", KeyCode::F5),
+    ("F6", KeyCode::F6),
+    ("F7", KeyCode::F7),
+    ("F8", KeyCode::F8),
+    ("F9", KeyCode::F9),
+    ("F10", KeyCode::F10),
+    ("F11", KeyCode::F11),
+    ("F12", KeyCode::F12),
+    ("Space", KeyCode::Space),
+    ("Tab", KeyCode::Tab),
+    ("/", KeyCode::Slash),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("Insert", KeyCode::Insert),
+    ("Delete", KeyCode::Delete),
+    ("Pause", KeyCode::Pause),
+    ("-", KeyCode::Minus),
+    ("=", KeyCode::Equal),
+    ("Backspace", KeyCode::Backspace),
+    ("Enter", KeyCode::Enter),
+    ("Escape", KeyCode::Escape),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+];
+
+// Keys with a fixed job of their own, which bindings are kept off
+const RESERVED: [KeyCode; 19] = [
+    KeyCode::Enter,
+    KeyCode::Escape,
+    KeyCode::Pause,
+    KeyCode::KpAdd,
+    KeyCode::KpSubtract,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Key0,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+];
+
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    KEY_NAMES
+        .iter()
+        .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+        .map(|&(_, key)| key)
+}
+
+pub fn key_name(key: KeyCode) -> &'static str {
+    KEY_NAMES
+        .iter()
+        .find(|&&(_, k)| k == key)
+        .map_or("?", |&(name, _)| name)
+}
+
+// Which key does what. Starts from the defaults; the config can move any
+// action to another key.
+#[derive(Clone)]
+pub struct KeyBindings {
+    keys: HashMap<Action, KeyCode>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> KeyBindings {
+        KeyBindings {
+            keys: ACTIONS
+                .iter()
+                .map(|&(action, _, key)| (action, key))
+                .collect(),
+        }
+    }
+}
+
+impl KeyBindings {
+    // The defaults with the config's `"action": "key"` pairs laid over them.
+    // Unknown actions or keys, reserved keys and keys bound twice are
+    // reported; bad entries are skipped, keeping that action's default.
+    pub fn from_config(overrides: &HashMap<String, String>) -> (KeyBindings, Vec<String>) {
+        let mut bindings = KeyBindings::default();
+        let mut warnings = Vec::new();
+        let mut names: Vec<&String> = overrides.keys().collect();
+        names.sort();
+        for name in names {
+            let key_name = &overrides[name];
+            let Some(&(action, _, _)) = ACTIONS.iter().find(|(_, n, _)| n == name) else {
+                warnings.push(format!("No action called '{}' to bind", name));
+                continue;
+            };
+            match key_from_name(key_name) {
+                Some(key) if RESERVED.contains(&key) => warnings.push(format!(
+                    "{} can't be rebound, so {} keeps its key",
+                    key_name, name
+                )),
+                Some(key) => {
+                    bindings.keys.insert(action, key);
+                }
+                None => warnings.push(format!("Unknown key '{}' for {}", key_name, name)),
+            }
+        }
+        for (first, second, key) in bindings.conflicts() {
+            warnings.push(format!(
+                "{} is bound to both {} and {}",
+                key_name(key),
+                action_name(first),
+                action_name(second)
+            ));
+        }
+        (bindings, warnings)
+    }
+
+    pub fn key(&self, action: Action) -> KeyCode {
+        self.keys[&action]
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        is_key_pressed(self.key(action))
+    }
+
+    pub fn down(&self, action: Action) -> bool {
+        is_key_down(self.key(action))
+    }
+
+    // The key's name, for the key help
+    pub fn name(&self, action: Action) -> &'static str {
+        key_name(self.key(action))
+    }
+
+    // Pairs of actions sharing a key while both are live, in the order
+    // they're listed
+    fn conflicts(&self) -> Vec<(Action, Action, KeyCode)> {
+        let mut conflicts = Vec::new();
+        for (i, &(first, _, _)) in ACTIONS.iter().enumerate() {
+            for &(second, _, _) in &ACTIONS[i + 1..] {
+                if self.key(first) == self.key(second) && live_together(first, second) {
+                    conflicts.push((first, second, self.key(first)));
+                }
+            }
+        }
+        conflicts
+    }
+}
+
+fn live_together(first: Action, second: Action) -> bool {
+    let viewer_only = |action| matches!(action, Action::ReplayStart | Action::ReplayEnd);
+    let in_viewer = |action| REPLAY_VIEWER.contains(&action);
+    match (viewer_only(first), viewer_only(second)) {
+        (false, false) => true,
+        (true, _) => in_viewer(second),
+        (_, true) => in_viewer(first),
+    }
+}
+
+fn action_name(action: Action) -> &'static str {
+    ACTIONS
+        .iter()
+        .find(|&&(a, _, _)| a == action)
+        .map_or("?", |&(_, name, _)| name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Action, KeyBindings};
+    use macroquad::prelude::KeyCode;
+    use std::collections::HashMap;
+
+    #[test]
+    fn config_overrides_defaults_and_clashes_are_reported() {
+        let (defaults, warnings) = KeyBindings::from_config(&HashMap::new());
+        assert!(warnings.is_empty());
+        assert_eq!(defaults.key(Action::Save), KeyCode::S);
+
+        let overrides: HashMap<String, String> = [
+            ("save", "k"),
+            ("load", "F5"),
+            ("mute", "k"),
+            ("undo", "Escape"),
+            ("fly", "Q"),
+            ("trade_cards", "Hyper"),
+            ("spectate", "Pause"),
+        ]
+        .into_iter()
+        .map(|(action, key)| (action.to_string(), key.to_string()))
+        .collect();
+        let (bindings, warnings) = KeyBindings::from_config(&overrides);
+        assert_eq!(bindings.key(Action::Save), KeyCode::K);
+        assert_eq!(bindings.key(Action::Load), KeyCode::F5);
+        assert_eq!(bindings.name(Action::Load), "F5");
+        // Bad entries keep the default
+        assert_eq!(bindings.key(Action::Undo), KeyCode::Z);
+        assert_eq!(bindings.key(Action::TradeCards), KeyCode::T);
+        assert_eq!(
+            warnings,
+            [
+                "No action called 'fly' to bind",
+                "Pause can't be rebound, so spectate keeps its key",
+                "Unknown key 'Hyper' for trade_cards",
+                "Escape can't be rebound, so undo keeps its key",
+                "K is bound to both save and mute",
+            ]
+        );
+    }
+
+    #[test]
+    fn replay_keys_only_clash_with_what_the_viewer_reads() {
+        let bind = |pairs: &[(&str, &str)]| {
+            let overrides: HashMap<String, String> = pairs
+                .iter()
+                .map(|&(action, key)| (action.to_string(), key.to_string()))
+                .collect();
+            KeyBindings::from_config(&overrides).1
+        };
+        // Home also shows your territories, but never while the viewer is open
+        assert!(bind(&[]).is_empty());
+        assert_eq!(
+            bind(&[("replay_end", "S")]),
+            ["S is bound to both save and replay_end"]
+        );
+        assert!(bind(&[("replay_end", "T")]).is_empty());
+    }
+}
@@ -0,0 +1,68 @@
+use serde::Deserialize;
+
+// How much writing the board carries at a given zoom
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Detail {
+    // Colors and markers only
+    Bare,
+    // Smaller army counts, no names
+    Counts,
+    // Names (if shown at all) and full-size counts
+    Full,
+}
+
+// Army counts shrink to this share of their size at `Detail::Counts`
+pub const SMALL_COUNT_SCALE: f32 = 0.75;
+
+// Zoomed far out, a large map's text runs together, so it thins out in
+// steps: names go first, then the counts. Both are camera zoom factors.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct LabelDetail {
+    // Below this only smaller counts are drawn
+    pub names_from: f32,
+    // Below this no text is drawn at all
+    pub counts_from: f32,
+}
+
+impl Default for LabelDetail {
+    fn default() -> LabelDetail {
+        LabelDetail {
+            names_from: 0.6,
+            counts_from: 0.35,
+        }
+    }
+}
+
+impl LabelDetail {
+    pub fn at(&self, zoom: f32) -> Detail {
+        if zoom < self.counts_from {
+            Detail::Bare
+        } else if zoom < self.names_from {
+            Detail::Counts
+        } else {
+            Detail::Full
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Detail, LabelDetail};
+
+    #[test]
+    fn detail_thins_out_as_the_camera_zooms_out() {
+        let detail = LabelDetail::default();
+        assert_eq!(detail.at(1.0), Detail::Full);
+        assert_eq!(detail.at(0.6), Detail::Full);
+        assert_eq!(detail.at(0.5), Detail::Counts);
+        assert_eq!(detail.at(0.3), Detail::Bare);
+
+        // Thresholds at zero keep everything at any zoom
+        let always = LabelDetail {
+            names_from: 0.0,
+            counts_from: 0.0,
+        };
+        assert_eq!(always.at(0.25), Detail::Full);
+    }
+}
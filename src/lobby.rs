@@ -0,0 +1,444 @@
+use crate::button::Button;
+use crate::config::PlayerConfig;
+use crate::game::map::MapOwner;
+use crate::screen::LoadedMap;
+use crate::theme::Theme;
+use macroquad::prelude::*;
+
+const MIN_PLAYERS: usize = 2;
+const MAX_PLAYERS: usize = 6;
+const MAX_NAME_LENGTH: usize = 16;
+
+// The colours on offer, one swatch each
+const SWATCHES: [[u8; 3]; 8] = [
+    [200, 40, 40],
+    [40, 80, 200],
+    [40, 160, 60],
+    [230, 180, 30],
+    [140, 60, 180],
+    [240, 120, 30],
+    [40, 170, 180],
+    [90, 90, 90],
+];
+
+const TOP: f32 = 110.0;
+const ROW_HEIGHT: f32 = 44.0;
+const ROW_WIDTH: f32 = 620.0;
+const NAME_WIDTH: f32 = 200.0;
+const SWATCH_SIZE: f32 = 22.0;
+const SWATCH_GAP: f32 = 4.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LobbyAction {
+    // Start typing the slot's name
+    EditName(usize),
+    ToggleAi(usize),
+    // A slot and the swatch picked for it
+    Color(usize, usize),
+    Remove(usize),
+    Add,
+    Back,
+    Start,
+}
+
+// What the lobby was left with
+pub enum LobbyChoice {
+    Start(Vec<PlayerConfig>),
+    Back,
+}
+
+// Who plays a hotseat game, set up once its map is picked: each seat's name
+// and colour, and whether the computer takes its turns. Starts from the
+// config's players, or enough default ones for the map's owners.
+pub struct Lobby {
+    pub map: LoadedMap,
+    players: Vec<PlayerConfig>,
+    // Seats the map hands territories to, which can't be removed
+    owner_seats: usize,
+    // The slot whose name is being typed
+    editing: Option<usize>,
+    // Why the last choice wasn't allowed
+    pub error: Option<String>,
+}
+
+fn default_player(seat: usize) -> PlayerConfig {
+    PlayerConfig {
+        name: format!("Player {}", seat + 1),
+        color: SWATCHES[seat % SWATCHES.len()],
+        pattern: None,
+        ai: false,
+        difficulty: Default::default(),
+    }
+}
+
+impl Lobby {
+    pub fn new(map: LoadedMap, configured: &[PlayerConfig]) -> Lobby {
+        let owner_seats = map
+            .0
+            .iter()
+            .filter_map(|t| t.owner.as_ref().and_then(MapOwner::seat))
+            .map(|owner| owner.0 + 1)
+            .max()
+            .unwrap_or(0);
+        let players = if configured.is_empty() {
+            // Enough seats for every owner the map names
+            let seats = owner_seats.clamp(MIN_PLAYERS, MAX_PLAYERS);
+            (0..seats).map(default_player).collect()
+        } else {
+            configured.to_vec()
+        };
+        Lobby {
+            map,
+            players,
+            owner_seats,
+            editing: None,
+            error: None,
+        }
+    }
+
+    // Removing a seat moves everyone after it up one, so only seats past
+    // the last one the map deals territories to can go
+    fn can_remove(&self, slot: usize) -> bool {
+        self.players.len() > MIN_PLAYERS && slot >= self.owner_seats
+    }
+
+    fn row(screen: Vec2, slot: usize) -> Rect {
+        Rect::new(
+            (screen.x - ROW_WIDTH) / 2.0,
+            TOP + ROW_HEIGHT * slot as f32,
+            ROW_WIDTH,
+            ROW_HEIGHT - 8.0,
+        )
+    }
+
+    fn name_rect(screen: Vec2, slot: usize) -> Rect {
+        let row = Lobby::row(screen, slot);
+        Rect::new(row.x, row.y, NAME_WIDTH, row.h)
+    }
+
+    fn swatch_rect(screen: Vec2, slot: usize, swatch: usize) -> Rect {
+        let row = Lobby::row(screen, slot);
+        Rect::new(
+            row.x + NAME_WIDTH + 110.0 + (SWATCH_SIZE + SWATCH_GAP) * swatch as f32,
+            row.y + (row.h - SWATCH_SIZE) / 2.0,
+            SWATCH_SIZE,
+            SWATCH_SIZE,
+        )
+    }
+
+    // Every button on a `screen`-sized window
+    fn buttons(&self, screen: Vec2) -> Vec<Button<LobbyAction>> {
+        let button = |rect, label: &str, action, enabled| Button {
+            rect,
+            label: label.to_string(),
+            action,
+            enabled,
+        };
+        let mut buttons = Vec::new();
+        for (slot, player) in self.players.iter().enumerate() {
+            let row = Lobby::row(screen, slot);
+            buttons.push(button(
+                Rect::new(row.x + NAME_WIDTH + 10.0, row.y, 90.0, row.h),
+                if player.ai { "AI" } else { "Human" },
+                LobbyAction::ToggleAi(slot),
+                true,
+            ));
+            buttons.push(button(
+                Rect::new(row.right() - 90.0, row.y, 90.0, row.h),
+                "Remove",
+                LobbyAction::Remove(slot),
+                self.can_remove(slot),
+            ));
+        }
+        let y = Lobby::row(screen, self.players.len()).y + 10.0;
+        let x = Lobby::row(screen, 0).x;
+        buttons.push(button(
+            Rect::new(x, y, 140.0, 32.0),
+            "Add player",
+            LobbyAction::Add,
+            self.players.len() < MAX_PLAYERS,
+        ));
+        buttons.push(button(
+            Rect::new(x + ROW_WIDTH - 290.0, y, 140.0, 32.0),
+            "Back (Esc)",
+            LobbyAction::Back,
+            true,
+        ));
+        buttons.push(button(
+            Rect::new(x + ROW_WIDTH - 140.0, y, 140.0, 32.0),
+            "Start (Enter)",
+            LobbyAction::Start,
+            true,
+        ));
+        buttons
+    }
+
+    fn action_at(&self, screen: Vec2, point: Vec2) -> Option<LobbyAction> {
+        if let Some(action) = self.buttons(screen).iter().find_map(|b| b.hit(point)) {
+            return Some(action);
+        }
+        (0..self.players.len()).find_map(|slot| {
+            if Lobby::name_rect(screen, slot).contains(point) {
+                return Some(LobbyAction::EditName(slot));
+            }
+            (0..SWATCHES.len())
+                .find(|&swatch| Lobby::swatch_rect(screen, slot, swatch).contains(point))
+                .map(|swatch| LobbyAction::Color(slot, swatch))
+        })
+    }
+
+    // Two seats can't share a colour, and everyone needs a name
+    pub fn validate(&self) -> Result<(), String> {
+        for (i, player) in self.players.iter().enumerate() {
+            if player.name.trim().is_empty() {
+                return Err(format!("Player {} needs a name", i + 1));
+            }
+            if let Some(other) = self.players[..i].iter().find(|p| p.color == player.color) {
+                return Err(format!(
+                    "{} and {} have the same color",
+                    other.name, player.name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn apply(&mut self, action: LobbyAction) -> Option<LobbyChoice> {
+        self.error = None;
+        self.editing = None;
+        match action {
+            LobbyAction::EditName(slot) => self.editing = Some(slot),
+            LobbyAction::ToggleAi(slot) => self.players[slot].ai = !self.players[slot].ai,
+            LobbyAction::Color(slot, swatch) => {
+                let color = SWATCHES[swatch];
+                match self.players.iter().find(|p| p.color == color) {
+                    Some(owner) if owner.color != self.players[slot].color => {
+                        self.error = Some(format!("{} already has that color", owner.name));
+                    }
+                    _ => self.players[slot].color = color,
+                }
+            }
+            LobbyAction::Remove(slot) => {
+                if self.can_remove(slot) {
+                    self.players.remove(slot);
+                }
+            }
+            LobbyAction::Add => {
+                if self.players.len() < MAX_PLAYERS {
+                    let mut player = default_player(self.players.len());
+                    if let Some(&free) = SWATCHES
+                        .iter()
+                        .find(|color| !self.players.iter().any(|p| p.color == **color))
+                    {
+                        player.color = free;
+                    }
+                    self.players.push(player);
+                }
+            }
+            LobbyAction::Back => return Some(LobbyChoice::Back),
+            LobbyAction::Start => match self.validate() {
+                Ok(()) => return Some(LobbyChoice::Start(self.players.clone())),
+                Err(message) => self.error = Some(message),
+            },
+        }
+        None
+    }
+
+    // Typing goes to the name being edited, which Enter or Escape finishes;
+    // otherwise Enter starts and Escape goes back to the maps
+    pub fn handle_input(&mut self) -> Option<LobbyChoice> {
+        if let Some(slot) = self.editing {
+            let name = &mut self.players[slot].name;
+            while let Some(c) = get_char_pressed() {
+                if !c.is_control() && name.chars().count() < MAX_NAME_LENGTH {
+                    name.push(c);
+                }
+            }
+            if is_key_pressed(KeyCode::Backspace) {
+                name.pop();
+            }
+            if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::Escape) {
+                self.editing = None;
+                return None;
+            }
+        } else {
+            // Keys pressed before a name was clicked aren't typed into it
+            while get_char_pressed().is_some() {}
+            if is_key_pressed(KeyCode::Enter) {
+                return self.apply(LobbyAction::Start);
+            }
+            if is_key_pressed(KeyCode::Escape) {
+                return self.apply(LobbyAction::Back);
+            }
+        }
+        if is_mouse_button_pressed(MouseButton::Left) {
+            let screen = vec2(screen_width(), screen_height());
+            match self.action_at(screen, mouse_position().into()) {
+                Some(action) => return self.apply(action),
+                None => self.editing = None,
+            }
+        }
+        None
+    }
+
+    pub fn draw(&self, theme: &Theme) {
+        let screen = vec2(screen_width(), screen_height());
+        let title = "Who's playing?";
+        let dimensions = measure_text(title, None, 40, 1.0);
+        draw_text(
+            title,
+            (screen.x - dimensions.width) / 2.0,
+            70.0,
+            40.0,
+            theme.text,
+        );
+
+        let mouse: Vec2 = mouse_position().into();
+        for (slot, player) in self.players.iter().enumerate() {
+            let rect = Lobby::name_rect(screen, slot);
+            let editing = self.editing == Some(slot);
+            draw_rectangle(rect.x, rect.y, rect.w, rect.h, theme.panel);
+            draw_rectangle_lines(
+                rect.x,
+                rect.y,
+                rect.w,
+                rect.h,
+                if editing { 3.0 } else { 1.0 },
+                if editing {
+                    theme.text
+                } else {
+                    theme.panel_border
+                },
+            );
+            let [r, g, b] = player.color;
+            draw_rectangle(rect.x, rect.y, 6.0, rect.h, Color::from_rgba(r, g, b, 255));
+            let name = if editing {
+                format!("{}_", player.name)
+            } else {
+                player.name.clone()
+            };
+            draw_text(&name, rect.x + 14.0, rect.y + 24.0, 22.0, theme.text);
+
+            for (swatch, &[r, g, b]) in SWATCHES.iter().enumerate() {
+                let rect = Lobby::swatch_rect(screen, slot, swatch);
+                draw_rectangle(
+                    rect.x,
+                    rect.y,
+                    rect.w,
+                    rect.h,
+                    Color::from_rgba(r, g, b, 255),
+                );
+                let taken = self.players.iter().any(|p| p.color == [r, g, b]);
+                if player.color == [r, g, b] {
+                    draw_rectangle_lines(
+                        rect.x - 3.0,
+                        rect.y - 3.0,
+                        rect.w + 6.0,
+                        rect.h + 6.0,
+                        3.0,
+                        theme.text,
+                    );
+                } else if taken {
+                    // Someone else's, so it can't be picked
+                    draw_line(
+                        rect.x,
+                        rect.y,
+                        rect.right(),
+                        rect.bottom(),
+                        2.0,
+                        theme.background,
+                    );
+                }
+            }
+        }
+        for button in self.buttons(screen) {
+            button.draw(mouse, theme);
+        }
+
+        let bottom = Lobby::row(screen, self.players.len()).y + 66.0;
+        let x = Lobby::row(screen, 0).x;
+        draw_text(
+            "Click a name to type it, a swatch to change color",
+            x,
+            bottom,
+            18.0,
+            theme.muted_text,
+        );
+        if let Some(error) = &self.error {
+            draw_text(error, x, bottom + 26.0, 20.0, theme.warning);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Lobby, LobbyAction, LobbyChoice, SWATCHES};
+    use crate::screen::LoadedMap;
+    use macroquad::prelude::*;
+
+    #[test]
+    fn colors_stay_unique_and_starting_hands_over_the_players() {
+        let mut lobby = Lobby::new(LoadedMap(Vec::new()), &[]);
+        assert_eq!(lobby.players.len(), 2);
+        assert!(lobby.validate().is_ok());
+
+        // Player 1's colour is taken, so Player 2 keeps theirs
+        lobby.apply(LobbyAction::Color(1, 0));
+        assert_eq!(lobby.players[1].color, SWATCHES[1]);
+        assert_eq!(
+            lobby.error.as_deref(),
+            Some("Player 1 already has that color")
+        );
+        lobby.apply(LobbyAction::Color(1, 4));
+        assert_eq!(lobby.players[1].color, SWATCHES[4]);
+
+        // A clash from the config is caught before starting
+        lobby.players[0].color = SWATCHES[4];
+        assert!(lobby.apply(LobbyAction::Start).is_none());
+        assert_eq!(
+            lobby.error.as_deref(),
+            Some("Player 1 and Player 2 have the same color")
+        );
+        lobby.apply(LobbyAction::Color(0, 2));
+
+        lobby.apply(LobbyAction::Add);
+        lobby.apply(LobbyAction::ToggleAi(2));
+        assert_ne!(lobby.players[2].color, lobby.players[0].color);
+        let Some(LobbyChoice::Start(players)) = lobby.apply(LobbyAction::Start) else {
+            panic!("the lobby should start");
+        };
+        assert_eq!(players.len(), 3);
+        assert!(players[2].ai && !players[0].ai);
+
+        // Clicking a swatch on the screen picks it
+        let screen = vec2(800.0, 600.0);
+        let point = Lobby::swatch_rect(screen, 0, 5).center();
+        assert_eq!(
+            lobby.action_at(screen, point),
+            Some(LobbyAction::Color(0, 5))
+        );
+    }
+
+    #[test]
+    fn seats_the_map_deals_to_stay() {
+        let map = serde_json::from_str(
+            r#"[{"name": "A", "vertices": [], "owner": 2, "armies": 1, "selected": false}]"#,
+        )
+        .unwrap();
+        let mut lobby = Lobby::new(LoadedMap(map), &[]);
+        assert_eq!(lobby.players.len(), 3);
+        lobby.apply(LobbyAction::Add);
+        lobby.apply(LobbyAction::Remove(2));
+        assert_eq!(lobby.players.len(), 4);
+        let screen = vec2(800.0, 600.0);
+        let removable: Vec<_> = lobby
+            .buttons(screen)
+            .iter()
+            .filter(|b| matches!(b.action, LobbyAction::Remove(_)) && b.enabled)
+            .map(|b| b.action)
+            .collect();
+        assert_eq!(removable, [LobbyAction::Remove(3)]);
+        lobby.apply(LobbyAction::Remove(3));
+        assert_eq!(lobby.players.len(), 3);
+    }
+}
@@ -1,156 +1,2581 @@
 use macroquad::prelude::*;
-use serde::Deserialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+mod alliances;
+mod attack_confirm;
+mod attack_drag;
+mod attack_target;
+mod audio;
+mod background;
+mod board_cache;
+mod borders;
+mod button;
+mod camera;
+mod cli;
+mod combat_animation;
+mod config;
+mod conquest_flash;
+mod context_menu;
+mod controller;
+mod debounce;
+mod debug_overlay;
+mod editor;
+mod fog;
+mod fortify_reach;
+mod frame_bench;
+mod frame_limit;
+mod game;
+mod hint;
+mod hold_preview;
+mod hud;
+mod hud_buttons;
+mod inspect;
+mod keys;
+mod label_detail;
+mod lobby;
+mod minimap;
+mod movement_animation;
+mod objectives;
+mod panels;
+mod patterns;
+mod pause_menu;
+mod pending_move;
+mod placement;
+mod progress_bar;
+mod replay;
+mod route;
+mod scenario;
+mod screen;
+mod search;
+mod spatial;
+mod stroke;
+mod svg_export;
+#[cfg(test)]
+mod test_support;
+mod theme;
+mod turn_timer;
+
+use attack_confirm::PendingAttack;
+use audio::{AudioManager, SoundEffect};
+use background::BackgroundGrid;
+use board_cache::BoardCache;
+use borders::Borders;
+use camera::{Camera, GLIDE_SECONDS};
+use cli::Args;
+use combat_animation::CombatAnimation;
+use config::{Config, PlayerConfig};
+use context_menu::ContextMenu;
+use debounce::ClickDebounce;
+use editor::Editor;
+use frame_limit::FrameLimiter;
+use game::events::GameEvent;
+use game::fortify::FortifyError;
+use game::map::{self, MapLoadError, MapSource, TerritoryData};
+use game::player::{Player, PlayerId};
+use game::save::{GameSnapshot, SaveError};
+use game::statistics::GameStatistics;
+use game::territory::{FillRule, Terrain};
+use game::turn::Phase;
+use game::validate;
+use game::{Game, Territory};
+use hold_preview::Hold;
+use hud::clamp_hud_scale;
+use keys::{Action, KeyBindings};
+use label_detail::{Detail, LabelDetail, SMALL_COUNT_SCALE};
+use lobby::{Lobby, LobbyChoice};
+use movement_animation::MovementAnimation;
+use panels::PlayerStats;
+use pause_menu::PauseMenu;
+use pending_move::PendingMove;
+use progress_bar::ProgressMeasure;
+use replay::{Replay, ReplayAction, ReplayViewer};
+use route::RoutePlanner;
+use scenario::{Objective, Outcome, Scenario};
+use screen::{GameScreen, MapEntry, MapSelect};
+use search::Search;
+use spatial::SpatialGrid;
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
+use std::io;
 use std::path::Path;
+use stroke::Strokes;
+use theme::{Theme, ThemeConfig};
+use turn_timer::TurnTimer;
 
-// Struct for deserializing JSON data
-#[derive(Deserialize)]
-struct TerritoryData {
-    name: String,
-    vertices: Vec<[f32; 2]>,
-    owner: usize,
-    armies: i32,
-    selected: bool,
-}
+const CONFIG_PATH: &str = "resources/config.json";
+const SAVE_PATH: &str = "savegame.json";
+const STATISTICS_PATH: &str = "statistics.json";
+// The selected territory gets a bright border (in the theme's selection
+// colour) and a wash of white over its fill, light enough that the owner's
+// color still shows through
+const SELECTED_OUTLINE_THICKNESS: f32 = 5.0;
+const SELECTED_WASH: f32 = 0.35;
+// Outline widths for borders between neighbours and for the coastline
+const BORDER_THICKNESS: f32 = 1.0;
+const COAST_THICKNESS: f32 = 3.0;
+// The theme's attack source colour is laid over the outline this faintly
+const ATTACK_SOURCE_ALPHA: f32 = 0.6;
+// Pause between a computer player's decisions
+const AI_STEP_SECONDS: f32 = 0.12;
+// How long a refused action's explanation stays up
+const WARNING_SECONDS: f32 = 3.0;
+// Shown on the first press of the reset key; pressing it again while this is
+// still on screen goes through with it
+const RESET_PROMPT: &str = "Press again to restart this game from the beginning";
+// Where the turn status, minimap and timer down the right side end
+const RIGHT_HUD_BOTTOM: f32 = 300.0;
+// Screen pixels kept clear around the map when the view is fitted to it
+const FIT_MARGIN: f32 = 40.0;
+// Screen pixels beyond the window a territory can be and still be drawn
+const CULL_MARGIN: f32 = 60.0;
 
-impl TerritoryData {
-    fn to_territory(&self) -> Territory {
-        Territory {
-            name: self.name.clone(),
-            vertices: self.vertices.iter().map(|v| vec2(v[0], v[1])).collect(),
-            owner: self.owner,
-            armies: self.armies,
-            selected: self.selected,
+// Drawing lives here with the rest of the UI; the territory itself is
+// part of the headless game
+impl Territory {
+    // Everything drawn over a visible territory's fill (the fills themselves
+    // are batched for the whole board): its pattern, and a wash when selected
+    fn draw_details(&self, owner: Option<&Player>, camera: &Camera) {
+        if let Some(owner) = owner {
+            self.draw_pattern(owner.pattern, darken(owner.color, 0.7), camera);
+        }
+        if self.selected {
+            self.draw_wash(camera, Color::new(1.0, 1.0, 1.0, SELECTED_WASH));
         }
     }
-}
 
-// Struct representing a territory
-struct Territory {
-    name: String,
-    vertices: Vec<Vec2>,
-    owner: usize,
-    armies: i32,
-    selected: bool,
-}
+    // A translucent `color` laid over the whole territory
+    fn draw_wash(&self, camera: &Camera, color: Color) {
+        let screen: Vec<Vec2> = self
+            .vertices
+            .iter()
+            .map(|&v| camera.world_to_screen(v))
+            .collect();
+        for &[a, b, c] in &self.triangles {
+            draw_triangle(screen[a], screen[b], screen[c], color);
+        }
+    }
 
-impl Territory {
-    fn is_point_inside(&self, point: Vec2) -> bool {
-        let mut is_inside = false;
-        let mut j = self.vertices.len() - 1;
-        for i in 0..self.vertices.len() {
-            let vi = &self.vertices[i];
-            let vj = &self.vertices[j];
+    fn draw_outline(&self, camera: &Camera, color: Color, thickness: f32) {
+        let screen: Vec<Vec2> = self
+            .vertices
+            .iter()
+            .map(|&v| camera.world_to_screen(v))
+            .collect();
+        let mut strokes = Strokes::default();
+        strokes.polygon(&screen, thickness, color);
+        strokes.draw();
+    }
 
-            if (vi.y > point.y) != (vj.y > point.y)
-                && (point.x < (vj.x - vi.x) * (point.y - vi.y) / (vj.y - vi.y) + vi.x)
-            {
-                is_inside = !is_inside;
+    // Borders with a neighbour are drawn as thin internal lines and the
+    // coastline thicker, so the shape of the land reads at a glance. The
+    // selected territory gets a thicker border all round instead so it
+    // stands out from its neighbours. Added to `strokes` so the whole
+    // board's outlines go out as one mesh.
+    fn add_borders(&self, strokes: &mut Strokes, camera: &Camera, color: Color, shared: &[bool]) {
+        let screen: Vec<Vec2> = self
+            .vertices
+            .iter()
+            .map(|&v| camera.world_to_screen(v))
+            .collect();
+        if self.selected {
+            strokes.polygon(&screen, SELECTED_OUTLINE_THICKNESS, color);
+            return;
+        }
+        let n = screen.len();
+        let thickness = |i: usize| {
+            if shared.get(i).copied().unwrap_or(false) {
+                BORDER_THICKNESS
+            } else {
+                COAST_THICKNESS
             }
-            j = i;
+        };
+        for i in 0..n {
+            strokes.segment(screen[i], screen[(i + 1) % n], thickness(i), color);
+            // The corner takes the thicker of the two edges meeting there
+            strokes.join(
+                screen[i],
+                thickness(i).max(thickness((i + n - 1) % n)),
+                color,
+            );
         }
-        is_inside
     }
 
-    fn draw(&self) {
-        let color = if self.selected {
-            YELLOW
-        } else {
-            match self.owner {
-                0 => BLUE,
-                1 => GREEN,
-                _ => GRAY,
-            }
+    fn draw_army_count(&self, text: &str, fill: Color, camera: &Camera, scale: f32) {
+        // Follows the zoom, shrinking for small territories but never below
+        // legibility; the HUD scale moves both bounds
+        let font_size = ((self.max - self.min).min_element() * camera.zoom * 0.5)
+            .clamp(14.0 * scale, 28.0 * scale);
+
+        let dimensions = measure_text(text, None, font_size as u16, 1.0);
+        let center = camera.world_to_screen(self.centroid());
+        draw_text(
+            text,
+            center.x - dimensions.width / 2.0,
+            center.y + dimensions.offset_y / 2.0,
+            font_size,
+            contrasting_text_color(fill),
+        );
+    }
+
+    // A star in the theme's capital colour under the army count. Capitals
+    // are common knowledge, so this shows through fog of war.
+    fn draw_capital_marker(&self, camera: &Camera, theme: &Theme) {
+        const POINTS: usize = 5;
+        let outer = ((self.max - self.min).min_element() * camera.zoom * 0.12).clamp(5.0, 10.0);
+        let inner = outer * 0.45;
+        let center = camera.world_to_screen(self.centroid()) + vec2(0.0, outer * 2.2);
+        let corner = |i: usize| {
+            let radius = if i.is_multiple_of(2) { outer } else { inner };
+            let angle = i as f32 / (2 * POINTS) as f32 * std::f32::consts::TAU
+                - std::f32::consts::FRAC_PI_2;
+            center + Vec2::from_angle(angle) * radius
         };
+        for i in 0..2 * POINTS {
+            draw_triangle(center, corner(i), corner(i + 1), theme.capital);
+        }
+        let outline: Vec<Vec2> = (0..2 * POINTS).map(corner).collect();
+        let mut strokes = Strokes::default();
+        strokes.polygon(&outline, 1.0, darken(theme.capital, 0.3));
+        strokes.draw();
+    }
 
-        let n = self.vertices.len();
-        for i in 0..n {
-            let start = self.vertices[i];
-            let end = self.vertices[(i + 1) % n];
-            draw_line(start.x, start.y, end.x, end.y, 2.0, color);
+    // A small faint sign beside the capital star's spot: two peaks for a
+    // mountain, a wavy line for a river
+    fn draw_terrain_marker(&self, camera: &Camera, theme: &Theme) {
+        let size = ((self.max - self.min).min_element() * camera.zoom * 0.1).clamp(4.0, 8.0);
+        let center = camera.world_to_screen(self.centroid()) + vec2(-size * 3.0, size * 2.6);
+        match self.terrain {
+            Terrain::Plains => {}
+            Terrain::Mountain => {
+                let color = Color {
+                    a: 0.55,
+                    ..theme.mountain
+                };
+                for (dx, height) in [(-0.5, 1.6), (0.6, 1.1)] {
+                    let base = center + vec2(dx * size, size * 0.8);
+                    draw_triangle(
+                        base - vec2(size, 0.0),
+                        base + vec2(size, 0.0),
+                        base - vec2(0.0, height * size),
+                        color,
+                    );
+                }
+            }
+            Terrain::River => {
+                let points: Vec<Vec2> = (0..=8)
+                    .map(|i| {
+                        let t = i as f32 / 8.0;
+                        center
+                            + vec2(
+                                (t - 0.5) * size * 3.0,
+                                (t * std::f32::consts::TAU).sin() * size * 0.4,
+                            )
+                    })
+                    .collect();
+                let color = Color {
+                    a: 0.6,
+                    ..theme.river
+                };
+                let mut strokes = Strokes::default();
+                for pair in points.windows(2) {
+                    strokes.segment(pair[0], pair[1], 2.0, color);
+                }
+                strokes.draw();
+            }
+        }
+    }
+
+    // Name drawn just above the army count. The label shrinks to fit the
+    // territory and is left out entirely if even the smallest size would
+    // spill well past the territory's bounds.
+    fn draw_name_label(&self, fill: Color, camera: &Camera) {
+        const MIN_FONT_SIZE: f32 = 10.0;
+        const MAX_FONT_SIZE: f32 = 18.0;
+
+        let available = (self.max.x - self.min.x) * camera.zoom;
+        let full_width = measure_text(&self.name, None, MAX_FONT_SIZE as u16, 1.0).width;
+        let font_size =
+            (MAX_FONT_SIZE * available / full_width.max(1.0)).clamp(MIN_FONT_SIZE, MAX_FONT_SIZE);
+        let dimensions = measure_text(&self.name, None, font_size as u16, 1.0);
+        if dimensions.width > available * 1.5 {
+            return;
         }
+
+        let center = camera.world_to_screen(self.centroid());
+        draw_text(
+            &self.name,
+            center.x - dimensions.width / 2.0,
+            center.y - font_size,
+            font_size,
+            contrasting_text_color(fill),
+        );
     }
 }
 
+// Picks black or white text depending on how bright the background is
+fn contrasting_text_color(background: Color) -> Color {
+    let luminance = 0.299 * background.r + 0.587 * background.g + 0.114 * background.b;
+    if luminance > 0.5 {
+        BLACK
+    } else {
+        WHITE
+    }
+}
+
+// Blends `amount` of white into a color
+fn lighten(color: Color, amount: f32) -> Color {
+    Color::new(
+        color.r + (1.0 - color.r) * amount,
+        color.g + (1.0 - color.g) * amount,
+        color.b + (1.0 - color.b) * amount,
+        color.a,
+    )
+}
+
+fn darken(color: Color, factor: f32) -> Color {
+    Color::new(
+        color.r * factor,
+        color.g * factor,
+        color.b * factor,
+        color.a,
+    )
+}
+
+// The game plus everything needed to show it and play it with a mouse and
+// keyboard
 struct GameState {
-    territories: Vec<Territory>,
+    game: Game,
+    // Rebuilt whenever the game's territories are replaced
+    spatial_index: SpatialGrid,
+    borders: Borders,
+    camera: Camera,
+    grid: BackgroundGrid,
+    show_labels: bool,
+    // Size of HUD text and panels (+ and - change it)
+    hud_scale: f32,
+    // Each player's holdings as of the last board redraw, for the bar
+    // across the top, and whether it counts territories or armies
+    progress: Vec<PlayerStats>,
+    progress_measure: ProgressMeasure,
+    // Colours for the board and HUD, and the config they came from
+    theme: Theme,
+    theme_config: ThemeConfig,
+    // Which key does what, from the defaults and the config
+    keys: KeyBindings,
+    // Continent panel expanded (C folds it down to its title)
+    show_continents: bool,
+    // Only show what the player to move owns or borders
+    fog_of_war: bool,
+    // How clicks are tested against outlines
+    fill_rule: FillRule,
+    // Per-turn countdown in timed games
+    turn_timer: Option<TurnTimer>,
+    // Computer player whose turn is being played out a step at a time
+    ai_turn: Option<PlayerId>,
+    // Seconds until the computer's next step
+    ai_wait: f32,
+    // Freezes input and the turn timer
+    paused: bool,
+    // Escape menu, while open (the game is paused behind it)
+    pause_menu: Option<PauseMenu>,
+    // Set by the menu's Quit, for the main loop to act on
+    quit_requested: bool,
+    // Makes each press of the left button one click on the board
+    click_debounce: ClickDebounce,
+    // Territory an attack is being dragged from, while the button is held
+    attack_drag: Option<usize>,
+    // The territory the button has been held down on, and since when
+    hold: Option<Hold>,
+    // A territory pressed on, clicked once the button is let go over it
+    // unless the press turned into a hold first
+    pending_click: Option<usize>,
+    // Alliances proposed and not yet accepted, proposer first
+    alliance_offers: HashSet<(PlayerId, PlayerId)>,
+    // Right-click menu, while open
+    context_menu: Option<ContextMenu>,
+    // Territory whose details panel is open
+    inspecting: Option<usize>,
+    // Picking two territories to show the shortest route between
+    route: Option<RoutePlanner>,
+    // Territory and time of the last click, to spot double clicks
+    last_click: Option<(usize, f64)>,
+    // Open while a territory name is being typed in
+    search: Option<Search>,
+    // Debug overlay of the adjacency graph
+    show_adjacency: bool,
+    // Frame rate, culling and animation counters (F1)
+    show_debug: bool,
+    // Fortify one army per click instead of through the spinner
+    step_fortify: bool,
+    // Whether holding H suggests a move
+    hints_enabled: bool,
+    // Fit the view to each human's territories as their turn starts
+    center_on_turn: bool,
+    // Whose turn the view last followed
+    turn_shown: Option<PlayerId>,
+    // Locks gameplay input while leaving the camera free, for handing the
+    // mouse over between hotseat turns
+    spectating: bool,
+    // Window size the camera was last laid out for, zero until first drawn
+    screen_size: Vec2,
     selected_territory: Option<usize>,
+    hovered_territory: Option<usize>,
+    // A fortify or post-conquest move waiting for the player to pick a count
+    pending_move: Option<PendingMove>,
+    // An attack waiting for the player to confirm it
+    pending_attack: Option<PendingAttack>,
+    // Ask before every attack (from the config)
+    confirm_attacks: bool,
+    // Zooms at which names and then counts drop off the board
+    label_detail: LabelDetail,
+    // A scenario's win conditions, and the seat they're set for
+    objectives: Vec<Objective>,
+    challenger: PlayerId,
+    // Armies scrolled onto territories in Reinforce, placed when the phase ends
+    pending_placements: BTreeMap<usize, i32>,
+    // Single armies step-by-step fortify has moved out of the selection, as
+    // (from, to), so a right-click only sends one back the way it came
+    fortify_steps: Vec<(usize, usize)>,
+    // Where the controller cursor is, from the D-pad being used until the
+    // mouse is
+    focused_territory: Option<usize>,
+    // Which of the selection's attack targets Tab has highlighted, as an
+    // index into them
+    attack_target: Option<usize>,
+    // Feedback for the last action, shown at the bottom of the screen
+    status_message: Option<String>,
+    // Why the last thing tried wasn't allowed, and the seconds left to show it
+    warning: Option<(String, f32)>,
+    // A human attack being shown before its casualties are applied
+    combat_animation: Option<CombatAnimation>,
+    // Armies shown travelling after a fortify or conquest
+    movements: Vec<MovementAnimation>,
+    // Seconds left of each newly conquered territory's flash
+    flash_timers: BTreeMap<usize, f32>,
+    // Sound effects triggered since the last frame, played by the main loop
+    pending_sounds: Vec<SoundEffect>,
+    // How many of the game's events have already had their sounds queued
+    events_heard: usize,
+    board_cache: BoardCache,
+    // How many entries back from the newest the history panel is scrolled
+    log_scroll: usize,
+    // Every decision since the game started (or was loaded), for replays
+    replay: Replay,
 }
 
 impl GameState {
-    fn new() -> GameState {
-        let territories = load_territories_from_json("resources/territories.json")
-            .into_iter()
-            .map(|data| data.to_territory())
+    fn new(config: &Config, source: &dyn MapSource, seed: u64) -> Result<GameState, MapLoadError> {
+        GameState::from_data(config, source.load()?, &config.players, seed)
+    }
+
+    // A game on a map written out in a test, with the default config
+    #[cfg(test)]
+    fn from_json_str(json: &str, seed: u64) -> Result<GameState, MapLoadError> {
+        GameState::new(&Config::default(), &map::StrMapSource(json), seed)
+    }
+
+    // The map's geometry with owners and armies taken from a scenario file
+    // instead. Its players are seated in the config beforehand, by
+    // `seat_scenario_players`, so the lobby can change them.
+    fn new_with_scenario(
+        config: &Config,
+        source: &dyn MapSource,
+        scenario_path: &Path,
+        seed: u64,
+    ) -> Result<GameState, MapLoadError> {
+        let mut data = source.load()?;
+        let scenario = Scenario::load(scenario_path)?;
+        for name in scenario.apply(&mut data) {
+            eprintln!(
+                "{}: no territory named '{}' on this map",
+                scenario_path.display(),
+                name
+            );
+        }
+        let mut game_state = GameState::from_data(config, data, &config.players, seed)?;
+        game_state.objectives = scenario.objectives;
+        if let Some(challenger) = &scenario.challenger {
+            game_state.challenger = game_state.objective_player(challenger).ok_or_else(|| {
+                MapLoadError::UnknownChallenger {
+                    path: scenario_path.to_path_buf(),
+                    challenger: objectives::describe_owner(challenger),
+                }
+            })?;
+        }
+        for target in game_state.unknown_objective_targets() {
+            eprintln!("{}: objective has {}", scenario_path.display(), target);
+        }
+        Ok(game_state)
+    }
+
+    fn from_data(
+        config: &Config,
+        mut data: Vec<TerritoryData>,
+        players: &[PlayerConfig],
+        seed: u64,
+    ) -> Result<GameState, MapLoadError> {
+        // Before anything looks at the geometry, so it all agrees
+        config.map_transform.apply_to(&mut data);
+        let players = players
+            .iter()
+            .enumerate()
+            .map(|(i, p)| p.to_player(PlayerId(i)))
             .collect();
+        let mut game = Game::from_map(&data, players, seed)?;
+        if let Some(epsilon) = config.infer_adjacency {
+            map::infer_adjacency(&mut game.territories, epsilon);
+        }
+        // Only once any inferred borders are in. Worth knowing about, but a
+        // map under construction is still worth opening.
+        if let Err(errors) = validate::validate_connectivity(&game.territories) {
+            for error in errors {
+                eprintln!("Map: {}", error);
+            }
+        }
+        // After inferring borders, which needs the vertices neighbours share
+        if let Some(tolerance) = config.simplify_tolerance {
+            let removed = map::simplify_territories(&mut game.territories, tolerance);
+            for (territory, removed) in game.territories.iter().zip(removed) {
+                if removed > 0 {
+                    eprintln!(
+                        "{}: simplified from {} to {} vertices",
+                        territory.name,
+                        territory.vertices.len() + removed,
+                        territory.vertices.len()
+                    );
+                }
+            }
+        }
+        game.combat_rules = config.combat;
+        game.rules = config.rules;
+        game.max_armies = config.max_armies;
+        game.capital_victory = config.capital_victory;
+        game.team_victory = config.team_victory;
+        game.concede_rule = config.concede_rule;
+        game.fortify_rule = config.fortify_rule;
+        game.designate_capitals(&config.capitals);
+        game.assign_terrain(&config.terrain);
+        game.assign_starting_territories(config.start_mode, seed);
+        if config.initial_placement {
+            game.begin_initial_placement();
+        }
+
+        let mut game_state = GameState::from_game(game);
+        game_state.show_labels = config.show_labels;
+        game_state.grid = config.grid;
+        game_state.hud_scale = clamp_hud_scale(config.hud_scale.unwrap_or(1.0));
+        let (keys, warnings) = KeyBindings::from_config(&config.keys);
+        for warning in warnings {
+            eprintln!("Key bindings: {}", warning);
+        }
+        game_state.keys = keys;
+        let (theme, warnings) = config.theme.build(config.theme.base);
+        for warning in warnings {
+            eprintln!("Theme: {}", warning);
+        }
+        game_state.theme = theme;
+        game_state.theme_config = config.theme.clone();
+        game_state.fog_of_war = config.fog_of_war;
+        game_state.fill_rule = config.fill_rule;
+        game_state.progress_measure = config.progress_bar;
+        game_state.step_fortify = config.step_fortify;
+        game_state.confirm_attacks = config.confirm_attacks;
+        game_state.hints_enabled = !config.hide_hints;
+        game_state.center_on_turn = config.center_on_turn;
+        game_state.camera.edge_scroll = config.edge_scroll;
+        game_state.label_detail = config.label_detail;
+        game_state.turn_timer = config
+            .turn_time_limit
+            .map(|limit| TurnTimer::new(limit, game_state.game.current_player));
+        Ok(game_state)
+    }
 
-        GameState {
-            territories,
+    fn from_game(game: Game) -> GameState {
+        let mut game_state = GameState {
+            spatial_index: SpatialGrid::new(&game.territories),
+            borders: Borders::new(&game.territories),
+            events_heard: game.events.len(),
+            game,
+            camera: Camera::new(),
+            grid: BackgroundGrid::default(),
+            show_labels: false,
+            hud_scale: 1.0,
+            progress: Vec::new(),
+            progress_measure: ProgressMeasure::default(),
+            theme: Theme::light(),
+            theme_config: ThemeConfig::default(),
+            keys: KeyBindings::default(),
+            show_continents: true,
+            fog_of_war: false,
+            fill_rule: FillRule::default(),
+            turn_timer: None,
+            ai_turn: None,
+            ai_wait: 0.0,
+            paused: false,
+            pause_menu: None,
+            quit_requested: false,
+            spectating: false,
+            show_adjacency: false,
+            show_debug: false,
+            step_fortify: false,
+            hints_enabled: true,
+            center_on_turn: false,
+            turn_shown: None,
+            search: None,
+            context_menu: None,
+            inspecting: None,
+            route: None,
+            last_click: None,
+            click_debounce: ClickDebounce::default(),
+            attack_drag: None,
+            hold: None,
+            pending_click: None,
+            alliance_offers: HashSet::new(),
+            screen_size: Vec2::ZERO,
             selected_territory: None,
+            hovered_territory: None,
+            pending_move: None,
+            pending_attack: None,
+            confirm_attacks: false,
+            label_detail: LabelDetail::default(),
+            objectives: Vec::new(),
+            challenger: PlayerId(0),
+            pending_placements: BTreeMap::new(),
+            fortify_steps: Vec::new(),
+            focused_territory: None,
+            attack_target: None,
+            status_message: None,
+            warning: None,
+            combat_animation: None,
+            movements: Vec::new(),
+            flash_timers: BTreeMap::new(),
+            pending_sounds: Vec::new(),
+            board_cache: BoardCache::default(),
+            log_scroll: 0,
+            replay: Replay::default(),
+        };
+        game_state.begin_replay();
+        game_state
+    }
+
+    // Skips the opening turn ahead to the phase named, or carries on with
+    // the usual start when the name isn't a phase the game can begin in
+    fn start_in_phase(&mut self, name: &str) {
+        let started = match Phase::from_name(name) {
+            Some(phase) => self.game.skip_to_phase(phase),
+            None => false,
+        };
+        if started {
+            self.events_heard = self.game.events.len();
+        } else {
+            eprintln!(
+                "Can't start in the '{}' phase; starting as usual (choose reinforce, attack or fortify, outside a draft)",
+                name
+            );
         }
     }
 
+    // Swaps in a saved position, dropping anything tied to the old board
+    fn restore(&mut self, snapshot: &GameSnapshot) -> Result<(), MapLoadError> {
+        self.game.restore(snapshot)?;
+        self.spatial_index = SpatialGrid::new(&self.game.territories);
+        self.borders = Borders::new(&self.game.territories);
+        self.selected_territory = self.game.territories.iter().position(|t| t.selected);
+        self.hovered_territory = None;
+        self.pending_move = None;
+        self.pending_attack = None;
+        self.focused_territory = None;
+        self.attack_target = None;
+        self.fortify_steps.clear();
+        self.inspecting = None;
+        self.route = None;
+        self.combat_animation = None;
+        self.movements.clear();
+        self.flash_timers.clear();
+        self.ai_turn = None;
+        self.events_heard = self.game.events.len();
+        self.log_scroll = 0;
+        self.pending_placements.clear();
+        self.alliance_offers.clear();
+        self.begin_replay();
+        Ok(())
+    }
+
+    // Back to where this game started, with the same dice to come, for a
+    // rematch or to try a plan again. The starting position is the one the
+    // replay was recorded from, so a scenario or dealt armies come back too.
+    fn reset(&mut self) -> Result<(), MapLoadError> {
+        let replay = self.replay.clone();
+        replay.play_to(self, 0)?;
+        self.begin_replay();
+        self.status_message = None;
+        self.warning = None;
+        self.pause_menu = None;
+        self.paused = false;
+        self.pending_sounds.clear();
+        let current = self.game.current_player;
+        if let Some(timer) = &mut self.turn_timer {
+            timer.restart(current);
+        }
+        Ok(())
+    }
+
+    // Asks first, since the game so far is lost
+    fn request_reset(&mut self) {
+        if self
+            .warning
+            .as_ref()
+            .is_some_and(|(message, _)| message == RESET_PROMPT)
+        {
+            if let Err(err) = self.reset() {
+                self.status_message = Some(format!("Restart failed: {}", err));
+            }
+        } else {
+            self.warn(RESET_PROMPT);
+        }
+    }
+
+    fn load_from_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), SaveError> {
+        let snapshot = GameSnapshot::load_from_file(path)?;
+        self.restore(&snapshot).map_err(SaveError::Map)
+    }
+
+    fn save_game(&mut self) {
+        self.status_message = Some(match self.game.save_to_file(SAVE_PATH) {
+            Ok(()) => format!("Game saved to {}", SAVE_PATH),
+            Err(err) => format!("Save failed: {}", err),
+        });
+    }
+
+    // Everything counted so far, to print or write out
+    fn statistics(&self) -> GameStatistics {
+        self.game.statistics()
+    }
+
+    fn save_statistics(&mut self) {
+        let written = serde_json::to_string_pretty(&self.statistics())
+            .map_err(io::Error::other)
+            .and_then(|json| fs::write(STATISTICS_PATH, json + "\n"));
+        self.status_message = Some(match written {
+            Ok(()) => format!("Statistics saved to {}", STATISTICS_PATH),
+            Err(err) => format!("Saving statistics failed: {}", err),
+        });
+    }
+
+    fn load_game(&mut self) {
+        self.status_message = Some(match self.load_from_file(SAVE_PATH) {
+            Ok(()) => format!("Game loaded from {}", SAVE_PATH),
+            Err(err) => format!("Load failed: {}", err),
+        });
+    }
+
+    // Ending a phase or undoing also drops the selection, which belongs to
+    // the phase it was made in
+    fn next_phase(&mut self) {
+        self.game.next_phase();
+        self.clear_selection();
+    }
+
+    // Drops anything half-done on the board first, since the player it
+    // belonged to may be the one leaving
+    fn concede(&mut self, player: PlayerId) -> bool {
+        self.pending_placements.clear();
+        self.pending_move = None;
+        self.pending_attack = None;
+        self.combat_animation = None;
+        // A computer's turn in progress ends with it
+        self.ai_turn = None;
+        self.clear_selection();
+        self.game.concede(player)
+    }
+
+    fn undo(&mut self) -> bool {
+        let undone = self.game.undo();
+        if undone {
+            self.movements.clear();
+            self.clear_selection();
+        }
+        undone
+    }
+
+    // Whether anything on the board is in motion, so frames shouldn't be
+    // held back
+    fn is_animating(&self) -> bool {
+        self.combat_animation.is_some()
+            || !self.movements.is_empty()
+            || !self.flash_timers.is_empty()
+    }
+
+    fn run_ai_turn(&mut self, player: PlayerId) {
+        self.clear_selection();
+        self.game.run_ai_turn(player);
+    }
+
+    // One decision by the computer player to move, with its conquests and
+    // fortifies shown travelling. The whole turn goes into the replay as it
+    // starts, since it replays exactly. Returns whether the turn is over.
+    fn ai_step(&mut self) -> bool {
+        let player = self.game.current_player;
+        if self.ai_turn != Some(player) {
+            self.ai_turn = Some(player);
+            self.replay.record(ReplayAction::AiTurn { player });
+            self.clear_selection();
+        }
+        let seen = self.game.events.len();
+        let done = self.game.ai_step(player);
+        for i in seen..self.game.events.len() {
+            match self.game.events[i] {
+                GameEvent::Attacked {
+                    from,
+                    to,
+                    conquered: true,
+                    ..
+                } => self.animate_move(from, to, self.game.territories[to].armies),
+                GameEvent::Fortified {
+                    from, to, count, ..
+                } => self.animate_move(from, to, count),
+                _ => {}
+            }
+        }
+        if done {
+            self.ai_turn = None;
+        }
+        done
+    }
+
+    // Combat sounds and conquest flashes follow the game's events, so
+    // computer turns and blitzes are heard and seen as well
+    fn queue_event_effects(&mut self) {
+        let start = self.events_heard.min(self.game.events.len());
+        for i in start..self.game.events.len() {
+            if let GameEvent::Attacked { to, conquered, .. } = self.game.events[i] {
+                self.pending_sounds.push(SoundEffect::Dice);
+                if conquered {
+                    self.pending_sounds.push(SoundEffect::Conquest);
+                    self.flash(to);
+                }
+            }
+        }
+        self.events_heard = self.game.events.len();
+    }
+
+    // Picks the topmost territory under a point. Territories are drawn in
+    // list order, so later ones are painted over earlier ones; where polygons
+    // overlap the one the player can actually see (the last drawn) wins.
+    fn territory_at(&self, world_point: Vec2) -> Option<usize> {
+        self.spatial_index
+            .candidates(world_point)
+            .iter()
+            .rev()
+            .copied()
+            .find(|&i| self.game.territories[i].contains_in_bounds(world_point, self.fill_rule))
+    }
+
+    // Smallest world box around everything `player` holds, however spread
+    // out it is; None once they hold nothing
+    fn player_bounds(&self, player: PlayerId) -> Option<(Vec2, Vec2)> {
+        self.game
+            .territories
+            .iter()
+            .filter(|t| t.owner == Some(player))
+            .map(|t| (t.min, t.max))
+            .reduce(|(min, max), (t_min, t_max)| (min.min(t_min), max.max(t_max)))
+    }
+
+    // Glides the view to fit the current player's territories
+    fn show_current_player(&mut self) {
+        if let Some((min, max)) = self.player_bounds(self.game.current_player) {
+            let screen = vec2(screen_width(), screen_height());
+            let (offset, zoom) = Camera::fit_target(min, max, screen, FIT_MARGIN);
+            self.camera.move_to(offset, zoom, GLIDE_SECONDS);
+        }
+    }
+
+    // With `center_on_turn` on, each human's turn opens on their own
+    // territories
+    fn follow_turn(&mut self) {
+        let current = self.game.current_player;
+        if self.turn_shown == Some(current) {
+            return;
+        }
+        self.turn_shown = Some(current);
+        if self.center_on_turn && !self.game.player(current).is_ai {
+            self.show_current_player();
+        }
+    }
+
+    // Keeps the same part of the map centred, scaled with the window, when
+    // it's resized
+    fn follow_resize(&mut self) {
+        let screen = vec2(screen_width(), screen_height());
+        if screen != self.screen_size {
+            self.camera.resize(self.screen_size, screen);
+            self.screen_size = screen;
+        }
+    }
+
+    fn update_hover(&mut self) {
+        let mouse_position = self.camera.screen_to_world(mouse_position().into());
+        self.hovered_territory = self.territory_at(mouse_position);
+    }
+
+    // Lets computer players take their turn, a step at a time so it can be
+    // watched; humans act through handle_input
+    fn update(&mut self, dt: f32) {
+        self.follow_turn();
+        if self.paused || self.game.winner().is_some() {
+            return;
+        }
+        if !self.game.player(self.game.current_player).is_ai {
+            return;
+        }
+        self.ai_wait -= dt;
+        if self.ai_wait > 0.0 || self.is_animating() {
+            return;
+        }
+        self.ai_wait = AI_STEP_SECONDS;
+        self.ai_step();
+    }
+
+    // Moves every animation on by `dt` real seconds, so they take as long at
+    // 30 frames a second as at 240. Pausing holds all but the camera.
+    fn advance_animations(&mut self, dt: f32) {
+        self.camera.advance_glide(dt);
+        if self.paused {
+            return;
+        }
+        self.update_combat_animation(dt, false);
+        self.update_movements(dt);
+        self.update_flashes(dt);
+    }
+
     fn handle_input(&mut self) {
-        if is_mouse_button_pressed(MouseButton::Left) {
-            let mouse_position = mouse_position().into();
+        // Scrolling over the selected territory in Reinforce buffers armies
+        // for it rather than zooming
+        self.camera.update(self.wheel_placement_target().is_none());
+        self.update_hover();
+        // Read every frame, even ones that go on to ignore it, so a press
+        // held through a menu closing doesn't land on the board after. A
+        // click let go within the frame still counts as down for it.
+        let down =
+            is_mouse_button_down(MouseButton::Left) || is_mouse_button_pressed(MouseButton::Left);
+        let click = self.click_debounce.click(down, get_time());
+        if click {
+            self.pending_click = None;
+        }
+        self.update_hold(down, get_time());
+
+        // Once the game is won the board is only there to look at
+        if self.game.winner().is_some() {
+            self.search = None;
+            return;
+        }
+
+        if self.handle_pause_menu_input() {
+            return;
+        }
+        // Letters typed into the search box below aren't shortcuts
+        let typing = self.is_typing();
+        if !typing && (is_key_pressed(KeyCode::Pause) || self.keys.pressed(Action::Pause)) {
+            self.paused = !self.paused;
+        }
+        if self.paused {
+            return;
+        }
+
+        if !typing && self.keys.pressed(Action::Spectate) {
+            self.spectating = !self.spectating;
+            self.clear_selection();
+        }
+        if self.spectating {
+            if click {
+                self.handle_minimap_click(self.hud_mouse());
+            }
+            return;
+        }
+        // The board is the computer's while it plays its turn
+        if self.game.player(self.game.current_player).is_ai {
+            return;
+        }
+        if self.handle_search_input() {
+            return;
+        }
+        if self.handle_context_menu_input() {
+            return;
+        }
+
+        // The board is frozen while a roll is on screen; a click skips it
+        if self.combat_animation.is_some() {
+            if click {
+                self.update_combat_animation(0.0, true);
+            }
+            return;
+        }
+
+        // Likewise while choosing how many armies to move
+        if self.pending_move.is_some() {
+            self.handle_pending_move_input();
+            return;
+        }
+        if self.pending_attack.is_some() {
+            self.handle_pending_attack_input();
+            return;
+        }
+
+        if self.handle_attack_target_input() || self.handle_controller_input() {
+            return;
+        }
+
+        if self.keys.pressed(Action::EndPhase) || is_key_pressed(KeyCode::Enter) {
+            self.end_phase();
+        }
+        if is_key_pressed(KeyCode::Escape) {
+            self.escape();
+        }
+        let ctrl = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl);
+        let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+        if ctrl && self.keys.pressed(Action::Undo) {
+            self.request_undo();
+        }
+        if self.keys.pressed(Action::TradeCards) {
+            self.trade_cards();
+        }
+        if self.keys.pressed(Action::Alliance) {
+            self.toggle_alliance();
+        }
+        if self.game.phase == Phase::Reinforce {
+            self.handle_quick_reinforce();
+            self.handle_wheel_placement();
+            if self.keys.pressed(Action::Distribute) {
+                self.auto_reinforce();
+                return;
+            }
+        }
+        if self.keys.pressed(Action::HistoryUp) {
+            self.scroll_log(1);
+        }
+        if self.keys.pressed(Action::HistoryDown) {
+            self.scroll_log(-1);
+        }
+        if self.keys.pressed(Action::ToggleLabels) {
+            self.show_labels = !self.show_labels;
+        }
+        if self.keys.pressed(Action::ToggleContinents) {
+            self.show_continents = !self.show_continents;
+        }
+        if self.keys.pressed(Action::Reset) {
+            self.request_reset();
+        }
+        if self.keys.pressed(Action::ToggleTheme) {
+            self.toggle_theme();
+        }
+        if self.keys.pressed(Action::ToggleGrid) {
+            self.grid.enabled = !self.grid.enabled;
+        }
+        if self.keys.pressed(Action::PlanRoute) {
+            self.toggle_route_planner();
+        }
+        if self.keys.pressed(Action::ShowTerritories) {
+            self.show_current_player();
+        }
+        if self.keys.pressed(Action::AdjacencyOverlay) {
+            self.show_adjacency = !self.show_adjacency;
+        }
+        if shift && self.keys.pressed(Action::Hint) {
+            self.hints_enabled = !self.hints_enabled;
+            self.status_message = Some(if self.hints_enabled {
+                format!("Hints on (hold {})", self.keys.name(Action::Hint))
+            } else {
+                "Hints off".to_string()
+            });
+        }
+        if self.keys.pressed(Action::StepFortify) {
+            self.step_fortify = !self.step_fortify;
+            self.status_message = Some(
+                if self.step_fortify {
+                    "Fortify: one army per click"
+                } else {
+                    "Fortify: choose how many to move"
+                }
+                .to_string(),
+            );
+        }
 
-            let mut newly_selected_territory: Option<usize> = None;
+        if self.keys.pressed(Action::Save) {
+            self.save_game();
+        }
+        if self.keys.pressed(Action::Load) {
+            self.load_game();
+        }
+        if self.keys.pressed(Action::SaveStatistics) {
+            self.save_statistics();
+        }
+        if self.keys.pressed(Action::ExportImage) {
+            self.export_board_image();
+        }
 
-            for (i, territory) in self.territories.iter_mut().enumerate() {
-                if territory.is_point_inside(mouse_position) {
-                    newly_selected_territory = Some(i);
+        if click && (self.handle_hud_button_click() || self.handle_minimap_click(self.hud_mouse()))
+        {
+            return;
+        }
+
+        if is_mouse_button_pressed(MouseButton::Right) {
+            let world = self.camera.screen_to_world(mouse_position().into());
+            if let Some(territory) = self.territory_at(world) {
+                if self.step_back(territory) {
+                    return;
                 }
+                self.open_context_menu(territory, self.hud_mouse());
+                return;
             }
+        }
 
-            if let Some(selected) = self.selected_territory {
-                self.territories[selected].selected = false;
+        if click {
+            // Territories live in world space, so hit testing must undo the camera
+            let mouse_position = self.camera.screen_to_world(mouse_position().into());
+
+            match self.territory_at(mouse_position) {
+                // Clicks only pick the ends of a route while planning one
+                Some(clicked) if self.route.is_some() => self.click_route(clicked),
+                Some(clicked) => self.press_territory(clicked, get_time()),
+                None => self.clear_selection(),
             }
+        }
+        // After the press, for a click let go within the same frame
+        if is_mouse_button_released(MouseButton::Left) {
+            self.release_press(shift);
+        }
+    }
+
+    // A press on the board. What it does waits for the release, so holding
+    // still to see the neighbours doesn't place or select anything. The
+    // second click of a double click only opens the territory's details; the
+    // first has already acted on it.
+    fn press_territory(&mut self, clicked: usize, now: f64) {
+        if self.is_double_click(clicked, now) {
+            self.inspect(clicked);
+            return;
+        }
+        self.pending_click = Some(clicked);
+        // Holding on and dragging to a neighbour attacks it too
+        self.begin_attack_drag(clicked);
+    }
 
-            if let Some(i) = newly_selected_territory {
-                self.territories[i].selected = true;
-                self.selected_territory = Some(i);
+    // Letting go attacks if the press was dragged onto a target, and is
+    // otherwise the click the press started, if it ends where it began
+    fn release_press(&mut self, blitz: bool) {
+        let pressed = self.pending_click.take();
+        if self.finish_attack_drag(blitz) {
+            return;
+        }
+        if let Some(pressed) = pressed.filter(|&t| self.hovered_territory == Some(t)) {
+            self.click_territory(pressed, blitz);
+        }
+    }
+
+    // What clicking (or pressing A on) a territory does in the current phase;
+    // `blitz` fights an attack out in one go
+    fn click_territory(&mut self, clicked: usize, blitz: bool) {
+        if self.game.phase == Phase::Claim {
+            if self.game.claim_territory(clicked) {
+                self.replay
+                    .record(ReplayAction::ClaimTerritory { territory: clicked });
+            } else {
+                self.warn("That territory has already been claimed");
             }
+            return;
         }
+        if self.game.phase == Phase::InitialPlacement {
+            if self.game.place_initial_army(clicked) {
+                self.replay
+                    .record(ReplayAction::PlaceInitialArmy { territory: clicked });
+            } else {
+                self.warn("Place your armies on your own territories");
+            }
+            return;
+        }
+        if self.game.territories[clicked].owner == Some(self.game.current_player) {
+            // In Fortify a second friendly click picks the destination
+            if self.game.phase == Phase::Fortify {
+                if let Some(source) = self.selected_territory {
+                    if source != clicked {
+                        if let Err(err) = self.game.check_fortify_route(source, clicked) {
+                            self.warn(err.to_string());
+                        } else if self.step_fortify {
+                            self.fortify_step(source, clicked);
+                        } else {
+                            self.begin_fortify_move(source, clicked);
+                        }
+                        return;
+                    }
+                }
+            }
+            self.select(clicked);
+            if self.game.phase == Phase::Reinforce && self.unbuffered_reinforcements() > 0 {
+                self.replay
+                    .record(ReplayAction::PlaceReinforcement { territory: clicked });
+                self.game.place_reinforcement(clicked);
+            }
+            return;
+        }
+
+        // Enemy territories can only be interacted with by attacking them
+        // from a selected bordering territory
+        match self.game.phase {
+            Phase::Reinforce => self.warn("You can only reinforce your own territories"),
+            Phase::Fortify => self.warn(FortifyError::NotOwned.to_string()),
+            Phase::Attack if self.selected_territory.is_none() => {
+                self.warn("Select one of your territories to attack from")
+            }
+            _ => {}
+        }
+        if self.game.phase == Phase::Attack {
+            if let Some(attacker) = self.selected_territory {
+                if let Err(err) = self.game.check_attack(attacker, clicked) {
+                    self.warn(err.to_string());
+                    return;
+                }
+                self.request_attack(attacker, clicked, blitz);
+            }
+        }
+    }
+
+    // Rolls the dice for an attack already checked to be legal. Shift-click
+    // (`blitz`) skips the dice animation and fights it out.
+    fn launch_attack(&mut self, attacker: usize, defender: usize, blitz: bool) {
+        if blitz {
+            self.replay
+                .record(ReplayAction::Blitz { attacker, defender });
+            let summary = self.game.blitz_attack(attacker, defender);
+            if summary.conquered {
+                self.begin_conquest_move(attacker, defender);
+            }
+            self.status_message = Some(format!(
+                "Blitz: {} round(s), attacker lost {}, defender lost {}{}",
+                summary.rounds,
+                summary.attacker_losses,
+                summary.defender_losses,
+                if summary.conquered {
+                    ", territory conquered!"
+                } else {
+                    ""
+                }
+            ));
+        } else {
+            self.replay
+                .record(ReplayAction::Attack { attacker, defender });
+            self.start_attack_animation(attacker, defender);
+        }
+    }
+
+    // Flashed in the middle of the screen for a few seconds
+    fn warn(&mut self, message: impl Into<String>) {
+        self.warning = Some((message.into(), WARNING_SECONDS));
     }
 
-    fn draw_map(&self) {
-        for territory in &self.territories {
-            territory.draw();
+    fn update_warning(&mut self, dt: f32) {
+        if let Some((_, remaining)) = &mut self.warning {
+            *remaining -= dt;
+            if *remaining <= 0.0 {
+                self.warning = None;
+            }
         }
+    }
+
+    // Fades out over its last second
+    fn draw_warning(&self) {
+        let Some((message, remaining)) = &self.warning else {
+            return;
+        };
+        let alpha = remaining.min(1.0);
+        let dimensions = measure_text(message, None, 26, 1.0);
+        let (width, height) = (dimensions.width + 24.0, 40.0);
+        let (x, y) = ((self.hud_size().x - width) / 2.0, self.hud_size().y * 0.25);
+        let color = Color {
+            a: alpha,
+            ..self.theme.warning
+        };
+        draw_rectangle(x, y, width, height, self.theme.panel_at(0.95 * alpha));
+        draw_rectangle_lines(x, y, width, height, 2.0, color);
+        draw_text(
+            message,
+            x + 12.0,
+            y + height / 2.0 + dimensions.offset_y / 2.0,
+            26.0,
+            color,
+        );
+    }
 
+    // Number keys drop that many armies on the selected territory at once
+    fn handle_quick_reinforce(&mut self) {
+        const NUMBER_KEYS: [KeyCode; 9] = [
+            KeyCode::Key1,
+            KeyCode::Key2,
+            KeyCode::Key3,
+            KeyCode::Key4,
+            KeyCode::Key5,
+            KeyCode::Key6,
+            KeyCode::Key7,
+            KeyCode::Key8,
+            KeyCode::Key9,
+        ];
+        let Some(count) = NUMBER_KEYS.iter().position(|&key| is_key_pressed(key)) else {
+            return;
+        };
+        let Some(territory) = self.selected_territory else {
+            return;
+        };
+        let available = self.unbuffered_reinforcements();
+        if available <= 0 {
+            self.warn("No reinforcements left to place");
+            return;
+        }
+        let placed = self
+            .game
+            .place_reinforcements(territory, (count as i32 + 1).min(available));
+        for _ in 0..placed {
+            self.replay
+                .record(ReplayAction::PlaceReinforcement { territory });
+        }
+    }
+
+    // Reinforcements have to be placed before the turn can move on; any the
+    // wheel buffered are placed first
+    fn end_phase(&mut self) {
+        if self.game.phase == Phase::Reinforce && self.commit_pending_placements() {
+            self.clear_selection();
+        } else if self.can_end_phase() {
+            self.replay.record(ReplayAction::EndPhase);
+            self.next_phase();
+        } else {
+            self.warn(format!(
+                "Place your {} remaining reinforcement(s) first",
+                self.game.reinforcements_remaining
+            ));
+        }
+    }
+
+    fn can_end_phase(&self) -> bool {
+        match self.game.phase {
+            Phase::Claim | Phase::InitialPlacement => false,
+            Phase::Reinforce => {
+                !self.pending_placements.is_empty() || self.game.reinforcements_placeable() == 0
+            }
+            Phase::Attack | Phase::Fortify => true,
+        }
+    }
+
+    fn request_undo(&mut self) {
+        if self.undo() {
+            self.replay.record(ReplayAction::Undo);
+        } else {
+            self.warn("Nothing to undo");
+        }
+    }
+
+    fn trade_cards(&mut self) {
+        let player = self.game.current_player;
+        let result = match self.game.find_set(player) {
+            Some(cards) => {
+                self.replay
+                    .record(ReplayAction::TradeCards { player, cards });
+                self.game
+                    .trade_cards(player, cards)
+                    .map_err(|err| err.to_string())
+            }
+            None => Err("No set of cards to trade".to_string()),
+        };
+        match result {
+            Ok(bonus) => self.status_message = Some(format!("Traded cards for {} armies", bonus)),
+            Err(message) => self.warn(message),
+        }
+    }
+
+    // Places anything the wheel buffered, then lets the game scatter the rest
+    fn auto_reinforce(&mut self) {
+        self.commit_pending_placements();
+        if self.game.phase != Phase::Reinforce {
+            return;
+        }
+        let player = self.game.current_player;
+        self.replay.record(ReplayAction::AutoReinforce { player });
+        self.game.auto_reinforce(player);
+        self.clear_selection();
+    }
+
+    fn select(&mut self, territory: usize) {
+        self.clear_selection();
+        self.game.board_dirty = true;
+        self.pending_sounds.push(SoundEffect::Click);
+        self.game.territories[territory].selected = true;
+        self.selected_territory = Some(territory);
+    }
+
+    fn clear_selection(&mut self) {
+        if let Some(selected) = self.selected_territory.take() {
+            self.game.territories[selected].selected = false;
+            self.game.board_dirty = true;
+        }
+        self.pending_move = None;
+        self.attack_target = None;
+        self.attack_drag = None;
+        self.fortify_steps.clear();
+    }
+
+    // The static part of the map, drawn into the board cache
+    // `screen_camera` is where the layer is being drawn, for switching back
+    // to after the world-space fills
+    // Returns how many territories were skipped for being off screen
+    fn draw_board_layer(&self, screen_camera: &Camera2D) -> usize {
+        self.draw_background_grid();
+        self.draw_continent_tints();
+        self.draw_sea_routes();
+        self.draw_fills(screen_camera);
+
+        // Outlines, counts and labels are only drawn for territories near
+        // enough the view to show; the margin is for counts and names that
+        // hang over the edge of their territory
+        let screen = vec2(screen_width(), screen_height());
+        let on_screen: Vec<bool> = self
+            .game
+            .territories
+            .iter()
+            .map(|t| self.camera.sees(t.min, t.max, screen, CULL_MARGIN))
+            .collect();
+
+        // Fog of war: a hidden territory's outline is known but not who
+        // holds it or with what
+        let viewer = self.game.current_player;
+        let mut strokes = Strokes::default();
+        for (i, territory) in self.game.territories.iter().enumerate() {
+            if !on_screen[i] {
+                continue;
+            }
+            if self.visible_to(viewer, i) {
+                territory
+                    .draw_details(territory.owner.map(|id| self.game.player(id)), &self.camera);
+            }
+            let outline = if territory.selected {
+                self.theme.selection
+            } else {
+                darken(self.fill_seen_by(viewer, i), 0.6)
+            };
+            territory.add_borders(&mut strokes, &self.camera, outline, self.borders.of(i));
+        }
+        strokes.draw();
+        self.draw_river_borders(&on_screen);
+        self.draw_alliance_borders(&on_screen);
+
+        let detail = self.label_detail.at(self.camera.zoom);
+        let count_scale = match detail {
+            Detail::Full => self.hud_scale,
+            _ => self.hud_scale * SMALL_COUNT_SCALE,
+        };
+        for (i, territory) in self.game.territories.iter().enumerate() {
+            if !on_screen[i] {
+                continue;
+            }
+            let fill = self.fill_seen_by(viewer, i);
+            if territory.is_capital {
+                territory.draw_capital_marker(&self.camera, &self.theme);
+            }
+            territory.draw_terrain_marker(&self.camera, &self.theme);
+            if detail == Detail::Bare {
+                continue;
+            }
+            if !self.visible_to(viewer, i) {
+                territory.draw_army_count("?", fill, &self.camera, count_scale);
+                continue;
+            }
+            // Armies still on their way in are counted when they arrive
+            let armies = (territory.armies - self.arriving(i)).to_string();
+            if territory.selected {
+                let shown = lighten(fill, SELECTED_WASH);
+                territory.draw_army_count(&armies, shown, &self.camera, count_scale);
+            } else {
+                territory.draw_army_count(&armies, fill, &self.camera, count_scale);
+            }
+        }
+        if self.show_labels && detail == Detail::Full {
+            for i in (0..self.game.territories.len()).filter(|&i| on_screen[i]) {
+                self.game.territories[i]
+                    .draw_name_label(self.fill_seen_by(viewer, i), &self.camera);
+            }
+        }
+        on_screen.iter().filter(|&&shown| !shown).count()
+    }
+
+    fn draw_map(&mut self) {
+        self.follow_resize();
+        self.draw_board();
+
+        // With nothing selected in the Attack phase, glow every territory
+        // an attack could start from
+        if self.game.phase == Phase::Attack && self.selected_territory.is_none() {
+            let glow = Color {
+                a: ATTACK_SOURCE_ALPHA,
+                ..self.theme.attack_source
+            };
+            for source in self.game.valid_attack_sources(self.game.current_player) {
+                self.game.territories[source].draw_outline(&self.camera, glow, 6.0);
+            }
+        }
+
+        // Neighbours of the selection get a highlighted border (in the Attack
+        // phase, only the ones it can actually attack, as targets), then the
+        // selected territory's own border is redrawn so it stays on top
+        if let Some(selected_index) = self.selected_territory {
+            let selected = &self.game.territories[selected_index];
+            for &neighbour in &selected.adjacent {
+                if self.game.phase != Phase::Attack {
+                    self.game.territories[neighbour].draw_outline(
+                        &self.camera,
+                        self.theme.highlight,
+                        3.0,
+                    );
+                } else if self.game.can_attack(selected_index, neighbour) {
+                    self.game.territories[neighbour].draw_outline(
+                        &self.camera,
+                        self.theme.attack_target,
+                        3.0,
+                    );
+                }
+            }
+            if let Some(pending) = self.pending_move {
+                self.game.territories[pending.to].draw_outline(
+                    &self.camera,
+                    self.theme.conquest_preview,
+                    4.0,
+                );
+            }
+            selected.draw_outline(
+                &self.camera,
+                self.theme.selection,
+                SELECTED_OUTLINE_THICKNESS,
+            );
+        }
+        self.draw_attack_target();
+        if let Some(focused) = self.focused_territory {
+            self.game.territories[focused].draw_outline(&self.camera, self.theme.focus, 3.0);
+        }
+        self.draw_fortify_reach();
+        self.draw_pending_placements();
+        self.draw_movements();
+        self.draw_attack_drag();
+        self.draw_adjacency_overlay();
+        self.draw_route();
+        self.draw_hovered_attack();
+        self.draw_hold_ring();
+
+        self.begin_hud();
+        self.draw_cull_stats();
         if let Some(selected_index) = self.selected_territory {
-            let selected = &self.territories[selected_index];
+            let selected = &self.game.territories[selected_index];
             draw_text(
                 &format!("Selected: {}", selected.name),
                 10.0,
                 20.0,
                 30.0,
-                DARKGRAY,
+                self.theme.text,
             );
             draw_text(
                 &format!("Armies: {}", selected.armies),
                 10.0,
                 50.0,
                 30.0,
-                DARKGRAY,
+                self.theme.text,
+            );
+            let owner = self.game.owner_name(selected.owner);
+            draw_text(
+                &format!("Owner: {}", owner),
+                10.0,
+                80.0,
+                30.0,
+                self.theme.text,
+            );
+        }
+
+        self.draw_turn_status();
+        self.draw_turn_timer();
+        self.draw_hand();
+        let log_bottom = self.draw_event_log();
+        let continents_bottom = self.draw_continent_panel(log_bottom + 10.0);
+        let objectives_bottom = self.draw_objectives_panel(continents_bottom + 10.0);
+        self.draw_legend(objectives_bottom + 10.0);
+        self.draw_minimap();
+        self.draw_hud_buttons();
+        self.draw_progress_bar();
+        self.draw_key_help();
+
+        if let Some(combat) = &self.game.last_combat {
+            let mut summary = format!(
+                "Dice: {:?} vs {:?}  Attacker lost {}, defender lost {}",
+                combat.attacker_dice,
+                combat.defender_dice,
+                combat.attacker_losses,
+                combat.defender_losses
+            );
+            if combat.conquered {
+                summary.push_str("  Territory conquered!");
+            }
+            draw_text(
+                &summary,
+                10.0,
+                self.hud_size().y - 20.0,
+                24.0,
+                self.theme.text,
+            );
+        }
+
+        // Small, but there to quote when sharing or reporting a game
+        let seed = format!("Seed: {}", self.game.seed);
+        draw_text(
+            &seed,
+            10.0,
+            self.hud_size().y - 76.0,
+            18.0,
+            self.theme.muted_text,
+        );
+
+        if let Some(message) = &self.status_message {
+            draw_text(
+                message,
+                10.0,
+                self.hud_size().y - 48.0,
+                24.0,
+                self.theme.warning,
+            );
+        }
+
+        self.draw_spectating_banner();
+        self.draw_search();
+        self.draw_hint();
+        self.draw_warning();
+        self.draw_inspect_panel();
+        self.draw_context_menu();
+        self.draw_tooltip();
+        self.draw_attack_tooltip();
+        self.draw_combat_animation();
+        self.draw_pending_move();
+        self.draw_pending_attack();
+        self.draw_game_over();
+        self.draw_pause_menu();
+        self.draw_debug_overlay();
+        set_default_camera();
+    }
+
+    // Where the spectating banner goes across the top, while spectating
+    fn spectating_banner(&self) -> Option<(String, Rect)> {
+        if !self.spectating {
+            return None;
+        }
+        let text = format!(
+            "Spectating: input locked ({} to play)",
+            self.keys.name(Action::Spectate)
+        );
+        let width = measure_text(&text, None, 28, 1.0).width + 24.0;
+        let x = (self.hud_size().x - width) / 2.0;
+        Some((text, Rect::new(x, 10.0, width, 40.0)))
+    }
+
+    fn draw_spectating_banner(&self) {
+        let Some((text, banner)) = self.spectating_banner() else {
+            return;
+        };
+        let dimensions = measure_text(&text, None, 28, 1.0);
+        draw_rectangle(
+            banner.x,
+            banner.y,
+            banner.w,
+            banner.h,
+            self.theme.panel_at(0.8),
+        );
+        draw_text(
+            &text,
+            banner.x + 12.0,
+            banner.y + banner.h / 2.0 + dimensions.offset_y / 2.0,
+            28.0,
+            self.theme.text,
+        );
+    }
+
+    fn draw_game_over(&self) {
+        // A challenge's result takes precedence over who holds the map
+        let (banner, reason) = match (self.check_objectives(), self.game.winner()) {
+            (Some(Outcome::Success), _) => ("Challenge complete!".to_string(), None),
+            (Some(Outcome::Failure(reason)), _) => ("Challenge failed".to_string(), Some(reason)),
+            (None, Some(winner)) if self.game.team_victory => {
+                let team: Vec<&str> = std::iter::once(winner)
+                    .chain(self.game.allies_of(winner))
+                    .filter(|&id| !self.game.is_eliminated(id))
+                    .map(|id| self.game.player(id).name.as_str())
+                    .collect();
+                match team.as_slice() {
+                    [one] => (format!("{} wins!", one), None),
+                    _ => (format!("{} win!", team.join(" and ")), None),
+                }
+            }
+            (None, Some(winner)) => (format!("{} wins!", self.game.player(winner).name), None),
+            (None, None) => return,
+        };
+
+        draw_rectangle(
+            0.0,
+            0.0,
+            self.hud_size().x,
+            self.hud_size().y,
+            self.theme.panel_at(0.75),
+        );
+        let dimensions = measure_text(&banner, None, 60, 1.0);
+        draw_text(
+            &banner,
+            (self.hud_size().x - dimensions.width) / 2.0,
+            self.hud_size().y / 2.0,
+            60.0,
+            self.theme.text,
+        );
+        if let Some(reason) = reason {
+            let dimensions = measure_text(&reason, None, 28, 1.0);
+            draw_text(
+                &reason,
+                (self.hud_size().x - dimensions.width) / 2.0,
+                self.hud_size().y / 2.0 - 60.0,
+                28.0,
+                self.theme.text,
+            );
+        }
+        let hints = [
+            format!(
+                "{}: rematch  {}: choose another map  {}: save statistics",
+                self.keys.name(Action::Reset),
+                self.keys.name(Action::ChooseMap),
+                self.keys.name(Action::SaveStatistics)
+            ),
+            format!(
+                "{}: watch the replay  {}: map editor",
+                self.keys.name(Action::Replay),
+                self.keys.name(Action::Editor)
+            ),
+        ];
+        for (i, hint) in hints.iter().enumerate() {
+            let dimensions = measure_text(hint, None, 22, 1.0);
+            draw_text(
+                hint,
+                (self.hud_size().x - dimensions.width) / 2.0,
+                self.hud_size().y / 2.0 + 30.0 + 24.0 * i as f32,
+                22.0,
+                self.theme.text,
+            );
+        }
+        let statistics = self.statistics().to_string();
+        let lines: Vec<&str> = statistics.lines().map(str::trim).collect();
+        let width = lines
+            .iter()
+            .map(|line| measure_text(line, None, 22, 1.0).width)
+            .fold(0.0, f32::max);
+        for (i, line) in lines.iter().enumerate() {
+            draw_text(
+                line,
+                (self.hud_size().x - width) / 2.0,
+                self.hud_size().y / 2.0 + 96.0 + 24.0 * i as f32,
+                22.0,
+                self.theme.muted_text,
+            );
+        }
+    }
+
+    // Small box next to the cursor describing the territory under it
+    fn draw_tooltip(&self) {
+        let Some(hovered) = self.hovered_territory else {
+            return;
+        };
+        let territory = &self.game.territories[hovered];
+        let mut lines = if self.visible_to(self.game.current_player, hovered) {
+            vec![
+                territory.name.clone(),
+                format!("Owner: {}", self.game.owner_name(territory.owner)),
+                format!("Armies: {}", territory.armies),
+            ]
+        } else {
+            vec![
+                territory.name.clone(),
+                "Owner: unknown".to_string(),
+                "Armies: ?".to_string(),
+            ]
+        };
+        if let Some(odds) = self.attack_odds() {
+            lines.push(format!("Win chance: {:.0}%", odds * 100.0));
+        }
+
+        let font_size = 18.0;
+        let line_height = 20.0;
+        let padding = 6.0;
+        let width = lines
+            .iter()
+            .map(|line| measure_text(line, None, font_size as u16, 1.0).width)
+            .fold(0.0, f32::max)
+            + padding * 2.0;
+        let height = line_height * lines.len() as f32 + padding * 2.0;
+
+        // Sit just below-right of the cursor, flipping back inside the window
+        let Vec2 {
+            x: mouse_x,
+            y: mouse_y,
+        } = self.hud_mouse();
+        let mut x = mouse_x + 16.0;
+        let mut y = mouse_y + 16.0;
+        if x + width > self.hud_size().x {
+            x = mouse_x - width - 4.0;
+        }
+        if y + height > self.hud_size().y {
+            y = mouse_y - height - 4.0;
+        }
+
+        draw_rectangle(x, y, width, height, self.theme.panel_at(0.95));
+        draw_rectangle_lines(x, y, width, height, 1.0, self.theme.panel_border);
+        for (i, line) in lines.iter().enumerate() {
+            draw_text(
+                line,
+                x + padding,
+                y + padding + line_height * (i as f32 + 0.8),
+                font_size,
+                self.theme.text,
+            );
+        }
+    }
+
+    // While the pointer is over a territory the selection could attack: the
+    // chance of taking it by attacking until one side is done
+    fn attack_odds(&self) -> Option<f32> {
+        let (attacker, defender) = (self.selected_territory?, self.hovered_territory?);
+        (self.game.phase == Phase::Attack && self.game.can_attack(attacker, defender))
+            .then(|| self.game.conquest_odds(attacker, defender))
+    }
+
+    fn draw_turn_status(&self) {
+        let player = self.game.player(self.game.current_player);
+        let status = format!("{}: {} phase", player.name, self.game.phase);
+        let dimensions = measure_text(&status, None, 30, 1.0);
+        draw_text(
+            &status,
+            self.hud_size().x - dimensions.width - 10.0,
+            30.0,
+            30.0,
+            player.color,
+        );
+        let placing = self.game.phase == Phase::InitialPlacement;
+        if self.game.phase == Phase::Claim || placing {
+            let hint = if placing {
+                format!(
+                    "Click one of your territories to place an army ({} left)",
+                    self.game.initial_armies[self.game.current_player.0]
+                )
+            } else {
+                "Click an unowned territory to claim it".to_string()
+            };
+            let dimensions = measure_text(&hint, None, 20, 1.0);
+            draw_text(
+                &hint,
+                self.hud_size().x - dimensions.width - 10.0,
+                55.0,
+                20.0,
+                self.theme.text,
+            );
+        }
+        if self.game.phase == Phase::Reinforce {
+            let buffered = self.game.reinforcements_remaining - self.unbuffered_reinforcements();
+            let hint = if buffered > 0 {
+                format!(
+                    "Reinforcements left: {} ({} to place on {})",
+                    self.game.reinforcements_remaining,
+                    buffered,
+                    self.keys.name(Action::EndPhase)
+                )
+            } else {
+                format!(
+                    "Reinforcements left: {}",
+                    self.game.reinforcements_remaining
+                )
+            };
+            let dimensions = measure_text(&hint, None, 20, 1.0);
+            draw_text(
+                &hint,
+                self.hud_size().x - dimensions.width - 10.0,
+                55.0,
+                20.0,
+                self.theme.text,
             );
         }
     }
+
+    fn draw_key_help(&self) {
+        let key = |action| self.keys.name(action);
+        let key_help = [
+            format!("{}/Enter: end phase", key(Action::EndPhase)),
+            "Esc: close details/route, deselect, or menu".to_string(),
+            "Up/Down: armies to move".to_string(),
+            "Arrows/D-pad: move cursor  Enter/A: act".to_string(),
+            format!(
+                "{}: next attack target  Enter: attack it",
+                key(Action::CycleTarget)
+            ),
+            format!("{}: save  {}: load", key(Action::Save), key(Action::Load)),
+            format!("{}: save statistics", key(Action::SaveStatistics)),
+            format!("{}: export the board as SVG", key(Action::ExportImage)),
+            format!("{} twice: restart this game", key(Action::Reset)),
+            "1-9: place that many armies".to_string(),
+            format!("{}: distribute the rest randomly", key(Action::Distribute)),
+            "Wheel on selection: buffer armies".to_string(),
+            "Shift+click: blitz attack".to_string(),
+            "Drag to a neighbour: attack it".to_string(),
+            "Hold on a territory: show its neighbours".to_string(),
+            "Right-click: territory actions".to_string(),
+            "Double-click: territory details".to_string(),
+            format!("{}: fortify one army per click", key(Action::StepFortify)),
+            format!("Hold {0}: hint  Shift+{0}: hints on/off", key(Action::Hint)),
+            format!("Ctrl+{}: undo", key(Action::Undo)),
+            format!("{}: trade cards", key(Action::TradeCards)),
+            format!(
+                "{}: offer, accept or break an alliance with the hovered owner",
+                key(Action::Alliance)
+            ),
+            format!("{}: toggle names", key(Action::ToggleLabels)),
+            format!("{}: toggle continents", key(Action::ToggleContinents)),
+            format!("{}: background grid", key(Action::ToggleGrid)),
+            format!("{}: light/dark theme", key(Action::ToggleTheme)),
+            format!(
+                "{}: plan a route (click two territories)",
+                key(Action::PlanRoute)
+            ),
+            format!("{}: adjacency overlay", key(Action::AdjacencyOverlay)),
+            format!("{}: frame rate and counters", key(Action::DebugOverlay)),
+            format!("{}: mute", key(Action::Mute)),
+            format!(
+                "{}/{}: text size",
+                key(Action::TextBigger),
+                key(Action::TextSmaller)
+            ),
+            format!(
+                "{}/{}: scroll history",
+                key(Action::HistoryUp),
+                key(Action::HistoryDown)
+            ),
+            format!("{}: find a territory by name", key(Action::Search)),
+            format!("{}: map editor", key(Action::Editor)),
+            format!("{}: replay viewer", key(Action::Replay)),
+            format!("{}: choose another map", key(Action::ChooseMap)),
+            format!("Pause/{}: pause", key(Action::Pause)),
+            format!("{}: spectate (lock input)", key(Action::Spectate)),
+            "Middle drag: pan".to_string(),
+            "Wheel: zoom".to_string(),
+            "Click minimap: recenter".to_string(),
+            format!("{}: show your territories", key(Action::ShowTerritories)),
+        ];
+        // Bottom-up in the right corner below the rest of the right-hand HUD,
+        // spilling into more columns leftwards when the window is short
+        let rows = ((self.hud_size().y - RIGHT_HUD_BOTTOM) / 20.0).max(1.0) as usize;
+        let mut right = self.hud_size().x - 10.0;
+        for column in key_help.rchunks(rows) {
+            let width = column
+                .iter()
+                .map(|line| measure_text(line, None, 18, 1.0).width)
+                .fold(0.0, f32::max);
+            for (i, line) in column.iter().rev().enumerate() {
+                let dimensions = measure_text(line, None, 18, 1.0);
+                draw_text(
+                    line,
+                    right - dimensions.width,
+                    self.hud_size().y - 10.0 - 20.0 * i as f32,
+                    18.0,
+                    self.theme.muted_text,
+                );
+            }
+            right -= width + 20.0;
+        }
+    }
 }
 
-fn load_territories_from_json<P: AsRef<Path>>(path: P) -> Vec<TerritoryData> {
-    let file_content = fs::read_to_string(path).expect("Failed to read territories.json");
-    serde_json::from_str(&file_content).expect("Failed to parse JSON data")
+fn time_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
 }
 
-#[macroquad::main("Interactive Risk Map")]
-async fn main() {
-    let mut game_state = GameState::new();
+// A scenario that lists players seats them in place of the configured ones,
+// so the lobby starts from them and whoever it's left with plays. One that
+// won't load is reported when the game starts.
+fn seat_scenario_players(config: &mut Config, path: Option<&Path>) -> Option<Scenario> {
+    let scenario = Scenario::load(path?).ok()?;
+    if !scenario.players.is_empty() {
+        config.players = scenario.players.clone();
+    }
+    Some(scenario)
+}
 
+// Builds a fresh game on `source`, applying any quick-start setup from the
+// command line
+fn start_game(
+    config: &Config,
+    args: &Args,
+    source: &dyn MapSource,
+) -> Result<GameState, MapLoadError> {
+    let seed = args.seed.unwrap_or_else(time_seed);
+    let mut game_state = match &args.scenario {
+        Some(path) => GameState::new_with_scenario(config, source, path, seed)?,
+        None => GameState::new(config, source, seed)?,
+    };
+    let (min, max) = game_state.game.map_bounds();
+    let screen = vec2(screen_width(), screen_height());
+    game_state.camera.fit(min, max, screen, FIT_MARGIN);
+    if let Some(armies) = args.distribute {
+        game_state
+            .game
+            .distribute_armies(armies, seed, config.distribute_weighting);
+    }
+    // After the armies are dealt, so the reinforcements land on top of them
+    if let Some(name) = args.start_phase.as_ref().or(config.start_phase.as_ref()) {
+        game_state.start_in_phase(name);
+    }
+    game_state.begin_replay();
+    Ok(game_state)
+}
+
+// Keeps the window open with the error on screen instead of crashing, so a
+// broken map file can be fixed and the game restarted
+async fn show_load_error(err: &MapLoadError, theme: &Theme) -> ! {
     loop {
-        clear_background(WHITE);
-        game_state.handle_input();
-        game_state.draw_map();
+        clear_background(theme.background);
+        draw_text("Could not load the map:", 10.0, 30.0, 30.0, theme.warning);
+        for (i, line) in err.to_string().lines().enumerate() {
+            draw_text(line, 10.0, 60.0 + 22.0 * i as f32, 20.0, theme.text);
+        }
         next_frame().await;
     }
-}
\ No newline at end of file
+}
+
+// Read before the window opens, so size and title come from the config too
+fn window_conf() -> Conf {
+    let window = Config::load(CONFIG_PATH).window;
+    Conf {
+        window_title: window.title,
+        window_width: window.width,
+        window_height: window.height,
+        platform: miniquad::conf::Platform {
+            swap_interval: window.vsync.map(i32::from),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+// Simulations run headless, so the window is only opened when playing
+fn main() {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(2);
+        }
+    };
+    if let Some(games) = args.simulate {
+        run_simulation(&args, games);
+        return;
+    }
+    if let Some(frames) = args.bench_frames {
+        let mut conf = window_conf();
+        conf.platform.swap_interval = Some(0);
+        macroquad::Window::from_config(conf, bench_frames(args, frames));
+        return;
+    }
+    macroquad::Window::from_config(window_conf(), play(args));
+}
+
+// The map from the command line, dealt to the configured players; a player
+// count it can't be dealt to is a usage error like any other
+fn map_source(args: &Args, config: &Config) -> Box<dyn MapSource> {
+    args.map_source(config.players.len())
+        .unwrap_or_else(|message| {
+            eprintln!("{}", message);
+            std::process::exit(2);
+        })
+}
+
+async fn bench_frames(args: Args, frames: usize) {
+    let mut config = Config::load(CONFIG_PATH);
+    seat_scenario_players(&mut config, args.scenario.as_deref());
+    let source = map_source(&args, &config);
+    match start_game(&config, &args, source.as_ref()) {
+        Ok(game_state) => frame_bench::run_frame_bench(game_state, frames).await,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+// Seeds run from 0, so the same command always reports the same results
+fn run_simulation(args: &Args, games: usize) {
+    let config = Config::load(CONFIG_PATH);
+    let seeds: Vec<u64> = (0..games as u64).collect();
+    let difficulties: Vec<_> = config.players.iter().map(|p| p.difficulty).collect();
+    let source = map_source(args, &config);
+    let outcome = source.load().and_then(|data| {
+        game::simulate::simulate(&data, &seeds, args.max_turns, &difficulties, args.strategy)
+    });
+    match outcome {
+        Ok(outcome) => println!("{}", outcome),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+async fn play(args: Args) {
+    // The lobby's players replace the configured ones for later games too
+    let mut config = Config::load(CONFIG_PATH);
+    let scenario = seat_scenario_players(&mut config, args.scenario.as_deref());
+    // The screens outside a game are drawn in the configured theme
+    let theme = config.theme.build(config.theme.base).0;
+    let current_map = map_source(&args, &config);
+    // A map given on the command line skips the selection screen
+    let mut screen = if args.map.is_some() {
+        match start_game(&config, &args, current_map.as_ref()) {
+            Ok(game_state) => GameScreen::InGame(Box::new(game_state)),
+            Err(err) => show_load_error(&err, &theme).await,
+        }
+    } else {
+        map_select()
+    };
+
+    let mut editor: Option<Editor> = None;
+    let mut replay_viewer: Option<ReplayViewer> = None;
+    let mut audio = AudioManager::load().await;
+    let mut frame_limiter = FrameLimiter::new(config.window.max_fps);
+
+    loop {
+        if matches!(&screen, GameScreen::InGame(game_state) if game_state.quit_requested) {
+            return;
+        }
+        // A game's own theme can be switched while playing
+        clear_background(match &screen {
+            GameScreen::InGame(game_state) | GameScreen::GameOver(game_state) => {
+                game_state.theme.background
+            }
+            _ => theme.background,
+        });
+
+        let next = match &mut screen {
+            GameScreen::MapSelect(select) => {
+                let picked = select.handle_input();
+                select.draw(&theme);
+                picked.map(|mut map| {
+                    // The lobby keeps every seat the scenario deals territories to
+                    if let Some(scenario) = &scenario {
+                        scenario.apply(&mut map.0);
+                    }
+                    GameScreen::Lobby(Lobby::new(map, &config.players))
+                })
+            }
+            GameScreen::Lobby(lobby) => {
+                let choice = lobby.handle_input();
+                lobby.draw(&theme);
+                match choice {
+                    Some(LobbyChoice::Start(players)) => {
+                        config.players = players;
+                        match start_game(&config, &args, &lobby.map) {
+                            Ok(game_state) => Some(GameScreen::InGame(Box::new(game_state))),
+                            Err(err) => {
+                                lobby.error = Some(err.to_string());
+                                None
+                            }
+                        }
+                    }
+                    Some(LobbyChoice::Back) => Some(map_select()),
+                    None => None,
+                }
+            }
+            GameScreen::InGame(game_state) => {
+                play_frame(game_state, &mut editor, &mut replay_viewer, &mut audio)
+            }
+            GameScreen::GameOver(game_state) => {
+                // The replay and editor open over a finished game as well
+                if play_tools(game_state, &mut editor, &mut replay_viewer) {
+                    None
+                } else {
+                    game_state.draw_map();
+                    // Resetting takes the winner away, which puts the game
+                    // back in play below
+                    if game_state.keys.pressed(Action::Reset) {
+                        if let Err(err) = game_state.reset() {
+                            show_load_error(&err, &game_state.theme).await;
+                        }
+                        None
+                    } else if game_state.keys.pressed(Action::ChooseMap) {
+                        Some(map_select())
+                    } else if game_state.keys.pressed(Action::SaveStatistics) {
+                        game_state.save_statistics();
+                        None
+                    } else {
+                        None
+                    }
+                }
+            }
+        };
+        if let Some(next) = next {
+            // Tools opened on the old game don't carry over to the next one
+            editor = None;
+            replay_viewer = None;
+            screen = next;
+        }
+        screen = screen.check_game_over();
+        let animating =
+            matches!(&screen, GameScreen::InGame(game_state) if game_state.is_animating());
+        frame_limiter.wait(animating);
+        next_frame().await;
+    }
+}
+
+fn map_select() -> GameScreen {
+    let mut maps = screen::find_maps(Path::new(screen::MAPS_DIR));
+    // Without the maps folder there's still the built-in map to play
+    if maps.is_empty() {
+        maps.push(MapEntry {
+            name: "classic (built in)".to_string(),
+            data: map::load_territories_from_str(cli::BUILT_IN_MAP).map_err(|err| err.to_string()),
+        });
+    }
+    GameScreen::MapSelect(MapSelect::new(maps))
+}
+
+// The map editor or replay viewer, if either is open or its key opens it,
+// drawn in place of the game for the frame. Returns whether one was.
+fn play_tools(
+    game_state: &mut GameState,
+    editor: &mut Option<Editor>,
+    replay_viewer: &mut Option<ReplayViewer>,
+) -> bool {
+    // The editor has its own input path and leaves the game untouched.
+    // Keys typed into a text box or the pause menu aren't shortcuts.
+    let typing = game_state.is_typing()
+        || game_state.pause_menu.is_some()
+        || editor.as_ref().is_some_and(Editor::is_typing);
+    if game_state.keys.pressed(Action::Editor) && !typing {
+        *editor = match editor {
+            Some(_) => None,
+            None => Some(Editor::new(
+                game_state.game.territory_data(),
+                game_state.grid.spacing,
+            )),
+        };
+    }
+    if let Some(editor) = editor {
+        game_state.camera.update(true);
+        editor.handle_input(&game_state.camera);
+        editor.draw(&game_state.camera, &game_state.theme, &game_state.keys);
+        return true;
+    }
+
+    // Likewise the replay viewer, which plays on its own copy of the game
+    if game_state.keys.pressed(Action::Replay) && !typing {
+        *replay_viewer = match replay_viewer {
+            Some(_) => None,
+            None => match ReplayViewer::open(game_state, game_state.replay.clone()) {
+                Ok(viewer) => Some(viewer),
+                Err(err) => {
+                    game_state.status_message = Some(format!("Replay failed: {}", err));
+                    None
+                }
+            },
+        };
+    }
+    if let Some(viewer) = replay_viewer {
+        viewer.handle_input();
+        viewer.draw();
+        return true;
+    }
+    false
+}
+
+// One frame of a game in progress. Returns the screen to switch to, if any.
+fn play_frame(
+    game_state: &mut GameState,
+    editor: &mut Option<Editor>,
+    replay_viewer: &mut Option<ReplayViewer>,
+    audio: &mut AudioManager,
+) -> Option<GameScreen> {
+    if play_tools(game_state, editor, replay_viewer) {
+        return None;
+    }
+    // Keys typed into a text box or the pause menu aren't shortcuts
+    let typing = game_state.is_typing() || game_state.pause_menu.is_some();
+    if game_state.keys.pressed(Action::ChooseMap) {
+        return Some(map_select());
+    }
+    if game_state.keys.pressed(Action::Mute) && !typing {
+        audio.muted = !audio.muted;
+    }
+    if game_state.keys.pressed(Action::DebugOverlay) {
+        game_state.show_debug = !game_state.show_debug;
+    }
+    if !typing {
+        game_state.handle_hud_scale_input();
+    }
+    // Read once, so everything this frame moves by the same step
+    let dt = get_frame_time();
+    game_state.update(dt);
+    game_state.update_turn_timer(dt);
+    game_state.update_warning(dt);
+    game_state.advance_animations(dt);
+    game_state.handle_input();
+    game_state.queue_event_effects();
+    audio.play_all(game_state.pending_sounds.drain(..));
+    game_state.draw_map();
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{square, territory_with_vertices};
+
+    #[test]
+    fn animations_take_the_same_time_at_any_frame_rate() {
+        for fps in [24.0, 60.0, 144.0] {
+            let mut state = crate::test_support::game_state(
+                vec![
+                    crate::test_support::territory("A", 0, 8, vec![1]),
+                    crate::test_support::territory("B", 1, 1, vec![0]),
+                ],
+                0,
+            );
+            state.game.phase = Phase::Attack;
+            state.start_attack_animation(0, 1);
+            state.animate_move(0, 1, 3);
+            state.flash(1);
+            state.camera.move_to(vec2(100.0, 0.0), 2.0, GLIDE_SECONDS);
+
+            // Glide 0.35s, flash 0.5s, three tokens 0.57s, dice 2.6s
+            crate::test_support::run_for(&mut state, 0.4, fps);
+            assert_eq!(state.camera.offset, vec2(100.0, 0.0), "{} fps", fps);
+            assert!(!state.flash_timers.is_empty(), "{} fps", fps);
+            crate::test_support::run_for(&mut state, 0.15, fps);
+            assert!(state.flash_timers.is_empty(), "{} fps", fps);
+            assert!(!state.movements.is_empty(), "{} fps", fps);
+            crate::test_support::run_for(&mut state, 0.1, fps);
+            assert!(state.movements.is_empty(), "{} fps", fps);
+            crate::test_support::run_for(&mut state, 1.9, fps);
+            assert!(state.combat_animation.is_some(), "{} fps", fps);
+            crate::test_support::run_for(&mut state, 0.1, fps);
+            assert!(state.combat_animation.is_none(), "{} fps", fps);
+        }
+    }
+
+    #[test]
+    fn overlapping_territories_pick_the_topmost() {
+        let game = crate::test_support::game_state(
+            vec![
+                territory_with_vertices(square(0.0, 10.0)),
+                territory_with_vertices(square(5.0, 15.0)),
+            ],
+            0,
+        );
+        // Only the bottom territory covers this point
+        assert_eq!(game.territory_at(vec2(2.0, 2.0)), Some(0));
+        // Both cover the overlap; the one drawn last is on top
+        assert_eq!(game.territory_at(vec2(7.0, 7.0)), Some(1));
+        assert_eq!(game.territory_at(vec2(12.0, 12.0)), Some(1));
+        assert_eq!(game.territory_at(vec2(20.0, 20.0)), None);
+    }
+
+    #[test]
+    fn illegal_clicks_explain_themselves() {
+        let mut state = crate::test_support::game_state(
+            vec![
+                crate::test_support::territory("Home", 0, 1, vec![1]),
+                crate::test_support::territory("Enemy", 1, 2, vec![0]),
+                crate::test_support::territory("Far", 0, 3, vec![]),
+            ],
+            0,
+        );
+        state.game.phase = Phase::Attack;
+        state.click_territory(1, false);
+        assert!(state.warning.is_some());
+
+        state.select(0);
+        state.click_territory(1, false);
+        let (message, _) = state.warning.clone().unwrap();
+        assert!(message.contains("two armies"), "{}", message);
+        assert!(state.combat_animation.is_none());
+
+        state.game.phase = Phase::Fortify;
+        state.select(2);
+        state.click_territory(0, false);
+        assert!(state.warning.as_ref().unwrap().0.contains("border"));
+        assert!(state.pending_move.is_none());
+
+        state.update_warning(WARNING_SECONDS);
+        assert!(state.warning.is_none());
+    }
+
+    #[test]
+    fn stepped_ai_turn_matches_the_whole_turn() {
+        let territories = || {
+            vec![
+                crate::test_support::territory("A", 0, 6, vec![1]),
+                crate::test_support::territory("B", 1, 2, vec![0, 2]),
+                crate::test_support::territory("C", 1, 3, vec![1]),
+            ]
+        };
+        let mut whole = crate::test_support::game(territories(), 4);
+        whole.run_ai_turn(PlayerId(0));
+
+        let mut state = crate::test_support::game_state(territories(), 4);
+        let mut steps = 1;
+        while !state.ai_step() {
+            steps += 1;
+        }
+        assert!(steps > 2, "took {} steps", steps);
+        assert_eq!(state.ai_turn, None);
+        assert_eq!(state.game.events, whole.events);
+        assert_eq!(state.game.current_player, PlayerId(1));
+    }
+
+    #[test]
+    fn reset_asks_then_restores_the_start_with_the_same_dice() {
+        let territories = || {
+            vec![
+                crate::test_support::territory("A", 0, 8, vec![1]),
+                crate::test_support::territory("B", 1, 3, vec![0]),
+            ]
+        };
+        let mut state = crate::test_support::game_state(territories(), 11);
+        state.begin_replay();
+        let board = |state: &GameState| {
+            state
+                .game
+                .territories
+                .iter()
+                .map(|t| (t.owner, t.armies))
+                .collect::<Vec<_>>()
+        };
+        let start = board(&state);
+        let blitz = |state: &mut GameState| {
+            state.game.reinforcements_remaining = 0;
+            state.next_phase();
+            state.game.blitz_attack(0, 1)
+        };
+        let first = blitz(&mut state);
+        state.select(0);
+
+        state.request_reset();
+        assert_ne!(board(&state), start);
+        assert_eq!(state.warning.as_ref().unwrap().0, RESET_PROMPT);
+        state.request_reset();
+        assert_eq!(board(&state), start);
+        assert_eq!(state.game.phase, Phase::Reinforce);
+        assert_eq!(state.selected_territory, None);
+        assert!(state.warning.is_none());
+        assert_eq!(state.replay.len(), 0);
+
+        // The rematch rolls the same dice
+        assert_eq!(blitz(&mut state), first);
+    }
+
+    #[test]
+    fn player_bounds_span_scattered_territories() {
+        let mut territories = crate::test_support::synthetic_map(3, 8);
+        for territory in &mut territories {
+            territory.owner = Some(PlayerId(1));
+        }
+        // Opposite corners of the grid, with nothing in between
+        territories[0].owner = Some(PlayerId(0));
+        territories[8].owner = Some(PlayerId(0));
+        let state = crate::test_support::game_state(territories, 0);
+        let (min, max) = state.player_bounds(PlayerId(0)).unwrap();
+        assert!(min.distance(vec2(5.0, 5.0)) < 1e-3);
+        assert!(max.distance(vec2(295.0, 295.0)) < 1e-3);
+        assert_eq!(state.player_bounds(PlayerId(2)), None);
+    }
+
+    #[test]
+    fn map_bounds_cover_every_territory() {
+        let game = crate::test_support::game_state(
+            vec![
+                territory_with_vertices(square(-50.0, 100.0)),
+                territory_with_vertices(square(50.0, 300.0)),
+            ],
+            0,
+        );
+        assert_eq!(
+            game.game.map_bounds(),
+            (vec2(-50.0, -50.0), vec2(300.0, 300.0))
+        );
+
+        let mut camera = Camera::new();
+        camera.fit(
+            vec2(-50.0, -50.0),
+            vec2(300.0, 300.0),
+            vec2(800.0, 600.0),
+            40.0,
+        );
+        // The window's height is the tighter fit: 600 - 2 * 40 pixels for 350 units
+        assert_eq!(camera.zoom, 520.0 / 350.0);
+        let center = camera.screen_to_world(vec2(400.0, 300.0));
+        assert!((center - vec2(125.0, 125.0)).length() < 1e-3);
+    }
+
+    #[test]
+    fn games_start_from_an_inline_map() {
+        let state = GameState::from_json_str(
+            r#"{"version": 2, "territories": [
+                {"name": "North", "vertices": [[0, 0], [10, 0], [10, 10], [0, 10]],
+                 "armies": 4, "owner": 0, "selected": false, "adjacent": ["South"]},
+                {"name": "South", "vertices": [[0, 10], [10, 10], [10, 20], [0, 20]],
+                 "armies": 2, "owner": 1, "selected": false, "adjacent": ["North"]}
+            ]}"#,
+            0,
+        )
+        .unwrap();
+        assert_eq!(state.game.players.len(), 2);
+        assert_eq!(state.game.territories[0].adjacent, [1]);
+        assert!(state.game.can_attack(0, 1));
+    }
+
+    #[test]
+    fn scenarios_must_challenge_a_player_in_the_game() {
+        let map = r#"[
+            {"name": "North", "vertices": [[0, 0], [10, 0], [10, 10], [0, 10]],
+             "armies": 1, "selected": false},
+            {"name": "South", "vertices": [[0, 10], [10, 10], [10, 20], [0, 20]],
+             "armies": 1, "selected": false}
+        ]"#;
+        let path = std::env::temp_dir().join("risk_scenario_challenger.json");
+        let start = |challenger: &str| {
+            fs::write(
+                &path,
+                format!(
+                    r#"{{"players": [{{"name": "Red", "color": [200, 0, 0]}},
+                                     {{"name": "Blue", "color": [0, 0, 200]}}],
+                        "challenger": {}}}"#,
+                    challenger
+                ),
+            )
+            .unwrap();
+            let mut config = Config::default();
+            seat_scenario_players(&mut config, Some(&path));
+            GameState::new_with_scenario(&config, &map::StrMapSource(map), &path, 0)
+        };
+        assert_eq!(start("\"Blue\"").unwrap().challenger, PlayerId(1));
+        assert!(matches!(
+            start("5"),
+            Err(MapLoadError::UnknownChallenger { .. })
+        ));
+        assert!(matches!(
+            start("\"Green\""),
+            Err(MapLoadError::UnknownChallenger { .. })
+        ));
+        fs::remove_file(&path).unwrap();
+    }
+}
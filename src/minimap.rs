@@ -0,0 +1,111 @@
+use crate::GameState;
+use macroquad::prelude::*;
+
+const WIDTH: f32 = 200.0;
+const HEIGHT: f32 = 150.0;
+const MARGIN: f32 = 10.0;
+// Leaves room for the turn status in the top-right corner
+const TOP: f32 = 65.0;
+
+// Where world coordinates land inside the minimap: the whole map is scaled
+// uniformly to fit and centered in the box
+struct MinimapTransform {
+    frame: Rect,
+    origin: Vec2,
+    scale: f32,
+}
+
+impl MinimapTransform {
+    fn to_minimap(&self, world: Vec2) -> Vec2 {
+        vec2(self.frame.x, self.frame.y) + (world - self.origin) * self.scale
+    }
+
+    fn to_world(&self, point: Vec2) -> Vec2 {
+        (point - vec2(self.frame.x, self.frame.y)) / self.scale + self.origin
+    }
+}
+
+impl GameState {
+    fn minimap_transform(&self) -> MinimapTransform {
+        let frame = Rect::new(self.hud_size().x - WIDTH - MARGIN, TOP, WIDTH, HEIGHT);
+        let (min, max) = self.game.map_bounds();
+        let size = (max - min).max(Vec2::ONE);
+        let scale = (WIDTH / size.x).min(HEIGHT / size.y);
+        // Center the map along whichever axis has room to spare
+        let slack = vec2(WIDTH, HEIGHT) / scale - size;
+        MinimapTransform {
+            frame,
+            origin: min - slack / 2.0,
+            scale,
+        }
+    }
+
+    // A click on the minimap (in HUD units) glides the main view over to
+    // centre on that spot. Returns whether the click was used, so it doesn't
+    // also select a territory.
+    pub fn handle_minimap_click(&mut self, hud_point: Vec2) -> bool {
+        let transform = self.minimap_transform();
+        if self.game.territories.is_empty() || !transform.frame.contains(hud_point) {
+            return false;
+        }
+        let world = transform.to_world(hud_point);
+        self.camera
+            .glide_to_center(world, vec2(screen_width(), screen_height()));
+        true
+    }
+
+    pub fn draw_minimap(&self) {
+        if self.game.territories.is_empty() {
+            return;
+        }
+        let transform = self.minimap_transform();
+        let frame = transform.frame;
+        draw_rectangle(
+            frame.x,
+            frame.y,
+            frame.w,
+            frame.h,
+            self.theme.panel_at(0.85),
+        );
+
+        for (i, territory) in self.game.territories.iter().enumerate() {
+            let fill = self.fill_seen_by(self.game.current_player, i);
+            let points: Vec<Vec2> = territory
+                .vertices
+                .iter()
+                .map(|&v| transform.to_minimap(v))
+                .collect();
+            for &[a, b, c] in &territory.triangles {
+                draw_triangle(points[a], points[b], points[c], fill);
+            }
+        }
+
+        // The part of the world the main view currently shows, clipped to
+        // the minimap so panning far away doesn't draw across the screen
+        let top_left = transform.to_minimap(self.camera.offset);
+        let bottom_right = transform.to_minimap(
+            self.camera.offset + vec2(screen_width(), screen_height()) / self.camera.zoom,
+        );
+        let min = top_left.clamp(frame.point(), frame.point() + frame.size());
+        let max = bottom_right.clamp(frame.point(), frame.point() + frame.size());
+        if max.x > min.x && max.y > min.y {
+            draw_rectangle_lines(
+                min.x,
+                min.y,
+                max.x - min.x,
+                max.y - min.y,
+                2.0,
+                self.theme.viewport,
+            );
+        }
+
+        draw_rectangle_lines(
+            frame.x,
+            frame.y,
+            frame.w,
+            frame.h,
+            2.0,
+            self.theme.panel_border,
+        );
+    }
+}
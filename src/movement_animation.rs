@@ -0,0 +1,116 @@
+use crate::GameState;
+use macroquad::prelude::*;
+
+// Seconds each token takes from one centroid to the other
+const MOVE_SECONDS: f32 = 0.45;
+// A big move is drawn as this many tokens at most
+const MAX_TOKENS: i32 = 5;
+// Gap between tokens setting off, so a group reads as a column
+const TOKEN_STAGGER: f32 = 0.06;
+const TOKEN_RADIUS: f32 = 6.0;
+
+// Armies travelling between two territories. The game has already moved
+// them; the destination's count just doesn't show them until they arrive.
+pub struct MovementAnimation {
+    pub from: usize,
+    pub to: usize,
+    pub count: i32,
+    color: Color,
+    elapsed: f32,
+}
+
+impl MovementAnimation {
+    fn tokens(&self) -> i32 {
+        self.count.clamp(1, MAX_TOKENS)
+    }
+
+    fn duration(&self) -> f32 {
+        MOVE_SECONDS + TOKEN_STAGGER * (self.tokens() - 1) as f32
+    }
+}
+
+impl GameState {
+    pub fn animate_move(&mut self, from: usize, to: usize, count: i32) {
+        if count <= 0 {
+            return;
+        }
+        let color = self.fill_seen_by(self.game.current_player, to);
+        self.movements.push(MovementAnimation {
+            from,
+            to,
+            count,
+            color,
+            elapsed: 0.0,
+        });
+    }
+
+    // Moves every token along, landing the finished moves on the board
+    pub fn update_movements(&mut self, dt: f32) {
+        for movement in &mut self.movements {
+            movement.elapsed += dt;
+        }
+        let before = self.movements.len();
+        self.movements
+            .retain(|movement| movement.elapsed < movement.duration());
+        if self.movements.len() != before {
+            self.game.board_dirty = true;
+        }
+    }
+
+    // Armies on their way into `territory` that its count doesn't show yet
+    pub fn arriving(&self, territory: usize) -> i32 {
+        self.movements
+            .iter()
+            .filter(|movement| movement.to == territory)
+            .map(|movement| movement.count)
+            .sum()
+    }
+
+    pub fn draw_movements(&self) {
+        for movement in &self.movements {
+            let start = self.game.territories[movement.from].centroid();
+            let end = self.game.territories[movement.to].centroid();
+            for token in 0..movement.tokens() {
+                let t = (movement.elapsed - TOKEN_STAGGER * token as f32) / MOVE_SECONDS;
+                if !(0.0..1.0).contains(&t) {
+                    continue;
+                }
+                // Eased so tokens set off and settle gently
+                let eased = t * t * (3.0 - 2.0 * t);
+                let at = self.camera.world_to_screen(start.lerp(end, eased));
+                draw_circle(at.x, at.y, TOKEN_RADIUS, movement.color);
+                draw_circle_lines(at.x, at.y, TOKEN_RADIUS, 1.5, self.theme.panel_border);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{game_state, territory};
+
+    #[test]
+    fn moves_arrive_independently() {
+        let mut state = game_state(
+            vec![
+                territory("A", 0, 6, vec![1, 2]),
+                territory("B", 0, 1, vec![0]),
+                territory("C", 0, 1, vec![0]),
+            ],
+            0,
+        );
+        state.animate_move(0, 1, 3);
+        state.update_movements(0.2);
+        state.animate_move(0, 1, 1);
+        state.animate_move(0, 2, 2);
+        assert_eq!((state.arriving(1), state.arriving(2)), (4, 2));
+
+        // The first move lands while the later ones are still on their way
+        state.update_movements(0.4);
+        assert_eq!((state.arriving(1), state.arriving(2)), (1, 2));
+        assert!(state.game.board_dirty);
+        state.update_movements(1.0);
+        assert!(state.movements.is_empty());
+        assert_eq!(state.arriving(1), 0);
+    }
+}
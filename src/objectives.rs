@@ -0,0 +1,210 @@
+use crate::game::events::GameEvent;
+use crate::game::map::MapOwner;
+use crate::game::player::PlayerId;
+use crate::game::turn::Phase;
+use crate::scenario::{Objective, Outcome};
+use crate::GameState;
+use macroquad::prelude::*;
+
+impl GameState {
+    // Turns the challenger has finished: each time play moves on to a new
+    // turn after one of theirs reached Fortify
+    pub fn challenger_turns(&self) -> usize {
+        let mut finished = 0;
+        let mut in_their_fortify = false;
+        for event in &self.game.events {
+            if let GameEvent::PhaseStarted { player, phase } = *event {
+                match phase {
+                    Phase::Fortify => in_their_fortify = player == self.challenger,
+                    Phase::Reinforce if in_their_fortify => {
+                        finished += 1;
+                        in_their_fortify = false;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        finished
+    }
+
+    pub fn objective_player(&self, owner: &MapOwner) -> Option<PlayerId> {
+        match owner {
+            MapOwner::Seat(id) => (id.0 < self.game.players.len()).then_some(*id),
+            MapOwner::Name(name) => self
+                .game
+                .players
+                .iter()
+                .position(|player| &player.name == name)
+                .map(PlayerId),
+        }
+    }
+
+    // Objectives naming a continent or player this game doesn't have, so
+    // they can be reported rather than silently never met
+    pub fn unknown_objective_targets(&self) -> Vec<String> {
+        let mut unknown = Vec::new();
+        for objective in &self.objectives {
+            match objective {
+                Objective::ControlContinents { continents, .. } => unknown.extend(
+                    continents
+                        .iter()
+                        .filter(|name| !self.game.continents.iter().any(|c| &c.name == *name))
+                        .map(|name| format!("no continent named '{}'", name)),
+                ),
+                Objective::EliminatePlayer { player, .. } => {
+                    if self.objective_player(player).is_none() {
+                        unknown.push(format!("no player {}", describe_owner(player)));
+                    }
+                }
+            }
+        }
+        unknown
+    }
+
+    pub fn objective_met(&self, objective: &Objective) -> bool {
+        match objective {
+            Objective::ControlContinents { continents, .. } => continents.iter().all(|name| {
+                self.game
+                    .continents
+                    .iter()
+                    .find(|c| &c.name == name)
+                    .is_some_and(|c| self.game.controls_continent(self.challenger, c))
+            }),
+            Objective::EliminatePlayer { player, .. } => self
+                .objective_player(player)
+                .is_some_and(|id| self.game.is_eliminated(id)),
+        }
+    }
+
+    // Success once every objective holds at the same time. The challenge is
+    // lost if the challenger is knocked out, or if an objective is still
+    // unmet when its turns run out.
+    pub fn check_objectives(&self) -> Option<Outcome> {
+        if self.objectives.is_empty()
+            || matches!(self.game.phase, Phase::Claim | Phase::InitialPlacement)
+        {
+            return None;
+        }
+        if self.game.is_eliminated(self.challenger) {
+            return Some(Outcome::Failure(format!(
+                "{} was eliminated",
+                self.game.player(self.challenger).name
+            )));
+        }
+        if self.objectives.iter().all(|o| self.objective_met(o)) {
+            return Some(Outcome::Success);
+        }
+        let turns = self.challenger_turns();
+        self.objectives
+            .iter()
+            .find(|o| {
+                o.within_turns().is_some_and(|limit| turns >= limit) && !self.objective_met(o)
+            })
+            .map(|o| Outcome::Failure(format!("Out of turns to {}", self.describe_objective(o))))
+    }
+
+    fn describe_objective(&self, objective: &Objective) -> String {
+        match objective {
+            Objective::ControlContinents { continents, .. } => {
+                format!("control {}", continents.join(", "))
+            }
+            Objective::EliminatePlayer { player, .. } => match self.objective_player(player) {
+                Some(id) => format!("eliminate {}", self.game.player(id).name),
+                None => format!("eliminate {}", describe_owner(player)),
+            },
+        }
+    }
+
+    // The challenger's objectives, ticked off as they're met, with the turns
+    // left for any that have a deadline. Returns where the panel ends.
+    pub fn draw_objectives_panel(&self, top: f32) -> f32 {
+        if self.objectives.is_empty() {
+            return top;
+        }
+        let (x, line_height) = (10.0, 18.0);
+        let width = 320.0;
+        let height = line_height * (self.objectives.len() + 1) as f32 + 8.0;
+        draw_rectangle(x, top, width, height, self.theme.panel_at(0.8));
+        draw_rectangle_lines(x, top, width, height, 1.0, self.theme.panel_border);
+        let title = format!("Objectives for {}", self.game.player(self.challenger).name);
+        draw_text(&title, x + 4.0, top + line_height, 16.0, self.theme.text);
+        let turns = self.challenger_turns();
+        for (i, objective) in self.objectives.iter().enumerate() {
+            let met = self.objective_met(objective);
+            let mut text = format!(
+                "[{}] {}",
+                if met { "x" } else { " " },
+                self.describe_objective(objective)
+            );
+            if let Some(limit) = objective.within_turns() {
+                text.push_str(&format!(" ({} turn(s) left)", limit.saturating_sub(turns)));
+            }
+            draw_text(
+                &text,
+                x + 4.0,
+                top + line_height * (i as f32 + 2.0),
+                16.0,
+                if met {
+                    self.theme.text
+                } else {
+                    self.theme.muted_text
+                },
+            );
+        }
+        top + height
+    }
+}
+
+pub fn describe_owner(owner: &MapOwner) -> String {
+    match owner {
+        MapOwner::Seat(id) => format!("in seat {}", id.0),
+        MapOwner::Name(name) => format!("'{}'", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::continent::Continent;
+    use crate::game::player::PlayerId;
+    use crate::game::turn::Phase;
+    use crate::scenario::{Objective, Outcome};
+    use crate::test_support::{game_state, territory};
+
+    #[test]
+    fn objectives_succeed_when_met_and_fail_when_time_runs_out() {
+        let mut state = game_state(
+            vec![
+                territory("A", 0, 3, vec![1]),
+                territory("B", 1, 3, vec![0, 2]),
+                territory("C", 1, 3, vec![1]),
+            ],
+            0,
+        );
+        state.game.continents.push(Continent {
+            name: "West".to_string(),
+            territories: vec![0, 1],
+            bonus: 2,
+            required_capital: None,
+        });
+        state.objectives = vec![Objective::ControlContinents {
+            continents: vec!["West".to_string()],
+            within_turns: Some(1),
+        }];
+        assert_eq!(state.check_objectives(), None);
+
+        state.game.territories[1].owner = Some(PlayerId(0));
+        assert_eq!(state.check_objectives(), Some(Outcome::Success));
+
+        // Lost again, and the challenger's one turn ends before it's back
+        state.game.territories[1].owner = Some(PlayerId(1));
+        while !(state.game.current_player == PlayerId(1) && state.game.phase == Phase::Reinforce) {
+            state.game.reinforcements_remaining = 0;
+            state.game.next_phase();
+        }
+        assert_eq!(state.challenger_turns(), 1);
+        assert_eq!(
+            state.check_objectives(),
+            Some(Outcome::Failure("Out of turns to control West".to_string()))
+        );
+    }
+}
@@ -0,0 +1,428 @@
+use crate::game::cards::Card;
+use crate::game::continent::Continent;
+use crate::game::events::GameEvent;
+use crate::game::player::PlayerId;
+use crate::game::turn::Phase;
+use crate::keys::Action;
+use crate::GameState;
+use macroquad::prelude::*;
+
+// How many history entries fit in the panel at once
+const VISIBLE_EVENTS: usize = 8;
+// Where the history panel sits on the HUD
+const LOG_TOP: f32 = 140.0;
+const LOG_X: f32 = 10.0;
+const LOG_WIDTH: f32 = 320.0;
+const LOG_LINE_HEIGHT: f32 = 18.0;
+
+// Faint colors used to halo each continent's territories
+const CONTINENT_TINTS: [Color; 6] = [
+    Color::new(0.9, 0.6, 0.2, 0.35),
+    Color::new(0.3, 0.7, 0.9, 0.35),
+    Color::new(0.6, 0.4, 0.8, 0.35),
+    Color::new(0.4, 0.8, 0.4, 0.35),
+    Color::new(0.9, 0.4, 0.5, 0.35),
+    Color::new(0.6, 0.6, 0.3, 0.35),
+];
+
+// One player's row of the legend
+#[derive(Debug, PartialEq)]
+pub struct PlayerStats {
+    pub player: PlayerId,
+    pub territories: usize,
+    pub armies: i32,
+}
+
+impl GameState {
+    // Page Up/Down step back and forth through older entries
+    pub fn scroll_log(&mut self, lines: isize) {
+        let max_scroll = self.game.events.len().saturating_sub(VISIBLE_EVENTS);
+        self.log_scroll = self.log_scroll.saturating_add_signed(lines).min(max_scroll);
+    }
+
+    // Indices of the events the history panel shows, oldest first
+    fn visible_events(&self) -> std::ops::Range<usize> {
+        let end = self.game.events.len().saturating_sub(self.log_scroll);
+        end.saturating_sub(VISIBLE_EVENTS)..end
+    }
+
+    // The event whose history row is under `point` (in HUD units)
+    pub fn log_event_at(&self, point: Vec2) -> Option<usize> {
+        let visible = self.visible_events();
+        if point.x < LOG_X || point.x > LOG_X + LOG_WIDTH || point.y < LOG_TOP + 4.0 {
+            return None;
+        }
+        // Rows are `LOG_LINE_HEIGHT` tall with their text on the bottom edge
+        let row = ((point.y - LOG_TOP - 4.0) / LOG_LINE_HEIGHT) as usize;
+        (row < visible.len()).then(|| visible.start + row)
+    }
+
+    // The logged attack the pointer is over, if any
+    fn hovered_attack(&self) -> Option<&GameEvent> {
+        let event = &self.game.events[self.log_event_at(self.hud_mouse())?];
+        matches!(event, GameEvent::Attacked { .. }).then_some(event)
+    }
+
+    // Rings the two sides of the hovered attack on the map
+    pub fn draw_hovered_attack(&self) {
+        if let Some(&GameEvent::Attacked { from, to, .. }) = self.hovered_attack() {
+            self.game.territories[from].draw_outline(&self.camera, self.theme.highlight, 4.0);
+            self.game.territories[to].draw_outline(&self.camera, self.theme.attack_target, 4.0);
+        }
+    }
+
+    // The dice behind the hovered attack, beside the history panel
+    pub fn draw_attack_tooltip(&self) {
+        let Some(GameEvent::Attacked {
+            attacker_dice,
+            defender_dice,
+            attacker_losses,
+            defender_losses,
+            ..
+        }) = self.hovered_attack()
+        else {
+            return;
+        };
+        let dice = |dice: &[u8]| {
+            if dice.is_empty() {
+                "?".to_string()
+            } else {
+                dice.iter().map(u8::to_string).collect::<Vec<_>>().join(" ")
+            }
+        };
+        let lines = [
+            format!("Attacker rolled {}", dice(attacker_dice)),
+            format!("Defender rolled {}", dice(defender_dice)),
+            format!(
+                "Attacker lost {}, defender lost {}",
+                attacker_losses, defender_losses
+            ),
+        ];
+        let mouse = self.hud_mouse();
+        let (x, y) = (LOG_X + LOG_WIDTH + 8.0, mouse.y - 12.0);
+        let width = lines
+            .iter()
+            .map(|line| measure_text(line, None, 16, 1.0).width)
+            .fold(0.0, f32::max)
+            + 12.0;
+        let height = LOG_LINE_HEIGHT * lines.len() as f32 + 8.0;
+        draw_rectangle(x, y, width, height, self.theme.panel);
+        draw_rectangle_lines(x, y, width, height, 1.0, self.theme.panel_border);
+        for (i, line) in lines.iter().enumerate() {
+            draw_text(
+                line,
+                x + 6.0,
+                y + LOG_LINE_HEIGHT * (i as f32 + 1.0),
+                16.0,
+                self.theme.text,
+            );
+        }
+    }
+
+    // History panel down the left side, newest entry at the bottom; an
+    // attack can be hovered for its dice
+    // Returns where the panel ends, for the one below it
+    pub fn draw_event_log(&self) -> f32 {
+        let top = LOG_TOP;
+        if self.game.events.is_empty() {
+            return top;
+        }
+        let visible = self.visible_events();
+        let (start, end) = (visible.start, visible.end);
+        let (x, line_height) = (LOG_X, LOG_LINE_HEIGHT);
+        let width = LOG_WIDTH;
+        let height = line_height * (end - start) as f32 + 8.0;
+        draw_rectangle(x, top, width, height, self.theme.panel_at(0.8));
+        draw_rectangle_lines(x, top, width, height, 1.0, self.theme.panel_border);
+        for (i, event) in self.game.events[start..end].iter().enumerate() {
+            draw_text(
+                &self.game.describe_event(event),
+                x + 4.0,
+                top + line_height * (i as f32 + 1.0),
+                16.0,
+                self.theme.text,
+            );
+        }
+        top + height
+    }
+
+    pub fn draw_hand(&self) {
+        if self.game.phase != Phase::Reinforce {
+            return;
+        }
+        let hand = self.game.hand(self.game.current_player);
+        if hand.is_empty() {
+            return;
+        }
+        let names: Vec<String> = hand.iter().map(Card::to_string).collect();
+        let mut text = format!("Cards: {}", names.join(", "));
+        if self.game.find_set(self.game.current_player).is_some() {
+            text.push_str(&format!(
+                "  ({}: trade a set)",
+                self.keys.name(Action::TradeCards)
+            ));
+        }
+        draw_text(&text, 10.0, 110.0, 24.0, self.theme.text);
+    }
+
+    // Each continent's bonus and holder, plus how much of it the player to
+    // move has, so they can see how close a bonus is. Folds to its title.
+    // Returns where the panel ends, for the one below it
+    pub fn draw_continent_panel(&self, top: f32) -> f32 {
+        if self.game.continents.is_empty() {
+            return top;
+        }
+        let (x, line_height) = (10.0, 18.0);
+        let rows = if self.show_continents {
+            self.game.continents.len()
+        } else {
+            0
+        };
+        let width = 320.0;
+        let height = line_height * (rows + 1) as f32 + 8.0;
+        draw_rectangle(x, top, width, height, self.theme.panel_at(0.8));
+        draw_rectangle_lines(x, top, width, height, 1.0, self.theme.panel_border);
+        let title = format!(
+            "Continents ({}: {})",
+            self.keys.name(Action::ToggleContinents),
+            if self.show_continents { "hide" } else { "show" }
+        );
+        draw_text(&title, x + 4.0, top + line_height, 16.0, self.theme.text);
+        if !self.show_continents {
+            return top + height;
+        }
+
+        for (i, continent) in self.game.continents.iter().enumerate() {
+            let (text, color) = self.continent_row(continent);
+            draw_text(
+                &text,
+                x + 4.0,
+                top + line_height * (i as f32 + 2.0),
+                16.0,
+                color,
+            );
+        }
+        top + height
+    }
+
+    // A continent's line in the panel and its colour. Under fog of war who
+    // holds it is only shown once none of it (capital included) is hidden.
+    fn continent_row(&self, continent: &Continent) -> (String, Color) {
+        let player = self.game.current_player;
+        let seen = continent
+            .territories
+            .iter()
+            .chain(&continent.required_capital)
+            .all(|&t| self.visible_to(player, t));
+        let (holder, color) = match self.game.continent_controller(continent) {
+            _ if !seen => ("unknown".to_string(), self.theme.muted_text),
+            Some(owner) => (
+                self.game.player(owner).name.clone(),
+                self.game.player(owner).color,
+            ),
+            None => ("contested".to_string(), self.theme.text),
+        };
+        let held = continent
+            .territories
+            .iter()
+            .filter(|&&t| self.game.territories[t].owner == Some(player))
+            .count();
+        let text = format!(
+            "{} +{}: {} (you {}/{})",
+            continent.name,
+            continent.bonus,
+            holder,
+            held,
+            continent.territories.len()
+        );
+        (text, color)
+    }
+
+    // Territories and armies held by each player, in seat order
+    pub fn player_stats(&self) -> Vec<PlayerStats> {
+        self.game
+            .players
+            .iter()
+            .map(|player| {
+                let held = self
+                    .game
+                    .territories
+                    .iter()
+                    .filter(|territory| territory.owner == Some(player.id));
+                PlayerStats {
+                    player: player.id,
+                    territories: held.clone().count(),
+                    armies: held.map(|territory| territory.armies).sum(),
+                }
+            })
+            .collect()
+    }
+
+    // A scoreboard of every player under the continents, with the player to
+    // move highlighted. Under fog of war only their own army total shows.
+    pub fn draw_legend(&self, top: f32) {
+        let (x, line_height) = (10.0, 20.0);
+        let stats = self.player_stats();
+        let width = 320.0;
+        let height = line_height * stats.len() as f32 + 8.0;
+        draw_rectangle(x, top, width, height, self.theme.panel_at(0.8));
+        draw_rectangle_lines(x, top, width, height, 1.0, self.theme.panel_border);
+        for (i, row) in stats.iter().enumerate() {
+            let y = top + 4.0 + line_height * i as f32;
+            let player = self.game.player(row.player);
+            if row.player == self.game.current_player {
+                draw_rectangle(
+                    x + 1.0,
+                    y,
+                    width - 2.0,
+                    line_height,
+                    self.theme.panel_highlight,
+                );
+            }
+            draw_rectangle(x + 6.0, y + 4.0, 12.0, 12.0, player.color);
+            draw_rectangle_lines(x + 6.0, y + 4.0, 12.0, 12.0, 1.0, self.theme.panel_border);
+            let armies = if self.fog_of_war && row.player != self.game.current_player {
+                "?".to_string()
+            } else {
+                row.armies.to_string()
+            };
+            let text = format!(
+                "{}: {} territories, {} armies",
+                player.name, row.territories, armies
+            );
+            let color = if row.territories == 0 {
+                self.theme.muted_text
+            } else {
+                self.theme.text
+            };
+            draw_text(&text, x + 24.0, y + 15.0, 16.0, color);
+        }
+    }
+
+    // Drawn under the territory fills, so only the outer half of each thick
+    // stroke shows as a soft halo around the continent's territories
+    pub fn draw_continent_tints(&self) {
+        for (i, continent) in self.game.continents.iter().enumerate() {
+            let tint = CONTINENT_TINTS[i % CONTINENT_TINTS.len()];
+            for &territory in &continent.territories {
+                let vertices = &self.game.territories[territory].vertices;
+                let n = vertices.len();
+                for j in 0..n {
+                    let start = self.camera.world_to_screen(vertices[j]);
+                    let end = self.camera.world_to_screen(vertices[(j + 1) % n]);
+                    draw_line(start.x, start.y, end.x, end.y, 8.0, tint);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Continent, PlayerStats};
+    use crate::game::events::GameEvent;
+    use crate::game::player::PlayerId;
+    use crate::test_support::{game_state, territory};
+    use macroquad::prelude::*;
+
+    #[test]
+    fn history_rows_map_back_to_their_events() {
+        let mut state = game_state(
+            vec![territory("A", 0, 9, vec![1]), territory("B", 1, 2, vec![0])],
+            0,
+        );
+        for _ in 0..10 {
+            state.game.events.push(GameEvent::Undone {
+                player: PlayerId(0),
+            });
+        }
+        let result = state.game.roll_attack(0, 1);
+        state.game.apply_combat(0, 1, &result);
+        let last = state.game.events.len() - 1;
+        match &state.game.events[last] {
+            GameEvent::Attacked {
+                attacker_dice,
+                defender_dice,
+                ..
+            } => {
+                assert_eq!(attacker_dice, &result.attacker_dice);
+                assert_eq!(defender_dice, &result.defender_dice);
+            }
+            event => panic!("expected an attack, got {:?}", event),
+        }
+
+        // The newest entry is the bottom of the eight rows shown
+        assert_eq!(
+            state.log_event_at(vec2(50.0, 140.0 + 4.0 + 18.0 * 7.5)),
+            Some(last)
+        );
+        assert_eq!(
+            state.log_event_at(vec2(50.0, 140.0 + 4.0 + 18.0 * 0.5)),
+            Some(last - 7)
+        );
+        assert_eq!(
+            state.log_event_at(vec2(50.0, 140.0 + 4.0 + 18.0 * 8.5)),
+            None
+        );
+        assert_eq!(state.log_event_at(vec2(400.0, 150.0)), None);
+        state.scroll_log(2);
+        assert_eq!(
+            state.log_event_at(vec2(50.0, 140.0 + 4.0 + 18.0 * 7.5)),
+            Some(last - 2)
+        );
+    }
+
+    #[test]
+    fn stats_total_each_players_holdings() {
+        let mut state = game_state(
+            vec![
+                territory("A", 0, 3, vec![1]),
+                territory("B", 1, 2, vec![0, 2]),
+                territory("C", 0, 4, vec![1]),
+                territory("D", 1, 1, vec![]),
+            ],
+            0,
+        );
+        state.game.territories[3].owner = None;
+        assert_eq!(
+            state.player_stats(),
+            [
+                PlayerStats {
+                    player: PlayerId(0),
+                    territories: 2,
+                    armies: 7
+                },
+                PlayerStats {
+                    player: PlayerId(1),
+                    territories: 1,
+                    armies: 2
+                },
+            ]
+        );
+    }
+    #[test]
+    fn fog_hides_who_holds_a_continent() {
+        let mut state = game_state(
+            vec![
+                territory("A", 0, 3, vec![1]),
+                territory("B", 1, 2, vec![0, 2]),
+                territory("C", 1, 4, vec![1]),
+            ],
+            0,
+        );
+        state.game.continents.push(Continent {
+            name: "East".to_string(),
+            territories: vec![1, 2],
+            bonus: 2,
+            required_capital: None,
+        });
+        let row = |state: &crate::GameState| state.continent_row(&state.game.continents[0]).0;
+        assert_eq!(row(&state), "East +2: Player 2 (you 0/2)");
+        // C is out of sight from A
+        state.fog_of_war = true;
+        assert_eq!(row(&state), "East +2: unknown (you 0/2)");
+        state.game.territories[2].adjacent.push(0);
+        state.game.territories[0].adjacent.push(2);
+        assert_eq!(row(&state), "East +2: Player 2 (you 0/2)");
+    }
+}
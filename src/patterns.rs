@@ -0,0 +1,76 @@
+use crate::camera::Camera;
+use crate::game::geometry::clip_segment;
+use crate::game::player::Pattern;
+use crate::game::Territory;
+use macroquad::prelude::*;
+
+// Screen pixels between stripes or dots, whatever the zoom
+const PATTERN_SPACING: f32 = 10.0;
+const PATTERN_THICKNESS: f32 = 2.0;
+const DOT_RADIUS: f32 = 2.0;
+
+impl Territory {
+    // Overlays the owner's pattern, clipped to the outline
+    pub fn draw_pattern(&self, pattern: Pattern, color: Color, camera: &Camera) {
+        let spacing = PATTERN_SPACING / camera.zoom;
+        match pattern {
+            Pattern::Solid => {}
+            Pattern::DiagonalStripes => self.draw_hatching(vec2(1.0, 1.0), spacing, color, camera),
+            Pattern::HorizontalStripes => {
+                self.draw_hatching(vec2(1.0, 0.0), spacing, color, camera)
+            }
+            Pattern::CrossHatch => {
+                self.draw_hatching(vec2(1.0, 1.0), spacing * 1.5, color, camera);
+                self.draw_hatching(vec2(1.0, -1.0), spacing * 1.5, color, camera);
+            }
+            Pattern::Dots => {
+                let mut y = self.min.y + spacing / 2.0;
+                while y < self.max.y {
+                    // Every other row is offset by half a step
+                    let row = ((y - self.min.y) / spacing) as i32;
+                    let mut x = self.min.x + spacing * if row % 2 == 0 { 0.5 } else { 1.0 };
+                    while x < self.max.x {
+                        let point = vec2(x, y);
+                        if self.is_point_inside(point) {
+                            let screen = camera.world_to_screen(point);
+                            draw_circle(screen.x, screen.y, DOT_RADIUS, color);
+                        }
+                        x += spacing;
+                    }
+                    y += spacing;
+                }
+            }
+        }
+    }
+
+    // Parallel lines running along `direction`, `spacing` world units apart
+    fn draw_hatching(&self, direction: Vec2, spacing: f32, color: Color, camera: &Camera) {
+        let along = direction.normalize();
+        let across = along.perp();
+        let corners = [
+            self.min,
+            vec2(self.max.x, self.min.y),
+            self.max,
+            vec2(self.min.x, self.max.y),
+        ];
+        let range = |axis: Vec2| {
+            corners
+                .iter()
+                .map(|&corner| corner.dot(axis))
+                .fold((f32::MAX, f32::MIN), |(lo, hi), d| (lo.min(d), hi.max(d)))
+        };
+        let (start, end) = range(along);
+        let (first, last) = range(across);
+        let mut offset = first + spacing / 2.0;
+        while offset < last {
+            let a = across * offset + along * start;
+            let b = across * offset + along * end;
+            for (from, to) in clip_segment(&self.vertices, a, b) {
+                let from = camera.world_to_screen(from);
+                let to = camera.world_to_screen(to);
+                draw_line(from.x, from.y, to.x, to.y, PATTERN_THICKNESS, color);
+            }
+            offset += spacing;
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use crate::replay::ReplayAction;
+use crate::GameState;
+use macroquad::prelude::*;
+
+const MENU_WIDTH: f32 = 240.0;
+const ITEM_HEIGHT: f32 = 40.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PauseItem {
+    Resume,
+    Save,
+    Load,
+    Concede,
+    Quit,
+}
+
+const ITEMS: [PauseItem; 5] = [
+    PauseItem::Resume,
+    PauseItem::Save,
+    PauseItem::Load,
+    PauseItem::Concede,
+    PauseItem::Quit,
+];
+
+impl PauseItem {
+    fn label(self, confirming: bool) -> &'static str {
+        match self {
+            PauseItem::Resume => "Resume",
+            PauseItem::Save => "Save game",
+            PauseItem::Load => "Load game",
+            PauseItem::Concede if confirming => "Really concede?",
+            PauseItem::Concede => "Concede",
+            PauseItem::Quit => "Quit",
+        }
+    }
+}
+
+// Opened by Escape when nothing is selected. The game is paused while it's
+// up, so the turn timer and any animations stand still.
+#[derive(Default)]
+pub struct PauseMenu {
+    highlighted: usize,
+    // Concede was chosen once, and choosing it again goes through with it
+    confirming: bool,
+}
+
+impl PauseMenu {
+    // HUD rect of each entry, in a column in the middle of a `hud`-sized
+    // window
+    fn items(hud: Vec2) -> impl Iterator<Item = (Rect, PauseItem)> {
+        let height = ITEM_HEIGHT * ITEMS.len() as f32;
+        let x = (hud.x - MENU_WIDTH) / 2.0;
+        let y = (hud.y - height) / 2.0;
+        ITEMS.into_iter().enumerate().map(move |(i, item)| {
+            let rect = Rect::new(x, y + ITEM_HEIGHT * i as f32, MENU_WIDTH, ITEM_HEIGHT - 4.0);
+            (rect, item)
+        })
+    }
+}
+
+impl GameState {
+    // Escape closes the details panel or route planner, then lets go of the
+    // selection, and only opens the menu when there's nothing left to let go of
+    pub fn escape(&mut self) {
+        if self.inspecting.is_some() {
+            self.inspecting = None;
+        } else if self.route.is_some() {
+            self.route = None;
+        } else if self.selected_territory.is_some() {
+            self.clear_selection();
+        } else {
+            self.pause_menu = Some(PauseMenu::default());
+            self.paused = true;
+        }
+    }
+
+    // Arrows and Enter, or a click on an entry; Escape resumes. Takes all
+    // input while the menu is open.
+    pub fn handle_pause_menu_input(&mut self) -> bool {
+        let (hud, mouse) = (self.hud_size(), self.hud_mouse());
+        let Some(menu) = &mut self.pause_menu else {
+            return false;
+        };
+        if is_key_pressed(KeyCode::Escape) {
+            self.choose(PauseItem::Resume);
+            return true;
+        }
+        if is_key_pressed(KeyCode::Down) {
+            menu.highlighted = (menu.highlighted + 1) % ITEMS.len();
+        }
+        if is_key_pressed(KeyCode::Up) {
+            menu.highlighted = (menu.highlighted + ITEMS.len() - 1) % ITEMS.len();
+        }
+        let clicked = is_mouse_button_pressed(MouseButton::Left)
+            .then(|| PauseMenu::items(hud).find(|(rect, _)| rect.contains(mouse)))
+            .flatten()
+            .map(|(_, item)| item);
+        if let Some(item) = clicked {
+            self.choose(item);
+        } else if is_key_pressed(KeyCode::Enter) {
+            let item = ITEMS[menu.highlighted];
+            self.choose(item);
+        }
+        true
+    }
+
+    // Only a person can give up; the menu can still be opened during a
+    // computer's turn, but not to concede for it
+    fn can_concede(&self) -> bool {
+        !self.game.player(self.game.current_player).is_ai
+    }
+
+    pub fn choose(&mut self, item: PauseItem) {
+        let confirmed = self.pause_menu.as_ref().is_some_and(|menu| menu.confirming);
+        let can_concede = self.can_concede();
+        if let Some(menu) = &mut self.pause_menu {
+            menu.confirming = item == PauseItem::Concede && !confirmed && can_concede;
+        }
+        match item {
+            PauseItem::Resume => {
+                self.pause_menu = None;
+                self.paused = false;
+            }
+            // Stays open, to carry on or quit after saving
+            PauseItem::Save => self.save_game(),
+            PauseItem::Load => {
+                self.load_game();
+                self.choose(PauseItem::Resume);
+            }
+            PauseItem::Concede if !can_concede => {
+                self.warn("Only a human player can concede, on their own turn")
+            }
+            // Asks first, as there's no coming back from it
+            PauseItem::Concede if !confirmed => {}
+            PauseItem::Concede => {
+                let player = self.game.current_player;
+                self.replay.record(ReplayAction::Concede { player });
+                self.concede(player);
+                self.status_message = Some(format!("{} conceded", self.game.player(player).name));
+                self.choose(PauseItem::Resume);
+            }
+            PauseItem::Quit => self.quit_requested = true,
+        }
+    }
+
+    pub fn draw_pause_menu(&self) {
+        let Some(menu) = &self.pause_menu else {
+            return;
+        };
+        draw_rectangle(
+            0.0,
+            0.0,
+            self.hud_size().x,
+            self.hud_size().y,
+            Color::new(0.0, 0.0, 0.0, 0.4),
+        );
+        let mouse = self.hud_mouse();
+        for (i, (rect, item)) in PauseMenu::items(self.hud_size()).enumerate() {
+            let fill = if i == menu.highlighted || rect.contains(mouse) {
+                self.theme.panel_highlight
+            } else {
+                self.theme.panel
+            };
+            draw_rectangle(rect.x, rect.y, rect.w, rect.h, fill);
+            draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, self.theme.panel_border);
+            let label = item.label(menu.confirming);
+            let dimensions = measure_text(label, None, 26, 1.0);
+            let color = if item == PauseItem::Concede && !self.can_concede() {
+                self.theme.muted_text
+            } else {
+                self.theme.text
+            };
+            draw_text(
+                label,
+                rect.x + (rect.w - dimensions.width) / 2.0,
+                rect.y + rect.h / 2.0 + dimensions.offset_y / 2.0,
+                26.0,
+                color,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PauseItem;
+    use crate::game::player::PlayerId;
+    use crate::test_support::{game_state, territory};
+
+    #[test]
+    fn escape_deselects_before_opening_the_menu() {
+        let mut state = game_state(vec![territory("A", 0, 3, vec![])], 0);
+        state.select(0);
+        state.escape();
+        assert_eq!(state.selected_territory, None);
+        assert!(state.pause_menu.is_none());
+
+        state.escape();
+        assert!(state.pause_menu.is_some() && state.paused);
+        state.choose(PauseItem::Resume);
+        assert!(state.pause_menu.is_none() && !state.paused);
+    }
+
+    #[test]
+    fn conceding_needs_confirming() {
+        let mut state = game_state(
+            vec![territory("A", 0, 3, vec![1]), territory("B", 1, 3, vec![0])],
+            0,
+        );
+        state.escape();
+        state.choose(PauseItem::Concede);
+        assert_eq!(state.game.territories[0].owner, Some(PlayerId(0)));
+        // Backing out in between starts the confirmation over
+        state.choose(PauseItem::Resume);
+        state.escape();
+        state.choose(PauseItem::Concede);
+        assert!(state.pause_menu.is_some());
+
+        state.choose(PauseItem::Concede);
+        assert_eq!(state.game.territories[0].owner, None);
+        assert_eq!(state.game.current_player, PlayerId(1));
+        assert!(state.pause_menu.is_none());
+    }
+
+    #[test]
+    fn computers_cant_be_conceded_for() {
+        let mut state = game_state(
+            vec![
+                territory("A", 0, 3, vec![1]),
+                territory("B", 1, 3, vec![0, 2]),
+                territory("C", 2, 3, vec![1]),
+            ],
+            0,
+        );
+        state.game.players[0].is_ai = true;
+        state.escape();
+        state.choose(PauseItem::Concede);
+        state.choose(PauseItem::Concede);
+        assert_eq!(state.game.territories[0].owner, Some(PlayerId(0)));
+        assert!(state.pause_menu.is_some());
+
+        // A computer turn part played when its seat drops out doesn't carry
+        // on into the next player's
+        state.ai_turn = Some(PlayerId(0));
+        state.concede(PlayerId(0));
+        assert_eq!(state.ai_turn, None);
+    }
+}
@@ -0,0 +1,359 @@
+use crate::game::turn::Phase;
+use crate::replay::ReplayAction;
+use crate::GameState;
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MoveKind {
+    Fortify,
+    // Extra armies following the attackers into a conquered territory
+    Conquest,
+}
+
+// Armies waiting to be moved while the player picks how many. `count` is the
+// total that will end up moved and always stays within `min..=max`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingMove {
+    pub kind: MoveKind,
+    pub from: usize,
+    pub to: usize,
+    pub count: i32,
+    pub min: i32,
+    pub max: i32,
+}
+
+impl PendingMove {
+    pub fn adjust(&mut self, delta: i32) {
+        self.count = (self.count + delta).clamp(self.min, self.max);
+    }
+}
+
+// Panel in the middle of a `hud`-sized window with its - and + buttons
+fn layout(hud: Vec2) -> (Rect, Rect, Rect) {
+    let (width, height) = (320.0, 130.0);
+    let panel = Rect::new((hud.x - width) / 2.0, (hud.y - height) / 2.0, width, height);
+    let button = 36.0;
+    let y = panel.y + 44.0;
+    let minus = Rect::new(panel.x + 40.0, y, button, button);
+    let plus = Rect::new(panel.right() - 40.0 - button, y, button, button);
+    (panel, minus, plus)
+}
+
+impl GameState {
+    // Opens the spinner for a fortify move, starting at everything that can
+    // leave the source
+    pub fn begin_fortify_move(&mut self, from: usize, to: usize) {
+        let max = self.game.territories[from].armies - 1;
+        if max < 1 {
+            self.warn(format!(
+                "{} has no armies to spare",
+                self.game.territories[from].name
+            ));
+            return;
+        }
+        let max = max.min(self.game.room_in(to));
+        if max < 1 {
+            self.warn(format!(
+                "{} can't hold any more armies",
+                self.game.territories[to].name
+            ));
+            return;
+        }
+        self.pending_move = Some(PendingMove {
+            kind: MoveKind::Fortify,
+            from,
+            to,
+            count: max,
+            min: 1,
+            max,
+        });
+    }
+
+    // Step-by-step fortify: moves a single army and keeps the source
+    // selected, so each further click moves one more
+    pub fn fortify_step(&mut self, from: usize, to: usize) {
+        if self.move_one(from, to) {
+            self.fortify_steps.push((from, to));
+        }
+    }
+
+    fn move_one(&mut self, from: usize, to: usize) -> bool {
+        match self.game.fortify(from, to, 1) {
+            Ok(_) => {
+                self.replay.record(ReplayAction::FortifyStep { from, to });
+                self.animate_move(from, to, 1);
+                let (from, to) = (&self.game.territories[from], &self.game.territories[to]);
+                self.status_message = Some(format!(
+                    "{} {}, {} {} (right-click to move one back)",
+                    from.name, from.armies, to.name, to.armies
+                ));
+                true
+            }
+            Err(err) => {
+                self.warn(err.to_string());
+                false
+            }
+        }
+    }
+
+    // Right-clicking a destination in step-by-step fortify moves one of the
+    // armies stepped there back to the selected source. Returns whether the
+    // click was used.
+    pub fn step_back(&mut self, territory: usize) -> bool {
+        let Some(source) = self.selected_territory else {
+            return false;
+        };
+        let step = self
+            .fortify_steps
+            .iter()
+            .rposition(|&step| step == (source, territory));
+        let Some(step) = step.filter(|_| self.step_fortify && self.game.phase == Phase::Fortify)
+        else {
+            return false;
+        };
+        if self.move_one(territory, source) {
+            self.fortify_steps.remove(step);
+        }
+        true
+    }
+
+    // Called once a conquest has already moved the minimum in; the player can
+    // then send in more, up to the rules' maximum
+    pub fn begin_conquest_move(&mut self, from: usize, to: usize) {
+        let moved = self.game.territories[to].armies;
+        let available = moved + self.game.territories[from].armies - 1;
+        let (_, max) = self.game.conquest_bounds(available, 0);
+        if max <= moved {
+            return;
+        }
+        self.pending_move = Some(PendingMove {
+            kind: MoveKind::Conquest,
+            from,
+            to,
+            count: max,
+            min: moved,
+            max,
+        });
+    }
+
+    pub fn confirm_pending_move(&mut self) {
+        let Some(pending) = self.pending_move.take() else {
+            return;
+        };
+        self.replay.record(ReplayAction::Move(pending));
+        let (from, to) = (pending.from, pending.to);
+        match pending.kind {
+            MoveKind::Fortify => {
+                self.status_message = Some(match self.game.fortify(from, to, pending.count) {
+                    Ok(moved) => {
+                        self.animate_move(from, to, moved);
+                        format!(
+                            "Moved {} army(s) from {} to {}",
+                            moved, self.game.territories[from].name, self.game.territories[to].name
+                        )
+                    }
+                    Err(err) => err.to_string(),
+                });
+                self.clear_selection();
+            }
+            MoveKind::Conquest => {
+                let extra = self.game.follow_conquest(from, to, pending.count);
+                self.animate_move(from, to, extra);
+            }
+        }
+    }
+
+    // Escape backs out of a fortify; a conquest keeps the armies already moved
+    pub fn cancel_pending_move(&mut self) {
+        if let Some(pending) = &mut self.pending_move {
+            pending.count = pending.min;
+        }
+        match self.pending_move.map(|pending| pending.kind) {
+            Some(MoveKind::Conquest) => self.confirm_pending_move(),
+            _ => self.pending_move = None,
+        }
+    }
+
+    pub fn handle_pending_move_input(&mut self) {
+        let (hud, mouse) = (self.hud_size(), self.hud_mouse());
+        let Some(pending) = &mut self.pending_move else {
+            return;
+        };
+        if is_key_pressed(KeyCode::Up) || is_key_pressed(KeyCode::Right) {
+            pending.adjust(1);
+        }
+        if is_key_pressed(KeyCode::Down) || is_key_pressed(KeyCode::Left) {
+            pending.adjust(-1);
+        }
+        if is_mouse_button_pressed(MouseButton::Left) {
+            let (_, minus, plus) = layout(hud);
+            if minus.contains(mouse) {
+                pending.adjust(-1);
+            } else if plus.contains(mouse) {
+                pending.adjust(1);
+            }
+        }
+
+        if is_key_pressed(KeyCode::Enter) {
+            self.confirm_pending_move();
+        } else if is_key_pressed(KeyCode::Escape) {
+            self.cancel_pending_move();
+        }
+    }
+
+    pub fn draw_pending_move(&self) {
+        let Some(pending) = &self.pending_move else {
+            return;
+        };
+        let (panel, minus, plus) = layout(self.hud_size());
+        draw_rectangle(panel.x, panel.y, panel.w, panel.h, self.theme.panel_at(0.9));
+        draw_rectangle_lines(
+            panel.x,
+            panel.y,
+            panel.w,
+            panel.h,
+            2.0,
+            self.theme.panel_border,
+        );
+        let title = format!(
+            "Move armies from {} to {}",
+            self.game.territories[pending.from].name, self.game.territories[pending.to].name
+        );
+        draw_text(
+            &title,
+            panel.x + 12.0,
+            panel.y + 26.0,
+            20.0,
+            self.theme.text,
+        );
+
+        for (rect, label, enabled) in [
+            (minus, "-", pending.count > pending.min),
+            (plus, "+", pending.count < pending.max),
+        ] {
+            let (fill, text) = if enabled {
+                (self.theme.panel_highlight, self.theme.text)
+            } else {
+                (self.theme.panel, self.theme.muted_text)
+            };
+            draw_rectangle(rect.x, rect.y, rect.w, rect.h, fill);
+            let dimensions = measure_text(label, None, 32, 1.0);
+            draw_text(
+                label,
+                rect.center().x - dimensions.width / 2.0,
+                rect.center().y + dimensions.offset_y / 2.0,
+                32.0,
+                text,
+            );
+        }
+
+        let count = pending.count.to_string();
+        let dimensions = measure_text(&count, None, 36, 1.0);
+        draw_text(
+            &count,
+            panel.center().x - dimensions.width / 2.0,
+            minus.center().y + dimensions.offset_y / 2.0,
+            36.0,
+            self.theme.text,
+        );
+
+        let hint = match pending.kind {
+            MoveKind::Fortify => "Up/Down to change, Enter to move, Esc to cancel",
+            MoveKind::Conquest => "Up/Down to change, Enter to move, Esc for minimum",
+        };
+        draw_text(
+            hint,
+            panel.x + 12.0,
+            panel.bottom() - 14.0,
+            16.0,
+            self.theme.muted_text,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::turn::Phase;
+    use crate::test_support::{game_state, territory};
+
+    #[test]
+    fn count_stays_within_bounds() {
+        let mut pending = PendingMove {
+            kind: MoveKind::Fortify,
+            from: 0,
+            to: 1,
+            count: 3,
+            min: 1,
+            max: 4,
+        };
+        pending.adjust(5);
+        assert_eq!(pending.count, 4);
+        pending.adjust(-10);
+        assert_eq!(pending.count, 1);
+    }
+
+    #[test]
+    fn fortify_moves_the_chosen_count() {
+        let mut state = game_state(
+            vec![territory("A", 0, 6, vec![1]), territory("B", 0, 1, vec![0])],
+            0,
+        );
+        state.game.phase = Phase::Fortify;
+        state.begin_fortify_move(0, 1);
+        assert_eq!(state.pending_move.map(|p| p.count), Some(5));
+        state.pending_move.as_mut().unwrap().adjust(-3);
+        state.confirm_pending_move();
+        assert_eq!(state.game.territories[0].armies, 4);
+        assert_eq!(state.game.territories[1].armies, 3);
+    }
+
+    #[test]
+    fn step_fortify_moves_one_army_per_click() {
+        let mut state = game_state(
+            vec![territory("A", 0, 3, vec![1]), territory("B", 0, 1, vec![0])],
+            0,
+        );
+        state.game.phase = Phase::Fortify;
+        state.step_fortify = true;
+        state.select(0);
+        for _ in 0..3 {
+            state.click_territory(1, false);
+        }
+        // The third click would have emptied the source
+        assert_eq!(state.game.territories[0].armies, 1);
+        assert_eq!(state.game.territories[1].armies, 3);
+        assert_eq!(state.selected_territory, Some(0));
+        assert_eq!(state.pending_move, None);
+
+        assert!(state.step_back(1));
+        assert_eq!(state.game.territories[0].armies, 2);
+        assert_eq!(state.game.territories[1].armies, 2);
+        assert!(!state.step_back(0));
+
+        // Only armies stepped out of the selection can be sent back, so B's
+        // own army stays put
+        assert!(state.step_back(1));
+        assert!(!state.step_back(1));
+        assert_eq!(state.game.territories[1].armies, 1);
+    }
+
+    #[test]
+    fn cancelling_a_conquest_keeps_the_minimum() {
+        let mut state = game_state(
+            vec![territory("A", 0, 5, vec![1]), territory("B", 0, 3, vec![0])],
+            0,
+        );
+        state.begin_conquest_move(0, 1);
+        assert_eq!(state.pending_move.map(|p| (p.min, p.max)), Some((3, 7)));
+        state.cancel_pending_move();
+        assert_eq!(state.pending_move, None);
+        assert_eq!(state.game.territories[0].armies, 5);
+
+        state.begin_conquest_move(0, 1);
+        state.confirm_pending_move();
+        assert_eq!(state.game.territories[0].armies, 1);
+        assert_eq!(state.game.territories[1].armies, 7);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::game::turn::Phase;
+use crate::replay::ReplayAction;
+use crate::GameState;
+use macroquad::prelude::*;
+
+impl GameState {
+    // Reinforcements not yet spoken for by the wheel buffer
+    pub fn unbuffered_reinforcements(&self) -> i32 {
+        self.game.reinforcements_remaining - self.pending_placements.values().sum::<i32>()
+    }
+
+    // The territory the wheel adds armies to: the selected friendly
+    // territory in Reinforce, while the pointer is over it
+    pub fn wheel_placement_target(&self) -> Option<usize> {
+        let selected = self.selected_territory?;
+        (self.game.phase == Phase::Reinforce
+            && self.hovered_territory == Some(selected)
+            && self.game.territories[selected].owner == Some(self.game.current_player))
+        .then_some(selected)
+    }
+
+    // Moves `delta` armies between the pool and a territory's buffered
+    // placement, clamped so the buffer never goes below zero or past the pool
+    pub fn adjust_pending_placement(&mut self, territory: usize, delta: i32) {
+        let buffered = self
+            .pending_placements
+            .get(&territory)
+            .copied()
+            .unwrap_or(0);
+        let count = (buffered + delta).clamp(0, buffered + self.unbuffered_reinforcements());
+        if count == 0 {
+            self.pending_placements.remove(&territory);
+        } else {
+            self.pending_placements.insert(territory, count);
+        }
+    }
+
+    // Places every buffered army for real. If that empties the pool the game
+    // moves on to Attack by itself, so returns whether anything was placed.
+    pub fn commit_pending_placements(&mut self) -> bool {
+        let placements = std::mem::take(&mut self.pending_placements);
+        for (&territory, &count) in &placements {
+            let placed = self.game.place_reinforcements(territory, count);
+            for _ in 0..placed {
+                self.replay
+                    .record(ReplayAction::PlaceReinforcement { territory });
+            }
+        }
+        !placements.is_empty()
+    }
+
+    pub fn handle_wheel_placement(&mut self) {
+        let Some(territory) = self.wheel_placement_target() else {
+            return;
+        };
+        // As with zooming only the sign of the wheel delta is used
+        let (_, wheel) = mouse_wheel();
+        if wheel > 0.0 {
+            self.adjust_pending_placement(territory, 1);
+        } else if wheel < 0.0 {
+            self.adjust_pending_placement(territory, -1);
+        }
+    }
+
+    // "+n" over each territory with armies waiting to be placed
+    pub fn draw_pending_placements(&self) {
+        for (&territory, &count) in &self.pending_placements {
+            let text = format!("+{}", count);
+            let center = self
+                .camera
+                .world_to_screen(self.game.territories[territory].centroid());
+            let dimensions = measure_text(&text, None, 24, 1.0);
+            draw_text(
+                &text,
+                center.x - dimensions.width / 2.0,
+                center.y - dimensions.height - 4.0,
+                24.0,
+                self.theme.placement,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::turn::Phase;
+    use crate::test_support::{game_state, territory};
+
+    fn reinforcing() -> crate::GameState {
+        let mut state = game_state(
+            vec![
+                territory("A", 0, 1, vec![1]),
+                territory("B", 0, 1, vec![0, 2]),
+                territory("C", 1, 1, vec![1]),
+            ],
+            0,
+        );
+        state.game.reinforcements_remaining = 3;
+        state
+    }
+
+    #[test]
+    fn buffer_is_clamped_to_the_pool_and_zero() {
+        let mut state = reinforcing();
+        state.adjust_pending_placement(0, 2);
+        state.adjust_pending_placement(1, 5);
+        assert_eq!(state.pending_placements.get(&0), Some(&2));
+        assert_eq!(state.pending_placements.get(&1), Some(&1));
+        assert_eq!(state.unbuffered_reinforcements(), 0);
+
+        state.adjust_pending_placement(1, -4);
+        assert_eq!(state.pending_placements.get(&1), None);
+        assert_eq!(state.unbuffered_reinforcements(), 1);
+        // Nothing is placed until the buffer is committed
+        assert_eq!(state.game.territories[0].armies, 1);
+    }
+
+    #[test]
+    fn committing_places_the_buffered_armies() {
+        let mut state = reinforcing();
+        state.adjust_pending_placement(0, 1);
+        assert!(state.commit_pending_placements());
+        assert_eq!(state.game.territories[0].armies, 2);
+        assert_eq!(state.game.phase, Phase::Reinforce);
+
+        state.adjust_pending_placement(1, 2);
+        assert!(state.commit_pending_placements());
+        assert_eq!(state.game.territories[1].armies, 3);
+        assert_eq!(state.game.phase, Phase::Attack);
+        assert!(!state.commit_pending_placements());
+    }
+}
@@ -0,0 +1,134 @@
+use crate::game::player::PlayerId;
+use crate::panels::PlayerStats;
+use crate::GameState;
+use macroquad::prelude::*;
+use serde::Deserialize;
+
+const BAR_WIDTH: f32 = 360.0;
+const BAR_HEIGHT: f32 = 18.0;
+const BAR_TOP: f32 = 4.0;
+
+// What the bar across the top of the screen splits between the players
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgressMeasure {
+    #[default]
+    Territories,
+    Armies,
+    Off,
+}
+
+// One player's stretch of the bar: what they hold and the fraction of the
+// whole it comes to
+#[derive(Debug, PartialEq)]
+pub struct Segment {
+    pub player: PlayerId,
+    pub count: i32,
+    pub share: f32,
+}
+
+// Each player's share of the total by `measure`, in seat order. A player
+// left with nothing gets a share of zero.
+pub fn segments(stats: &[PlayerStats], measure: ProgressMeasure) -> Vec<Segment> {
+    let count = |row: &PlayerStats| match measure {
+        ProgressMeasure::Armies => row.armies,
+        _ => row.territories as i32,
+    };
+    let total: i32 = stats.iter().map(count).sum();
+    stats
+        .iter()
+        .map(|row| Segment {
+            player: row.player,
+            count: count(row),
+            share: if total > 0 {
+                count(row) as f32 / total as f32
+            } else {
+                0.0
+            },
+        })
+        .collect()
+}
+
+impl GameState {
+    // Taken again whenever the board is redrawn into the cache, since that's
+    // when anything it counts can have changed
+    pub fn refresh_progress(&mut self) {
+        self.progress = self.player_stats();
+    }
+
+    pub fn draw_progress_bar(&self) {
+        // Other players' armies are hidden under fog of war, so there the bar
+        // falls back to territories, which everyone can count
+        let measure = match self.progress_measure {
+            ProgressMeasure::Off => return,
+            ProgressMeasure::Armies if self.fog_of_war => ProgressMeasure::Territories,
+            measure => measure,
+        };
+        let x = (self.hud_size().x - BAR_WIDTH) / 2.0;
+        // Moved down out of the way of the spectating banner
+        let top = self
+            .spectating_banner()
+            .map_or(BAR_TOP, |(_, banner)| banner.bottom() + BAR_TOP);
+        let mut left = x;
+        for segment in segments(&self.progress, measure) {
+            let width = BAR_WIDTH * segment.share;
+            if width <= 0.0 {
+                continue;
+            }
+            let color = self.game.player(segment.player).color;
+            draw_rectangle(left, top, width, BAR_HEIGHT, color);
+            let label = segment.count.to_string();
+            let dimensions = measure_text(&label, None, 16, 1.0);
+            // Under fog of war only the viewer's own count is given
+            let hidden = self.fog_of_war && segment.player != self.game.current_player;
+            if !hidden && dimensions.width + 4.0 <= width {
+                draw_text(
+                    &label,
+                    left + (width - dimensions.width) / 2.0,
+                    top + (BAR_HEIGHT + dimensions.offset_y) / 2.0,
+                    16.0,
+                    crate::contrasting_text_color(color),
+                );
+            }
+            left += width;
+        }
+        draw_rectangle_lines(x, top, BAR_WIDTH, BAR_HEIGHT, 1.0, self.theme.panel_border);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{segments, ProgressMeasure};
+    use crate::game::player::PlayerId;
+    use crate::test_support::{game_state, territory};
+
+    #[test]
+    fn shares_follow_the_board_down_to_zero() {
+        let mut state = game_state(
+            vec![
+                territory("A", 0, 6, vec![1]),
+                territory("B", 0, 1, vec![0, 2]),
+                territory("C", 1, 3, vec![1]),
+            ],
+            0,
+        );
+        state.refresh_progress();
+        let by_territory = segments(&state.progress, ProgressMeasure::Territories);
+        assert_eq!(
+            by_territory.iter().map(|s| s.count).collect::<Vec<_>>(),
+            [2, 1]
+        );
+        assert!((by_territory[0].share - 2.0 / 3.0).abs() < 1e-6);
+        let by_army = segments(&state.progress, ProgressMeasure::Armies);
+        assert_eq!(by_army[0].count, 7);
+        assert!((by_army[1].share - 0.3).abs() < 1e-6);
+
+        // Losing the last territory leaves nothing of the bar
+        state.game.territories[2].owner = Some(PlayerId(0));
+        state.refresh_progress();
+        let after = segments(&state.progress, ProgressMeasure::Territories);
+        assert_eq!(after[1].count, 0);
+        assert_eq!(after[1].share, 0.0);
+        assert_eq!(after[0].share, 1.0);
+    }
+}
@@ -0,0 +1,373 @@
+use crate::game::map::MapLoadError;
+use crate::game::player::PlayerId;
+use crate::game::rng::Rng;
+use crate::game::save::{GameSnapshot, SaveError};
+use crate::game::Game;
+use crate::keys::Action;
+use crate::pending_move::PendingMove;
+use crate::GameState;
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+pub const REPLAY_PATH: &str = "replay.json";
+
+// One decision made by a player, recorded at the point the input is handled
+// rather than deep in the rules, so knock-on effects (an empty reinforcement
+// pool ending the phase, say) aren't recorded twice
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ReplayAction {
+    ClaimTerritory { territory: usize },
+    PlaceInitialArmy { territory: usize },
+    PlaceReinforcement { territory: usize },
+    AutoReinforce { player: PlayerId },
+    TradeCards { player: PlayerId, cards: [usize; 3] },
+    // A single round of dice
+    Attack { attacker: usize, defender: usize },
+    Blitz { attacker: usize, defender: usize },
+    Move(PendingMove),
+    // One army moved by a click in step-by-step fortify
+    FortifyStep { from: usize, to: usize },
+    EndPhase,
+    ForfeitTurn,
+    Concede { player: PlayerId },
+    FormAlliance { a: PlayerId, b: PlayerId },
+    BreakAlliance { a: PlayerId, b: PlayerId },
+    Undo,
+    // The AI only looks at the board, so its whole turn replays from this
+    AiTurn { player: PlayerId },
+}
+
+// A game from some starting position: the board, the state of the RNG at that
+// moment and every action taken since. Dice and cards are the only random
+// parts, so restoring the RNG makes replaying the actions exact.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Replay {
+    seed: u64,
+    start: GameSnapshot,
+    actions: Vec<ReplayAction>,
+}
+
+impl Replay {
+    pub fn new(game: &GameState) -> Replay {
+        Replay {
+            seed: game.game.rng.state(),
+            start: game.game.snapshot(),
+            actions: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    pub fn record(&mut self, action: ReplayAction) {
+        self.actions.push(action);
+    }
+
+    pub fn play(&self, game: &mut GameState) -> Result<(), MapLoadError> {
+        self.play_to(game, self.actions.len())
+    }
+
+    // Rebuilds the board as it was after the first `step` actions
+    pub fn play_to(&self, game: &mut GameState, step: usize) -> Result<(), MapLoadError> {
+        game.restore(&self.start)?;
+        game.game.rng = Rng::new(self.seed);
+        for &action in &self.actions[..step.min(self.actions.len())] {
+            game.apply_replay_action(action);
+        }
+        // Nobody is listening to the replayed game
+        game.pending_sounds.clear();
+        Ok(())
+    }
+
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveError> {
+        let path = path.as_ref();
+        let json = serde_json::to_string(self).map_err(|source| SaveError::Json {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        fs::write(path, json).map_err(|source| SaveError::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Replay, SaveError> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path).map_err(|source| SaveError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        serde_json::from_str(&json).map_err(|source| SaveError::Json {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+}
+
+impl GameState {
+    // Starts a fresh recording from the current position
+    pub fn begin_replay(&mut self) {
+        self.replay = Replay::new(self);
+    }
+
+    // Carries out a recorded action the same way the input handling did
+    fn apply_replay_action(&mut self, action: ReplayAction) {
+        match action {
+            ReplayAction::ClaimTerritory { territory } => {
+                self.game.claim_territory(territory);
+            }
+            ReplayAction::PlaceInitialArmy { territory } => {
+                self.game.place_initial_army(territory);
+            }
+            ReplayAction::PlaceReinforcement { territory } => {
+                self.game.place_reinforcement(territory)
+            }
+            ReplayAction::AutoReinforce { player } => self.game.auto_reinforce(player),
+            ReplayAction::TradeCards { player, cards } => {
+                let _ = self.game.trade_cards(player, cards);
+            }
+            ReplayAction::Attack { attacker, defender } => {
+                self.start_attack_animation(attacker, defender);
+                self.update_combat_animation(0.0, true);
+            }
+            ReplayAction::Blitz { attacker, defender } => {
+                self.game.blitz_attack(attacker, defender);
+            }
+            ReplayAction::Move(pending) => {
+                self.pending_move = Some(pending);
+                self.confirm_pending_move();
+            }
+            ReplayAction::FortifyStep { from, to } => {
+                let _ = self.game.fortify(from, to, 1);
+            }
+            ReplayAction::EndPhase => self.next_phase(),
+            ReplayAction::ForfeitTurn => self.forfeit_turn(),
+            ReplayAction::Concede { player } => {
+                self.concede(player);
+            }
+            ReplayAction::FormAlliance { a, b } => {
+                self.game.form_alliance(a, b);
+            }
+            ReplayAction::BreakAlliance { a, b } => {
+                self.game.break_alliance(a, b);
+            }
+            ReplayAction::Undo => {
+                self.undo();
+            }
+            ReplayAction::AiTurn { player } => self.run_ai_turn(player),
+        }
+        // Replays jump straight to the result
+        self.movements.clear();
+    }
+}
+
+// Steps through a replay on its own copy of the game, leaving the live one
+// untouched
+pub struct ReplayViewer {
+    replay: Replay,
+    step: usize,
+    game: GameState,
+    status_message: Option<String>,
+}
+
+impl ReplayViewer {
+    // Replays don't store players, so the live game's are borrowed for names
+    // and colours
+    pub fn open(live: &GameState, replay: Replay) -> Result<ReplayViewer, MapLoadError> {
+        let mut rules = Game::from_territories(Vec::new(), 0);
+        rules.players = live.game.players.clone();
+        rules.combat_rules = live.game.combat_rules;
+        rules.rules = live.game.rules;
+        rules.max_armies = live.game.max_armies;
+        rules.capital_victory = live.game.capital_victory;
+        rules.team_victory = live.game.team_victory;
+        rules.concede_rule = live.game.concede_rule;
+        rules.fortify_rule = live.game.fortify_rule;
+        let mut game = GameState::from_game(rules);
+        game.show_labels = live.show_labels;
+        game.grid = live.grid;
+        game.hud_scale = live.hud_scale;
+        game.theme = live.theme;
+        game.theme_config = live.theme_config.clone();
+        game.keys = live.keys.clone();
+        replay.play_to(&mut game, 0)?;
+        let (min, max) = game.game.map_bounds();
+        let screen = vec2(screen_width(), screen_height());
+        game.camera.fit(min, max, screen, crate::FIT_MARGIN);
+        Ok(ReplayViewer {
+            replay,
+            step: 0,
+            game,
+            status_message: None,
+        })
+    }
+
+    fn step_to(&mut self, step: usize) {
+        let step = step.min(self.replay.len());
+        if step == self.step {
+            return;
+        }
+        // Stepping forward only needs the next action, but the RNG can't run
+        // backwards, so stepping back replays from the start
+        let result = if step == self.step + 1 {
+            self.game
+                .apply_replay_action(self.replay.actions[self.step]);
+            self.game.pending_sounds.clear();
+            Ok(())
+        } else {
+            self.replay.play_to(&mut self.game, step)
+        };
+        match result {
+            Ok(()) => self.step = step,
+            Err(err) => self.status_message = Some(err.to_string()),
+        }
+    }
+
+    pub fn handle_input(&mut self) {
+        self.game.camera.update(true);
+        if is_key_pressed(KeyCode::Right) {
+            self.step_to(self.step + 1);
+        }
+        if is_key_pressed(KeyCode::Left) {
+            self.step_to(self.step.saturating_sub(1));
+        }
+        let keys = &self.game.keys;
+        let (start, end, save, load) = (
+            keys.pressed(Action::ReplayStart),
+            keys.pressed(Action::ReplayEnd),
+            keys.pressed(Action::Save),
+            keys.pressed(Action::Load),
+        );
+        if start {
+            self.step_to(0);
+        }
+        if end {
+            match self.replay.play(&mut self.game) {
+                Ok(()) => self.step = self.replay.len(),
+                Err(err) => self.status_message = Some(err.to_string()),
+            }
+        }
+        if save {
+            self.status_message = Some(match self.replay.save_to_file(REPLAY_PATH) {
+                Ok(()) => format!("Replay saved to {}", REPLAY_PATH),
+                Err(err) => format!("Save failed: {}", err),
+            });
+        }
+        if load {
+            let loaded = Replay::load_from_file(REPLAY_PATH).and_then(|replay| {
+                replay
+                    .play_to(&mut self.game, 0)
+                    .map(|()| replay)
+                    .map_err(SaveError::Map)
+            });
+            self.status_message = Some(match loaded {
+                Ok(replay) => {
+                    self.replay = replay;
+                    self.step = 0;
+                    format!("Replay loaded from {}", REPLAY_PATH)
+                }
+                Err(err) => format!("Load failed: {}", err),
+            });
+        }
+    }
+
+    pub fn draw(&mut self) {
+        self.game.draw_board();
+        self.game.begin_hud();
+        let hud = self.game.hud_size();
+        let keys = &self.game.keys;
+        let lines = [
+            format!("Replay: step {} of {}", self.step, self.replay.len()),
+            format!(
+                "Left/Right: step  {}/{}: jump  {}/{}: save/load replay  {}: back to game",
+                keys.name(Action::ReplayStart),
+                keys.name(Action::ReplayEnd),
+                keys.name(Action::Save),
+                keys.name(Action::Load),
+                keys.name(Action::Replay)
+            ),
+        ];
+        for (i, line) in lines.iter().enumerate() {
+            draw_text(
+                line,
+                10.0,
+                hud.y - 40.0 + 22.0 * i as f32,
+                22.0,
+                self.game.theme.text,
+            );
+        }
+        if let Some(message) = &self.status_message {
+            draw_text(
+                message,
+                10.0,
+                hud.y - 62.0,
+                20.0,
+                self.game.theme.muted_text,
+            );
+        }
+        set_default_camera();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::turn::Phase;
+    use crate::test_support::{game, game_state, territory};
+
+    #[test]
+    fn replaying_rebuilds_the_same_board() {
+        let territories = || {
+            vec![
+                territory("A", 0, 12, vec![1]),
+                territory("B", 1, 2, vec![0, 2]),
+                territory("C", 1, 1, vec![1]),
+            ]
+        };
+        let mut live = game_state(territories(), 7);
+        while live.game.phase == Phase::Reinforce {
+            live.replay
+                .record(ReplayAction::PlaceReinforcement { territory: 0 });
+            live.game.place_reinforcement(0);
+        }
+        live.replay.record(ReplayAction::Attack {
+            attacker: 0,
+            defender: 1,
+        });
+        live.apply_replay_action(ReplayAction::Attack {
+            attacker: 0,
+            defender: 1,
+        });
+        live.replay.record(ReplayAction::Blitz {
+            attacker: 0,
+            defender: 1,
+        });
+        live.game.blitz_attack(0, 1);
+        if let Some(pending) = live.pending_move {
+            live.replay.record(ReplayAction::Move(pending));
+            live.confirm_pending_move();
+        }
+        live.replay.record(ReplayAction::EndPhase);
+        live.next_phase();
+
+        // A different seed proves the replay brings its own dice
+        let mut replayed = game_state(territories(), 99);
+        live.replay.play(&mut replayed).unwrap();
+        let board = |game: &Game| {
+            game.territories
+                .iter()
+                .map(|t| (t.owner, t.armies))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(board(&replayed.game), board(&live.game));
+        assert_eq!(replayed.game.phase, live.game.phase);
+        assert_eq!(replayed.game.events, live.game.events);
+
+        // Stepping back to the start restores the opening board
+        live.replay.play_to(&mut replayed, 0).unwrap();
+        assert_eq!(board(&replayed.game), board(&game(territories(), 7)));
+    }
+}
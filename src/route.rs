@@ -0,0 +1,150 @@
+use crate::GameState;
+use macroquad::prelude::*;
+use std::collections::VecDeque;
+
+const ROUTE_ALPHA: f32 = 0.9;
+
+// B, then two clicks: the fewest borders to cross from one territory to
+// another, for planning an offensive over several turns
+#[derive(Default)]
+pub struct RoutePlanner {
+    from: Option<usize>,
+    path: Option<Vec<usize>>,
+}
+
+impl GameState {
+    // Breadth-first over the adjacency graph, through anyone's territory.
+    // Neighbours are tried in list order, so ties always resolve the same way.
+    pub fn shortest_path(&self, from: usize, to: usize) -> Option<Vec<usize>> {
+        let mut previous: Vec<Option<usize>> = vec![None; self.game.territories.len()];
+        let mut seen = vec![false; self.game.territories.len()];
+        let mut queue = VecDeque::from([from]);
+        seen[from] = true;
+        while let Some(current) = queue.pop_front() {
+            if current == to {
+                let mut path = vec![to];
+                while let Some(step) = previous[*path.last().unwrap()] {
+                    path.push(step);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for &next in &self.game.territories[current].adjacent {
+                if !seen[next] {
+                    seen[next] = true;
+                    previous[next] = Some(current);
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    pub fn toggle_route_planner(&mut self) {
+        self.route = match self.route {
+            Some(_) => None,
+            None => {
+                self.status_message = Some("Route: click where to start".to_string());
+                Some(RoutePlanner::default())
+            }
+        };
+    }
+
+    // The first click picks the start, the second the end. A click after
+    // that starts a new route.
+    pub fn click_route(&mut self, territory: usize) {
+        let Some(route) = &mut self.route else {
+            return;
+        };
+        let Some(from) = route.from.filter(|_| route.path.is_none()) else {
+            route.from = Some(territory);
+            route.path = None;
+            self.status_message = Some(format!(
+                "Route from {}: click where to go",
+                self.game.territories[territory].name
+            ));
+            return;
+        };
+        let path = self.shortest_path(from, territory);
+        let names = |i: usize| self.game.territories[i].name.clone();
+        match &path {
+            Some(path) => {
+                self.status_message = Some(format!(
+                    "Route from {} to {}: {} border(s) to cross",
+                    names(from),
+                    names(territory),
+                    path.len() - 1
+                ))
+            }
+            None => self.warn(format!(
+                "No route from {} to {}",
+                names(from),
+                names(territory)
+            )),
+        }
+        if let Some(route) = &mut self.route {
+            // A failed pick leaves the start chosen, to try another end
+            route.path = path;
+        }
+    }
+
+    pub fn draw_route(&self) {
+        let Some(route) = &self.route else {
+            return;
+        };
+        let point = |i: usize| {
+            self.camera
+                .world_to_screen(self.game.territories[i].centroid())
+        };
+        let color = Color {
+            a: ROUTE_ALPHA,
+            ..self.theme.route
+        };
+        if let Some(from) = route.from {
+            self.game.territories[from].draw_outline(&self.camera, color, 3.0);
+        }
+        let Some(path) = &route.path else {
+            return;
+        };
+        for pair in path.windows(2) {
+            let (start, end) = (point(pair[0]), point(pair[1]));
+            draw_line(start.x, start.y, end.x, end.y, 4.0, color);
+        }
+        for &step in path {
+            let at = point(step);
+            draw_circle(at.x, at.y, 6.0, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{game_state, territory};
+
+    #[test]
+    fn routes_take_the_fewest_borders() {
+        // A square A-B-D-C-A with E off on its own
+        let mut state = game_state(
+            vec![
+                territory("A", 0, 1, vec![1, 2]),
+                territory("B", 1, 1, vec![0, 3]),
+                territory("C", 0, 1, vec![0, 3]),
+                territory("D", 1, 1, vec![1, 2]),
+                territory("E", 1, 1, vec![]),
+            ],
+            0,
+        );
+        assert_eq!(state.shortest_path(0, 3), Some(vec![0, 1, 3]));
+        assert_eq!(state.shortest_path(2, 2), Some(vec![2]));
+        assert_eq!(state.shortest_path(0, 4), None);
+
+        state.toggle_route_planner();
+        state.click_route(0);
+        state.click_route(4);
+        assert!(state.warning.is_some());
+        // The start is kept, so another end can be tried
+        state.click_route(3);
+        let route = state.route.as_ref().unwrap();
+        assert_eq!(route.path.as_deref(), Some(&[0, 1, 3][..]));
+    }
+}
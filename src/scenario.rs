@@ -0,0 +1,167 @@
+use crate::config::PlayerConfig;
+use crate::game::map::{self, MapLoadError, MapOwner, TerritoryData};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+// Who holds a territory at the start, and with how many armies
+#[derive(Deserialize)]
+pub struct Holding {
+    // Absent or null leaves it neutral; a seat or a player's name, as in maps
+    #[serde(default)]
+    pub owner: Option<MapOwner>,
+    #[serde(default = "one_army")]
+    pub armies: i32,
+}
+
+fn one_army() -> i32 {
+    1
+}
+
+// Something the challenger has to bring about, instead of (or on the way to)
+// taking the whole map. A deadline counts the challenger's own turns.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Objective {
+    // Every one of these continents held at the same time
+    ControlContinents {
+        continents: Vec<String>,
+        #[serde(default)]
+        within_turns: Option<usize>,
+    },
+    // A seat or a player's name
+    EliminatePlayer {
+        player: MapOwner,
+        #[serde(default)]
+        within_turns: Option<usize>,
+    },
+}
+
+impl Objective {
+    pub fn within_turns(&self) -> Option<usize> {
+        match self {
+            Objective::ControlContinents { within_turns, .. }
+            | Objective::EliminatePlayer { within_turns, .. } => *within_turns,
+        }
+    }
+}
+
+// How a challenge ended
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Success,
+    // Why it was lost
+    Failure(String),
+}
+
+// A starting setup laid over a map's geometry, so one map can be played
+// from several positions. Territories are matched by name, or by id written
+// as "#12" so the scenario survives the map renaming them; any the scenario
+// leaves out start neutral.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct Scenario {
+    // Seated in place of the config's players when given, before the lobby
+    pub players: Vec<PlayerConfig>,
+    pub territories: HashMap<String, Holding>,
+    // Win conditions for the challenger; none means play for the whole map
+    pub objectives: Vec<Objective>,
+    // The seat or player's name the objectives are set for; the first seat
+    // when absent
+    pub challenger: Option<MapOwner>,
+}
+
+impl Scenario {
+    pub fn load(path: &Path) -> Result<Scenario, MapLoadError> {
+        let content = fs::read_to_string(path).map_err(|source| MapLoadError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        serde_json::from_str(&content).map_err(|source| MapLoadError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    // Sets every territory's owner and armies from the scenario, an id
+    // taking precedence over a name. Returns the names and ids it gives that
+    // aren't on the map.
+    pub fn apply(&self, data: &mut [TerritoryData]) -> Vec<String> {
+        let ids = map::territory_ids(data);
+        let keys =
+            |i: usize, territory: &TerritoryData| [format!("#{}", ids[i]), territory.name.clone()];
+        for (i, territory) in data.iter_mut().enumerate() {
+            let holding = keys(i, territory)
+                .iter()
+                .find_map(|key| self.territories.get(key));
+            territory.owner = holding.and_then(|holding| holding.owner.clone());
+            territory.armies = holding.map_or(1, |holding| holding.armies);
+        }
+        let mut unknown: Vec<String> = self
+            .territories
+            .keys()
+            .filter(|key| {
+                !data
+                    .iter()
+                    .enumerate()
+                    .any(|(i, territory)| keys(i, territory).contains(key))
+            })
+            .cloned()
+            .collect();
+        unknown.sort();
+        unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Objective, Scenario};
+    use crate::game::map::{MapOwner, TerritoryData};
+    use crate::game::player::PlayerId;
+
+    #[test]
+    fn scenario_sets_holdings_by_name_or_id() {
+        let mut data: Vec<TerritoryData> = serde_json::from_str(
+            r#"[
+                {"name": "Alaska", "vertices": [[0, 0], [1, 0], [0, 1]], "owner": 0,
+                 "armies": 3, "selected": false},
+                {"name": "Kamchatka", "vertices": [[9, 0], [10, 0], [9, 1]], "owner": 0,
+                 "armies": 5, "selected": false},
+                {"name": "Yakutsk", "vertices": [[9, 5], [10, 5], [9, 6]], "owner": 0,
+                 "armies": 6, "selected": false}
+            ]"#,
+        )
+        .unwrap();
+        let scenario: Scenario = serde_json::from_str(
+            r##"{
+                "players": [{"name": "Red", "color": [200, 0, 0]},
+                            {"name": "Blue", "color": [0, 0, 200], "ai": true}],
+                "territories": {"Alaska": {"owner": 1, "armies": 8}, "Atlantis": {},
+                                "#1": {"owner": 0, "armies": 4}, "#7": {}},
+                "objectives": [{"kind": "eliminate_player", "player": "Blue", "within_turns": 10}]
+            }"##,
+        )
+        .unwrap();
+        assert_eq!(scenario.apply(&mut data), ["#7", "Atlantis"]);
+        assert_eq!(
+            (data[0].owner.clone(), data[0].armies),
+            (Some(PlayerId(1).into()), 8)
+        );
+        // Named by the id it's given as it loads
+        assert_eq!(
+            (data[1].owner.clone(), data[1].armies),
+            (Some(PlayerId(0).into()), 4)
+        );
+        // Left out of the scenario, so neutral
+        assert_eq!((data[2].owner.clone(), data[2].armies), (None, 1));
+        assert!(scenario.players[1].ai);
+        assert_eq!(
+            scenario.objectives,
+            [Objective::EliminatePlayer {
+                player: MapOwner::Name("Blue".to_string()),
+                within_turns: Some(10),
+            }]
+        );
+    }
+}
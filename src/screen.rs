@@ -0,0 +1,244 @@
+use crate::game::map::{self, MapLoadError, MapSource, TerritoryData};
+use crate::lobby::Lobby;
+use crate::theme::Theme;
+use crate::GameState;
+use macroquad::prelude::*;
+use std::fs;
+use std::path::Path;
+
+pub const MAPS_DIR: &str = "resources/maps";
+// Extensions `map::source_for_path` knows how to read (`gz` being a
+// compressed JSON map)
+const MAP_EXTENSIONS: [&str; 4] = ["json", "svg", "geojson", "gz"];
+
+const ROW_HEIGHT: f32 = 34.0;
+const LIST_TOP: f32 = 110.0;
+const LIST_WIDTH: f32 = 420.0;
+
+// What the window is showing. Each game is rebuilt from the chosen map, so
+// switching maps never needs a restart.
+pub enum GameScreen {
+    MapSelect(MapSelect),
+    // Seats for the picked map
+    Lobby(Lobby),
+    InGame(Box<GameState>),
+    // The finished game stays on screen behind the result
+    GameOver(Box<GameState>),
+}
+
+impl GameScreen {
+    // A game that has just been won, or whose challenge has been decided,
+    // moves on to the game-over screen, and one reset from there goes back
+    // to being played
+    pub fn check_game_over(self) -> GameScreen {
+        let finished = |game_state: &GameState| {
+            game_state.game.winner().is_some() || game_state.check_objectives().is_some()
+        };
+        match self {
+            GameScreen::InGame(game_state) if finished(&game_state) => {
+                GameScreen::GameOver(game_state)
+            }
+            GameScreen::GameOver(game_state) if !finished(&game_state) => {
+                GameScreen::InGame(game_state)
+            }
+            screen => screen,
+        }
+    }
+}
+
+// A map found on disk, loaded up front so picking one is instant and a
+// broken file shows as broken in the list
+pub struct MapEntry {
+    pub name: String,
+    pub data: Result<Vec<TerritoryData>, String>,
+}
+
+// Already-loaded territories handed to the game as if freshly read
+pub struct LoadedMap(pub Vec<TerritoryData>);
+
+impl MapSource for LoadedMap {
+    fn load(&self) -> Result<Vec<TerritoryData>, MapLoadError> {
+        Ok(self.0.clone())
+    }
+}
+
+// Every map file in `dir`, by name. A missing directory just means no maps.
+pub fn find_maps(dir: &Path) -> Vec<MapEntry> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| MAP_EXTENSIONS.contains(&ext))
+        })
+        .collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|path| MapEntry {
+            name: path
+                .file_stem()
+                .map(|stem| {
+                    let stem = stem.to_string_lossy();
+                    let stem = stem.strip_suffix(".json").unwrap_or(&stem);
+                    stem.replace('_', " ")
+                })
+                .unwrap_or_default(),
+            data: map::source_for_path(&path)
+                .load()
+                .map_err(|err| err.to_string()),
+        })
+        .collect()
+}
+
+pub struct MapSelect {
+    maps: Vec<MapEntry>,
+    highlighted: usize,
+    // Why the last pick couldn't start a game
+    pub error: Option<String>,
+}
+
+impl MapSelect {
+    pub fn new(maps: Vec<MapEntry>) -> MapSelect {
+        MapSelect {
+            maps,
+            highlighted: 0,
+            error: None,
+        }
+    }
+
+    fn row(i: usize) -> Rect {
+        let x = (screen_width() - LIST_WIDTH) / 2.0;
+        Rect::new(
+            x,
+            LIST_TOP + ROW_HEIGHT * i as f32,
+            LIST_WIDTH,
+            ROW_HEIGHT - 4.0,
+        )
+    }
+
+    // Arrows and Enter, or a click on a row. Returns the map to start, if
+    // one was picked and it loaded.
+    pub fn handle_input(&mut self) -> Option<LoadedMap> {
+        let count = self.maps.len();
+        if count == 0 {
+            return None;
+        }
+        if is_key_pressed(KeyCode::Down) {
+            self.highlighted = (self.highlighted + 1) % count;
+        }
+        if is_key_pressed(KeyCode::Up) {
+            self.highlighted = (self.highlighted + count - 1) % count;
+        }
+        let mouse: Vec2 = mouse_position().into();
+        let clicked = is_mouse_button_pressed(MouseButton::Left)
+            .then(|| (0..count).find(|&i| MapSelect::row(i).contains(mouse)))
+            .flatten();
+        if let Some(i) = clicked {
+            self.highlighted = i;
+        }
+        if clicked.is_none() && !is_key_pressed(KeyCode::Enter) {
+            return None;
+        }
+        let entry = &self.maps[self.highlighted];
+        match &entry.data {
+            Ok(data) => Some(LoadedMap(data.clone())),
+            Err(err) => {
+                self.error = Some(format!("{}: {}", entry.name, err));
+                None
+            }
+        }
+    }
+
+    pub fn draw(&self, theme: &Theme) {
+        let title = "Choose a map";
+        let dimensions = measure_text(title, None, 40, 1.0);
+        draw_text(
+            title,
+            (screen_width() - dimensions.width) / 2.0,
+            70.0,
+            40.0,
+            theme.text,
+        );
+        if self.maps.is_empty() {
+            let message = format!("No maps found in {}", MAPS_DIR);
+            draw_text(
+                &message,
+                MapSelect::row(0).x,
+                LIST_TOP + 20.0,
+                22.0,
+                theme.warning,
+            );
+            return;
+        }
+
+        let mouse: Vec2 = mouse_position().into();
+        for (i, entry) in self.maps.iter().enumerate() {
+            let rect = MapSelect::row(i);
+            let fill = if i == self.highlighted || rect.contains(mouse) {
+                theme.panel_highlight
+            } else {
+                theme.panel
+            };
+            draw_rectangle(rect.x, rect.y, rect.w, rect.h, fill);
+            draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, theme.panel_border);
+            let (detail, color) = match &entry.data {
+                Ok(data) => (format!("{} territories", data.len()), theme.muted_text),
+                Err(_) => ("can't be loaded".to_string(), theme.warning),
+            };
+            draw_text(&entry.name, rect.x + 10.0, rect.y + 22.0, 22.0, theme.text);
+            let width = measure_text(&detail, None, 18, 1.0).width;
+            draw_text(
+                &detail,
+                rect.right() - width - 10.0,
+                rect.y + 21.0,
+                18.0,
+                color,
+            );
+        }
+
+        let bottom = MapSelect::row(self.maps.len()).y + 20.0;
+        draw_text(
+            "Up/Down and Enter, or click a map",
+            MapSelect::row(0).x,
+            bottom,
+            18.0,
+            theme.muted_text,
+        );
+        if let Some(error) = &self.error {
+            draw_text(
+                error,
+                MapSelect::row(0).x,
+                bottom + 26.0,
+                20.0,
+                theme.warning,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_maps, MAPS_DIR};
+    use crate::game::Game;
+    use std::path::Path;
+
+    #[test]
+    fn bundled_maps_are_listed_and_load() {
+        let maps = find_maps(Path::new(MAPS_DIR));
+        let names: Vec<&str> = maps.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, ["classic", "islands"]);
+        for entry in &maps {
+            let data = entry.data.as_ref().unwrap();
+            assert!(
+                Game::from_map(data, Vec::new(), 0).is_ok(),
+                "{}",
+                entry.name
+            );
+        }
+        assert!(find_maps(Path::new("no/such/dir")).is_empty());
+    }
+}
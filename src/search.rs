@@ -0,0 +1,156 @@
+use crate::game::Territory;
+use crate::keys::Action;
+use crate::GameState;
+use macroquad::prelude::*;
+
+// Most matches listed under the search box at once
+const MAX_RESULTS: usize = 8;
+
+// Jump-to-territory box opened with '/'
+#[derive(Default)]
+pub struct Search {
+    query: String,
+    // Index into the current matches of the one Enter would pick
+    highlighted: usize,
+}
+
+// Territories whose name contains `query`, ignoring case, in map order
+pub fn matching_territories(territories: &[Territory], query: &str) -> Vec<usize> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let query = query.to_lowercase();
+    (0..territories.len())
+        .filter(|&i| territories[i].name.to_lowercase().contains(&query))
+        .collect()
+}
+
+impl GameState {
+    // Typing into the search box swallows the keyboard, so global shortcuts
+    // must check this
+    pub fn is_typing(&self) -> bool {
+        self.search.is_some()
+    }
+
+    // Opens the box on '/', then feeds it keys until Enter or Esc. Returns
+    // whether the input was taken.
+    pub fn handle_search_input(&mut self) -> bool {
+        let Some(search) = &mut self.search else {
+            if self.keys.pressed(Action::Search) {
+                // The key itself is queued as a character too
+                while get_char_pressed().is_some() {}
+                self.search = Some(Search::default());
+                return true;
+            }
+            return false;
+        };
+
+        let mut edited = false;
+        while let Some(c) = get_char_pressed() {
+            if !c.is_control() {
+                search.query.push(c);
+                edited = true;
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            edited |= search.query.pop().is_some();
+        }
+        if edited {
+            search.highlighted = 0;
+        }
+        let matches = matching_territories(&self.game.territories, &search.query);
+        let shown = matches.len().min(MAX_RESULTS);
+        if shown > 0 {
+            if is_key_pressed(KeyCode::Down) {
+                search.highlighted = (search.highlighted + 1) % shown;
+            }
+            if is_key_pressed(KeyCode::Up) {
+                search.highlighted = (search.highlighted + shown - 1) % shown;
+            }
+        }
+
+        if is_key_pressed(KeyCode::Escape) {
+            self.search = None;
+        } else if is_key_pressed(KeyCode::Enter) {
+            let picked = matches.get(search.highlighted).copied();
+            self.search = None;
+            if let Some(territory) = picked {
+                self.jump_to(territory);
+            }
+        }
+        true
+    }
+
+    // Glides the view to centre on a territory and puts the cursor on it, selecting
+    // it too if it's one the current player could act from
+    fn jump_to(&mut self, territory: usize) {
+        let screen = vec2(screen_width(), screen_height());
+        self.camera
+            .glide_to_center(self.game.territories[territory].centroid(), screen);
+        self.focused_territory = Some(territory);
+        if self.game.territories[territory].owner == Some(self.game.current_player) {
+            self.select(territory);
+        } else {
+            self.clear_selection();
+        }
+    }
+
+    pub fn draw_search(&self) {
+        let Some(search) = &self.search else {
+            return;
+        };
+        let width = 320.0;
+        let x = (self.hud_size().x - width) / 2.0;
+        let (top, line_height) = (60.0, 22.0);
+        let matches = matching_territories(&self.game.territories, &search.query);
+        let shown = matches.len().min(MAX_RESULTS);
+        let height = line_height * (shown + 1) as f32 + 8.0;
+        draw_rectangle(x, top, width, height, self.theme.panel_at(0.95));
+        draw_rectangle_lines(x, top, width, height, 2.0, self.theme.panel_border);
+        draw_text(
+            &format!("Find: {}_", search.query),
+            x + 8.0,
+            top + line_height,
+            20.0,
+            self.theme.text,
+        );
+        for (row, &territory) in matches.iter().take(shown).enumerate() {
+            let y = top + line_height * (row + 2) as f32;
+            if row == search.highlighted {
+                draw_rectangle(
+                    x + 4.0,
+                    y - line_height + 6.0,
+                    width - 8.0,
+                    line_height,
+                    self.theme.panel_highlight,
+                );
+            }
+            draw_text(
+                &self.game.territories[territory].name,
+                x + 16.0,
+                y,
+                20.0,
+                self.theme.text,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::matching_territories;
+    use crate::test_support::territory;
+
+    #[test]
+    fn names_match_ignoring_case() {
+        let territories = vec![
+            territory("Western Europe", 0, 1, vec![]),
+            territory("Northern Europe", 0, 1, vec![]),
+            territory("Egypt", 0, 1, vec![]),
+        ];
+        assert_eq!(matching_territories(&territories, "EUROPE"), vec![0, 1]);
+        assert_eq!(matching_territories(&territories, "gyp"), vec![2]);
+        assert!(matching_territories(&territories, "").is_empty());
+        assert!(matching_territories(&territories, "Asia").is_empty());
+    }
+}
@@ -0,0 +1,136 @@
+use crate::game::Territory;
+use macroquad::prelude::*;
+use std::collections::HashMap;
+
+// Uniform grid over territory bounding boxes. Each cell lists the territories
+// whose box overlaps it, so a hit test only looks at a handful of candidates.
+pub struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl SpatialGrid {
+    pub fn new(territories: &[Territory]) -> SpatialGrid {
+        // Cells roughly the size of an average territory keep lists short
+        let sizes: Vec<f32> = territories
+            .iter()
+            .filter(|t| !t.vertices.is_empty())
+            .map(|t| (t.max - t.min).max_element())
+            .collect();
+        let cell_size = if sizes.is_empty() {
+            1.0
+        } else {
+            (sizes.iter().sum::<f32>() / sizes.len() as f32).max(1.0)
+        };
+
+        let mut grid = SpatialGrid {
+            cell_size,
+            cells: HashMap::new(),
+        };
+        for (i, territory) in territories.iter().enumerate() {
+            if territory.vertices.is_empty() {
+                continue;
+            }
+            let (x0, y0) = grid.cell_of(territory.min);
+            let (x1, y1) = grid.cell_of(territory.max);
+            for x in x0..=x1 {
+                for y in y0..=y1 {
+                    grid.cells.entry((x, y)).or_default().push(i);
+                }
+            }
+        }
+        grid
+    }
+
+    fn cell_of(&self, point: Vec2) -> (i32, i32) {
+        (
+            (point.x / self.cell_size).floor() as i32,
+            (point.y / self.cell_size).floor() as i32,
+        )
+    }
+
+    // Territories that might contain `point`, in ascending index order
+    pub fn candidates(&self, point: Vec2) -> &[usize] {
+        self.cells
+            .get(&self.cell_of(point))
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::territory::FillRule;
+    use crate::test_support::{generate_stress_map, synthetic_map};
+    use std::time::Instant;
+
+    #[test]
+    fn grid_agrees_with_brute_force() {
+        let territories = synthetic_map(5, 12);
+        let grid = SpatialGrid::new(&territories);
+        for x in (0..500).step_by(7) {
+            for y in (0..500).step_by(11) {
+                let point = vec2(x as f32, y as f32);
+                let brute = territories.iter().rposition(|t| t.is_point_inside(point));
+                let indexed = grid
+                    .candidates(point)
+                    .iter()
+                    .rev()
+                    .copied()
+                    .find(|&i| territories[i].is_point_inside(point));
+                assert_eq!(brute, indexed);
+            }
+        }
+    }
+
+    // cargo test --release bench_hit_testing -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_hit_testing() {
+        let territories = generate_stress_map(400, 64);
+        let grid = SpatialGrid::new(&territories);
+        let points: Vec<Vec2> = (0..20_000)
+            .map(|i| vec2((i * 37 % 2000) as f32, (i * 91 % 2000) as f32))
+            .collect();
+
+        let start = Instant::now();
+        let brute_hits = points
+            .iter()
+            .filter(|&&p| territories.iter().any(|t| t.is_point_inside(p)))
+            .count();
+        let brute = start.elapsed();
+
+        let start = Instant::now();
+        let aabb_hits = points
+            .iter()
+            .filter(|&&p| {
+                territories
+                    .iter()
+                    .any(|t| t.contains_in_bounds(p, FillRule::EvenOdd))
+            })
+            .count();
+        let aabb = start.elapsed();
+
+        let start = Instant::now();
+        let grid_hits = points
+            .iter()
+            .filter(|&&p| {
+                grid.candidates(p)
+                    .iter()
+                    .any(|&i| territories[i].contains_in_bounds(p, FillRule::EvenOdd))
+            })
+            .count();
+        let indexed = start.elapsed();
+
+        assert_eq!(brute_hits, aabb_hits);
+        assert_eq!(brute_hits, grid_hits);
+        println!(
+            "{} territories, {} queries: brute force {:?}, bounding boxes {:?}, grid {:?}",
+            territories.len(),
+            points.len(),
+            brute,
+            aabb,
+            indexed
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use macroquad::models::{draw_mesh, Mesh, Vertex};
+use macroquad::prelude::*;
+
+// Width in pixels over which a line's edge fades out, which hides the
+// stair-stepping plain draw_line shows on diagonals
+const FEATHER: f32 = 1.0;
+// Sides of the fan filling each round join
+const JOIN_SEGMENTS: usize = 8;
+// Below this the gap at a corner is too small to see, so joins are skipped
+const MIN_JOINED_THICKNESS: f32 = 2.0;
+
+// macroquad's batch sizes: it silently cuts off any mesh that reaches
+// either, so meshes are split below them
+pub const MAX_DRAW_VERTICES: usize = 10000;
+pub const MAX_DRAW_INDICES: usize = 5000;
+
+// Line segments collected into as few meshes as possible, so a whole outline
+// costs one draw call rather than one per edge
+#[derive(Default)]
+pub struct Strokes {
+    vertices: Vec<Vertex>,
+    indices: Vec<u16>,
+}
+
+impl Strokes {
+    // A feathered line from `start` to `end`, in screen pixels
+    pub fn segment(&mut self, start: Vec2, end: Vec2, thickness: f32, color: Color) {
+        let direction = (end - start).normalize_or_zero();
+        if direction == Vec2::ZERO {
+            return;
+        }
+        let normal = direction.perp() * (thickness / 2.0);
+        let feather = direction.perp() * (thickness / 2.0 + FEATHER);
+        let clear = Color { a: 0.0, ..color };
+        self.reserve(8, 18);
+        let base = self.vertices.len() as u16;
+        for point in [start, end] {
+            self.push(point + feather, clear);
+            self.push(point + normal, color);
+            self.push(point - normal, color);
+            self.push(point - feather, clear);
+        }
+        // Three quads across the line: fade in, solid core, fade out
+        for strip in 0..3 {
+            let (a, b) = (base + strip, base + strip + 1);
+            let (c, d) = (a + 4, b + 4);
+            self.indices.extend_from_slice(&[a, b, d, a, d, c]);
+        }
+    }
+
+    // Rounds off the corner where two segments of the same thickness meet
+    pub fn join(&mut self, at: Vec2, thickness: f32, color: Color) {
+        if thickness < MIN_JOINED_THICKNESS {
+            return;
+        }
+        let radius = thickness / 2.0;
+        let clear = Color { a: 0.0, ..color };
+        self.reserve(1 + 2 * JOIN_SEGMENTS, 9 * JOIN_SEGMENTS);
+        let center = self.vertices.len() as u16;
+        self.push(at, color);
+        for i in 0..JOIN_SEGMENTS {
+            let angle = i as f32 / JOIN_SEGMENTS as f32 * std::f32::consts::TAU;
+            let offset = Vec2::from_angle(angle);
+            self.push(at + offset * radius, color);
+            self.push(at + offset * (radius + FEATHER), clear);
+        }
+        for i in 0..JOIN_SEGMENTS as u16 {
+            let next = (i + 1) % JOIN_SEGMENTS as u16;
+            let (inner, outer) = (center + 1 + 2 * i, center + 2 + 2 * i);
+            let (next_inner, next_outer) = (center + 1 + 2 * next, center + 2 + 2 * next);
+            self.indices.extend_from_slice(&[center, inner, next_inner]);
+            self.indices
+                .extend_from_slice(&[inner, outer, next_outer, inner, next_outer, next_inner]);
+        }
+    }
+
+    // A closed outline through `points` with rounded corners
+    pub fn polygon(&mut self, points: &[Vec2], thickness: f32, color: Color) {
+        let n = points.len();
+        for i in 0..n {
+            self.segment(points[i], points[(i + 1) % n], thickness, color);
+            self.join(points[i], thickness, color);
+        }
+    }
+
+    pub fn draw(&mut self) {
+        if self.indices.is_empty() {
+            return;
+        }
+        draw_mesh(&Mesh {
+            vertices: std::mem::take(&mut self.vertices),
+            indices: std::mem::take(&mut self.indices),
+            texture: None,
+        });
+    }
+
+    // A draw only takes so much, so a long outline goes out in several
+    fn reserve(&mut self, vertices: usize, indices: usize) {
+        if self.vertices.len() + vertices >= MAX_DRAW_VERTICES
+            || self.indices.len() + indices >= MAX_DRAW_INDICES
+        {
+            self.draw();
+        }
+    }
+
+    fn push(&mut self, position: Vec2, color: Color) {
+        self.vertices
+            .push(Vertex::new(position.x, position.y, 0.0, 0.0, 0.0, color));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Strokes, FEATHER};
+    use macroquad::prelude::*;
+
+    #[test]
+    fn segment_has_a_solid_core_and_a_clear_feathered_edge() {
+        let mut strokes = Strokes::default();
+        strokes.segment(vec2(0.0, 0.0), vec2(10.0, 0.0), 4.0, RED);
+        assert_eq!(strokes.vertices.len(), 8);
+        assert_eq!(strokes.indices.len(), 18);
+        for vertex in &strokes.vertices {
+            let distance = vertex.position.y.abs();
+            if vertex.color[3] == 0 {
+                assert!((distance - (2.0 + FEATHER)).abs() < 1e-5);
+            } else {
+                assert!((distance - 2.0).abs() < 1e-5);
+            }
+        }
+
+        // A zero-length edge adds nothing
+        strokes.segment(vec2(3.0, 3.0), vec2(3.0, 3.0), 4.0, RED);
+        assert_eq!(strokes.vertices.len(), 8);
+    }
+}
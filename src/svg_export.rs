@@ -0,0 +1,164 @@
+use crate::GameState;
+use macroquad::prelude::*;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+pub const SVG_EXPORT_PATH: &str = "board.svg";
+
+// Room left around the map, as a share of its larger side
+const PADDING: f32 = 0.03;
+
+// "#rrggbb" for an SVG fill
+fn hex(color: Color) -> String {
+    let [r, g, b, _]: [u8; 4] = color.into();
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+// Names are free text, so anything XML treats specially is escaped
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl GameState {
+    // The board as the current player sees it, drawn straight from the
+    // territories rather than the screen, so it's the same at any zoom or
+    // window size. The viewBox is the map's bounds with a little padding.
+    pub fn board_svg(&self) -> String {
+        let (min, max) = self.game.map_bounds();
+        let span = (max - min).max_element().max(1.0);
+        let (origin, size) = (
+            min - Vec2::splat(span * PADDING),
+            max - min + Vec2::splat(span * PADDING * 2.0),
+        );
+        let font_size = span / 60.0;
+        let viewer = self.game.current_player;
+
+        let mut svg = String::new();
+        // Writing to a String can't fail
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+            origin.x, origin.y, size.x, size.y
+        );
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+            origin.x,
+            origin.y,
+            size.x,
+            size.y,
+            hex(self.theme.background)
+        );
+        for (i, territory) in self.game.territories.iter().enumerate() {
+            let fill = self.fill_seen_by(viewer, i);
+            let points: Vec<String> = territory
+                .vertices
+                .iter()
+                .map(|v| format!("{},{}", v.x, v.y))
+                .collect();
+            let _ = writeln!(
+                svg,
+                r#"<polygon points="{}" fill="{}" stroke="{}" stroke-width="{}"/>"#,
+                points.join(" "),
+                hex(fill),
+                hex(crate::darken(fill, 0.6)),
+                span / 500.0
+            );
+        }
+        for (i, territory) in self.game.territories.iter().enumerate() {
+            let text = crate::contrasting_text_color(self.fill_seen_by(viewer, i));
+            let armies = if self.visible_to(viewer, i) {
+                territory.armies.to_string()
+            } else {
+                "?".to_string()
+            };
+            let center = territory.centroid();
+            for (line, y, size) in [
+                (
+                    escape(&territory.name),
+                    center.y - font_size * 0.3,
+                    font_size * 0.8,
+                ),
+                (armies, center.y + font_size * 0.9, font_size),
+            ] {
+                let _ = writeln!(
+                    svg,
+                    r#"<text x="{}" y="{}" font-size="{}" font-family="sans-serif" text-anchor="middle" fill="{}">{}</text>"#,
+                    center.x,
+                    y,
+                    size,
+                    hex(text),
+                    line
+                );
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    pub fn export_svg<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.board_svg())
+    }
+
+    pub fn export_board_image(&mut self) {
+        self.status_message = Some(match self.export_svg(SVG_EXPORT_PATH) {
+            Ok(()) => format!("Board exported to {}", SVG_EXPORT_PATH),
+            Err(err) => format!("Export failed: {}", err),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::player::PlayerId;
+    use crate::game::Territory;
+    use crate::test_support::game_state;
+    use macroquad::prelude::*;
+
+    #[test]
+    fn svg_has_every_territory_inside_the_map_bounds() {
+        let square = |x: f32| {
+            vec![
+                vec2(x, 0.0),
+                vec2(x + 10.0, 0.0),
+                vec2(x + 10.0, 10.0),
+                vec2(x, 10.0),
+            ]
+        };
+        let mut state = game_state(
+            vec![
+                Territory::new(
+                    "Rock & Roll".to_string(),
+                    square(0.0),
+                    Some(PlayerId(0)),
+                    3,
+                    vec![1],
+                ),
+                Territory::new(
+                    "Far <East>".to_string(),
+                    square(90.0),
+                    Some(PlayerId(1)),
+                    7,
+                    vec![0],
+                ),
+            ],
+            0,
+        );
+        state.fog_of_war = false;
+        let svg = state.board_svg();
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="-3 -3 106 16">"#)
+        );
+        assert_eq!(svg.matches("<polygon").count(), 2);
+        assert!(svg.contains(r#"points="90,0 100,0 100,10 90,10""#));
+        assert!(svg.contains(">Rock &amp; Roll</text>"));
+        assert!(svg.contains(">Far &lt;East&gt;</text>"));
+        assert!(svg.contains(">7</text>"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+}
@@ -0,0 +1,150 @@
+// Builders for small synthetic games used across the unit tests
+use crate::game::player::PlayerId;
+use crate::game::{Game, Territory};
+use crate::GameState;
+use macroquad::prelude::*;
+
+pub fn territory(name: &str, owner: usize, armies: i32, adjacent: Vec<usize>) -> Territory {
+    Territory::new(
+        name.to_string(),
+        vec![vec2(0.0, 0.0), vec2(1.0, 0.0), vec2(0.0, 1.0)],
+        Some(PlayerId(owner)),
+        armies,
+        adjacent,
+    )
+}
+
+pub fn game(territories: Vec<Territory>, seed: u64) -> Game {
+    Game::from_territories(territories, seed)
+}
+
+// The same game wrapped in the UI state, for tests of input handling
+pub fn game_state(territories: Vec<Territory>, seed: u64) -> GameState {
+    GameState::from_game(game(territories, seed))
+}
+
+// A `side` x `side` grid of round territories with `resolution` vertices
+pub fn synthetic_map(side: usize, resolution: usize) -> Vec<Territory> {
+    let mut territories = Vec::new();
+    for row in 0..side {
+        for col in 0..side {
+            let center = vec2(col as f32 * 100.0 + 50.0, row as f32 * 100.0 + 50.0);
+            let vertices = (0..resolution)
+                .map(|k| {
+                    let angle = k as f32 / resolution as f32 * std::f32::consts::TAU;
+                    center + vec2(angle.cos(), angle.sin()) * 45.0
+                })
+                .collect();
+            territories.push(Territory::new(
+                format!("{}-{}", row, col),
+                vertices,
+                Some(PlayerId(0)),
+                1,
+                Vec::new(),
+            ));
+        }
+    }
+    territories
+}
+
+pub fn territory_with_vertices(vertices: Vec<Vec2>) -> Territory {
+    Territory::new(
+        "Test".to_string(),
+        vertices,
+        Some(PlayerId(0)),
+        1,
+        Vec::new(),
+    )
+}
+
+pub fn square(min: f32, max: f32) -> Vec<Vec2> {
+    vec![
+        vec2(min, min),
+        vec2(max, min),
+        vec2(max, max),
+        vec2(min, max),
+    ]
+}
+
+// A large map for benchmarks: `n_territories` cells of a square grid, each
+// bordering the cells beside it. Every side is split into wavy segments
+// (about `verts_per_territory` vertices in all) shared exactly with the
+// neighbouring cell, so the map tiles with no gaps or overlaps. Owners go
+// round four players.
+pub fn generate_stress_map(n_territories: usize, verts_per_territory: usize) -> Vec<Territory> {
+    const CELL: f32 = 100.0;
+    let cols = (n_territories as f32).sqrt().ceil().max(1.0) as usize;
+    let segments = (verts_per_territory / 4).max(1);
+    let step = CELL / segments as f32;
+    // Kept well inside a segment so neighbouring sides can't cross
+    let amplitude = step * 0.3;
+    // The same offset for a point whichever cell walks over it
+    let wobble = |line: usize, along: usize, salt: u64| {
+        let hash = (line as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (along as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+            ^ salt;
+        let hash = hash.wrapping_mul(0x2545_F491_4F6C_DD1D);
+        ((hash >> 40) as f32 / (1u64 << 24) as f32 * 2.0 - 1.0) * amplitude
+    };
+    // Points along a horizontal grid line and a vertical one, the corners
+    // left where they are
+    let across = |row: usize, x: usize| {
+        let dy = if x.is_multiple_of(segments) {
+            0.0
+        } else {
+            wobble(row, x, 1)
+        };
+        vec2(x as f32 * step, row as f32 * CELL + dy)
+    };
+    let down = |col: usize, y: usize| {
+        let dx = if y.is_multiple_of(segments) {
+            0.0
+        } else {
+            wobble(col, y, 2)
+        };
+        vec2(col as f32 * CELL + dx, y as f32 * step)
+    };
+    (0..n_territories)
+        .map(|i| {
+            let (row, col) = (i / cols, i % cols);
+            let (left, top) = (col * segments, row * segments);
+            let mut vertices = Vec::with_capacity(segments * 4);
+            vertices.extend((0..segments).map(|k| across(row, left + k)));
+            vertices.extend((0..segments).map(|k| down(col + 1, top + k)));
+            vertices.extend((0..segments).map(|k| across(row + 1, left + segments - k)));
+            vertices.extend((0..segments).map(|k| down(col, top + segments - k)));
+            let mut adjacent = Vec::new();
+            if col > 0 {
+                adjacent.push(i - 1);
+            }
+            if col + 1 < cols && i + 1 < n_territories {
+                adjacent.push(i + 1);
+            }
+            if row > 0 {
+                adjacent.push(i - cols);
+            }
+            if i + cols < n_territories {
+                adjacent.push(i + cols);
+            }
+            Territory::new(
+                format!("Stress {}", i),
+                vertices,
+                Some(PlayerId(i % 4)),
+                3,
+                adjacent,
+            )
+        })
+        .collect()
+}
+
+// Steps the animations through `seconds` of simulated time in frames of
+// `1 / fps`, standing in for the real frame clock. The last frame is cut
+// short so the total comes out exact.
+pub fn run_for(state: &mut GameState, seconds: f32, fps: f32) {
+    let mut left = seconds;
+    while left > 0.0 {
+        let dt = (1.0 / fps).min(left);
+        state.advance_animations(dt);
+        left -= dt;
+    }
+}
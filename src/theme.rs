@@ -0,0 +1,265 @@
+use crate::GameState;
+use macroquad::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeName {
+    #[default]
+    Light,
+    Dark,
+}
+
+// The colours the board and HUD are drawn in. Player colours stay as
+// configured whatever the theme.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    pub name: ThemeName,
+    pub background: Color,
+    pub text: Color,
+    // Help lines, hints and other text that shouldn't compete with the rest
+    pub muted_text: Color,
+    pub panel: Color,
+    // An entry under the mouse or the keyboard's cursor
+    pub panel_highlight: Color,
+    pub panel_border: Color,
+    // The selected territory's border
+    pub selection: Color,
+    // Borders of the selection's neighbours
+    pub highlight: Color,
+    // Fill for territories nobody holds
+    pub neutral: Color,
+    // Errors and status messages
+    pub warning: Color,
+    // The capital star
+    pub capital: Color,
+    // Terrain signs and river borders, drawn faint over the fills
+    pub mountain: Color,
+    pub river: Color,
+    // Territories hidden by fog of war
+    pub fog: Color,
+    // The glow round territories an attack can be launched from
+    pub attack_source: Color,
+    // What the selection can attack, and the drag arrow aimed at it
+    pub attack_target: Color,
+    // Where the armies of a conquest are about to move
+    pub conquest_preview: Color,
+    // The controller's cursor and the neighbours shown on a hold
+    pub focus: Color,
+    // The minimap's frame round what the main view shows
+    pub viewport: Color,
+    // Armies waiting to be placed
+    pub placement: Color,
+    // Territories a fortify can reach, the planned route, sea routes and
+    // borders between allies
+    pub reach: Color,
+    pub route: Color,
+    pub sea_route: Color,
+    pub alliance: Color,
+    pub attacker_die: Color,
+    pub attacker_pips: Color,
+    pub defender_die: Color,
+    pub defender_pips: Color,
+    // The editor's territory outlines, the polygon being drawn and where
+    // the next vertex snaps to
+    pub editor_outline: Color,
+    pub editor_polygon: Color,
+    pub editor_snap: Color,
+}
+
+impl Theme {
+    pub fn light() -> Theme {
+        Theme {
+            name: ThemeName::Light,
+            background: WHITE,
+            text: DARKGRAY,
+            muted_text: GRAY,
+            panel: WHITE,
+            panel_highlight: LIGHTGRAY,
+            panel_border: GRAY,
+            selection: YELLOW,
+            highlight: ORANGE,
+            neutral: LIGHTGRAY,
+            warning: MAROON,
+            capital: GOLD,
+            mountain: Color::new(0.3, 0.25, 0.2, 1.0),
+            river: Color::new(0.15, 0.4, 0.8, 1.0),
+            fog: Color::new(0.35, 0.35, 0.38, 1.0),
+            attack_source: Color::new(1.0, 0.85, 0.0, 1.0),
+            attack_target: RED,
+            conquest_preview: LIME,
+            focus: Color::new(0.15, 0.45, 0.85, 1.0),
+            viewport: RED,
+            placement: GOLD,
+            reach: Color::new(0.2, 0.9, 0.4, 1.0),
+            route: Color::new(1.0, 0.45, 0.0, 1.0),
+            sea_route: Color::new(0.2, 0.35, 0.6, 1.0),
+            alliance: Color::new(0.95, 0.8, 0.2, 1.0),
+            attacker_die: RED,
+            attacker_pips: WHITE,
+            defender_die: WHITE,
+            defender_pips: BLACK,
+            editor_outline: DARKGRAY,
+            editor_polygon: BLUE,
+            editor_snap: MAGENTA,
+        }
+    }
+
+    pub fn dark() -> Theme {
+        Theme {
+            name: ThemeName::Dark,
+            background: Color::new(0.11, 0.12, 0.14, 1.0),
+            text: Color::new(0.86, 0.87, 0.9, 1.0),
+            muted_text: Color::new(0.55, 0.57, 0.6, 1.0),
+            panel: Color::new(0.17, 0.18, 0.21, 1.0),
+            panel_highlight: Color::new(0.28, 0.3, 0.34, 1.0),
+            panel_border: Color::new(0.4, 0.42, 0.46, 1.0),
+            selection: Color::new(1.0, 0.85, 0.2, 1.0),
+            highlight: Color::new(1.0, 0.6, 0.2, 1.0),
+            neutral: Color::new(0.45, 0.46, 0.5, 1.0),
+            warning: Color::new(1.0, 0.5, 0.45, 1.0),
+            capital: Color::new(1.0, 0.8, 0.25, 1.0),
+            mountain: Color::new(0.75, 0.68, 0.58, 1.0),
+            river: Color::new(0.4, 0.65, 1.0, 1.0),
+            fog: Color::new(0.24, 0.25, 0.28, 1.0),
+            attack_source: Color::new(1.0, 0.85, 0.2, 1.0),
+            attack_target: Color::new(1.0, 0.35, 0.3, 1.0),
+            conquest_preview: Color::new(0.45, 0.95, 0.4, 1.0),
+            focus: Color::new(0.45, 0.75, 1.0, 1.0),
+            viewport: Color::new(1.0, 0.4, 0.35, 1.0),
+            placement: Color::new(1.0, 0.8, 0.25, 1.0),
+            reach: Color::new(0.3, 0.9, 0.5, 1.0),
+            route: Color::new(1.0, 0.55, 0.15, 1.0),
+            sea_route: Color::new(0.45, 0.6, 0.9, 1.0),
+            alliance: Color::new(1.0, 0.85, 0.3, 1.0),
+            attacker_die: Color::new(0.8, 0.2, 0.2, 1.0),
+            attacker_pips: WHITE,
+            defender_die: Color::new(0.9, 0.9, 0.92, 1.0),
+            defender_pips: Color::new(0.1, 0.1, 0.12, 1.0),
+            editor_outline: Color::new(0.7, 0.72, 0.76, 1.0),
+            editor_polygon: Color::new(0.4, 0.6, 1.0, 1.0),
+            editor_snap: Color::new(1.0, 0.45, 1.0, 1.0),
+        }
+    }
+
+    // The panel colour at `alpha`, for panels the board shows through
+    pub fn panel_at(&self, alpha: f32) -> Color {
+        Color {
+            a: alpha,
+            ..self.panel
+        }
+    }
+
+    fn color_mut(&mut self, name: &str) -> Option<&mut Color> {
+        Some(match name {
+            "background" => &mut self.background,
+            "text" => &mut self.text,
+            "muted_text" => &mut self.muted_text,
+            "panel" => &mut self.panel,
+            "panel_highlight" => &mut self.panel_highlight,
+            "panel_border" => &mut self.panel_border,
+            "selection" => &mut self.selection,
+            "highlight" => &mut self.highlight,
+            "neutral" => &mut self.neutral,
+            "warning" => &mut self.warning,
+            "capital" => &mut self.capital,
+            "mountain" => &mut self.mountain,
+            "river" => &mut self.river,
+            "fog" => &mut self.fog,
+            "attack_source" => &mut self.attack_source,
+            "attack_target" => &mut self.attack_target,
+            "conquest_preview" => &mut self.conquest_preview,
+            "focus" => &mut self.focus,
+            "viewport" => &mut self.viewport,
+            "placement" => &mut self.placement,
+            "reach" => &mut self.reach,
+            "route" => &mut self.route,
+            "sea_route" => &mut self.sea_route,
+            "alliance" => &mut self.alliance,
+            "attacker_die" => &mut self.attacker_die,
+            "attacker_pips" => &mut self.attacker_pips,
+            "defender_die" => &mut self.defender_die,
+            "defender_pips" => &mut self.defender_pips,
+            "editor_outline" => &mut self.editor_outline,
+            "editor_polygon" => &mut self.editor_polygon,
+            "editor_snap" => &mut self.editor_snap,
+            _ => return None,
+        })
+    }
+}
+
+// Which theme to start in, and any colours to change in it (and in the
+// other bundled theme, when switching)
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub base: ThemeName,
+    // RGB, 0-255 per channel, by the theme's field names
+    pub colors: HashMap<String, [u8; 3]>,
+}
+
+impl ThemeConfig {
+    // The bundled theme `name` with the config's colours laid over it, and
+    // a warning for each colour name it doesn't have
+    pub fn build(&self, name: ThemeName) -> (Theme, Vec<String>) {
+        let mut theme = match name {
+            ThemeName::Light => Theme::light(),
+            ThemeName::Dark => Theme::dark(),
+        };
+        let mut warnings = Vec::new();
+        let mut names: Vec<&String> = self.colors.keys().collect();
+        names.sort();
+        for color_name in names {
+            let [r, g, b] = self.colors[color_name];
+            match theme.color_mut(color_name) {
+                Some(color) => *color = Color::from_rgba(r, g, b, 255),
+                None => warnings.push(format!("The theme has no colour called '{}'", color_name)),
+            }
+        }
+        (theme, warnings)
+    }
+}
+
+impl GameState {
+    // Swaps between the light and dark themes, keeping the config's colours
+    pub fn toggle_theme(&mut self) {
+        let other = match self.theme.name {
+            ThemeName::Light => ThemeName::Dark,
+            ThemeName::Dark => ThemeName::Light,
+        };
+        // Any warnings were shown when the config was loaded
+        self.theme = self.theme_config.build(other).0;
+        // Neutral fills and the background are part of the cached board
+        self.game.board_dirty = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Theme, ThemeConfig, ThemeName};
+    use crate::test_support::{game_state, territory};
+    use macroquad::prelude::*;
+
+    #[test]
+    fn config_colours_apply_to_either_theme() {
+        let config: ThemeConfig = serde_json::from_str(
+            r#"{"base": "dark", "colors": {"selection": [255, 0, 255], "glow": [0, 0, 0]}}"#,
+        )
+        .unwrap();
+        let (theme, warnings) = config.build(config.base);
+        assert_eq!(theme.name, ThemeName::Dark);
+        assert_eq!(theme.selection, MAGENTA);
+        assert_eq!(theme.text, Theme::dark().text);
+        assert_eq!(warnings, ["The theme has no colour called 'glow'"]);
+
+        let mut state = game_state(vec![territory("A", 0, 1, vec![])], 0);
+        state.theme_config = config;
+        state.theme = state.theme_config.build(ThemeName::Dark).0;
+        state.game.board_dirty = false;
+        state.toggle_theme();
+        assert_eq!(state.theme.background, WHITE);
+        assert_eq!(state.theme.selection, MAGENTA);
+        assert!(state.game.board_dirty);
+    }
+}
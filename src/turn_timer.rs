@@ -0,0 +1,154 @@
+use crate::game::player::PlayerId;
+use crate::replay::ReplayAction;
+use crate::GameState;
+use macroquad::prelude::*;
+
+// Countdown for timed games, restarted whenever the turn changes hands
+pub struct TurnTimer {
+    limit: f32,
+    remaining: f32,
+    player: PlayerId,
+}
+
+impl TurnTimer {
+    pub fn new(limit: f32, player: PlayerId) -> TurnTimer {
+        TurnTimer {
+            limit,
+            remaining: limit,
+            player,
+        }
+    }
+
+    // A full clock for `player`
+    pub fn restart(&mut self, player: PlayerId) {
+        self.player = player;
+        self.remaining = self.limit;
+    }
+
+    // Counts `dt` seconds off `current`'s turn, starting afresh for a new
+    // player; true once their time is up
+    pub fn tick(&mut self, dt: f32, current: PlayerId) -> bool {
+        if current != self.player {
+            self.player = current;
+            self.remaining = self.limit;
+        }
+        self.remaining = (self.remaining - dt).max(0.0);
+        self.remaining == 0.0
+    }
+}
+
+impl GameState {
+    // Runs the clock while a human is to move and not paused. A computer
+    // turn plays out over many frames and is already in the replay whole,
+    // so it's never cut short. When the clock runs out whatever was in hand
+    // is settled and the turn passes on.
+    pub fn update_turn_timer(&mut self, dt: f32) {
+        let current = self.game.current_player;
+        if self.paused
+            || self.game.winner().is_some()
+            || self.ai_turn.is_some()
+            || self.game.player(current).is_ai
+        {
+            return;
+        }
+        let Some(timer) = &mut self.turn_timer else {
+            return;
+        };
+        if !timer.tick(dt, current) {
+            return;
+        }
+        if self.combat_animation.is_some() {
+            self.update_combat_animation(0.0, true);
+        }
+        if self.pending_move.is_some() {
+            self.confirm_pending_move();
+        }
+        self.cancel_pending_attack();
+        self.replay.record(ReplayAction::ForfeitTurn);
+        self.forfeit_turn();
+        self.status_message = Some("Out of time: turn passed".to_string());
+    }
+
+    pub fn forfeit_turn(&mut self) {
+        self.pending_placements.clear();
+        self.ai_turn = None;
+        self.clear_selection();
+        self.game.forfeit_turn();
+    }
+
+    pub fn draw_turn_timer(&self) {
+        if self.paused && self.pause_menu.is_none() {
+            let dimensions = measure_text("Paused", None, 48, 1.0);
+            draw_text(
+                "Paused",
+                (self.hud_size().x - dimensions.width) / 2.0,
+                self.hud_size().y / 2.0,
+                48.0,
+                self.theme.text,
+            );
+        }
+        let Some(timer) = &self.turn_timer else {
+            return;
+        };
+        let seconds = timer.remaining.ceil() as i32;
+        let text = format!("Time left {}:{:02}", seconds / 60, seconds % 60);
+        let color = if timer.remaining <= 10.0 {
+            self.theme.warning
+        } else {
+            self.theme.text
+        };
+        let dimensions = measure_text(&text, None, 20, 1.0);
+        draw_text(
+            &text,
+            self.hud_size().x - dimensions.width - 10.0,
+            self.hud_buttons_bottom() + 27.0,
+            20.0,
+            color,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TurnTimer;
+    use crate::game::player::PlayerId;
+    use crate::game::turn::Phase;
+    use crate::test_support::{game_state, territory};
+
+    #[test]
+    fn timer_restarts_for_each_player() {
+        let mut timer = TurnTimer::new(10.0, PlayerId(0));
+        assert!(!timer.tick(6.0, PlayerId(0)));
+        assert!(timer.tick(6.0, PlayerId(0)));
+        assert!(!timer.tick(6.0, PlayerId(1)));
+    }
+
+    #[test]
+    fn pausing_freezes_the_clock() {
+        let mut state = game_state(
+            vec![territory("A", 0, 2, vec![1]), territory("B", 1, 2, vec![0])],
+            0,
+        );
+        state.turn_timer = Some(TurnTimer::new(5.0, PlayerId(0)));
+        state.paused = true;
+        state.update_turn_timer(60.0);
+        assert_eq!(state.game.current_player, PlayerId(0));
+
+        state.paused = false;
+        state.update_turn_timer(60.0);
+        assert_eq!(state.game.current_player, PlayerId(1));
+        assert_eq!(state.game.phase, Phase::Reinforce);
+    }
+
+    #[test]
+    fn computer_turns_are_not_timed() {
+        let mut state = game_state(
+            vec![territory("A", 0, 2, vec![1]), territory("B", 1, 2, vec![0])],
+            0,
+        );
+        state.game.players[0].is_ai = true;
+        state.turn_timer = Some(TurnTimer::new(5.0, PlayerId(0)));
+        state.update_turn_timer(60.0);
+        assert_eq!(state.game.current_player, PlayerId(0));
+    }
+}